@@ -6,7 +6,7 @@ use chrono::NaiveDateTime;
 use ethers_core::{
     abi::{
         token::{LenientTokenizer, Tokenizer},
-        Abi, Function, HumanReadableParser, Token,
+        Abi, Function, HumanReadableParser, ParamType, RawLog, Token,
     },
     types::{Chain, *},
     utils::{
@@ -14,22 +14,34 @@ use ethers_core::{
         parse_units, rlp,
     },
 };
-use ethers_etherscan::Client;
+use ethers_etherscan::{contract::Metadata, Client};
 use ethers_providers::{Middleware, PendingTransaction};
 use eyre::{Context, Result};
 use foundry_common::fmt::*;
+use foundry_config::Config;
 pub use foundry_evm::*;
+use foundry_evm::{
+    revm::{opcode, OpCode},
+    trace::identifier::SignaturesIdentifier,
+};
 use foundry_utils::encode_args;
+pub use proxy::ProxyKind;
 use rustc_hex::{FromHexIter, ToHex};
+pub use state_override::{AccountOverride, StateOverride};
 use std::{
+    collections::HashSet,
+    io::Read,
     ops::{Shl, Shr},
-    path::PathBuf,
+    path::{Component, Path, PathBuf},
     str::FromStr,
 };
-pub use tx::TxBuilder;
+use tiny_keccak::{Hasher, Keccak};
+pub use tx::{ensure_blobs_supported, TxBuilder};
 use tx::{TxBuilderOutput, TxBuilderPeekOutput};
 
+pub mod proxy;
 mod rlp_converter;
+mod state_override;
 mod tx;
 
 // TODO: CastContract with common contract initializers? Same for CastProviders?
@@ -78,7 +90,7 @@ where
     ///     .set_args(sig, args).await?;
     /// let builder_output = builder.build();
     /// let cast = Cast::new(provider);
-    /// let data = cast.call(builder_output, None).await?;
+    /// let data = cast.call(builder_output, None, None).await?;
     /// println!("{}", data);
     /// # Ok(())
     /// # }
@@ -87,12 +99,11 @@ where
         &self,
         builder_output: TxBuilderOutput,
         block: Option<BlockId>,
+        state_override: Option<&StateOverride>,
     ) -> Result<String> {
-        let (tx, func) = builder_output;
-        let res = self.provider.call(&tx, block).await?;
+        let (res, func) = self.eth_call(builder_output, block, state_override).await?;
 
         // decode args into tokens
-        let func = func.expect("no valid function signature was provided.");
         let decoded = func.decode_output(res.as_ref()).wrap_err(
             "could not decode output. did you specify the wrong function return data type perhaps?",
         )?;
@@ -121,8 +132,64 @@ where
         })
     }
 
+    /// Makes a read-only call to the specified address and returns the decoded output as JSON
+    ///
+    /// The output is an object keyed by the names of the return values if they are all named,
+    /// otherwise an array, see [`tokens_to_json`]. If the function has no return type, the raw
+    /// output is returned as a hex string.
+    pub async fn call_json(
+        &self,
+        builder_output: TxBuilderOutput,
+        block: Option<BlockId>,
+        state_override: Option<&StateOverride>,
+    ) -> Result<serde_json::Value> {
+        let (res, func) = self.eth_call(builder_output, block, state_override).await?;
+        if func.outputs.is_empty() {
+            return Ok(serde_json::Value::String(res.to_string()))
+        }
+
+        let decoded = func.decode_output(res.as_ref()).wrap_err(
+            "could not decode output. did you specify the wrong function return data type perhaps?",
+        )?;
+        let names = func.outputs.iter().map(|output| output.name.as_str()).collect::<Vec<_>>();
+        Ok(tokens_to_json(&names, &decoded))
+    }
+
+    /// Executes the `eth_call` and returns its output together with the called function
+    async fn eth_call(
+        &self,
+        builder_output: TxBuilderOutput,
+        block: Option<BlockId>,
+        state_override: Option<&StateOverride>,
+    ) -> Result<(Bytes, Function)> {
+        let (tx, func) = builder_output;
+        let res = match state_override {
+            Some(state_override) => {
+                let block = block.unwrap_or_else(|| BlockNumber::Latest.into());
+                let params =
+                    (utils::serialize(&tx), utils::serialize(&block), state_override.to_json()?);
+                self.provider
+                    .provider()
+                    .request::<_, Bytes>("eth_call", params)
+                    .await
+                    .map_err(|err| err.to_string())
+            }
+            None => self.provider.call(&tx, block).await.map_err(|err| err.to_string()),
+        };
+        let res = match res {
+            Ok(res) => res,
+            Err(err) => eyre::bail!("{}", resolve_revert_reason(&err, false).await),
+        };
+
+        let func = func.expect("no valid function signature was provided.");
+        Ok((res, func))
+    }
+
     /// Generates an access list for the specified transaction
     ///
+    /// If `with_gas` is set, the gas of the transaction is estimated both with and without the
+    /// access list, at the same block the access list was created at.
+    ///
     /// ```no_run
     /// 
     /// use cast::{Cast, TxBuilder};
@@ -140,7 +207,7 @@ where
     ///     .set_args(sig, args).await?;
     /// let builder_output = builder.peek();
     /// let cast = Cast::new(&provider);
-    /// let access_list = cast.access_list(builder_output, None, false).await?;
+    /// let access_list = cast.access_list(builder_output, None, false, false).await?;
     /// println!("{}", access_list);
     /// # Ok(())
     /// # }
@@ -149,12 +216,35 @@ where
         &self,
         builder_output: TxBuilderPeekOutput<'_>,
         block: Option<BlockId>,
+        with_gas: bool,
         to_json: bool,
     ) -> Result<String> {
         let (tx, _) = builder_output;
+
+        // pin the block, so all estimates are based on the same state
+        let block = match block {
+            None if with_gas => Some(BlockId::from(self.provider.get_block_number().await?)),
+            block => block,
+        };
         let access_list = self.provider.create_access_list(tx, block).await?;
+
+        let estimates = if with_gas {
+            let mut tx_with_access_list = tx.clone();
+            tx_with_access_list.set_access_list(access_list.access_list.clone());
+            let without = self.estimate_at(tx, block).await?;
+            let with = self.estimate_at(&tx_with_access_list, block).await?;
+            Some((without, with))
+        } else {
+            None
+        };
+
         let res = if to_json {
-            serde_json::to_string(&access_list)?
+            let mut json = serde_json::to_value(&access_list)?;
+            if let (Some((without, with)), Some(fields)) = (estimates, json.as_object_mut()) {
+                fields.insert("gasEstimate".to_string(), serde_json::to_value(without)?);
+                fields.insert("gasEstimateWithAccessList".to_string(), serde_json::to_value(with)?);
+            }
+            serde_json::to_string(&json)?
         } else {
             let mut s =
                 vec![format!("gas used: {}", access_list.gas_used), "access list:".to_string()];
@@ -167,12 +257,34 @@ where
                     }
                 }
             }
+            if let Some((without, with)) = estimates {
+                s.push(format!("gas estimate without access list: {}", without));
+                s.push(format!("gas estimate with access list: {}", with));
+                if with <= without {
+                    s.push(format!("savings: {}", without - with));
+                } else {
+                    s.push(format!("overhead: {}", with - without));
+                }
+            }
             s.join("\n")
         };
 
         Ok(res)
     }
 
+    /// Estimates the gas of the transaction at the given block
+    async fn estimate_at(&self, tx: &TypedTransaction, block: Option<BlockId>) -> Result<U256> {
+        let tx = utils::serialize(tx);
+        let gas = match block {
+            Some(block) => {
+                let block = utils::serialize(&block);
+                self.provider.provider().request("eth_estimateGas", [tx, block]).await?
+            }
+            None => self.provider.provider().request("eth_estimateGas", [tx]).await?,
+        };
+        Ok(gas)
+    }
+
     pub async fn balance<T: Into<NameOrAddress> + Send + Sync>(
         &self,
         who: T,
@@ -181,6 +293,65 @@ where
         Ok(self.provider.get_balance(who, block).await?)
     }
 
+    /// Returns the ERC20 token balance of an account, in the smallest unit of the token
+    ///
+    /// ```no_run
+    /// use cast::Cast;
+    /// use ethers_providers::{Provider, Http};
+    /// use std::convert::TryFrom;
+    ///
+    /// # async fn foo() -> eyre::Result<()> {
+    /// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+    /// let cast = Cast::new(provider);
+    /// let usdc = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48".parse::<ethers_core::types::Address>()?;
+    /// let balance = cast.erc20_balance(usdc, "vitalik.eth", None).await?;
+    /// let decimals = cast.erc20_decimals(usdc, None).await?;
+    /// println!("{}", ethers_core::utils::format_units(balance, decimals as u32)?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn erc20_balance<
+        T: Into<NameOrAddress> + Send + Sync,
+        W: Into<NameOrAddress> + Send + Sync,
+    >(
+        &self,
+        token: T,
+        who: W,
+        block: Option<BlockId>,
+    ) -> Result<U256> {
+        let who = match who.into() {
+            NameOrAddress::Name(name) => self.provider.resolve_name(&name).await?,
+            NameOrAddress::Address(address) => address,
+        };
+        let calldata = erc20_calldata("balanceOf(address)", &[Token::Address(who)])?;
+        self.erc20_uint(token, calldata, block).await.wrap_err("Failed to get the token balance")
+    }
+
+    /// Returns the number of decimals of an ERC20 token
+    pub async fn erc20_decimals<T: Into<NameOrAddress> + Send + Sync>(
+        &self,
+        token: T,
+        block: Option<BlockId>,
+    ) -> Result<u8> {
+        let decimals = self
+            .erc20_uint(token, erc20_calldata("decimals()", &[])?, block)
+            .await
+            .wrap_err("Failed to get the token decimals")?;
+        erc20_decimals(decimals)
+    }
+
+    /// Calls a view function of a token that returns a single `uint256`
+    async fn erc20_uint<T: Into<NameOrAddress> + Send + Sync>(
+        &self,
+        token: T,
+        calldata: Vec<u8>,
+        block: Option<BlockId>,
+    ) -> Result<U256> {
+        let tx: TypedTransaction = TransactionRequest::new().to(token).data(calldata).into();
+        let output = self.provider.call(&tx, block).await?;
+        erc20_uint_output(&output)
+    }
+
     /// Sends a transaction to the specified address
     ///
     /// ```no_run
@@ -216,11 +387,41 @@ where
         builder_output: TxBuilderOutput,
     ) -> Result<PendingTransaction<'_, M::Provider>> {
         let (tx, _) = builder_output;
-        let res = self.provider.send_transaction(tx, None).await?;
+        let res = match self.provider.send_transaction(tx, None).await {
+            Ok(res) => res,
+            Err(err) => eyre::bail!("{}", resolve_revert_reason(&err.to_string(), false).await),
+        };
 
         Ok::<_, eyre::Error>(res)
     }
 
+    /// Creates the access list of the transaction and attaches it if it lowers the gas estimate
+    /// of the transaction, or regardless of the estimate if `force` is set.
+    ///
+    /// Returns the gas estimates without and with the access list, and whether it was attached.
+    pub async fn attach_access_list(
+        &self,
+        tx: &mut TypedTransaction,
+        force: bool,
+    ) -> Result<(U256, U256, bool)> {
+        ensure_access_list_supported(tx)?;
+
+        // pin the block, so all estimates are based on the same state
+        let block = Some(BlockId::from(self.provider.get_block_number().await?));
+        let access_list = self.provider.create_access_list(tx, block).await?;
+
+        let mut tx_with_access_list = tx.clone();
+        tx_with_access_list.set_access_list(access_list.access_list);
+        let without = self.estimate_at(tx, block).await?;
+        let with = self.estimate_at(&tx_with_access_list, block).await?;
+
+        let attach = should_attach_access_list(without, with, force);
+        if attach {
+            *tx = tx_with_access_list;
+        }
+        Ok((without, with, attach))
+    }
+
     /// Publishes a raw transaction to the network
     ///
     /// ```no_run
@@ -267,15 +468,22 @@ where
     ///     .set_args(sig, args).await?;
     /// let builder_output = builder.peek();
     /// let cast = Cast::new(&provider);
-    /// let data = cast.estimate(builder_output).await?;
+    /// let data = cast.estimate(builder_output, None).await?;
     /// println!("{}", data);
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn estimate(&self, builder_output: TxBuilderPeekOutput<'_>) -> Result<U256> {
+    pub async fn estimate(
+        &self,
+        builder_output: TxBuilderPeekOutput<'_>,
+        block: Option<BlockId>,
+    ) -> Result<U256> {
         let (tx, _) = builder_output;
 
-        let res = self.provider.estimate_gas(tx).await?;
+        let res = match block {
+            Some(block) => self.estimate_at(tx, Some(block)).await?,
+            None => self.provider.estimate_gas(tx).await?,
+        };
 
         Ok::<_, eyre::Error>(res)
     }
@@ -374,6 +582,34 @@ where
         Cast::block_field_as_num(self, block, "timestamp".to_string()).await
     }
 
+    /// Returns the average time in seconds it took to mine a block between the blocks `from` and
+    /// `to`
+    pub async fn average_block_time<F: Into<BlockId>, T: Into<BlockId>>(
+        &self,
+        from: F,
+        to: T,
+    ) -> Result<f64> {
+        let (from, to) = (from.into(), to.into());
+        let mut blocks = Vec::with_capacity(2);
+        for id in [from, to] {
+            let block = self
+                .provider
+                .get_block(id)
+                .await?
+                .ok_or_else(|| eyre::eyre!("block {:?} not found", id))?;
+            let number =
+                block.number.ok_or_else(|| eyre::eyre!("block {:?} is still pending", id))?;
+            blocks.push((number.as_u64(), block.timestamp.as_u64()));
+        }
+
+        let (from, to) = (blocks[0], blocks[1]);
+        let block_count = from.0.abs_diff(to.0);
+        if block_count == 0 {
+            eyre::bail!("Estimating the block time requires two different blocks")
+        }
+        Ok(from.1.abs_diff(to.1) as f64 / block_count as f64)
+    }
+
     pub async fn chain(&self) -> Result<&str> {
         let genesis_hash = Cast::block(
             self,
@@ -440,6 +676,41 @@ where
         Ok(self.provider.get_gas_price().await?)
     }
 
+    /// Returns the base fees of the last `blocks` blocks and the priority fees paid in them at the
+    /// given `percentiles`, fetched with a single `eth_feeHistory` request.
+    ///
+    /// The suggested priority fee of a percentile is its median over all blocks.
+    ///
+    /// ```no_run
+    /// use cast::Cast;
+    /// use ethers_providers::{Provider, Http};
+    /// use std::convert::TryFrom;
+    ///
+    /// # async fn foo() -> eyre::Result<()> {
+    /// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+    /// let cast = Cast::new(provider);
+    /// let history = cast.fee_history(10, &[25.0, 50.0, 75.0], false).await?;
+    /// println!("{}", history);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn fee_history(
+        &self,
+        blocks: u64,
+        percentiles: &[f64],
+        to_json: bool,
+    ) -> Result<String> {
+        if percentiles.iter().any(|p| !(0.0..=100.0).contains(p)) {
+            eyre::bail!("Percentiles must be between 0 and 100")
+        }
+        if percentiles.windows(2).any(|w| w[0] > w[1]) {
+            eyre::bail!("Percentiles must be in ascending order")
+        }
+
+        let history = self.provider.fee_history(blocks, BlockNumber::Latest, percentiles).await?;
+        format_fee_history(&history, percentiles, to_json)
+    }
+
     /// ```no_run
     /// use cast::Cast;
     /// use ethers_providers::{Provider, Http};
@@ -518,6 +789,86 @@ where
         Ok(format!("{}", self.provider.get_code(who, block).await?))
     }
 
+    /// Returns the implementation of the proxy at `who` and the pattern it was recognized by, or
+    /// `None` if `who` is not a recognized proxy.
+    ///
+    /// The EIP-1967 implementation and beacon slots are checked first, then the EIP-1822
+    /// `PROXIABLE` slot and the slot of OpenZeppelin's transparent proxies prior to EIP-1967.
+    ///
+    /// ```no_run
+    /// use cast::Cast;
+    /// use ethers_providers::{Provider, Http};
+    /// use ethers_core::types::Address;
+    /// use std::{str::FromStr, convert::TryFrom};
+    ///
+    /// # async fn foo() -> eyre::Result<()> {
+    /// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+    /// let cast = Cast::new(provider);
+    /// let proxy = Address::from_str("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48")?;
+    /// if let Some((implementation, kind)) = cast.implementation(proxy, None).await? {
+    ///     println!("{:?} ({})", implementation, kind);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn implementation(
+        &self,
+        who: Address,
+        block: Option<BlockId>,
+    ) -> Result<Option<(Address, ProxyKind)>> {
+        if let Some(implementation) =
+            self.address_at_slot(who, proxy::EIP1967_IMPLEMENTATION_SLOT, block).await?
+        {
+            return Ok(Some((implementation, ProxyKind::Eip1967)))
+        }
+
+        if let Some(beacon) = self.address_at_slot(who, proxy::EIP1967_BEACON_SLOT, block).await? {
+            let tx: TypedTransaction = TransactionRequest::new()
+                .to(beacon)
+                .data(proxy::IMPLEMENTATION_SELECTOR.to_vec())
+                .into();
+            let res = self.provider.call(&tx, block).await.wrap_err_with(|| {
+                format!("Failed to call implementation() on beacon {beacon:?}")
+            })?;
+            if let Some(implementation) = proxy::word_to_address(&res) {
+                return Ok(Some((implementation, ProxyKind::Beacon(beacon))))
+            }
+        }
+
+        for (slot, kind) in [
+            (proxy::EIP1822_PROXIABLE_SLOT, ProxyKind::Eip1822),
+            (proxy::ZEPPELINOS_IMPLEMENTATION_SLOT, ProxyKind::Transparent),
+        ] {
+            if let Some(implementation) = self.address_at_slot(who, slot, block).await? {
+                return Ok(Some((implementation, kind)))
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Returns the admin of the proxy at `who`, read from the EIP-1967 admin slot or the admin slot
+    /// of OpenZeppelin's transparent proxies prior to EIP-1967, or `None` if neither is set.
+    pub async fn admin(&self, who: Address, block: Option<BlockId>) -> Result<Option<Address>> {
+        for slot in [proxy::EIP1967_ADMIN_SLOT, proxy::ZEPPELINOS_ADMIN_SLOT] {
+            if let Some(admin) = self.address_at_slot(who, slot, block).await? {
+                return Ok(Some(admin))
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns the address stored in `slot` of `who`, or `None` if the slot is empty
+    async fn address_at_slot(
+        &self,
+        who: Address,
+        slot: &str,
+        block: Option<BlockId>,
+    ) -> Result<Option<Address>> {
+        let value = self.provider.get_storage_at(who, proxy::slot(slot), block).await?;
+        Ok(proxy::word_to_address(value.as_bytes()))
+    }
+
     /// ```no_run
     /// use cast::Cast;
     /// use ethers_providers::{Provider, Http};
@@ -564,6 +915,47 @@ where
         Ok(transaction)
     }
 
+    /// Fetches a transaction and reconstructs its signed, RLP encoded form from its fields and
+    /// signature.
+    ///
+    /// Legacy, EIP-2930 and EIP-1559 transactions are supported. The hash of the reconstructed
+    /// transaction is checked against the hash of the fetched transaction.
+    ///
+    /// ```no_run
+    /// use cast::Cast;
+    /// use ethers_providers::{Provider, Http};
+    /// use std::convert::TryFrom;
+    ///
+    /// # async fn foo() -> eyre::Result<()> {
+    /// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+    /// let cast = Cast::new(provider);
+    /// let tx_hash = "0xf8d1713ea15a81482958fb7ddf884baee8d3bcc478c5f2f604e008dc788ee4fc";
+    /// let raw_tx = cast.raw_transaction(tx_hash.to_string()).await?;
+    /// println!("{}", raw_tx);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn raw_transaction(&self, tx_hash: String) -> Result<Bytes> {
+        let tx = self
+            .provider
+            .get_transaction(H256::from_str(&tx_hash)?)
+            .await?
+            .ok_or_else(|| eyre::eyre!("transaction {:?} not found", tx_hash))?;
+
+        if let Some(ty) = tx.transaction_type.filter(|ty| *ty > 2u64.into()) {
+            eyre::bail!("Transactions of type {} are not supported", ty)
+        }
+
+        let raw = tx.rlp();
+        if H256::from(keccak256(&raw)) != tx.hash {
+            eyre::bail!(
+                "The reconstructed transaction does not match the hash of transaction {:?}",
+                tx.hash
+            )
+        }
+        Ok(raw)
+    }
+
     /// ```no_run
     /// use cast::Cast;
     /// use ethers_providers::{Provider, Http};
@@ -573,7 +965,7 @@ where
     /// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
     /// let cast = Cast::new(provider);
     /// let tx_hash = "0xf8d1713ea15a81482958fb7ddf884baee8d3bcc478c5f2f604e008dc788ee4fc";
-    /// let receipt = cast.receipt(tx_hash.to_string(), None, 1, false, false).await?;
+    /// let receipt = cast.receipt(tx_hash.to_string(), None, 1, false, false, false).await?;
     /// println!("{}", receipt);
     /// # Ok(())
     /// # }
@@ -584,6 +976,7 @@ where
         field: Option<String>,
         confs: usize,
         cast_async: bool,
+        cost: bool,
         to_json: bool,
     ) -> Result<String> {
         let tx_hash = H256::from_str(&tx_hash)?;
@@ -600,8 +993,9 @@ where
             }
         } else {
             match receipt {
-                Some(inner) => inner,
-                None => {
+                // a mined receipt already has one confirmation, only poll if more are required
+                Some(inner) if confs <= 1 => inner,
+                _ => {
                     let tx = PendingTransaction::new(tx_hash, self.provider.provider());
                     match tx.confirmations(confs).await? {
                         Some(inner) => inner,
@@ -620,17 +1014,52 @@ where
             serde_json::to_value(&receipt_result)?
         };
 
+        let cost = if cost && field.is_none() {
+            Some(self.receipt_cost(&receipt_result).await?)
+        } else {
+            None
+        };
+
         let receipt = if let Some(ref field) = field {
             get_pretty_tx_receipt_attr(&receipt_result, field)
                 .unwrap_or_else(|| format!("{field} is not a valid tx receipt field"))
         } else if to_json {
+            let mut receipt = receipt;
+            if let (Some(cost), Some(fields)) = (&cost, receipt.as_object_mut()) {
+                fields.insert("cost".to_string(), cost.to_json()?);
+            }
             serde_json::to_string(&receipt)?
         } else {
-            receipt_result.pretty()
+            match cost {
+                Some(cost) => format!("{}\n{}", receipt_result.pretty(), cost.pretty()?),
+                None => receipt_result.pretty(),
+            }
         };
         Ok(receipt)
     }
 
+    /// Computes the fee paid by the transaction of the receipt
+    async fn receipt_cost(&self, receipt: &TransactionReceipt) -> Result<ReceiptCost> {
+        let gas_used = receipt.gas_used.unwrap_or_default();
+        // nodes that predate EIP-1559 don't return the effective gas price
+        let effective_gas_price = match receipt.effective_gas_price {
+            Some(price) => price,
+            None => self
+                .provider
+                .get_transaction(receipt.transaction_hash)
+                .await?
+                .and_then(|tx| tx.gas_price)
+                .ok_or_else(|| eyre::eyre!("could not get the gas price of the transaction"))?,
+        };
+        let base_fee_per_gas = match receipt.block_number {
+            Some(number) => {
+                self.provider.get_block(number).await?.and_then(|block| block.base_fee_per_gas)
+            }
+            None => None,
+        };
+        Ok(ReceiptCost { gas_used, effective_gas_price, base_fee_per_gas })
+    }
+
     /// Perform a raw JSON-RPC request
     ///
     /// ```no_run
@@ -656,11 +1085,139 @@ where
     }
 }
 
+/// Encodes the call of an ERC20 function given by its human-readable signature
+fn erc20_calldata(sig: &str, args: &[Token]) -> Result<Vec<u8>> {
+    let func = HumanReadableParser::parse_function(sig)?;
+    Ok(func.encode_input(args)?)
+}
+
+/// Decodes the `uint256` returned by a view function of a token
+fn erc20_uint_output(output: &[u8]) -> Result<U256> {
+    if output.len() < 32 {
+        eyre::bail!(
+            "Unexpected return data `0x{}`, is the address an ERC20 token?",
+            hex::encode(output)
+        )
+    }
+    Ok(U256::from_big_endian(&output[..32]))
+}
+
+/// Checks that the decimals returned by a token fit into a `uint8`
+fn erc20_decimals(decimals: U256) -> Result<u8> {
+    if decimals > U256::from(u8::MAX) {
+        eyre::bail!("Invalid token decimals: {decimals}")
+    }
+    Ok(decimals.as_u32() as u8)
+}
+
+/// Fails if the transaction can't carry an access list, which is the case for legacy transactions
+fn ensure_access_list_supported(tx: &TypedTransaction) -> Result<()> {
+    if matches!(tx, TypedTransaction::Legacy(_)) {
+        eyre::bail!("Access lists are not supported by legacy transactions")
+    }
+    Ok(())
+}
+
+/// Whether to attach an access list, given the gas estimates of the transaction without and with
+/// it: only if it lowers the estimate, unless `force` is set.
+fn should_attach_access_list(without: U256, with: U256, force: bool) -> bool {
+    force || with < without
+}
+
+/// Returns the revert data in the error message of a failed `eth_call`.
+///
+/// Nodes return the revert data as hex in the error.
+fn revert_data(err: &str) -> impl Iterator<Item = Vec<u8>> + '_ {
+    err.match_indices("0x")
+        .filter_map(|(start, _)| {
+            let data = &err[start + 2..];
+            let len = data.find(|c: char| !c.is_ascii_hexdigit()).unwrap_or(data.len());
+            hex::decode(&data[..len]).ok()
+        })
+        .filter(|data| data.len() >= 4)
+}
+
+/// Extracts the revert reason from the error message of a failed `eth_call`.
+///
+/// The revert data in the error is decoded if possible, looking up custom errors in the local
+/// signature cache or, unless `offline` is set, at `sig.eth.samczsun.com`. Otherwise the error
+/// message is returned as is.
+async fn resolve_revert_reason(err: &str, offline: bool) -> String {
+    for data in revert_data(err) {
+        if let Ok(reason) = decode_revert_data(&data, offline).await {
+            return reason
+        }
+    }
+    err.to_string()
+}
+
+/// Decodes ABI encoded revert data, looking up custom errors with the signature identifier, see
+/// [decode::decode_revert_data]
+///
+/// If `offline` is set, custom errors are only looked up in the local signature cache.
+async fn decode_revert_data(data: &[u8], offline: bool) -> Result<String> {
+    let mut identifier = SignaturesIdentifier::new(Config::foundry_cache_dir(), offline).ok();
+    decode::decode_revert_data(data, identifier.as_mut()).await
+}
+
+/// The fee paid by a mined transaction
+pub struct ReceiptCost {
+    pub gas_used: U256,
+    pub effective_gas_price: U256,
+    /// The base fee of the block, if it's an EIP-1559 block
+    pub base_fee_per_gas: Option<U256>,
+}
+
+impl ReceiptCost {
+    /// The total fee paid in wei
+    pub fn fee(&self) -> U256 {
+        self.gas_used * self.effective_gas_price
+    }
+
+    /// The base fee and priority fee portions of the fee in wei, for EIP-1559 blocks
+    pub fn fee_portions(&self) -> Option<(U256, U256)> {
+        let base_fee = self.gas_used * self.base_fee_per_gas?;
+        Some((base_fee, self.fee().saturating_sub(base_fee)))
+    }
+
+    fn pretty(&self) -> Result<String> {
+        let mut s = vec![
+            format!("gasUsed              {}", self.gas_used),
+            format!("effectiveGasPrice    {}", self.effective_gas_price),
+            format!(
+                "fee                  {} wei ({} ETH)",
+                self.fee(),
+                SimpleCast::from_wei(self.fee(), "ether")?
+            ),
+        ];
+        if let Some((base_fee, priority_fee)) = self.fee_portions() {
+            s.push(format!("baseFee              {} wei", base_fee));
+            s.push(format!("priorityFee          {} wei", priority_fee));
+        }
+        Ok(s.join("\n"))
+    }
+
+    fn to_json(&self) -> Result<serde_json::Value> {
+        let mut json = serde_json::json!({
+            "gasUsed": self.gas_used,
+            "effectiveGasPrice": self.effective_gas_price,
+            "fee": self.fee(),
+            "feeEther": SimpleCast::from_wei(self.fee(), "ether")?,
+        });
+        if let Some((base_fee, priority_fee)) = self.fee_portions() {
+            json["baseFee"] = serde_json::to_value(base_fee)?;
+            json["priorityFee"] = serde_json::to_value(priority_fee)?;
+        }
+        Ok(json)
+    }
+}
+
 pub struct InterfaceSource {
     pub name: String,
     pub source: String,
 }
 
+#[derive(Debug, Clone)]
 pub enum InterfacePath {
     Local { path: String, name: Option<String> },
     Etherscan { address: Address, chain: Chain, api_key: String },
@@ -668,6 +1225,46 @@ pub enum InterfacePath {
 
 pub struct SimpleCast;
 impl SimpleCast {
+    /// Formats the time from `now` to the `timestamp` as a duration in its two largest units
+    ///
+    /// ```
+    /// use cast::SimpleCast as Cast;
+    ///
+    /// assert_eq!(Cast::relative_time(0, 273600), "3 days, 4 hours ago");
+    /// assert_eq!(Cast::relative_time(90, 0), "in 1 minute, 30 seconds");
+    /// assert_eq!(Cast::relative_time(86400, 86400 * 366), "1 year ago");
+    /// assert_eq!(Cast::relative_time(42, 42), "just now");
+    /// ```
+    pub fn relative_time(timestamp: u64, now: u64) -> String {
+        const UNITS: [(&str, u64); 5] =
+            [("year", 31_536_000), ("day", 86_400), ("hour", 3_600), ("minute", 60), ("second", 1)];
+
+        let mut remaining = timestamp.abs_diff(now);
+        if remaining == 0 {
+            return "just now".to_string()
+        }
+
+        let parts = UNITS
+            .iter()
+            .filter_map(|(unit, seconds)| {
+                let count = remaining / seconds;
+                remaining %= seconds;
+                (count > 0).then(|| {
+                    let plural = if count == 1 { "" } else { "s" };
+                    format!("{count} {unit}{plural}")
+                })
+            })
+            .take(2)
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        if timestamp < now {
+            format!("{parts} ago")
+        } else {
+            format!("in {parts}")
+        }
+    }
+
     /// Converts UTF-8 text input to hex
     ///
     /// ```
@@ -680,6 +1277,29 @@ impl SimpleCast {
         let s: String = s.as_bytes().to_hex();
         format!("0x{s}")
     }
+    /// Decodes ABI encoded revert data, as returned by a reverted call.
+    ///
+    /// The builtin `Error(string)` and `Panic(uint256)` errors are decoded directly, other
+    /// selectors are looked up as custom errors in the local signature cache or at
+    /// `sig.eth.samczsun.com`.
+    ///
+    /// ```no_run
+    /// use cast::SimpleCast as Cast;
+    ///
+    /// # async fn foo() -> eyre::Result<()> {
+    /// let panic = "0x4e487b710000000000000000000000000000000000000000000000000000000000000011";
+    /// assert_eq!(
+    ///     Cast::decode_error(panic, false).await?,
+    ///     "Panic(0x11): Arithmetic over/underflow"
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn decode_error(data: &str, offline: bool) -> Result<String> {
+        let data = hex::decode(strip_0x(data.trim())).wrap_err("Invalid hex revert data")?;
+        decode_revert_data(&data, offline).await
+    }
+
     /// Generates an interface in solidity from either a local file ABI or a verified contract on
     /// Etherscan. It returns a vector of [`InterfaceSource`] structs that contain the source of the
     /// interface and their name.
@@ -914,16 +1534,93 @@ impl SimpleCast {
         foundry_utils::abi_decode(sig, calldata, input)
     }
 
-    /// Performs ABI encoding based off of the function signature. Does not include
-    /// the function selector in the result.
+    /// Decodes a log of the event with the given signature.
+    ///
+    /// Indexed parameters are decoded from the `topics`, all others from the `data`. If the
+    /// signature does not mark any parameter as `indexed`, the indexed parameters are guessed from
+    /// the number of topics. Anonymous events have no signature topic, so all topics are values
+    /// of indexed parameters.
+    ///
+    /// Returns the name and value of every parameter, in the order of the signature. Parameters
+    /// without a name are named by their position.
     ///
     /// ```
-    /// # use cast::SimpleCast as Cast;
+    /// use cast::SimpleCast as Cast;
     ///
     /// # fn main() -> eyre::Result<()> {
+    ///     let sig = "Transfer(address indexed from, address indexed to, uint256 value)";
+    ///     let topics = [
+    ///         "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef",
+    ///         "0x0000000000000000000000008dbd1b711dc621e1404633da156fcc779e1c6f3e",
+    ///         "0x000000000000000000000000d9f3c9cc99548bf3b44a43e0a2d07399eb918adc",
+    ///     ];
+    ///     let data = "0x000000000000000000000000000000000000000000000000000000000000002a";
+    ///     let decoded = Cast::decode_event(sig, &topics, data, false)?;
+    ///     let decoded = decoded
+    ///         .iter()
+    ///         .map(|(name, value)| format!("{name}: {value}"))
+    ///         .collect::<Vec<_>>();
     ///     assert_eq!(
-    ///         "0x0000000000000000000000000000000000000000000000000000000000000001",
-    ///         Cast::abi_encode("f(uint a)", &["1"]).unwrap().as_str()
+    ///         decoded,
+    ///         vec![
+    ///             "from: 8dbd1b711dc621e1404633da156fcc779e1c6f3e",
+    ///             "to: d9f3c9cc99548bf3b44a43e0a2d07399eb918adc",
+    ///             "value: 2a"
+    ///         ]
+    ///     );
+    ///
+    ///     # Ok(())
+    /// }
+    /// ```
+    pub fn decode_event(
+        sig: &str,
+        topics: &[impl AsRef<str>],
+        data: &str,
+        anonymous: bool,
+    ) -> Result<Vec<(String, Token)>> {
+        let mut event = HumanReadableParser::parse_event(sig)
+            .wrap_err_with(|| format!("Could not parse event signature `{sig}`"))?;
+        event.anonymous |= anonymous;
+
+        let topics = topics
+            .iter()
+            .map(|topic| {
+                let topic = topic.as_ref();
+                H256::from_str(topic).wrap_err_with(|| format!("Invalid topic `{topic}`"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let data =
+            hex::decode(data.strip_prefix("0x").unwrap_or(data)).wrap_err("Invalid log data")?;
+        let log = RawLog { topics, data };
+
+        if !event.inputs.iter().any(|param| param.indexed) {
+            event = foundry_utils::get_indexed_event(event, &log);
+        }
+
+        let params = event
+            .parse_log(log)
+            .wrap_err_with(|| format!("Could not decode the log as `{}`", event.name))?
+            .params
+            .into_iter()
+            .enumerate()
+            .map(|(i, param)| {
+                let name = if param.name.is_empty() { i.to_string() } else { param.name };
+                (name, param.value)
+            })
+            .collect();
+        Ok(params)
+    }
+
+    /// Performs ABI encoding based off of the function signature. Does not include
+    /// the function selector in the result.
+    ///
+    /// ```
+    /// # use cast::SimpleCast as Cast;
+    ///
+    /// # fn main() -> eyre::Result<()> {
+    ///     assert_eq!(
+    ///         "0x0000000000000000000000000000000000000000000000000000000000000001",
+    ///         Cast::abi_encode("f(uint a)", &["1"]).unwrap().as_str()
     ///     );
     ///     assert_eq!(
     ///         "0x0000000000000000000000000000000000000000000000000000000000000001",
@@ -933,29 +1630,56 @@ impl SimpleCast {
     /// # }
     /// ```
     pub fn abi_encode(sig: &str, args: &[impl AsRef<str>]) -> Result<String> {
-        let func = match HumanReadableParser::parse_function(sig) {
-            Ok(func) => func,
-            Err(err) => {
-                if let Ok(constructor) = HumanReadableParser::parse_constructor(sig) {
-                    #[allow(deprecated)]
-                    Function {
-                        name: "constructor".to_string(),
-                        inputs: constructor.inputs,
-                        outputs: vec![],
-                        constant: None,
-                        state_mutability: Default::default(),
-                    }
-                } else {
-                    // we return the `Function` parse error as this case is more likely
-                    return Err(err.into())
-                }
-            }
-        };
+        let func = parse_encode_signature(sig)?;
         let calldata = encode_args(&func, args)?.to_hex::<String>();
         let encoded = &calldata[8..];
         Ok(format!("0x{encoded}"))
     }
 
+    /// Performs non-standard packed ABI encoding of the input data, matching Solidity's
+    /// `abi.encodePacked`. Structs and arrays of dynamic types are rejected, as their packed
+    /// encoding is ambiguous.
+    ///
+    /// ```
+    /// # use cast::SimpleCast as Cast;
+    ///
+    /// # fn main() -> eyre::Result<()> {
+    ///     assert_eq!(
+    ///         "0x0001",
+    ///         Cast::abi_encode_packed("f(uint8,uint8)", &["0", "1"]).unwrap().as_str()
+    ///     );
+    ///     assert_eq!(
+    ///         "0x68656c6c6f01",
+    ///         Cast::abi_encode_packed("f(string,bool)", &["hello", "true"]).unwrap().as_str()
+    ///     );
+    /// #    Ok(())
+    /// # }
+    /// ```
+    pub fn abi_encode_packed(sig: &str, args: &[impl AsRef<str>]) -> Result<String> {
+        let func = parse_encode_signature(sig)?;
+        if func.inputs.len() != args.len() {
+            eyre::bail!(
+                "expected {} argument(s) for {}, got {}",
+                func.inputs.len(),
+                sig,
+                args.len()
+            )
+        }
+        let params = func
+            .inputs
+            .iter()
+            .zip(args)
+            .map(|(input, arg)| (&input.kind, arg.as_ref()))
+            .collect::<Vec<_>>();
+        let tokens = foundry_utils::parse_tokens(params, true)?;
+
+        let mut encoded = Vec::new();
+        for (input, token) in func.inputs.iter().zip(&tokens) {
+            encode_packed_token(&input.kind, token, false, &mut encoded)?;
+        }
+        Ok(format!("0x{}", encoded.to_hex::<String>()))
+    }
+
     /// Converts decimal input to hex
     ///
     /// ```
@@ -1058,23 +1782,240 @@ impl SimpleCast {
     }
 
     pub fn left_shift(value: &str, bits: &str, base_in: u32) -> Result<U256> {
-        let value = U256::from_str_radix(value, base_in)
-            .wrap_err("Cannot parse input as base {base_in}")?;
+        let value = Self::parse_uint(value, base_in)?;
         let bits = U256::from_dec_str(bits).wrap_err("Cannot parse bits input")?;
 
         Ok(value.shl(bits))
     }
 
     pub fn right_shift(value: &str, bits: &str, base_in: u32) -> Result<U256> {
-        let value = U256::from_str_radix(value, base_in)
-            .wrap_err("Cannot parse input as base {base_in}")?;
+        let value = Self::parse_uint(value, base_in)?;
         let bits = U256::from_dec_str(bits).wrap_err("Cannot parse bits input")?;
 
         Ok(value.shr(bits))
     }
 
+    /// Parses a numeric base, either a number from 2 to 36 or one of `bin`, `oct`, `dec` and
+    /// `hex`
+    ///
+    /// ```
+    /// use cast::SimpleCast as Cast;
+    ///
+    /// assert_eq!(Cast::parse_base("hex").unwrap(), 16);
+    /// assert_eq!(Cast::parse_base("36").unwrap(), 36);
+    /// assert!(Cast::parse_base("37").is_err());
+    /// ```
+    pub fn parse_base(base: &str) -> Result<u32> {
+        match base {
+            "bin" => Ok(2),
+            "oct" => Ok(8),
+            "dec" => Ok(10),
+            "hex" => Ok(16),
+            _ => match base.parse::<u32>() {
+                Ok(base) if (2..=36).contains(&base) => Ok(base),
+                _ => eyre::bail!(
+                    "Invalid base `{base}`: expected a number from 2 to 36, bin, oct, dec or hex"
+                ),
+            },
+        }
+    }
+
+    /// Parses an unsigned integer in the given base.
+    ///
+    /// A `0b`, `0o` or `0x` prefix is allowed for base 2, 8 and 16. Digits above 9 are letters,
+    /// in upper or lower case. Fails at the first digit that is not valid in the base, naming the
+    /// digit and its position.
+    ///
+    /// ```
+    /// use cast::SimpleCast as Cast;
+    /// use ethers_core::types::U256;
+    ///
+    /// assert_eq!(Cast::parse_uint("0b101", 2).unwrap(), U256::from(5));
+    /// assert_eq!(Cast::parse_uint("zz", 36).unwrap(), U256::from(1295));
+    /// assert_eq!(
+    ///     Cast::parse_uint("1021", 2).unwrap_err().to_string(),
+    ///     "Invalid digit `2` at position 2 of `1021` for base 2"
+    /// );
+    /// ```
+    pub fn parse_uint(value: &str, base: u32) -> Result<U256> {
+        if !(2..=36).contains(&base) {
+            eyre::bail!("Invalid base {base}: expected a number from 2 to 36")
+        }
+        let prefix = match base {
+            2 => "0b",
+            8 => "0o",
+            16 => "0x",
+            _ => "",
+        };
+        let digits =
+            if prefix.is_empty() { value } else { value.strip_prefix(prefix).unwrap_or(value) };
+        if digits.is_empty() {
+            eyre::bail!("Cannot parse `{value}` as base {base}: no digits")
+        }
+
+        let offset = value.len() - digits.len();
+        digits.char_indices().try_fold(U256::zero(), |acc, (i, c)| {
+            let digit = c.to_digit(base).ok_or_else(|| {
+                eyre::eyre!(
+                    "Invalid digit `{c}` at position {} of `{value}` for base {base}",
+                    offset + i
+                )
+            })?;
+            acc.checked_mul(base.into())
+                .and_then(|acc| acc.checked_add(digit.into()))
+                .ok_or_else(|| eyre::eyre!("`{value}` does not fit into 256 bits"))
+        })
+    }
+
+    /// Formats an unsigned integer in the given base.
+    ///
+    /// Base 2, 8 and 16 are prefixed with `0b`, `0o` and `0x`, digits above 9 are lower case
+    /// letters.
+    ///
+    /// ```
+    /// use cast::SimpleCast as Cast;
+    /// use ethers_core::types::U256;
+    ///
+    /// assert_eq!(Cast::format_uint(U256::from(5), 2).unwrap(), "0b101");
+    /// assert_eq!(Cast::format_uint(U256::from(1295), 36).unwrap(), "zz");
+    /// ```
+    pub fn format_uint(value: U256, base: u32) -> Result<String> {
+        if !(2..=36).contains(&base) {
+            eyre::bail!("Invalid base {base}: expected a number from 2 to 36")
+        }
+        let prefix = match base {
+            2 => "0b",
+            8 => "0o",
+            16 => "0x",
+            _ => "",
+        };
+        if value.is_zero() {
+            return Ok(format!("{prefix}0"))
+        }
+
+        let mut digits = Vec::new();
+        let mut value = value;
+        let base_u256 = U256::from(base);
+        while !value.is_zero() {
+            let digit = (value % base_u256).as_u32();
+            digits.push(std::char::from_digit(digit, base).expect("digit is below base"));
+            value /= base_u256;
+        }
+        Ok(prefix.chars().chain(digits.into_iter().rev()).collect())
+    }
+
+    /// Converts an unsigned integer from one base to another.
+    ///
+    /// If no input base is given, it is detected from a `0b`, `0o` or `0x` prefix, without prefix
+    /// the value is decimal.
+    ///
+    /// ```
+    /// use cast::SimpleCast as Cast;
+    ///
+    /// assert_eq!(Cast::to_base("0xff", None, 2).unwrap(), "0b11111111");
+    /// assert_eq!(Cast::to_base("255", None, 16).unwrap(), "0xff");
+    /// assert_eq!(Cast::to_base("zz", Some(36), 10).unwrap(), "1295");
+    /// ```
+    pub fn to_base(value: &str, base_in: Option<u32>, base_out: u32) -> Result<String> {
+        let base_in = base_in.unwrap_or_else(|| {
+            if value.starts_with("0x") {
+                16
+            } else if value.starts_with("0o") {
+                8
+            } else if value.starts_with("0b") {
+                2
+            } else {
+                10
+            }
+        });
+        Self::format_uint(Self::parse_uint(value, base_in)?, base_out)
+    }
+
+    /// Returns the number of decimals of an ether unit
+    ///
+    /// ```
+    /// use cast::SimpleCast as Cast;
+    ///
+    /// fn main() -> eyre::Result<()> {
+    ///     assert_eq!(Cast::unit_decimals("wei")?, 0);
+    ///     assert_eq!(Cast::unit_decimals("gwei")?, 9);
+    ///     assert_eq!(Cast::unit_decimals("finney")?, 15);
+    ///     assert_eq!(Cast::unit_decimals("ether")?, 18);
+    ///     assert!(Cast::unit_decimals("foo").is_err());
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn unit_decimals(unit: &str) -> Result<u32> {
+        Ok(match unit.to_lowercase().as_str() {
+            "wei" => 0,
+            "kwei" | "babbage" | "femtoether" => 3,
+            "mwei" | "lovelace" | "picoether" => 6,
+            "gwei" | "shannon" | "nano" | "nanoether" => 9,
+            "szabo" | "micro" | "microether" => 12,
+            "finney" | "milli" | "milliether" => 15,
+            "eth" | "ether" => 18,
+            _ => eyre::bail!(
+                "invalid unit `{}`, expected one of wei, kwei, mwei, gwei, szabo, finney or ether",
+                unit
+            ),
+        })
+    }
+
+    /// Parses a decimal amount into an integer with `decimals` decimals, without any rounding
+    ///
+    /// ```
+    /// use cast::SimpleCast as Cast;
+    /// use ethers_core::types::U256;
+    ///
+    /// fn main() -> eyre::Result<()> {
+    ///     assert_eq!(Cast::parse_decimals("1.5", 6)?, U256::from(1_500_000));
+    ///     assert_eq!(Cast::parse_decimals("0.000001", 6)?, U256::from(1));
+    ///     assert_eq!(Cast::parse_decimals("42", 0)?, U256::from(42));
+    ///     assert!(Cast::parse_decimals("0.0000001", 6).is_err());
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn parse_decimals(value: &str, decimals: u32) -> Result<U256> {
+        let value = value.trim();
+        let (int, frac) = value.split_once('.').unwrap_or((value, ""));
+        if (int.is_empty() && frac.is_empty()) ||
+            !int.chars().chain(frac.chars()).all(|c| c.is_ascii_digit())
+        {
+            eyre::bail!("invalid decimal amount `{}`", value)
+        }
+
+        // trailing zeros don't change the amount
+        let frac = frac.trim_end_matches('0');
+        if frac.len() > decimals as usize {
+            eyre::bail!("`{}` has more than {} decimals", value, decimals)
+        }
+        let digits = format!("{}{}{}", int, frac, "0".repeat(decimals as usize - frac.len()));
+        U256::from_dec_str(&digits).map_err(|_| eyre::eyre!("`{}` does not fit in 256 bits", value))
+    }
+
+    /// Formats an integer with `decimals` decimals as a decimal amount, without any rounding
+    ///
+    /// ```
+    /// use cast::SimpleCast as Cast;
+    ///
+    /// fn main() -> eyre::Result<()> {
+    ///     assert_eq!(Cast::format_decimals(1_500_000.into(), 6)?, "1.500000");
+    ///     assert_eq!(Cast::format_decimals(1.into(), 6)?, "0.000001");
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn format_decimals(value: U256, decimals: u32) -> Result<String> {
+        Ok(ethers_core::utils::format_units(value, decimals)?)
+    }
+
     /// Converts an eth amount into a specified unit
     ///
+    /// The amount can be tagged with a unit, e.g. "1.5ether" or "100 gwei", otherwise it is in
+    /// wei.
+    ///
     /// ```
     /// use cast::SimpleCast as Cast;
     ///
@@ -1083,22 +2024,44 @@ impl SimpleCast {
     ///     assert_eq!(Cast::to_unit("1".to_string(), "wei".to_string())?, "1");
     ///     assert_eq!(Cast::to_unit("1ether".to_string(), "wei".to_string())?, "1000000000000000000");
     ///     assert_eq!(Cast::to_unit("100 gwei".to_string(), "gwei".to_string())?, "100");
+    ///     assert_eq!(Cast::to_unit("1.5 finney".to_string(), "szabo".to_string())?, "1500");
+    ///     assert_eq!(Cast::to_unit("1 kwei".to_string(), "ether".to_string())?, "0.000000000000001000");
     ///
     ///     Ok(())
     /// }
     /// ```
     pub fn to_unit(value: String, unit: String) -> Result<String> {
-        let value = U256::from(LenientTokenizer::tokenize_uint(&value)?);
-
-        Ok(match &unit[..] {
-            "ether" => ethers_core::utils::format_units(value, 18)?
-                .trim_end_matches(".000000000000000000")
-                .to_string(),
-            "gwei" | "nano" | "nanoether" => ethers_core::utils::format_units(value, 9)?
-                .trim_end_matches(".000000000")
-                .to_string(),
-            "wei" => ethers_core::utils::format_units(value, 0)?.trim_end_matches(".0").to_string(),
-            _ => return Err(eyre::eyre!("invalid unit")),
+        Self::to_unit_decimals(&value, Self::unit_decimals(&unit)?)
+    }
+
+    /// Converts an eth amount into an amount with `decimals` decimals, see
+    /// [`SimpleCast::to_unit`]
+    ///
+    /// ```
+    /// use cast::SimpleCast as Cast;
+    ///
+    /// fn main() -> eyre::Result<()> {
+    ///     assert_eq!(Cast::to_unit_decimals("1234567", 6)?, "1.234567");
+    ///     assert_eq!(Cast::to_unit_decimals("2000000", 6)?, "2");
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn to_unit_decimals(value: &str, decimals: u32) -> Result<String> {
+        let value = value.trim();
+        let number = value.trim_end_matches(|c: char| c.is_ascii_alphabetic()).trim_end();
+        let value = if number.len() < value.len() && !value.starts_with("0x") {
+            let unit = value[number.len()..].trim();
+            Self::parse_decimals(number, Self::unit_decimals(unit)?)?
+        } else {
+            U256::from(LenientTokenizer::tokenize_uint(value)?)
+        };
+
+        let formatted = Self::format_decimals(value, decimals)?;
+        // amounts without a fraction are printed as integers
+        Ok(match formatted.split_once('.') {
+            Some((int, frac)) if frac.chars().all(|c| c == '0') => int.to_string(),
+            _ => formatted,
         })
     }
 
@@ -1108,22 +2071,18 @@ impl SimpleCast {
     /// use cast::SimpleCast as Cast;
     ///
     /// fn main() -> eyre::Result<()> {
-    ///     assert_eq!(Cast::to_wei(1.into(), "".to_string())?, "1000000000000000000");
-    ///     assert_eq!(Cast::to_wei(100.into(), "gwei".to_string())?, "100000000000");
-    ///     assert_eq!(Cast::to_wei(100.into(), "eth".to_string())?, "100000000000000000000");
-    ///     assert_eq!(Cast::to_wei(1000.into(), "ether".to_string())?, "1000000000000000000000");
+    ///     assert_eq!(Cast::to_wei("1", "ether")?, "1000000000000000000");
+    ///     assert_eq!(Cast::to_wei("100", "gwei")?, "100000000000");
+    ///     assert_eq!(Cast::to_wei("100", "eth")?, "100000000000000000000");
+    ///     assert_eq!(Cast::to_wei("1000", "ether")?, "1000000000000000000000");
+    ///     assert_eq!(Cast::to_wei("0.123456789123456789", "ether")?, "123456789123456789");
+    ///     assert_eq!(Cast::to_wei("2.5", "szabo")?, "2500000000000");
     ///
     ///     Ok(())
     /// }
     /// ```
-    pub fn to_wei(value: f64, unit: String) -> Result<String> {
-        let value = value.to_string();
-        Ok(match &unit[..] {
-            "gwei" => ethers_core::utils::parse_units(value, 9),
-            "eth" | "ether" => ethers_core::utils::parse_units(value, 18),
-            _ => ethers_core::utils::parse_units(value, 18),
-        }?
-        .to_string())
+    pub fn to_wei(value: &str, unit: &str) -> Result<String> {
+        Ok(Self::parse_decimals(value, Self::unit_decimals(unit)?)?.to_string())
     }
 
     /// Converts wei into an eth amount
@@ -1132,24 +2091,30 @@ impl SimpleCast {
     /// use cast::SimpleCast as Cast;
     ///
     /// fn main() -> eyre::Result<()> {
-    ///     assert_eq!(Cast::from_wei(1.into(), "gwei".to_string())?, "0.000000001");
-    ///     assert_eq!(Cast::from_wei(12340000005u64.into(), "gwei".to_string())?, "12.340000005");
-    ///     assert_eq!(Cast::from_wei(10.into(), "ether".to_string())?, "0.000000000000000010");
-    ///     assert_eq!(Cast::from_wei(100.into(), "eth".to_string())?, "0.000000000000000100");
-    ///     assert_eq!(Cast::from_wei(17.into(), "".to_string())?, "0.000000000000000017");
+    ///     assert_eq!(Cast::from_wei(1.into(), "gwei")?, "0.000000001");
+    ///     assert_eq!(Cast::from_wei(12340000005u64.into(), "gwei")?, "12.340000005");
+    ///     assert_eq!(Cast::from_wei(10.into(), "ether")?, "0.000000000000000010");
+    ///     assert_eq!(Cast::from_wei(100.into(), "eth")?, "0.000000000000000100");
+    ///     assert_eq!(Cast::from_wei(1234.into(), "kwei")?, "1.234");
     ///
     ///     Ok(())
     /// }
     /// ```
-    pub fn from_wei(value: U256, unit: String) -> Result<String> {
-        Ok(match &unit[..] {
-            "gwei" => ethers_core::utils::format_units(value, 9),
-            "eth" | "ether" => ethers_core::utils::format_units(value, 18),
-            _ => ethers_core::utils::format_units(value, 18),
-        }?)
+    pub fn from_wei(value: U256, unit: &str) -> Result<String> {
+        Self::format_decimals(value, Self::unit_decimals(unit)?)
     }
 
-    /// Encodes hex data or list of hex data to hexadecimal rlp
+    /// Encodes a value, or nested lists of values, to hexadecimal rlp
+    ///
+    /// The input is JSON, where
+    /// - lists are encoded as RLP lists
+    /// - `0x` prefixed strings are encoded as raw bytes
+    /// - numbers and unprefixed decimal strings are encoded as integers, i.e. as their minimal big
+    ///   endian bytes. Zero is the empty byte string
+    /// - `null` and `""` are encoded as the empty byte string
+    ///
+    /// This is the output format of [`SimpleCast::from_rlp`], so its output can be encoded again.
+    /// With `as_int`, this only holds for integers without leading zero bytes.
     ///
     /// ```
     /// use cast::SimpleCast as Cast;
@@ -1158,7 +2123,9 @@ impl SimpleCast {
     ///     assert_eq!(Cast::to_rlp("[]").unwrap(),"0xc0".to_string());
     ///     assert_eq!(Cast::to_rlp("0x22").unwrap(),"0x22".to_string());
     ///     assert_eq!(Cast::to_rlp("[\"0x61\"]",).unwrap(), "0xc161".to_string());
-    ///     assert_eq!(Cast::to_rlp( "[\"0xf1\",\"f2\"]").unwrap(), "0xc481f181f2".to_string());
+    ///     assert_eq!(Cast::to_rlp( "[\"0xf1\",242]").unwrap(), "0xc481f181f2".to_string());
+    ///     assert_eq!(Cast::to_rlp("[0,\"1024\"]").unwrap(), "0xc480820400".to_string());
+    ///     assert!(Cast::to_rlp("[\"f2\"]").is_err());
     ///     Ok(())
     /// }
     /// ```
@@ -1168,26 +2135,32 @@ impl SimpleCast {
         Ok(format!("0x{}", hex::encode(rlp::encode(&item))))
     }
 
-    /// Decodes rlp encoded list with hex data
+    /// Decodes rlp encoded data into JSON, preserving the structure of nested lists.
+    ///
+    /// Byte strings are output as hex, unless `as_int` is set, in which case they are interpreted
+    /// as integers. The hex output can be encoded again with [`SimpleCast::to_rlp`].
     ///
     /// ```
     /// use cast::SimpleCast as Cast;
     ///
     /// fn main() -> eyre::Result<()> {
-    ///     assert_eq!(Cast::from_rlp("0xc0".to_string()).unwrap(), "[]");
-    ///     assert_eq!(Cast::from_rlp("0x0f".to_string()).unwrap(), "\"0x0f\"");
-    ///     assert_eq!(Cast::from_rlp("0x33".to_string()).unwrap(), "\"0x33\"");
-    ///     assert_eq!(Cast::from_rlp("0xc161".to_string()).unwrap(), "[\"0x61\"]");
-    ///     assert_eq!(Cast::from_rlp("0xc26162".to_string()).unwrap(), "[\"0x61\",\"0x62\"]");
+    ///     assert_eq!(Cast::from_rlp("0xc0", false).unwrap(), "[]");
+    ///     assert_eq!(Cast::from_rlp("0x0f", false).unwrap(), "\"0x0f\"");
+    ///     assert_eq!(Cast::from_rlp("0x33", false).unwrap(), "\"0x33\"");
+    ///     assert_eq!(Cast::from_rlp("0xc161", false).unwrap(), "[\"0x61\"]");
+    ///     assert_eq!(Cast::from_rlp("0xc26162", false).unwrap(), "[\"0x61\",\"0x62\"]");
+    ///     assert_eq!(Cast::from_rlp("0xc5c4820100c0", false).unwrap(), "[[\"0x0100\",[]]]");
+    ///     assert_eq!(Cast::from_rlp("0xc5c4820100c0", true).unwrap(), "[[256,[]]]");
     ///     Ok(())
     /// }
     /// ```
-    pub fn from_rlp(value: impl AsRef<str>) -> Result<String> {
+    pub fn from_rlp(value: impl AsRef<str>, as_int: bool) -> Result<String> {
         let value = value.as_ref();
         let striped_value = strip_0x(value);
-        let bytes = hex::decode(striped_value).expect("Could not decode hex");
-        let item = rlp::decode::<Item>(&bytes).expect("Could not decode rlp");
-        Ok(format!("{}", item))
+        let bytes = hex::decode(striped_value).wrap_err("Could not decode hex")?;
+        let item = rlp::decode::<Item>(&bytes)
+            .map_err(|err| eyre::eyre!("Could not decode rlp: {err}"))?;
+        Ok(item.to_value(as_int).to_string())
     }
 
     /// Converts an Ethereum address to its checksum format
@@ -1210,6 +2183,111 @@ impl SimpleCast {
         Ok(utils::to_checksum(address, None))
     }
 
+    /// Converts an Ethereum address to its checksum format, salted with the chain id according to
+    /// [EIP-1191](https://github.com/ethereum/EIPs/blob/master/EIPS/eip-1191.md) if one is given,
+    /// or according to EIP-55 otherwise
+    ///
+    /// ```
+    /// use cast::SimpleCast as Cast;
+    /// use ethers_core::types::Address;
+    /// use std::str::FromStr;
+    ///
+    /// # fn main() -> eyre::Result<()> {
+    /// let addr = Address::from_str("0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed")?;
+    /// assert_eq!(
+    ///     Cast::checksum_address_with_chain_id(&addr, Some(30)),
+    ///     "0x5aaEB6053f3e94c9b9a09f33669435E7ef1bEAeD"
+    /// );
+    /// assert_eq!(
+    ///     Cast::checksum_address_with_chain_id(&addr, None),
+    ///     Cast::checksum_address(&addr)?
+    /// );
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn checksum_address_with_chain_id(address: &Address, chain_id: Option<u64>) -> String {
+        let addr = format!("{address:x}");
+        let hash = match chain_id {
+            Some(chain_id) => keccak256(format!("{chain_id}0x{addr}")),
+            None => keccak256(&addr),
+        };
+        let checksummed = addr
+            .chars()
+            .enumerate()
+            .map(|(i, c)| {
+                let nibble = if i % 2 == 0 { hash[i / 2] >> 4 } else { hash[i / 2] & 0xf };
+                if nibble >= 8 {
+                    c.to_ascii_uppercase()
+                } else {
+                    c
+                }
+            })
+            .collect::<String>();
+        format!("0x{checksummed}")
+    }
+
+    /// Returns `true` if `address` is mixed-case, i.e. claims to be checksummed, but doesn't match
+    /// the checksum of [`Self::checksum_address_with_chain_id`]
+    ///
+    /// ```
+    /// use cast::SimpleCast as Cast;
+    ///
+    /// # fn main() -> eyre::Result<()> {
+    /// assert!(!Cast::has_invalid_checksum("0xB7e390864a90b7b923C9f9310C6F98aafE43F707", None)?);
+    /// assert!(Cast::has_invalid_checksum("0xb7E390864a90b7b923C9f9310C6F98aafE43F707", None)?);
+    /// assert!(!Cast::has_invalid_checksum("0xb7e390864a90b7b923c9f9310c6f98aafe43f707", None)?);
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn has_invalid_checksum(address: &str, chain_id: Option<u64>) -> Result<bool> {
+        let address = address.trim();
+        let hex = strip_0x(address);
+        let parsed =
+            Address::from_str(hex).wrap_err_with(|| format!("Invalid address: {address}"))?;
+        let mixed_case = hex.chars().any(|c| c.is_ascii_lowercase()) &&
+            hex.chars().any(|c| c.is_ascii_uppercase());
+        Ok(mixed_case && strip_0x(&Self::checksum_address_with_chain_id(&parsed, chain_id)) != hex)
+    }
+
+    /// Computes the address of a contract deployed with `CREATE2` by `deployer`, i.e.
+    /// `keccak256(0xff ++ deployer ++ salt ++ keccak256(init_code))[12:]`.
+    ///
+    /// The salt is hex and left padded to 32 bytes, the init code is hex.
+    ///
+    /// ```
+    /// use cast::SimpleCast as Cast;
+    /// use ethers_core::types::Address;
+    /// use std::str::FromStr;
+    ///
+    /// # fn main() -> eyre::Result<()> {
+    /// let deployer = Address::from_str("0xdeadbeef00000000000000000000000000000000")?;
+    /// let addr = Cast::compute_create2_address(deployer, "0x00", "0x00")?;
+    /// assert_eq!(Cast::checksum_address(&addr)?, "0xB928f69Bb1D91Cd65274e3c79d8986362984fDA3");
+    ///
+    /// let salt = "0x000000000000000000000000feed000000000000000000000000000000000000";
+    /// let addr = Cast::compute_create2_address(deployer, salt, "0x00")?;
+    /// assert_eq!(Cast::checksum_address(&addr)?, "0xD04116cDd17beBE565EB2422F2497E06cC1C9833");
+    ///
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn compute_create2_address(
+        deployer: Address,
+        salt: &str,
+        init_code: &str,
+    ) -> Result<Address> {
+        let salt = strip_0x(salt.trim());
+        if salt.len() > 64 {
+            eyre::bail!("salt >32 bytes");
+        }
+        let salt = H256::from_str(&format!("{salt:0>64}")).wrap_err("Invalid hex salt")?;
+        let init_code =
+            hex::decode(strip_0x(init_code.trim())).wrap_err("Invalid hex init code")?;
+        Ok(utils::get_create2_address(deployer, salt, init_code))
+    }
+
     /// Converts hexdata into bytes32 value
     /// ```
     /// use cast::SimpleCast as Cast;
@@ -1262,6 +2340,51 @@ impl SimpleCast {
         Ok(format!("0x{hash}"))
     }
 
+    /// Keccak-256 hashes hex data, with or without a `0x` prefix
+    ///
+    /// ```
+    /// use cast::SimpleCast as Cast;
+    ///
+    /// fn main() -> eyre::Result<()> {
+    ///     assert_eq!(Cast::keccak_hex("12")?, "0x5fa2358263196dbbf23d1ca7a509451f7a2f64c15837bfbb81298b1e3e24e4fa");
+    ///     assert_eq!(Cast::keccak_hex("0x12")?, "0x5fa2358263196dbbf23d1ca7a509451f7a2f64c15837bfbb81298b1e3e24e4fa");
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn keccak_hex(data: &str) -> Result<String> {
+        let bytes = hex::decode(strip_0x(data)).wrap_err("Invalid hex data")?;
+        Ok(format!("0x{}", keccak256(bytes).to_hex::<String>()))
+    }
+
+    /// Keccak-256 hashes everything read from `reader`, without buffering all of it in memory
+    ///
+    /// ```
+    /// use cast::SimpleCast as Cast;
+    ///
+    /// fn main() -> eyre::Result<()> {
+    ///     assert_eq!(Cast::keccak_reader("foo".as_bytes())?, Cast::keccak("foo")?);
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn keccak_reader(mut reader: impl Read) -> Result<String> {
+        let mut hasher = Keccak::v256();
+        let mut buf = [0u8; 64 * 1024];
+        loop {
+            let read = match reader.read(&mut buf) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err.into()),
+            };
+            hasher.update(&buf[..read]);
+        }
+        let mut hash = [0u8; 32];
+        hasher.finalize(&mut hash);
+        Ok(format!("0x{}", hash.to_hex::<String>()))
+    }
+
     /// Converts ENS names to their namehash representation
     /// [Namehash reference](https://docs.ens.domains/contract-api-reference/name-processing#hashing-names)
     /// [namehash-rust reference](https://github.com/InstateDev/namehash-rust/blob/master/src/lib.rs)
@@ -1350,8 +2473,27 @@ impl SimpleCast {
         Ok(code)
     }
 
+    /// Returns the name of the contract at `contract_address` if it is verified on Etherscan
+    pub async fn etherscan_name(
+        chain: Chain,
+        contract_address: Address,
+        etherscan_api_key: String,
+    ) -> Result<Option<String>> {
+        let client = Client::new(chain, etherscan_api_key)?;
+        let meta = client.contract_source_code(contract_address).await?;
+        Ok(meta
+            .items
+            .into_iter()
+            .map(|item| item.contract_name)
+            .find(|name| !name.is_empty()))
+    }
+
     /// Fetches the source code of verified contracts from etherscan and expands the resulting
     /// files to a directory for easy perusal.
+    ///
+    /// Contracts verified with multiple files keep their original layout: every source is written
+    /// at the path it was declared with in the Standard JSON input, and a `remappings.txt` is
+    /// generated so that the directory can be built with forge as is.
     /// ```
     /// # use cast::SimpleCast as Cast;
     /// # use ethers_core::types::Chain;
@@ -1370,11 +2512,65 @@ impl SimpleCast {
     ) -> eyre::Result<()> {
         let client = Client::new(chain, etherscan_api_key)?;
         let meta = client.contract_source_code(contract_address.parse()?).await?;
-        let source_tree = meta.source_tree()?;
-        source_tree.write_to(&output_directory)?;
+
+        let multi_file = meta.items.len() == 1 && meta.items[0].sources().len() > 1;
+        if !multi_file {
+            let source_tree = meta.source_tree()?;
+            source_tree.write_to(&output_directory)?;
+            return Ok(())
+        }
+
+        write_sources(&meta.items[0], &output_directory)?;
         Ok(())
     }
 
+    /// Fetches the source code of a verified contract from etherscan and flattens it into a single
+    /// file, like `forge flatten` does for local sources.
+    ///
+    /// The sources are flattened in a temporary directory. Their SPDX license identifiers are
+    /// consolidated into a single one at the top, and duplicate pragmas are removed.
+    /// ```no_run
+    /// # use cast::SimpleCast as Cast;
+    /// # use ethers_core::types::Chain;
+    ///
+    /// # async fn flatten() -> eyre::Result<()> {
+    /// let source = Cast::etherscan_source_flattened(
+    ///     Chain::Mainnet,
+    ///     "0xBB9bc244D798123fDe783fCc1C72d3Bb8C189413".to_string(),
+    ///     "<etherscan_api_key>".to_string(),
+    /// )
+    /// .await?;
+    /// println!("{}", source);
+    /// #    Ok(())
+    /// # }
+    /// ```
+    pub async fn etherscan_source_flattened(
+        chain: Chain,
+        contract_address: String,
+        etherscan_api_key: String,
+    ) -> Result<String> {
+        let client = Client::new(chain, etherscan_api_key)?;
+        let meta = client.contract_source_code(contract_address.parse()?).await?;
+
+        let multi_file = meta.items.len() == 1 && meta.items[0].sources().len() > 1;
+        if !multi_file {
+            let code = meta.source_code();
+            if code.is_empty() {
+                return Err(eyre::eyre!("unverified contract"))
+            }
+            return Ok(consolidate_flattened(&code))
+        }
+
+        let dir = std::env::temp_dir().join(format!(
+            "cast-flatten-{}-{}",
+            contract_address.to_lowercase(),
+            std::process::id()
+        ));
+        let flattened = flatten_sources(&meta.items[0], &dir);
+        let _ = std::fs::remove_dir_all(&dir);
+        Ok(consolidate_flattened(&flattened?))
+    }
+
     /// Prints the slot number for the specified mapping type and input data
     /// Uses abi_encode to pad the data to 32 bytes.
     /// For value types v, slot number of v is keccak256(concat(h(v) , p)) where h is the padding
@@ -1396,6 +2592,26 @@ impl SimpleCast {
         Ok(location)
     }
 
+    /// Returns the base storage slot of the ERC-7201 namespace with the given id.
+    ///
+    /// The slot is `keccak256(abi.encode(uint256(keccak256(id)) - 1)) & ~bytes32(uint256(0xff))`.
+    /// ```
+    /// # use cast::SimpleCast as Cast;
+    ///
+    /// assert_eq!(
+    ///     Cast::index_erc7201("example.main"),
+    ///     "0x183a6125c38840424c4a85fa12bab2ab606c4b6d0e7cc73c0c06ba5300eab500"
+    /// );
+    /// ```
+    pub fn index_erc7201(id: &str) -> String {
+        let id = U256::from_big_endian(&keccak256(id.as_bytes()));
+        let mut location = [0u8; 32];
+        id.overflowing_sub(U256::one()).0.to_big_endian(&mut location);
+        let mut slot = keccak256(location);
+        slot[31] = 0;
+        format!("0x{}", hex::encode(slot))
+    }
+
     /// Encodes string into bytes32 value
     pub fn format_bytes32_string(s: &str) -> Result<String> {
         let formatted = format_bytes32_string(s)?;
@@ -1415,6 +2631,378 @@ impl SimpleCast {
 
         Ok(parse_bytes32_string(&buffer)?.to_owned())
     }
+
+    /// Disassembles hex encoded EVM bytecode into one `offset: OPCODE [immediate]` line per
+    /// instruction.
+    ///
+    /// Offsets are hex byte offsets into the code and PUSH immediates are shown as hex. Bytes that
+    /// are not a known opcode are labeled `UNDEFINED(0x..)`, and a PUSH whose immediate runs past
+    /// the end of the code is marked as truncated.
+    ///
+    /// ```
+    /// # use cast::SimpleCast as Cast;
+    ///
+    /// assert_eq!(
+    ///     Cast::disassemble("0x6080604052").unwrap(),
+    ///     "00000000: PUSH1 0x80\n00000002: PUSH1 0x40\n00000004: MSTORE"
+    /// );
+    /// ```
+    pub fn disassemble(bytecode: &str) -> Result<String> {
+        let code = hex::decode(strip_0x(bytecode.trim())).wrap_err("Invalid bytecode")?;
+
+        let mut lines = Vec::new();
+        let mut pc = 0;
+        while pc < code.len() {
+            let op = code[pc];
+            let name = OpCode::try_from_u8(op)
+                .map_or_else(|| format!("UNDEFINED(0x{op:02x})"), |op| op.as_str().to_string());
+            let size = if (opcode::PUSH1..=opcode::PUSH32).contains(&op) {
+                (op - opcode::PUSH1 + 1) as usize
+            } else {
+                0
+            };
+
+            let mut line = format!("{pc:08x}: {name}");
+            if size > 0 {
+                let immediate = &code[(pc + 1).min(code.len())..(pc + 1 + size).min(code.len())];
+                line.push_str(&format!(" 0x{}", hex::encode(immediate)));
+                if immediate.len() < size {
+                    line.push_str(" (truncated)");
+                }
+            }
+            lines.push(line);
+            pc += 1 + size;
+        }
+        Ok(lines.join("\n"))
+    }
+}
+
+/// Writes the sources of a contract verified with multiple files to `output_directory`, with a
+/// `remappings.txt` to build them, and returns their paths relative to the directory.
+fn write_sources(item: &Metadata, output_directory: &Path) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for (name, entry) in item.sources() {
+        let path = source_file_path(&name)?;
+        let target = output_directory.join(&path);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&target, entry.content)
+            .wrap_err_with(|| format!("failed to write {}", target.display()))?;
+        paths.push(path);
+    }
+
+    let declared = serde_json::to_value(&item.source_code)?["settings"]["remappings"]
+        .as_array()
+        .map(|remappings| {
+            remappings.iter().filter_map(|r| r.as_str().map(str::to_owned)).collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    let remappings = project_remappings(declared, &paths);
+    if !remappings.is_empty() {
+        std::fs::write(output_directory.join("remappings.txt"), remappings.join("\n") + "\n")?;
+    }
+    Ok(paths)
+}
+
+/// Writes the sources of a contract verified with multiple files to `dir` and flattens the file
+/// that declares the contract.
+fn flatten_sources(item: &Metadata, dir: &Path) -> Result<String> {
+    let paths = write_sources(item, dir)?;
+    let target = paths
+        .iter()
+        .map(|path| dir.join(path))
+        .find(|path| {
+            std::fs::read_to_string(path)
+                .map_or(false, |source| declares_contract(&source, &item.contract_name))
+        })
+        .ok_or_else(|| eyre::eyre!("no source declares the contract {}", item.contract_name))?;
+
+    let mut config = Config::load_with_root(dir).sanitized();
+    config.src = dir.to_path_buf();
+    config
+        .project_paths()
+        .flatten(&target)
+        .map_err(|err| eyre::eyre!("Failed to flatten the sources: {err}"))
+}
+
+/// Returns `true` if `source` declares a contract, library or interface called `name`
+fn declares_contract(source: &str, name: &str) -> bool {
+    let is_ident = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '$';
+    ["contract", "library", "interface"].iter().any(|kind| {
+        let declaration = format!("{kind} {name}");
+        source.match_indices(&declaration).any(|(i, _)| {
+            !source[..i].chars().next_back().map_or(false, is_ident) &&
+                !source[i + declaration.len()..].chars().next().map_or(false, is_ident)
+        })
+    })
+}
+
+/// Replaces the SPDX license identifiers of a flattened source with a single one at the top that
+/// combines all distinct licenses, and removes repeated pragmas.
+fn consolidate_flattened(source: &str) -> String {
+    const SPDX: &str = "SPDX-License-Identifier:";
+
+    let mut licenses: Vec<&str> = Vec::new();
+    let mut pragmas = HashSet::new();
+    let mut lines = Vec::new();
+    for line in source.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("//") || trimmed.starts_with("/*") {
+            if let Some(pos) = trimmed.find(SPDX) {
+                let license = trimmed[pos + SPDX.len()..].trim_end_matches("*/").trim();
+                if !license.is_empty() && !licenses.contains(&license) {
+                    licenses.push(license);
+                }
+                continue
+            }
+        }
+        if trimmed.starts_with("pragma ") && !pragmas.insert(trimmed) {
+            continue
+        }
+        lines.push(line);
+    }
+
+    let mut consolidated = String::new();
+    match licenses.as_slice() {
+        [] => {}
+        [license] => consolidated.push_str(&format!("// {SPDX} {license}\n")),
+        licenses => {
+            let licenses = licenses
+                .iter()
+                .map(|license| {
+                    if license.contains(' ') {
+                        format!("({license})")
+                    } else {
+                        license.to_string()
+                    }
+                })
+                .collect::<Vec<_>>();
+            consolidated.push_str(&format!("// {SPDX} {}\n", licenses.join(" AND ")));
+        }
+    }
+    consolidated.push_str(&lines.join("\n"));
+    if source.ends_with('\n') {
+        consolidated.push('\n');
+    }
+    consolidated
+}
+
+/// Turns a source path declared in the Standard JSON input into a path relative to the output
+/// directory, rejecting paths that would escape it.
+fn source_file_path(declared: &str) -> Result<PathBuf> {
+    let mut path = PathBuf::new();
+    for component in Path::new(declared).components() {
+        match component {
+            Component::Normal(part) => path.push(part),
+            Component::CurDir | Component::RootDir | Component::Prefix(_) => {}
+            Component::ParentDir => eyre::bail!("invalid source path: {declared}"),
+        }
+    }
+    if path.as_os_str().is_empty() {
+        eyre::bail!("invalid source path: {declared}")
+    }
+    Ok(path)
+}
+
+/// Returns the remappings needed to build the expanded sources: the ones the contract was verified
+/// with, followed by an identity remapping for every top level directory so that imports that were
+/// resolved without a remapping keep resolving.
+fn project_remappings(declared: Vec<String>, paths: &[PathBuf]) -> Vec<String> {
+    let mut remappings = declared;
+    let roots = paths
+        .iter()
+        .filter(|path| path.components().count() > 1)
+        .filter_map(|path| path.components().next())
+        .map(|root| root.as_os_str().to_string_lossy().into_owned())
+        .collect::<std::collections::BTreeSet<_>>();
+    for root in roots {
+        let remapping = format!("{root}/={root}/");
+        if !remappings.iter().any(|r| r.starts_with(&format!("{root}/="))) {
+            remappings.push(remapping);
+        }
+    }
+    remappings
+}
+
+/// Converts decoded values into JSON.
+///
+/// If all values are named, the result is an object keyed by the names, otherwise an array.
+/// Integers are converted into decimal strings, so that they don't lose precision, tuples and
+/// arrays into nested arrays.
+///
+/// ```
+/// use cast::tokens_to_json;
+/// use ethers_core::abi::Token;
+///
+/// let tokens = [Token::Uint(1.into()), Token::Tuple(vec![Token::Bool(true)])];
+/// assert_eq!(tokens_to_json(&["a", "b"], &tokens).to_string(), r#"{"a":"1","b":[true]}"#);
+/// assert_eq!(tokens_to_json(&["a", ""], &tokens).to_string(), r#"["1",[true]]"#);
+/// ```
+pub fn tokens_to_json(names: &[&str], tokens: &[Token]) -> serde_json::Value {
+    let named = names.len() == tokens.len() &&
+        names.iter().all(|name| !name.is_empty()) &&
+        names.iter().collect::<HashSet<_>>().len() == names.len();
+    if named {
+        let values = names
+            .iter()
+            .zip(tokens)
+            .map(|(name, token)| (name.to_string(), token_to_json(token)))
+            .collect::<serde_json::Map<_, _>>();
+        serde_json::Value::Object(values)
+    } else {
+        serde_json::Value::Array(tokens.iter().map(token_to_json).collect())
+    }
+}
+
+/// Converts a decoded value into JSON, see [`tokens_to_json`]
+fn token_to_json(token: &Token) -> serde_json::Value {
+    match token {
+        Token::Address(inner) => utils::to_checksum(inner, None).into(),
+        Token::Bytes(inner) | Token::FixedBytes(inner) => {
+            format!("0x{}", hex::encode(inner)).into()
+        }
+        Token::Uint(inner) => inner.to_string().into(),
+        Token::Int(inner) => I256::from_raw(*inner).to_string().into(),
+        Token::Bool(inner) => (*inner).into(),
+        Token::String(inner) => inner.clone().into(),
+        Token::Array(tokens) | Token::FixedArray(tokens) | Token::Tuple(tokens) => {
+            serde_json::Value::Array(tokens.iter().map(token_to_json).collect())
+        }
+    }
+}
+
+/// Parses the signature given to `abi-encode`, which may either be a function or a constructor.
+fn parse_encode_signature(sig: &str) -> Result<Function> {
+    match HumanReadableParser::parse_function(sig) {
+        Ok(func) => Ok(func),
+        Err(err) => {
+            if let Ok(constructor) = HumanReadableParser::parse_constructor(sig) {
+                #[allow(deprecated)]
+                Ok(Function {
+                    name: "constructor".to_string(),
+                    inputs: constructor.inputs,
+                    outputs: vec![],
+                    constant: None,
+                    state_mutability: Default::default(),
+                })
+            } else {
+                // we return the `Function` parse error as this case is more likely
+                Err(err.into())
+            }
+        }
+    }
+}
+
+/// Appends the packed encoding of `token` to `out`.
+///
+/// Array elements are padded to 32 bytes, as they are in Solidity, which is why elements that
+/// have no fixed size are rejected.
+fn encode_packed_token(
+    kind: &ParamType,
+    token: &Token,
+    in_array: bool,
+    out: &mut Vec<u8>,
+) -> Result<()> {
+    match (kind, token) {
+        (ParamType::Address, Token::Address(addr)) => {
+            if in_array {
+                out.extend_from_slice(&[0u8; 12]);
+            }
+            out.extend_from_slice(addr.as_bytes());
+        }
+        (ParamType::Uint(bits), Token::Uint(value)) | (ParamType::Int(bits), Token::Int(value)) => {
+            let size = if in_array { 32 } else { bits / 8 };
+            let mut word = [0u8; 32];
+            value.to_big_endian(&mut word);
+            out.extend_from_slice(&word[32 - size..]);
+        }
+        (ParamType::Bool, Token::Bool(value)) => {
+            if in_array {
+                out.extend_from_slice(&[0u8; 31]);
+            }
+            out.push(*value as u8);
+        }
+        (ParamType::FixedBytes(size), Token::FixedBytes(bytes)) => {
+            out.extend_from_slice(&bytes[..*size]);
+            if in_array {
+                out.extend_from_slice(&vec![0u8; 32 - size]);
+            }
+        }
+        (ParamType::Bytes, Token::Bytes(bytes)) if !in_array => out.extend_from_slice(bytes),
+        (ParamType::String, Token::String(s)) if !in_array => out.extend_from_slice(s.as_bytes()),
+        (ParamType::Array(inner), Token::Array(tokens)) |
+        (ParamType::FixedArray(inner, _), Token::FixedArray(tokens))
+            if !in_array =>
+        {
+            for token in tokens {
+                encode_packed_token(inner, token, true, out)?;
+            }
+        }
+        (ParamType::Tuple(_), _) => {
+            eyre::bail!("packed encoding of tuples is ambiguous and not supported")
+        }
+        (kind, _) if in_array => {
+            eyre::bail!("packed encoding of arrays of {kind} is ambiguous and not supported")
+        }
+        (kind, token) => eyre::bail!("invalid value {token} for type {kind}"),
+    }
+    Ok(())
+}
+
+/// Formats the fee history as a table of the blocks, followed by the base fee of the next block
+/// and the suggested priority fees, or as the JSON of the `eth_feeHistory` response with the
+/// `percentiles` and `suggestedPriorityFees` added.
+fn format_fee_history(history: &FeeHistory, percentiles: &[f64], to_json: bool) -> Result<String> {
+    // the median of every percentile over all blocks
+    let suggested = (0..percentiles.len())
+        .map(|i| {
+            let mut fees =
+                history.reward.iter().filter_map(|fees| fees.get(i).copied()).collect::<Vec<_>>();
+            fees.sort();
+            fees.get(fees.len() / 2).copied().unwrap_or_default()
+        })
+        .collect::<Vec<_>>();
+
+    if to_json {
+        let mut value = serde_json::to_value(history)?;
+        value["percentiles"] = serde_json::json!(percentiles);
+        value["suggestedPriorityFees"] = serde_json::json!(suggested);
+        return Ok(value.to_string())
+    }
+
+    let percentiles = percentiles.iter().map(|p| format!("{p}%")).collect::<Vec<_>>();
+    let mut out = format!(
+        "{:<12} {:<16} {:<10} Priority fees ({})\n",
+        "Block",
+        "Base fee",
+        "Gas used",
+        percentiles.join(", ")
+    );
+    for (i, ratio) in history.gas_used_ratio.iter().enumerate() {
+        let fees = history
+            .reward
+            .get(i)
+            .map(|fees| fees.iter().map(U256::to_string).collect::<Vec<_>>().join(", "))
+            .unwrap_or_default();
+        out.push_str(&format!(
+            "{:<12} {:<16} {:<10} {}\n",
+            history.oldest_block + i,
+            history.base_fee_per_gas.get(i).copied().unwrap_or_default(),
+            format!("{:.2}%", ratio * 100.0),
+            fees
+        ));
+    }
+    if let Some(next) = history.base_fee_per_gas.get(history.gas_used_ratio.len()) {
+        out.push_str(&format!("\nNext base fee: {next}\n"));
+    }
+    let suggested = percentiles
+        .iter()
+        .zip(&suggested)
+        .map(|(p, fee)| format!("{p}: {fee}"))
+        .collect::<Vec<_>>();
+    out.push_str(&format!("Suggested priority fees: {}", suggested.join(", ")));
+    Ok(out)
 }
 
 fn strip_0x(s: &str) -> &str {
@@ -1425,6 +3013,198 @@ fn strip_0x(s: &str) -> &str {
 mod tests {
     use super::SimpleCast as Cast;
 
+    #[test]
+    fn erc20_calls() {
+        use ethers_core::{abi::Token, types::Address};
+
+        let who = Address::from_low_u64_be(0xbeef);
+        let calldata = super::erc20_calldata("balanceOf(address)", &[Token::Address(who)]).unwrap();
+        assert_eq!(&calldata[..4], &[0x70, 0xa0, 0x82, 0x31]);
+        assert_eq!(&calldata[4..], ethers_core::abi::encode(&[Token::Address(who)]).as_slice());
+        assert_eq!(super::erc20_calldata("decimals()", &[]).unwrap(), vec![0x31, 0x3c, 0xe5, 0x67]);
+
+        let output = ethers_core::abi::encode(&[Token::Uint(6.into())]);
+        let decimals = super::erc20_uint_output(&output).unwrap();
+        assert_eq!(super::erc20_decimals(decimals).unwrap(), 6);
+        assert!(super::erc20_decimals(256.into()).is_err());
+        assert!(super::erc20_uint_output(&[]).is_err());
+    }
+
+    #[test]
+    fn access_list_decision() {
+        use ethers_core::types::{
+            Eip1559TransactionRequest, Eip2930TransactionRequest, TransactionRequest,
+        };
+
+        let legacy = TransactionRequest::new().into();
+        assert_eq!(
+            super::ensure_access_list_supported(&legacy).unwrap_err().to_string(),
+            "Access lists are not supported by legacy transactions"
+        );
+        let eip2930 =
+            Eip2930TransactionRequest::new(TransactionRequest::new(), Default::default()).into();
+        assert!(super::ensure_access_list_supported(&eip2930).is_ok());
+        let eip1559 = Eip1559TransactionRequest::new().into();
+        assert!(super::ensure_access_list_supported(&eip1559).is_ok());
+
+        // only attached if it lowers the estimate
+        assert!(super::should_attach_access_list(30_000.into(), 29_000.into(), false));
+        assert!(!super::should_attach_access_list(30_000.into(), 30_000.into(), false));
+        assert!(!super::should_attach_access_list(30_000.into(), 31_000.into(), false));
+        // unless forced
+        assert!(super::should_attach_access_list(30_000.into(), 31_000.into(), true));
+    }
+
+    #[tokio::test]
+    async fn resolve_revert_reason() {
+        // `Error(string)` with the message "insufficient balance"
+        let err = "(code: 3, message: execution reverted: insufficient balance, data: Some(String(\"0x08c379a000000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000000000000000014696e73756666696369656e742062616c616e6365000000000000000000000000\")))";
+        assert_eq!(super::revert_data(err).count(), 1);
+        assert_eq!(super::resolve_revert_reason(err, true).await, "insufficient balance");
+
+        let err = "(code: -32000, message: out of gas, data: None)";
+        assert_eq!(super::revert_data(err).count(), 0);
+        assert_eq!(super::resolve_revert_reason(err, true).await, err);
+    }
+
+    #[tokio::test]
+    async fn decode_builtin_errors() {
+        // `Error(string)` with the message "insufficient balance"
+        let error = "0x08c379a000000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000000000000000014696e73756666696369656e742062616c616e6365000000000000000000000000";
+        assert_eq!(Cast::decode_error(error, true).await.unwrap(), "insufficient balance");
+
+        let panic = "0x4e487b710000000000000000000000000000000000000000000000000000000000000011";
+        assert_eq!(
+            Cast::decode_error(panic, true).await.unwrap(),
+            "Panic(0x11): Arithmetic over/underflow"
+        );
+        let panic = "4e487b710000000000000000000000000000000000000000000000000000000000000099";
+        assert_eq!(Cast::decode_error(panic, true).await.unwrap(), "Panic(0x99)");
+
+        assert!(Cast::decode_error("0x4e487b", true).await.is_err());
+        assert!(Cast::decode_error("0xzz", true).await.is_err());
+    }
+
+    #[test]
+    fn abi_encode_packed() {
+        // abi.encodePacked(address(0x5B38Da6a701c568545dCfcB03FcB875f56beddC4), uint256(42),
+        // "hello")
+        assert_eq!(
+            Cast::abi_encode_packed(
+                "f(address,uint256,string)",
+                &["0x5B38Da6a701c568545dCfcB03FcB875f56beddC4", "42", "hello"]
+            )
+            .unwrap(),
+            "0x5b38da6a701c568545dcfcb03fcb875f56beddc4000000000000000000000000000000000000000000000000000000000000002a68656c6c6f"
+        );
+        // array elements are padded, signed integers use two's complement at their own width
+        assert_eq!(
+            Cast::abi_encode_packed("f(uint16[],int8,int16)", &["[1,2]", "-1", "-2"]).unwrap(),
+            "0x00000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000000000000000000000000000000000000002fffffe"
+        );
+
+        assert!(Cast::abi_encode_packed("f(string[])", &["[\"a\",\"b\"]"]).is_err());
+        assert!(Cast::abi_encode_packed("f(uint256[][])", &["[[1],[2]]"]).is_err());
+        assert!(Cast::abi_encode_packed("f((uint256,uint256))", &["(1,2)"]).is_err());
+        assert!(Cast::abi_encode_packed("f(uint256,uint256)", &["1"]).is_err());
+    }
+
+    #[test]
+    fn abi_encode_constructor() {
+        // constructor(string name, string symbol, uint8 decimals) of an ERC20
+        let encoded = Cast::abi_encode(
+            "constructor(string name,string symbol,uint8 decimals)",
+            &["Token", "TKN", "18"],
+        )
+        .unwrap();
+        assert_eq!(
+            encoded,
+            "0x000000000000000000000000000000000000000000000000000000000000006000000000000000000000000000000000000000000000000000000000000000a000000000000000000000000000000000000000000000000000000000000000120000000000000000000000000000000000000000000000000000000000000005546f6b656e0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000003544b4e0000000000000000000000000000000000000000000000000000000000"
+        );
+        // the encoding has no selector, same as for a function signature
+        assert_eq!(
+            encoded,
+            Cast::abi_encode("f(string,string,uint8)", &["Token", "TKN", "18"]).unwrap()
+        );
+    }
+
+    #[test]
+    fn consolidates_flattened_source() {
+        let source = "// SPDX-License-Identifier: MIT\npragma solidity ^0.8.0;\n\ncontract A {}\n\n// SPDX-License-Identifier: MIT OR Apache-2.0\npragma solidity ^0.8.0;\npragma abicoder v2;\n\n/* SPDX-License-Identifier: MIT */\ncontract B is A {}\n";
+        assert_eq!(
+            super::consolidate_flattened(source),
+            "// SPDX-License-Identifier: MIT AND (MIT OR Apache-2.0)\npragma solidity ^0.8.0;\n\ncontract A {}\n\npragma abicoder v2;\n\ncontract B is A {}\n"
+        );
+
+        let source = "// SPDX-License-Identifier: MIT\ncontract A {}";
+        assert_eq!(super::consolidate_flattened(source), source);
+    }
+
+    #[test]
+    fn declares_contract() {
+        let source = "import \"./Token.sol\";\nabstract contract TokenBase {}\ncontract Token is TokenBase {}";
+        assert!(super::declares_contract(source, "Token"));
+        assert!(super::declares_contract(source, "TokenBase"));
+        assert!(!super::declares_contract(source, "Base"));
+        assert!(!super::declares_contract("interface IToken {}", "Token"));
+        assert!(super::declares_contract("library Math {}", "Math"));
+    }
+
+    #[test]
+    fn source_file_path() {
+        use std::path::PathBuf;
+
+        assert_eq!(
+            super::source_file_path("@openzeppelin/contracts/token/ERC20/ERC20.sol").unwrap(),
+            PathBuf::from("@openzeppelin/contracts/token/ERC20/ERC20.sol")
+        );
+        assert_eq!(
+            super::source_file_path("/home/dev/project/src/Token.sol").unwrap(),
+            PathBuf::from("home/dev/project/src/Token.sol")
+        );
+        assert!(super::source_file_path("../Token.sol").is_err());
+    }
+
+    #[test]
+    fn project_remappings() {
+        use std::path::PathBuf;
+
+        let paths = vec![
+            PathBuf::from("src/Token.sol"),
+            PathBuf::from("lib/openzeppelin-contracts/contracts/token/ERC20/ERC20.sol"),
+            PathBuf::from("@uniswap/v3-core/contracts/interfaces/IUniswapV3Pool.sol"),
+            PathBuf::from("Flat.sol"),
+        ];
+        let declared = vec!["@openzeppelin/=lib/openzeppelin-contracts/".to_string()];
+        assert_eq!(
+            super::project_remappings(declared, &paths),
+            vec![
+                "@openzeppelin/=lib/openzeppelin-contracts/",
+                "@uniswap/=@uniswap/",
+                "lib/=lib/",
+                "src/=src/"
+            ]
+        );
+    }
+
+    #[test]
+    fn receipt_cost() {
+        let cost = super::ReceiptCost {
+            gas_used: 21_000u64.into(),
+            effective_gas_price: 12_000_000_000u64.into(),
+            base_fee_per_gas: Some(10_000_000_000u64.into()),
+        };
+        assert_eq!(cost.fee(), 252_000_000_000_000u64.into());
+        assert_eq!(
+            cost.fee_portions(),
+            Some((210_000_000_000_000u64.into(), 42_000_000_000_000u64.into()))
+        );
+
+        let json = cost.to_json().unwrap();
+        assert_eq!(json["feeEther"], "0.000252000000000000");
+        assert_eq!(json["priorityFee"], "0x2632e314a000");
+    }
+
     #[test]
     fn calldata_uint() {
         assert_eq!(
@@ -1450,6 +3230,62 @@ mod tests {
         );
     }
 
+    #[test]
+    fn fee_history() {
+        use ethers_core::types::{FeeHistory, U256};
+
+        let history = FeeHistory {
+            base_fee_per_gas: vec![10.into(), 12.into(), 11.into()],
+            gas_used_ratio: vec![0.5, 0.25],
+            oldest_block: 100.into(),
+            reward: vec![vec![1.into(), 3.into()], vec![2.into(), 5.into()]],
+        };
+
+        let out = super::format_fee_history(&history, &[25.0, 75.0], false).unwrap();
+        assert!(out.contains("Next base fee: 11"));
+        assert!(out.ends_with("Suggested priority fees: 25%: 2, 75%: 5"));
+
+        let json = super::format_fee_history(&history, &[25.0, 75.0], true).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(json["percentiles"], serde_json::json!([25.0, 75.0]));
+        assert_eq!(
+            json["suggestedPriorityFees"],
+            serde_json::json!([U256::from(2), U256::from(5)])
+        );
+    }
+
+    #[test]
+    fn eip1191_checksum() {
+        // test vectors of EIP-1191
+        for (chain_id, addresses) in [
+            (
+                30,
+                [
+                    "0x5aaEB6053f3e94c9b9a09f33669435E7ef1bEAeD",
+                    "0xFb6916095cA1Df60bb79ce92cE3EA74c37c5d359",
+                    "0xDBF03B407c01E7CD3cBea99509D93F8Dddc8C6FB",
+                    "0xD1220A0Cf47c7B9BE7a2e6ba89F429762E7B9adB",
+                ],
+            ),
+            (
+                31,
+                [
+                    "0x5aAeb6053F3e94c9b9A09F33669435E7EF1BEaEd",
+                    "0xFb6916095CA1dF60bb79CE92ce3Ea74C37c5D359",
+                    "0xdbF03B407C01E7cd3cbEa99509D93f8dDDc8C6fB",
+                    "0xd1220a0CF47c7B9Be7A2E6Ba89f429762E7b9adB",
+                ],
+            ),
+        ] {
+            for address in addresses {
+                let parsed = address.parse().unwrap();
+                assert_eq!(Cast::checksum_address_with_chain_id(&parsed, Some(chain_id)), address);
+                assert!(!Cast::has_invalid_checksum(address, Some(chain_id)).unwrap());
+                assert!(Cast::has_invalid_checksum(address, None).unwrap());
+            }
+        }
+    }
+
     #[test]
     fn concat_hex() {
         assert_eq!(Cast::concat_hex(vec!["0x00".to_string(), "0x01".to_string()]), "0x0001");
@@ -1459,10 +3295,142 @@ mod tests {
     #[test]
     fn from_rlp() {
         let rlp = "0xf8b1a02b5df5f0757397573e8ff34a8b987b21680357de1f6c8d10273aa528a851eaca8080a02838ac1d2d2721ba883169179b48480b2ba4f43d70fcf806956746bd9e83f90380a0e46fff283b0ab96a32a7cc375cecc3ed7b6303a43d64e0a12eceb0bc6bd8754980a01d818c1c414c665a9c9a0e0c0ef1ef87cacb380b8c1f6223cb2a68a4b2d023f5808080a0236e8f61ecde6abfebc6c529441f782f62469d8a2cc47b7aace2c136bd3b1ff08080808080";
-        let item = Cast::from_rlp(rlp).unwrap();
+        let item = Cast::from_rlp(rlp, false).unwrap();
         assert_eq!(
             item,
             r#"["0x2b5df5f0757397573e8ff34a8b987b21680357de1f6c8d10273aa528a851eaca","0x","0x","0x2838ac1d2d2721ba883169179b48480b2ba4f43d70fcf806956746bd9e83f903","0x","0xe46fff283b0ab96a32a7cc375cecc3ed7b6303a43d64e0a12eceb0bc6bd87549","0x","0x1d818c1c414c665a9c9a0e0c0ef1ef87cacb380b8c1f6223cb2a68a4b2d023f5","0x","0x","0x","0x236e8f61ecde6abfebc6c529441f782f62469d8a2cc47b7aace2c136bd3b1ff0","0x","0x","0x","0x","0x"]"#
+        );
+        assert_eq!(Cast::to_rlp(&item).unwrap(), rlp);
+    }
+
+    #[test]
+    fn rlp_typed_roundtrip() {
+        // nested lists mixing integers, including zero and values beyond u64, and byte strings
+        let input = r#"[1024,[0,["0xdeadbeef","18446744073709551616"]],"0x",[[127,128]]]"#;
+        let rlp = Cast::to_rlp(input).unwrap();
+        assert_eq!(rlp, "0xdb820400d180cf84deadbeef8901000000000000000080c4c37f8180");
+
+        let ints = Cast::from_rlp(&rlp, true).unwrap();
+        assert_eq!(ints, r#"[1024,[0,[3735928559,"18446744073709551616"]],0,[[127,128]]]"#);
+        assert_eq!(Cast::to_rlp(&ints).unwrap(), rlp);
+
+        let hex = Cast::from_rlp(&rlp, false).unwrap();
+        assert_eq!(
+            hex,
+            r#"["0x0400",["0x",["0xdeadbeef","0x010000000000000000"]],"0x",[["0x7f","0x80"]]]"#
+        );
+        assert_eq!(Cast::to_rlp(&hex).unwrap(), rlp);
+    }
+
+    #[test]
+    fn rlp_nested_roundtrip() {
+        // a list of a list of lists, like a block body with empty transaction and uncle lists
+        let rlp = "0xd1d0c4820100c0cac483010203c4c3c281ff";
+        let decoded = Cast::from_rlp(rlp, false).unwrap();
+        assert_eq!(decoded, r#"[[["0x0100",[]],[["0x010203"],[[["0xff"]]]]]]"#);
+        assert_eq!(Cast::to_rlp(&decoded).unwrap(), rlp);
+    }
+
+    #[test]
+    fn decode_event() {
+        let from = "0x0000000000000000000000008dbd1b711dc621e1404633da156fcc779e1c6f3e";
+        let to = "0x000000000000000000000000d9f3c9cc99548bf3b44a43e0a2d07399eb918adc";
+        let value = "0x000000000000000000000000000000000000000000000000000000000000002a";
+        let transfer = "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+        let format = |params: Vec<(String, super::Token)>| {
+            params.into_iter().map(|(name, value)| format!("{name}: {value}")).collect::<Vec<_>>()
+        };
+
+        // the indexed parameters are guessed from the number of topics
+        let decoded = Cast::decode_event(
+            "Transfer(address,address,uint256)",
+            &[transfer, from, to],
+            value,
+            false,
         )
+        .unwrap();
+        assert_eq!(
+            format(decoded),
+            vec![
+                "param0: 8dbd1b711dc621e1404633da156fcc779e1c6f3e",
+                "param1: d9f3c9cc99548bf3b44a43e0a2d07399eb918adc",
+                "param2: 2a"
+            ]
+        );
+
+        // anonymous events have no signature topic
+        let decoded = Cast::decode_event(
+            "Deposit(address indexed from, uint256 value)",
+            &[from],
+            value,
+            true,
+        )
+        .unwrap();
+        assert_eq!(
+            format(decoded),
+            vec!["from: 8dbd1b711dc621e1404633da156fcc779e1c6f3e", "value: 2a"]
+        );
+
+        // the signature topic must match the event
+        assert!(Cast::decode_event(
+            "Approval(address,address,uint256)",
+            &[transfer, from, to],
+            value,
+            false
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn index_erc7201() {
+        // reference values of the OpenZeppelin upgradeable contracts
+        assert_eq!(
+            Cast::index_erc7201("openzeppelin.storage.ERC20"),
+            "0x52c63247e1f47db19d5ce0460030c497f067ca4cebf71ba98eeadabe20bace00"
+        );
+        assert_eq!(
+            Cast::index_erc7201("openzeppelin.storage.Ownable"),
+            "0x9016d09d72d40fdae2fd8ceac6b6234c7706214fd39c1cd1e609a0528c199300"
+        );
+    }
+
+    #[test]
+    fn base_conversion() {
+        assert_eq!(Cast::to_base("0b1010", None, 8).unwrap(), "0o12");
+        assert_eq!(Cast::to_base("0", Some(36), 2).unwrap(), "0b0");
+        // a hex value with a leading `0b` digit pair is not binary
+        assert_eq!(Cast::to_base("0b1", Some(16), 10).unwrap(), "177");
+        assert_eq!(
+            Cast::to_base(
+                "0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                None,
+                36
+            )
+            .unwrap(),
+            "6dp5qcb22im238nr3wvp0ic7q99w035jmy2iw7i6n43d37jtof"
+        );
+
+        let err = Cast::to_base("0x12g4", None, 10).unwrap_err();
+        assert_eq!(err.to_string(), "Invalid digit `g` at position 4 of `0x12g4` for base 16");
+        let err = Cast::to_base("19", Some(8), 10).unwrap_err();
+        assert_eq!(err.to_string(), "Invalid digit `9` at position 1 of `19` for base 8");
+        assert!(Cast::to_base("1", Some(10), 37).is_err());
+        assert!(Cast::to_base("", Some(10), 16).is_err());
+        assert!(Cast::parse_uint(&"z".repeat(50), 36).is_err());
+    }
+
+    #[test]
+    fn disassemble() {
+        assert_eq!(Cast::disassemble("0x").unwrap(), "");
+        assert_eq!(
+            Cast::disassemble("5b0cfe61ab").unwrap(),
+            "00000000: JUMPDEST\n00000001: UNDEFINED(0x0c)\n00000002: INVALID\n00000003: PUSH2 0xab (truncated)"
+        );
+        assert_eq!(
+            Cast::disassemble("0x7f000000000000000000000000000000000000000000000000000000000000000100")
+                .unwrap(),
+            "00000000: PUSH32 0x0000000000000000000000000000000000000000000000000000000000000001\n00000021: STOP"
+        );
+        assert!(Cast::disassemble("0x6g").is_err());
     }
 }
@@ -0,0 +1,109 @@
+//! Storage slots of the common proxy patterns
+
+use ethers_core::types::{Address, H256};
+use std::{fmt, str::FromStr};
+
+/// The storage slot of the implementation address of EIP-1967 proxies,
+/// `bytes32(uint256(keccak256('eip1967.proxy.implementation')) - 1)`
+pub const EIP1967_IMPLEMENTATION_SLOT: &str =
+    "0x360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc";
+
+/// The storage slot of the admin address of EIP-1967 proxies,
+/// `bytes32(uint256(keccak256('eip1967.proxy.admin')) - 1)`
+pub const EIP1967_ADMIN_SLOT: &str =
+    "0xb53127684a568b3173ae13b9f8a6016e243e63b6e8ee1178d6a717850b5d6103";
+
+/// The storage slot of the beacon address of EIP-1967 beacon proxies,
+/// `bytes32(uint256(keccak256('eip1967.proxy.beacon')) - 1)`
+pub const EIP1967_BEACON_SLOT: &str =
+    "0xa3f0ad74e5423aebfd80d3ef4346578335a9a72aeaee59ff6cb3582b35133d50";
+
+/// The storage slot of the implementation address of EIP-1822 (UUPS) proxies,
+/// `keccak256('PROXIABLE')`
+pub const EIP1822_PROXIABLE_SLOT: &str =
+    "0xc5f16f0fcc639fa48a6947836d9850f504798523bf8c9a3a87d5876cf622bcf7";
+
+/// The storage slot of the implementation address of the transparent proxies of OpenZeppelin
+/// before EIP-1967, `keccak256('org.zeppelinos.proxy.implementation')`
+pub const ZEPPELINOS_IMPLEMENTATION_SLOT: &str =
+    "0x7050c9e0f4ca769c69bd3a8ef740bc37934f8e2c036e5a723fd8ee048ed3f8c3";
+
+/// The storage slot of the admin address of the transparent proxies of OpenZeppelin before
+/// EIP-1967, `keccak256('org.zeppelinos.proxy.admin')`
+pub const ZEPPELINOS_ADMIN_SLOT: &str =
+    "0x10d6a54a4754c8869d6886b5f5d7fbfa5b4522237ea5c60d11bc4e7a1ff9390b";
+
+/// The selector of `implementation()`, implemented by EIP-1967 beacons
+pub const IMPLEMENTATION_SELECTOR: [u8; 4] = [0x5c, 0x60, 0xda, 0x1b];
+
+/// The proxy patterns `cast implementation` recognizes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProxyKind {
+    /// The implementation is stored in the EIP-1967 implementation slot
+    Eip1967,
+    /// The implementation is returned by the beacon stored in the EIP-1967 beacon slot
+    Beacon(Address),
+    /// The implementation is stored in the EIP-1822 `PROXIABLE` slot
+    Eip1822,
+    /// The implementation is stored in the slot of OpenZeppelin's transparent proxies prior to
+    /// EIP-1967
+    Transparent,
+}
+
+impl fmt::Display for ProxyKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProxyKind::Eip1967 => f.write_str("EIP-1967"),
+            ProxyKind::Beacon(beacon) => write!(f, "EIP-1967 beacon {beacon:?}"),
+            ProxyKind::Eip1822 => f.write_str("EIP-1822"),
+            ProxyKind::Transparent => f.write_str("transparent"),
+        }
+    }
+}
+
+/// Parses one of the slot constants
+pub(crate) fn slot(slot: &str) -> H256 {
+    H256::from_str(slot).expect("valid slot")
+}
+
+/// Returns the address stored in the lower 20 bytes of a word, or `None` if the word is empty
+pub(crate) fn word_to_address(word: &[u8]) -> Option<Address> {
+    if word.len() < 32 {
+        return None
+    }
+    let address = Address::from_slice(&word[12..32]);
+    (!address.is_zero()).then_some(address)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_core::utils::keccak256;
+
+    #[test]
+    fn slots_match_their_preimages() {
+        let eip1967 = |name: &str| {
+            let mut slot = [0u8; 32];
+            (ethers_core::types::U256::from(keccak256(name)) - 1).to_big_endian(&mut slot);
+            H256(slot)
+        };
+        assert_eq!(slot(EIP1967_IMPLEMENTATION_SLOT), eip1967("eip1967.proxy.implementation"));
+        assert_eq!(slot(EIP1967_ADMIN_SLOT), eip1967("eip1967.proxy.admin"));
+        assert_eq!(slot(EIP1967_BEACON_SLOT), eip1967("eip1967.proxy.beacon"));
+        assert_eq!(slot(EIP1822_PROXIABLE_SLOT), H256(keccak256("PROXIABLE")));
+        assert_eq!(
+            slot(ZEPPELINOS_IMPLEMENTATION_SLOT),
+            H256(keccak256("org.zeppelinos.proxy.implementation"))
+        );
+        assert_eq!(slot(ZEPPELINOS_ADMIN_SLOT), H256(keccak256("org.zeppelinos.proxy.admin")));
+        assert_eq!(IMPLEMENTATION_SELECTOR, keccak256("implementation()")[..4]);
+    }
+
+    #[test]
+    fn reads_address_from_word() {
+        assert_eq!(word_to_address(&[0u8; 32]), None);
+        let mut word = [0u8; 32];
+        word[31] = 1;
+        assert_eq!(word_to_address(&word), Some(Address::from_low_u64_be(1)));
+    }
+}
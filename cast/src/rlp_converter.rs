@@ -1,4 +1,7 @@
-use ethers_core::utils::rlp::{Decodable, DecoderError, Encodable, Rlp, RlpStream};
+use ethers_core::{
+    types::U256,
+    utils::rlp::{Decodable, DecoderError, Encodable, Rlp, RlpStream},
+};
 use serde_json::Value;
 use std::fmt::{Debug, Display, Formatter, Write};
 
@@ -38,18 +41,41 @@ impl Decodable for Item {
 }
 
 impl Item {
+    /// Converts JSON input into an item.
+    ///
+    /// - lists are encoded as RLP lists
+    /// - `0x` prefixed strings are encoded as the raw bytes they represent in hex
+    /// - numbers and unprefixed decimal strings are encoded as integers, i.e. as their big endian
+    ///   bytes without leading zeros. Zero is encoded as the empty byte string
+    /// - `null` and the empty string are encoded as the empty byte string
     pub(crate) fn value_to_item(value: &Value) -> eyre::Result<Item> {
         return match value {
             Value::Null => Ok(Item::Data(vec![])),
             Value::Bool(_) => {
                 eyre::bail!("RLP input should not contain booleans")
             }
-            // If a value is passed without quotes we cast it to string
-            Value::Number(n) => Ok(Item::value_to_item(&Value::String(n.to_string()))?),
-            Value::String(s) => {
-                let hex_string = s.strip_prefix("0x").unwrap_or(s);
-                Ok(Item::Data(hex::decode(hex_string).expect("Could not decode hex")))
+            Value::Number(n) => {
+                let int = n.as_u64().ok_or_else(|| {
+                    eyre::eyre!("RLP input can only contain non-negative integers, got {n}")
+                })?;
+                Ok(Item::from_int(int.into()))
             }
+            Value::String(s) if s.is_empty() => Ok(Item::Data(vec![])),
+            Value::String(s) => match s.strip_prefix("0x") {
+                Some(hex_string) => {
+                    let data = hex::decode(hex_string)
+                        .map_err(|err| eyre::eyre!("Could not decode hex {s:?}: {err}"))?;
+                    Ok(Item::Data(data))
+                }
+                None => {
+                    let int = U256::from_dec_str(s).map_err(|err| {
+                        eyre::eyre!(
+                            "Could not parse {s:?} as an integer, byte strings must be 0x prefixed: {err}"
+                        )
+                    })?;
+                    Ok(Item::from_int(int))
+                }
+            },
             Value::Array(values) => values.iter().map(Item::value_to_item).collect(),
             Value::Object(_) => {
                 eyre::bail!("RLP input can not contain objects")
@@ -58,6 +84,37 @@ impl Item {
     }
 }
 
+impl Item {
+    /// Returns the minimal big endian encoding of `int`, the empty byte string for zero
+    fn from_int(int: U256) -> Item {
+        let mut bytes = [0u8; 32];
+        int.to_big_endian(&mut bytes);
+        let start = bytes.iter().position(|byte| *byte != 0).unwrap_or(bytes.len());
+        Item::Data(bytes[start..].to_vec())
+    }
+
+    /// Converts the item to JSON, keeping the list structure and turning byte strings into hex.
+    ///
+    /// With `as_int`, byte strings of up to 32 bytes are interpreted as big endian integers
+    /// instead. Those that fit in a `u64` become JSON numbers, larger ones decimal strings.
+    pub(crate) fn to_value(&self, as_int: bool) -> Value {
+        match self {
+            Item::Data(data) if as_int && data.len() <= 32 => {
+                let int = U256::from_big_endian(data);
+                if int <= U256::from(u64::MAX) {
+                    Value::from(int.as_u64())
+                } else {
+                    Value::String(int.to_string())
+                }
+            }
+            Item::Data(data) => Value::String(format!("0x{}", hex::encode(data))),
+            Item::Array(arr) => {
+                Value::Array(arr.iter().map(|item| item.to_value(as_int)).collect())
+            }
+        }
+    }
+}
+
 impl FromIterator<Item> for Item {
     fn from_iter<T: IntoIterator<Item = Item>>(iter: T) -> Self {
         Item::Array(iter.into_iter().collect())
@@ -91,7 +148,10 @@ impl Display for Item {
 #[cfg(test)]
 mod test {
     use crate::rlp_converter::Item;
-    use ethers_core::utils::{rlp, rlp::DecoderError};
+    use ethers_core::{
+        types::U256,
+        utils::{rlp, rlp::DecoderError},
+    };
     use serde_json::Result as JsonResult;
 
     // https://en.wikipedia.org/wiki/Set-theoretic_definition_of_natural_numbers
@@ -151,17 +211,27 @@ mod test {
             (2, "[\"0x646f67\"]", Item::Array(vec![Item::Data(vec![0x64, 0x6f, 0x67])])),
             (
                 3,
-                "[[\"646f67\"]]",
+                "[[\"0x646f67\"]]",
                 Item::Array(vec![Item::Array(vec![Item::Data(vec![0x64, 0x6f, 0x67])])]),
             ),
             (
                 4,
-                "[\"646f67\",\"0x636174\"]",
+                "[6582119,\"0x636174\"]",
                 Item::Array(vec![
                     Item::Data(vec![0x64, 0x6f, 0x67]),
                     Item::Data(vec![0x63, 0x61, 0x74]),
                 ]),
             ),
+            (
+                5,
+                "[0,\"0\",\"256\",\"0x0100\"]",
+                Item::Array(vec![
+                    Item::Data(vec![]),
+                    Item::Data(vec![]),
+                    Item::Data(vec![0x01, 0x00]),
+                    Item::Data(vec![0x01, 0x00]),
+                ]),
+            ),
             (6, "[[],[[]],[[],[[]]]]", array_von_neuman()),
         ];
         for params in parameters {
@@ -173,4 +243,48 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn deserialize_invalid_input() {
+        for input in ["[true]", "[-1]", "[1.5]", "[\"ff\"]", "[\"0xzz\"]", "{}"] {
+            let val = serde_json::from_str(input).unwrap();
+            assert!(Item::value_to_item(&val).is_err(), "{input}");
+        }
+    }
+
+    #[test]
+    fn to_value_test() {
+        let item = Item::Array(vec![
+            Item::Data(vec![]),
+            Item::Data(vec![0x01, 0x00]),
+            Item::Array(vec![Item::Array(vec![Item::Data(vec![0xff; 32])]), Item::Array(vec![])]),
+            Item::Data(vec![0xab; 33]),
+        ]);
+
+        let hex = item.to_value(false);
+        assert_eq!(
+            hex,
+            serde_json::json!([
+                "0x",
+                "0x0100",
+                [[format!("0x{}", "ff".repeat(32))], []],
+                format!("0x{}", "ab".repeat(33))
+            ])
+        );
+        // the hex form is the input format of `value_to_item`
+        assert_eq!(Item::value_to_item(&hex).unwrap(), item);
+
+        let ints = item.to_value(true);
+        assert_eq!(
+            ints,
+            serde_json::json!([
+                0,
+                256,
+                [[U256::MAX.to_string()], []],
+                format!("0x{}", "ab".repeat(33))
+            ])
+        );
+        // integers are encoded without leading zeros, so canonical integers round-trip as well
+        assert_eq!(Item::value_to_item(&ints).unwrap(), item);
+    }
 }
@@ -0,0 +1,138 @@
+//! State overrides for `eth_call`
+
+use ethers_core::types::{Address, Bytes, H256, U256};
+use eyre::Result;
+use std::{collections::BTreeMap, fmt::Debug};
+
+/// The state override set of an `eth_call`, that replaces the balance, code or individual storage
+/// slots of accounts for the duration of the call.
+///
+/// Overrides of the same account are merged, but setting the same field or storage slot to
+/// different values is an error.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StateOverride {
+    accounts: BTreeMap<Address, AccountOverride>,
+}
+
+/// The overrides of a single account
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AccountOverride {
+    pub balance: Option<U256>,
+    pub code: Option<Bytes>,
+    /// Storage slots to override, all other slots keep their value
+    pub state_diff: BTreeMap<H256, H256>,
+}
+
+impl StateOverride {
+    /// Returns `true` if no account is overridden
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+    }
+
+    /// Returns the overrides by account
+    pub fn accounts(&self) -> &BTreeMap<Address, AccountOverride> {
+        &self.accounts
+    }
+
+    /// Overrides the balance of `address`
+    pub fn set_balance(&mut self, address: Address, balance: U256) -> Result<&mut Self> {
+        let account = self.accounts.entry(address).or_default();
+        set_once(&mut account.balance, balance, || format!("balance of {address:?}"))?;
+        Ok(self)
+    }
+
+    /// Overrides the code of `address`
+    pub fn set_code(&mut self, address: Address, code: Bytes) -> Result<&mut Self> {
+        let account = self.accounts.entry(address).or_default();
+        set_once(&mut account.code, code, || format!("code of {address:?}"))?;
+        Ok(self)
+    }
+
+    /// Overrides the value of the storage `slot` of `address`
+    pub fn set_storage(&mut self, address: Address, slot: H256, value: H256) -> Result<&mut Self> {
+        let account = self.accounts.entry(address).or_default();
+        let mut current = account.state_diff.get(&slot).copied();
+        set_once(&mut current, value, || format!("storage slot {slot:?} of {address:?}"))?;
+        account.state_diff.insert(slot, value);
+        Ok(self)
+    }
+
+    /// Returns the state override set as expected by the `eth_call` RPC method
+    pub fn to_json(&self) -> Result<serde_json::Value> {
+        let mut accounts = serde_json::Map::new();
+        for (address, account) in self.accounts.iter() {
+            let mut fields = serde_json::Map::new();
+            if let Some(balance) = account.balance {
+                fields.insert("balance".to_string(), serde_json::to_value(balance)?);
+            }
+            if let Some(ref code) = account.code {
+                fields.insert("code".to_string(), serde_json::to_value(code)?);
+            }
+            if !account.state_diff.is_empty() {
+                fields.insert("stateDiff".to_string(), serde_json::to_value(&account.state_diff)?);
+            }
+            accounts.insert(format!("{address:?}"), fields.into());
+        }
+        Ok(accounts.into())
+    }
+}
+
+/// Sets `field` to `value`, failing if it's already set to a different value
+fn set_once<T: PartialEq + Debug>(
+    field: &mut Option<T>,
+    value: T,
+    name: impl FnOnce() -> String,
+) -> Result<()> {
+    if let Some(current) = field {
+        if *current != value {
+            eyre::bail!("Conflicting overrides for the {}: {:?} and {:?}", name(), current, value)
+        }
+    }
+    *field = Some(value);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merges_account_overrides() {
+        let address = Address::repeat_byte(0xaa);
+        let mut overrides = StateOverride::default();
+        overrides
+            .set_balance(address, 100u64.into())
+            .unwrap()
+            .set_storage(address, H256::zero(), H256::repeat_byte(1))
+            .unwrap()
+            .set_storage(address, H256::repeat_byte(2), H256::repeat_byte(3))
+            .unwrap();
+
+        assert_eq!(overrides.accounts().len(), 1);
+        let account = &overrides.accounts()[&address];
+        assert_eq!(account.balance, Some(100u64.into()));
+        assert_eq!(account.state_diff.len(), 2);
+
+        let json = overrides.to_json().unwrap();
+        let account = &json[format!("{address:?}")];
+        assert_eq!(account["balance"], "0x64");
+        assert!(account.get("code").is_none());
+        assert_eq!(
+            account["stateDiff"][format!("{:?}", H256::zero())],
+            format!("{:?}", H256::repeat_byte(1))
+        );
+    }
+
+    #[test]
+    fn rejects_conflicting_overrides() {
+        let address = Address::repeat_byte(0xaa);
+        let mut overrides = StateOverride::default();
+        overrides.set_storage(address, H256::zero(), H256::repeat_byte(1)).unwrap();
+        // setting the same value again is fine
+        overrides.set_storage(address, H256::zero(), H256::repeat_byte(1)).unwrap();
+        assert!(overrides.set_storage(address, H256::zero(), H256::repeat_byte(2)).is_err());
+
+        overrides.set_balance(address, 1u64.into()).unwrap();
+        assert!(overrides.set_balance(address, 2u64.into()).is_err());
+    }
+}
@@ -1,8 +1,8 @@
 use ethers_core::{
     abi::Function,
     types::{
-        transaction::eip2718::TypedTransaction, Eip1559TransactionRequest, NameOrAddress,
-        TransactionRequest, H160, U256,
+        transaction::{eip2718::TypedTransaction, eip2930::AccessList},
+        Eip1559TransactionRequest, NameOrAddress, TransactionRequest, H160, U256,
     },
 };
 use ethers_providers::Middleware;
@@ -13,6 +13,12 @@ use futures::future::join_all;
 
 use crate::strip_0x;
 
+/// The maximum number of blobs a transaction can carry, see EIP-4844
+pub const MAX_BLOBS_PER_TX: usize = 6;
+
+/// The size of a blob in bytes, see EIP-4844
+pub const BYTES_PER_BLOB: usize = 131072;
+
 pub struct TxBuilder<'a, M: Middleware> {
     to: Option<H160>,
     chain: Chain,
@@ -117,6 +123,20 @@ impl<'a, M: Middleware> TxBuilder<'a, M> {
         self
     }
 
+    /// Set access list
+    pub fn set_access_list(&mut self, v: AccessList) -> &mut Self {
+        self.tx.set_access_list(v);
+        self
+    }
+
+    /// Set access list, if `v` is not None
+    pub fn access_list(&mut self, v: Option<AccessList>) -> &mut Self {
+        if let Some(value) = v {
+            self.set_access_list(value);
+        }
+        self
+    }
+
     /// Set value
     pub fn set_value(&mut self, v: U256) -> &mut Self {
         self.tx.set_value(v);
@@ -260,9 +280,32 @@ async fn resolve_name_args<M: Middleware>(args: &[String], provider: &M) -> Vec<
     .await
 }
 
+/// Validates the blobs of an EIP-4844 transaction and rejects it, because this version can only
+/// build legacy, EIP-2930 and EIP-1559 transactions
+pub fn ensure_blobs_supported(blobs: &[Vec<u8>]) -> Result<()> {
+    if blobs.len() > MAX_BLOBS_PER_TX {
+        eyre::bail!("A transaction can carry at most {MAX_BLOBS_PER_TX} blobs, got {}", blobs.len())
+    }
+    for (i, blob) in blobs.iter().enumerate() {
+        if blob.is_empty() {
+            eyre::bail!("Blob {i} is empty")
+        }
+        if blob.len() > BYTES_PER_BLOB {
+            eyre::bail!(
+                "Blob {i} is {} bytes, but a blob holds at most {BYTES_PER_BLOB} bytes",
+                blob.len()
+            )
+        }
+    }
+    eyre::bail!("Blob transactions (EIP-4844) are not supported yet: only legacy, EIP-2930 and EIP-1559 transactions can be built")
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::TxBuilder;
+    use crate::{
+        tx::{ensure_blobs_supported, BYTES_PER_BLOB, MAX_BLOBS_PER_TX},
+        TxBuilder,
+    };
 
     use ethers_core::types::{
         transaction::eip2718::TypedTransaction, Address, Chain, NameOrAddress, H160, U256,
@@ -384,4 +427,22 @@ mod tests {
         // could test function.inputs() but that should be covered by utils's unit test
         Ok(())
     }
+
+    #[test]
+    fn rejects_blobs() {
+        let err = ensure_blobs_supported(&vec![vec![1]; MAX_BLOBS_PER_TX + 1]).unwrap_err();
+        assert_eq!(err.to_string(), "A transaction can carry at most 6 blobs, got 7");
+
+        let err = ensure_blobs_supported(&[vec![1], vec![]]).unwrap_err();
+        assert_eq!(err.to_string(), "Blob 1 is empty");
+
+        let err = ensure_blobs_supported(&[vec![0; BYTES_PER_BLOB + 1]]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Blob 0 is 131073 bytes, but a blob holds at most 131072 bytes"
+        );
+
+        let err = ensure_blobs_supported(&[vec![0; BYTES_PER_BLOB]]).unwrap_err();
+        assert!(err.to_string().starts_with("Blob transactions (EIP-4844) are not supported yet"));
+    }
 }
@@ -1,38 +1,56 @@
-use cast::{Cast, InterfacePath, SimpleCast, TxBuilder};
+use cast::{
+    ensure_blobs_supported, trace::identifier::SignaturesIdentifier, Cast, InterfacePath,
+    InterfaceSource, SimpleCast, StateOverride, TxBuilder,
+};
 use clap::{IntoApp, Parser};
 use clap_complete::generate;
 use ethers::{
-    abi::HumanReadableParser,
-    core::types::{BlockId, BlockNumber::Latest, H256},
+    abi::{HumanReadableParser, Token},
+    core::types::{BlockId, BlockNumber, BlockNumber::Latest, H256},
+    middleware::SignerMiddleware,
     providers::Middleware,
-    types::{Address, NameOrAddress, U256},
+    signers::Signer,
+    types::{
+        transaction::eip2718::TypedTransaction, Address, Bytes, NameOrAddress, Transaction, U256,
+    },
+    utils::{keccak256, rlp},
 };
 use eyre::WrapErr;
 use foundry_cli::{
-    cmd::Cmd,
+    cmd::{
+        cast::run::{simulate_tx, trace_call},
+        forge::cache::CacheSubcommands,
+        Cmd,
+    },
     handler,
     opts::{
         cast::{Opts, Subcommands},
-        WalletType,
+        TransactionOpts, WalletType,
     },
     utils,
-    utils::consume_config_rpc_url,
+    utils::{consume_config_rpc_url, resolve_name_or_address},
+};
+use foundry_common::{
+    fmt::{to_bytes, UIfmt},
+    fs, get_http_provider, RetryProvider,
 };
-use foundry_common::{fs, get_http_provider};
 use foundry_config::{Chain, Config};
 use foundry_utils::{
-    format_tokens,
+    format_token, format_tokens,
     selectors::{
-        decode_calldata, decode_event_topic, decode_function_selector, import_selectors,
-        parse_signatures, pretty_calldata, ParsedSignatures, SelectorImportData,
+        format_custom_error, import_selectors, parse_signatures, prefixed_event_topic,
+        prefixed_function_selector, pretty_calldata, ParsedSignatures, SelectorImportData,
+        SelectorType,
     },
+    Retry,
 };
 use rustc_hex::ToHex;
 use std::{
-    io::{self, Read, Write},
+    io::{self, BufRead, Read, Write},
     path::Path,
     str::FromStr,
     sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
 };
 
 #[tokio::main]
@@ -59,13 +77,21 @@ async fn main() -> eyre::Result<()> {
         Subcommands::HashZero => {
             println!("{:?}", H256::zero());
         }
-        Subcommands::FromUtf8 { text } => {
-            let val = unwrap_or_stdin(text)?;
-            println!("{}", SimpleCast::from_utf8(&val));
+        Subcommands::FromUtf8 { text, stdin } => {
+            if stdin {
+                map_stdin_lines(|line| Ok(SimpleCast::from_utf8(line)))?;
+            } else {
+                let val = unwrap_or_stdin(text)?;
+                println!("{}", SimpleCast::from_utf8(&val));
+            }
         }
-        Subcommands::ToHex { decimal } => {
-            let val = unwrap_or_stdin(decimal)?;
-            println!("{}", SimpleCast::hex(U256::from_dec_str(&val)?));
+        Subcommands::ToHex { decimal, stdin } => {
+            if stdin {
+                map_stdin_lines(|line| Ok(SimpleCast::hex(U256::from_dec_str(line.trim())?)))?;
+            } else {
+                let val = unwrap_or_stdin(decimal)?;
+                println!("{}", SimpleCast::hex(U256::from_dec_str(&val)?));
+            }
         }
         Subcommands::ConcatHex { data } => {
             println!("{}", SimpleCast::concat_hex(data))
@@ -99,9 +125,13 @@ async fn main() -> eyre::Result<()> {
             };
             println!("0x{output}");
         }
-        Subcommands::ToCheckSumAddress { address } => {
-            let val = unwrap_or_stdin(address)?;
-            println!("{}", SimpleCast::checksum_address(&val)?);
+        Subcommands::ToCheckSumAddress { address, chain_id, stdin } => {
+            if stdin {
+                map_stdin_lines(|line| checksum_address(line, chain_id))?;
+            } else {
+                let val = unwrap_or_stdin(address)?;
+                println!("{}", checksum_address(&val, chain_id)?);
+            }
         }
         Subcommands::ToAscii { hexdata } => {
             let val = unwrap_or_stdin(hexdata)?;
@@ -111,13 +141,21 @@ async fn main() -> eyre::Result<()> {
             let val = unwrap_or_stdin(value)?;
             println!("{}", SimpleCast::from_fix(unwrap_or_stdin(decimals)? as u32, &val)?);
         }
-        Subcommands::ToBytes32 { bytes } => {
-            let val = unwrap_or_stdin(bytes)?;
-            println!("{}", SimpleCast::bytes32(&val)?);
+        Subcommands::ToBytes32 { bytes, stdin } => {
+            if stdin {
+                map_stdin_lines(|line| SimpleCast::bytes32(line.trim()))?;
+            } else {
+                let val = unwrap_or_stdin(bytes)?;
+                println!("{}", SimpleCast::bytes32(&val)?);
+            }
         }
-        Subcommands::ToDec { hexvalue } => {
-            let val = unwrap_or_stdin(hexvalue)?;
-            println!("{}", SimpleCast::to_dec(&val)?);
+        Subcommands::ToDec { hexvalue, stdin } => {
+            if stdin {
+                map_stdin_lines(|line| Ok(SimpleCast::to_dec(line.trim())?.to_string()))?;
+            } else {
+                let val = unwrap_or_stdin(hexvalue)?;
+                println!("{}", SimpleCast::to_dec(&val)?);
+            }
         }
         Subcommands::ToFix { decimals, value } => {
             let val = unwrap_or_stdin(value)?;
@@ -137,54 +175,57 @@ async fn main() -> eyre::Result<()> {
         Subcommands::LeftShift { value, bits, base_in, base_out } => {
             println!(
                 "{}",
-                format_uint(
+                SimpleCast::format_uint(
                     SimpleCast::left_shift(&value, &bits, det_base_in(&value, base_in)?)?,
-                    det_base_out(&base_out)?
+                    base_out
                 )?
             );
         }
         Subcommands::RightShift { value, bits, base_in, base_out } => {
             println!(
                 "{}",
-                format_uint(
+                SimpleCast::format_uint(
                     SimpleCast::right_shift(&value, &bits, det_base_in(&value, base_in)?)?,
-                    det_base_out(&base_out)?
+                    base_out
                 )?
             );
         }
-        Subcommands::ToUnit { value, unit } => {
+        Subcommands::ToBase { value, base_in, base_out } => {
+            println!("{}", SimpleCast::to_base(&value, base_in, base_out)?);
+        }
+        Subcommands::ToUnit { value, unit, decimals } => {
             let val = unwrap_or_stdin(value)?;
-            println!("{}", SimpleCast::to_unit(val, unit)?);
+            let output = match decimals {
+                Some(decimals) => SimpleCast::to_unit_decimals(&val, decimals)?,
+                None => SimpleCast::to_unit(val, unit)?,
+            };
+            println!("{}", output);
         }
-        Subcommands::ToWei { value, unit } => {
+        Subcommands::ToWei { value, unit, decimals } => {
             let val = unwrap_or_stdin(value)?;
-            println!(
-                "{}",
-                SimpleCast::to_wei(
-                    val.parse::<f64>()?,
-                    unit.unwrap_or_else(|| String::from("eth"))
-                )?
-            );
+            let decimals = match decimals {
+                Some(decimals) => decimals,
+                None => SimpleCast::unit_decimals(unit.as_deref().unwrap_or("eth"))?,
+            };
+            println!("{}", SimpleCast::parse_decimals(&val, decimals)?);
         }
-        Subcommands::FromWei { value, unit } => {
+        Subcommands::FromWei { value, unit, decimals } => {
             let val = unwrap_or_stdin(value)?;
-            println!(
-                "{}",
-                SimpleCast::from_wei(
-                    U256::from_dec_str(&val)?,
-                    unit.unwrap_or_else(|| String::from("eth"))
-                )?
-            );
+            let decimals = match decimals {
+                Some(decimals) => decimals,
+                None => SimpleCast::unit_decimals(unit.as_deref().unwrap_or("eth"))?,
+            };
+            println!("{}", SimpleCast::format_decimals(U256::from_dec_str(&val)?, decimals)?);
         }
         Subcommands::ToRlp { value } => {
             let val = unwrap_or_stdin(value)?;
             println!("{}", SimpleCast::to_rlp(&val)?);
         }
-        Subcommands::FromRlp { value } => {
+        Subcommands::FromRlp { value, as_int } => {
             let val = unwrap_or_stdin(value)?;
-            println!("{}", SimpleCast::from_rlp(val)?);
+            println!("{}", SimpleCast::from_rlp(val, as_int)?);
         }
-        Subcommands::AccessList { eth, address, sig, args, block, to_json } => {
+        Subcommands::AccessList { eth, address, sig, args, block, with_gas, to_json } => {
             let config = Config::from(&eth);
             let provider = get_http_provider(
                 config.eth_rpc_url.unwrap_or_else(|| "http://localhost:8545".to_string()),
@@ -201,11 +242,15 @@ async fn main() -> eyre::Result<()> {
             builder.set_args(&sig, args).await?;
             let builder_output = builder.peek();
 
-            println!("{}", Cast::new(&provider).access_list(builder_output, block, to_json).await?);
+            println!(
+                "{}",
+                Cast::new(&provider).access_list(builder_output, block, with_gas, to_json).await?
+            );
         }
         Subcommands::Block { rpc_url, block, full, field, to_json } => {
             let rpc_url = consume_config_rpc_url(rpc_url);
             let provider = get_http_provider(rpc_url);
+            let block = block.resolve(&provider).await?;
             println!("{}", Cast::new(provider).block(block, full, field, to_json).await?);
         }
         Subcommands::BlockNumber { rpc_url } => {
@@ -214,11 +259,26 @@ async fn main() -> eyre::Result<()> {
             println!("{}", Cast::new(provider).block_number().await?);
         }
 
-        Subcommands::Call { address, sig, args, block, eth } => {
+        Subcommands::Call {
+            address,
+            sig,
+            args,
+            block,
+            from_block,
+            to_block,
+            step,
+            override_balance,
+            override_code,
+            override_state,
+            trace,
+            label,
+            with_local_artifacts,
+            to_json,
+            eth,
+        } => {
             let config = Config::from(&eth);
-            let provider = get_http_provider(
-                config.eth_rpc_url.unwrap_or_else(|| "http://localhost:8545".to_string()),
-            );
+            let rpc_url = config.eth_rpc_url.unwrap_or_else(|| "http://localhost:8545".to_string());
+            let provider = get_http_provider(&rpc_url);
 
             let chain: Chain = if let Some(chain) = eth.chain {
                 chain
@@ -230,7 +290,61 @@ async fn main() -> eyre::Result<()> {
                 TxBuilder::new(&provider, config.sender, Some(address), chain, false).await?;
             builder.etherscan_api_key(config.etherscan_api_key).set_args(&sig, args).await?;
             let builder_output = builder.build();
-            println!("{}", Cast::new(provider).call(builder_output, block).await?);
+
+            if trace {
+                trace_call(rpc_url, block, &builder_output.0, &label, with_local_artifacts, false)
+                    .await?;
+                return Ok(())
+            }
+
+            let mut state_override = StateOverride::default();
+            for (address, balance) in override_balance {
+                state_override.set_balance(address, balance)?;
+            }
+            for (address, code) in override_code {
+                state_override.set_code(address, code)?;
+            }
+            for (address, slot, value) in override_state {
+                state_override.set_storage(address, slot, value)?;
+            }
+            let state_override = (!state_override.is_empty()).then_some(state_override);
+
+            let cast = Cast::new(provider);
+            if let (Some(from_block), Some(to_block)) = (from_block, to_block) {
+                if from_block > to_block {
+                    eyre::bail!("--from-block must not be after --to-block")
+                }
+                if step == 0 {
+                    eyre::bail!("--step must be at least 1")
+                }
+
+                let mut samples = Vec::new();
+                for number in (from_block..=to_block).step_by(step as usize) {
+                    let block = Some(BlockId::Number(number.into()));
+                    if to_json {
+                        let result = cast
+                            .call_json(builder_output.clone(), block, state_override.as_ref())
+                            .await?;
+                        samples.push(serde_json::json!({ "block": number, "result": result }));
+                    } else {
+                        let result = cast
+                            .call(builder_output.clone(), block, state_override.as_ref())
+                            .await?;
+                        println!("{}\t{}", number, result.trim_end().replace('\n', "\t"));
+                    }
+                }
+                if to_json {
+                    println!("{}", serde_json::to_string(&samples)?);
+                }
+                return Ok(())
+            }
+
+            if to_json {
+                let output = cast.call_json(builder_output, block, state_override.as_ref()).await?;
+                println!("{}", serde_json::to_string(&output)?);
+            } else {
+                println!("{}", cast.call(builder_output, block, state_override.as_ref()).await?);
+            }
         }
 
         Subcommands::Calldata { sig, args } => {
@@ -253,10 +367,19 @@ async fn main() -> eyre::Result<()> {
             let provider = get_http_provider(rpc_url);
             println!("{}", provider.client_version().await?);
         }
-        Subcommands::ComputeAddress { rpc_url, address, nonce } => {
-            let rpc_url = consume_config_rpc_url(rpc_url);
-
+        Subcommands::ComputeAddress { rpc_url, address, nonce, salt, init_code } => {
             let pubkey = Address::from_str(&address).expect("invalid pubkey provided");
+            if let (Some(salt), Some(init_code)) = (salt, init_code) {
+                let init_code = match init_code.strip_prefix('@') {
+                    Some(path) => fs::read_to_string(path)?,
+                    None => init_code,
+                };
+                let addr = SimpleCast::compute_create2_address(pubkey, &salt, &init_code)?;
+                println!("Computed Address: {}", SimpleCast::checksum_address(&addr)?);
+                return Ok(())
+            }
+
+            let rpc_url = consume_config_rpc_url(rpc_url);
             let provider = get_http_provider(rpc_url);
             let addr = Cast::new(&provider).compute_address(pubkey, nonce).await?;
             println!("Computed Address: {}", SimpleCast::checksum_address(&addr)?);
@@ -264,15 +387,76 @@ async fn main() -> eyre::Result<()> {
         Subcommands::Code { block, who, rpc_url } => {
             let rpc_url = consume_config_rpc_url(rpc_url);
             let provider = get_http_provider(rpc_url);
+            let who = resolve_name_or_address(&provider, who).await?;
             println!("{}", Cast::new(provider).code(who, block).await?);
         }
+        Subcommands::Implementation {
+            block,
+            proxy,
+            admin,
+            name,
+            chain,
+            etherscan_api_key,
+            rpc_url,
+        } => {
+            let rpc_url = consume_config_rpc_url(rpc_url);
+            let provider = get_http_provider(rpc_url);
+            let proxy = resolve_name_or_address(&provider, proxy).await?;
+            let block = match block {
+                Some(block) => Some(block.resolve(&provider).await?),
+                None => None,
+            };
+            let cast = Cast::new(provider);
+            if admin {
+                match cast.admin(proxy, block).await? {
+                    Some(admin) => println!("{}", SimpleCast::checksum_address(&admin)?),
+                    None => {
+                        eyre::bail!("{:?} is not a recognized proxy: no admin slot is set", proxy)
+                    }
+                }
+                return Ok(())
+            }
+
+            let implementation = match cast.implementation(proxy, block).await? {
+                Some((implementation, _)) => implementation,
+                None => eyre::bail!(
+                    "{:?} is not a recognized proxy: no known implementation slot is set",
+                    proxy
+                ),
+            };
+            let checksummed = SimpleCast::checksum_address(&implementation)?;
+            if name {
+                let api_key = match etherscan_api_key.or_else(|| Config::load().etherscan_api_key) {
+                    Some(inner) => inner,
+                    None => {
+                        eyre::bail!("No Etherscan API Key is set. Consider using the ETHERSCAN_API_KEY env var, or setting the -e CLI argument or etherscan-api-key in foundry.toml")
+                    }
+                };
+                match SimpleCast::etherscan_name(chain.inner, implementation, api_key).await? {
+                    Some(name) => println!("{checksummed} {name}"),
+                    None => println!("{checksummed}"),
+                }
+            } else {
+                println!("{checksummed}");
+            }
+        }
+        Subcommands::CreationCode(cmd) => cmd.run()?,
+        Subcommands::RuntimeCode(cmd) => cmd.run()?.await?,
+        Subcommands::CodeSize(cmd) => cmd.run()?.await?,
         Subcommands::Namehash { name } => {
             println!("{}", SimpleCast::namehash(&name)?);
         }
-        Subcommands::Tx { rpc_url, hash, field, to_json } => {
+        Subcommands::Tx { rpc_url, hash, field, to_json, decode, sig, raw, etherscan_api_key } => {
             let rpc_url = consume_config_rpc_url(rpc_url);
             let provider = get_http_provider(rpc_url);
-            println!("{}", Cast::new(&provider).transaction(hash, field, to_json).await?)
+            if raw {
+                println!("{}", Cast::new(&provider).raw_transaction(hash).await?);
+                return Ok(())
+            }
+            println!("{}", Cast::new(&provider).transaction(hash.clone(), field, to_json).await?);
+            if decode || sig.is_some() {
+                decode_tx_input(&provider, &hash, sig, etherscan_api_key).await?;
+            }
         }
         Subcommands::SendTx {
             eth,
@@ -284,17 +468,34 @@ async fn main() -> eyre::Result<()> {
             confirmations,
             to_json,
             resend,
+            pending_nonce,
+            simulate,
+            dry_run,
+            access_list_auto,
+            force_access_list,
+            blob,
         } => {
+            if !blob.is_empty() {
+                let blobs = blob.iter().map(fs::read).collect::<Result<Vec<_>, _>>()?;
+                ensure_blobs_supported(&blobs)?;
+            }
+
             let config = Config::from(&eth);
-            let provider = Arc::new(get_http_provider(
-                &config.eth_rpc_url.unwrap_or_else(|| "http://localhost:8545".to_string()),
-            ));
+            let rpc_url = config.eth_rpc_url.unwrap_or_else(|| "http://localhost:8545".to_string());
+            let provider = Arc::new(get_http_provider(&rpc_url));
             let chain: Chain = if let Some(chain) = eth.chain {
                 chain
             } else {
                 provider.get_chainid().await?.into()
             };
             let sig = sig.unwrap_or_default();
+            let options = SendOptions {
+                to_json,
+                simulate: simulate || dry_run,
+                dry_run,
+                access_list: access_list_auto || force_access_list,
+                force_access_list,
+            };
 
             if let Ok(Some(signer)) = eth.signer_with(chain.into(), provider.clone()).await {
                 let from = match &signer {
@@ -311,14 +512,17 @@ async fn main() -> eyre::Result<()> {
                     }
                 }
 
-                if resend {
-                    tx.nonce = Some(provider.get_transaction_count(from, None).await?);
+                if resend || pending_nonce {
+                    let block =
+                        if pending_nonce { Some(BlockNumber::Pending.into()) } else { None };
+                    tx.nonce = Some(provider.get_transaction_count(from, block).await?);
                 }
 
                 match signer {
                     WalletType::Ledger(signer) => {
                         cast_send(
                             &signer,
+                            &rpc_url,
                             from,
                             to,
                             (sig, args),
@@ -332,13 +536,14 @@ async fn main() -> eyre::Result<()> {
                             cast_async,
                             tx.legacy,
                             confirmations,
-                            to_json,
+                            options,
                         )
                         .await?;
                     }
                     WalletType::Local(signer) => {
                         cast_send(
                             &signer,
+                            &rpc_url,
                             from,
                             to,
                             (sig, args),
@@ -352,13 +557,14 @@ async fn main() -> eyre::Result<()> {
                             cast_async,
                             tx.legacy,
                             confirmations,
-                            to_json,
+                            options,
                         )
                         .await?;
                     }
                     WalletType::Trezor(signer) => {
                         cast_send(
                             &signer,
+                            &rpc_url,
                             from,
                             to,
                             (sig, args),
@@ -372,7 +578,7 @@ async fn main() -> eyre::Result<()> {
                             cast_async,
                             tx.legacy,
                             confirmations,
-                            to_json,
+                            options,
                         )
                         .await?;
                     }
@@ -381,12 +587,15 @@ async fn main() -> eyre::Result<()> {
             } else if config.sender !=
                 Address::from_str("00a329c0648769A73afAc7F9381E08FB43dBEA72").unwrap()
             {
-                if resend {
-                    tx.nonce = Some(provider.get_transaction_count(config.sender, None).await?);
+                if resend || pending_nonce {
+                    let block =
+                        if pending_nonce { Some(BlockNumber::Pending.into()) } else { None };
+                    tx.nonce = Some(provider.get_transaction_count(config.sender, block).await?);
                 }
 
                 cast_send(
                     provider,
+                    &rpc_url,
                     config.sender,
                     to,
                     (sig, args),
@@ -400,14 +609,54 @@ async fn main() -> eyre::Result<()> {
                     cast_async,
                     tx.legacy,
                     confirmations,
-                    to_json,
+                    options,
                 )
                 .await?;
             } else {
                 eyre::bail!("No wallet or sender address provided. Consider passing it via the --from flag or setting the ETH_FROM env variable or setting in the foundry.toml file");
             }
         }
-        Subcommands::PublishTx { eth, raw_tx, cast_async } => {
+        Subcommands::MakeTx { to, sig, args, tx, eth } => {
+            let config = Config::from(&eth);
+            let provider = Arc::new(get_http_provider(
+                &config.eth_rpc_url.unwrap_or_else(|| "http://localhost:8545".to_string()),
+            ));
+            let chain: Chain = if let Some(chain) = eth.chain {
+                chain
+            } else {
+                provider.get_chainid().await?.into()
+            };
+            let sig = sig.unwrap_or_default();
+
+            let signer = eth.signer_with(chain.into(), provider.clone()).await?.ok_or_else(|| {
+                eyre::eyre!("No wallet provided. Consider passing a private key, keystore, mnemonic or hardware wallet to sign the transaction with")
+            })?;
+
+            let raw_tx = match signer {
+                WalletType::Ledger(signer) => {
+                    cast_mktx(&signer, to, (sig, args), tx, chain, config.etherscan_api_key).await?
+                }
+                WalletType::Local(signer) => {
+                    cast_mktx(&signer, to, (sig, args), tx, chain, config.etherscan_api_key).await?
+                }
+                WalletType::Trezor(signer) => {
+                    cast_mktx(&signer, to, (sig, args), tx, chain, config.etherscan_api_key).await?
+                }
+            };
+            println!("0x{}", hex::encode(raw_tx));
+        }
+        Subcommands::DecodeTransaction { raw_tx, sig, to_json } => {
+            let tx = decode_raw_transaction(&unwrap_or_stdin(raw_tx)?)?;
+            if to_json {
+                println!("{}", serde_json::to_string(&tx)?);
+            } else {
+                println!("{}", pretty_raw_transaction(&tx));
+                if let Some(sig) = sig {
+                    print_decoded_input(&tx.input, vec![sig])?;
+                }
+            }
+        }
+        Subcommands::PublishTx { eth, raw_tx, cast_async, confirmations } => {
             let config = Config::from(&eth);
             let provider = get_http_provider(
                 config.eth_rpc_url.unwrap_or_else(|| "http://localhost:8545".to_string()),
@@ -419,58 +668,140 @@ async fn main() -> eyre::Result<()> {
             if cast_async {
                 println!("{:?}", pending_tx);
             } else {
-                let receipt =
-                    pending_tx.await?.ok_or_else(|| eyre::eyre!("tx {tx_hash} not found"))?;
-                println!("{}", serde_json::json!(receipt));
+                let receipt = cast
+                    .receipt(format!("{:#x}", tx_hash), None, confirmations, false, false, true)
+                    .await?;
+                println!("{receipt}");
             }
         }
-        Subcommands::CalldataDecode { sig, calldata } => {
-            let tokens = SimpleCast::abi_decode(&sig, &calldata, true)?;
-            let tokens = format_tokens(&tokens);
-            tokens.for_each(|t| println!("{t}"));
+        Subcommands::CalldataDecode { sig, calldata, file } => {
+            abi_decode(&sig, calldata, file.as_deref(), true)?;
         }
-        Subcommands::AbiDecode { sig, calldata, input } => {
-            let tokens = SimpleCast::abi_decode(&sig, &calldata, input)?;
-            let tokens = format_tokens(&tokens);
-            tokens.for_each(|t| println!("{t}"));
+        Subcommands::AbiDecode { sig, calldata, file, input } => {
+            abi_decode(&sig, calldata, file.as_deref(), input)?;
         }
-        Subcommands::AbiEncode { sig, args } => {
-            println!("{}", SimpleCast::abi_encode(&sig, &args)?);
+        Subcommands::AbiEncode { sig, args, packed } => {
+            if packed {
+                println!("{}", SimpleCast::abi_encode_packed(&sig, &args)?);
+            } else {
+                println!("{}", SimpleCast::abi_encode(&sig, &args)?);
+            }
         }
         Subcommands::Index { key_type, key, slot_number } => {
             let encoded = SimpleCast::index(&key_type, &key, &slot_number)?;
             println!("{encoded}");
         }
-        Subcommands::FourByte { selector } => {
-            let sigs = decode_function_selector(&selector).await?;
-            sigs.iter().for_each(|sig| println!("{}", sig));
+        Subcommands::IndexErc7201 { namespace } => {
+            println!("{}", SimpleCast::index_erc7201(&namespace));
+        }
+        Subcommands::FourByte { selector, offline, error } => {
+            let data = selector.strip_prefix("0x").unwrap_or(&selector);
+            if error && data.len() > 8 {
+                // decode the revert data with every matching error signature
+                let sigs = SignaturesIdentifier::new(Config::foundry_cache_dir(), offline)?
+                    .decode_selector(&prefixed_function_selector(&data[..8])?, SelectorType::Error)
+                    .await?;
+                let decoded = sigs
+                    .iter()
+                    .filter_map(|sig| format_custom_error(sig, data).ok())
+                    .collect::<Vec<_>>();
+                if decoded.is_empty() {
+                    eyre::bail!("No error signatures found that decode the revert data")
+                }
+                decoded.iter().for_each(|error| println!("{error}"));
+            } else {
+                let selector_type =
+                    if error { SelectorType::Error } else { SelectorType::Function };
+                let selector = prefixed_function_selector(&selector)?;
+                let sigs = SignaturesIdentifier::new(Config::foundry_cache_dir(), offline)?
+                    .decode_selector(&selector, selector_type)
+                    .await?;
+                sigs.iter().for_each(|sig| println!("{}", sig));
+            }
         }
-        Subcommands::FourByteDecode { calldata } => {
+        Subcommands::FourByteDecode { calldata, offline, error } => {
             let calldata = unwrap_or_stdin(calldata)?;
-            let sigs = decode_calldata(&calldata).await?;
-            sigs.iter().enumerate().for_each(|(i, sig)| println!("{}) \"{}\"", i + 1, sig));
-
-            let sig = match sigs.len() {
-                0 => Err(eyre::eyre!("No signatures found")),
-                1 => Ok(sigs.get(0).unwrap()),
-                _ => {
-                    print!("Select a function signature by number: ");
-                    io::stdout().flush()?;
-                    let mut input = String::new();
-                    io::stdin().read_line(&mut input)?;
-                    let i: usize = input.trim().parse()?;
-                    Ok(sigs.get(i - 1).expect("Invalid signature index"))
-                }
-            }?;
+            let data = calldata.strip_prefix("0x").unwrap_or(&calldata);
+            if data.len() < 8 {
+                eyre::bail!(
+                    "Calldata too short: expected at least 8 characters (excluding 0x prefix), got {}.",
+                    data.len()
+                )
+            }
+            let selector = prefixed_function_selector(&data[..8])?;
+            let selector_type = if error { SelectorType::Error } else { SelectorType::Function };
+            let sigs = SignaturesIdentifier::new(Config::foundry_cache_dir(), offline)?
+                .decode_selector(&selector, selector_type)
+                .await?
+                .into_iter()
+                // filter for signatures that can be decoded
+                .filter(|sig| SimpleCast::abi_decode(sig, &calldata, true).is_ok())
+                .collect::<Vec<_>>();
+            let kind = if error { "an error" } else { "a function" };
+            let sig = &sigs[select_signature(&sigs, kind)?];
 
             let tokens = SimpleCast::abi_decode(sig, &calldata, true)?;
             let tokens = format_tokens(&tokens);
 
             tokens.for_each(|t| println!("{t}"));
         }
-        Subcommands::FourByteEvent { topic } => {
-            let sigs = decode_event_topic(&topic).await?;
-            sigs.iter().for_each(|sig| println!("{}", sig));
+        Subcommands::DecodeError { data, offline } => {
+            println!("{}", SimpleCast::decode_error(&data, offline).await?);
+        }
+        Subcommands::FourByteEvent { topic, offline, decode, topics, data } => {
+            let topic = prefixed_event_topic(&topic)?;
+            let sigs = SignaturesIdentifier::new(Config::foundry_cache_dir(), offline)?
+                .decode_selector(&topic, SelectorType::Event)
+                .await?;
+            if !decode {
+                sigs.iter().for_each(|sig| println!("{}", sig));
+                return Ok(())
+            }
+
+            let topics = std::iter::once(topic).chain(topics).collect::<Vec<_>>();
+            let decoded = sigs
+                .into_iter()
+                // filter for signatures that can be decoded
+                .filter_map(|sig| {
+                    SimpleCast::decode_event(&sig, &topics, &data, false)
+                        .ok()
+                        .map(|params| (sig, params))
+                })
+                .collect::<Vec<_>>();
+            let sigs = decoded.iter().map(|(sig, _)| sig.clone()).collect::<Vec<_>>();
+            let (_, params) = &decoded[select_signature(&sigs, "an event")?];
+            print_event_params(params, "");
+        }
+
+        Subcommands::DecodeEvent { sig, topics, data, anonymous, offline } => {
+            if let Some(sig) = sig {
+                print_event_params(&SimpleCast::decode_event(&sig, &topics, &data, anonymous)?, "");
+            } else {
+                let topic = topics.first().ok_or_else(|| {
+                    eyre::eyre!(
+                        "The log has no topics, the event signature must be given with --sig"
+                    )
+                })?;
+                let topic = prefixed_event_topic(topic)?;
+                let decoded = SignaturesIdentifier::new(Config::foundry_cache_dir(), offline)?
+                    .decode_selector(&topic, SelectorType::Event)
+                    .await?
+                    .into_iter()
+                    // filter for signatures that can be decoded
+                    .filter_map(|sig| {
+                        SimpleCast::decode_event(&sig, &topics, &data, false)
+                            .ok()
+                            .map(|params| (sig, params))
+                    })
+                    .collect::<Vec<_>>();
+                if decoded.is_empty() {
+                    eyre::bail!("No event signatures found that decode the log")
+                }
+                for (sig, params) in &decoded {
+                    println!("{sig}");
+                    print_event_params(params, "  ");
+                }
+            }
         }
 
         Subcommands::UploadSignature { signatures } => {
@@ -491,68 +822,176 @@ async fn main() -> eyre::Result<()> {
             let pretty_data = pretty_calldata(&calldata, offline).await?;
             println!("{pretty_data}");
         }
-        Subcommands::Age { block, rpc_url } => {
+        Subcommands::Age { block, human, since, rpc_url } => {
             let rpc_url = consume_config_rpc_url(rpc_url);
             let provider = get_http_provider(rpc_url);
-            println!(
-                "{}",
-                Cast::new(provider).age(block.unwrap_or(BlockId::Number(Latest))).await?
-            );
+            let block = match block {
+                Some(block) => block.resolve(&provider).await?,
+                None => BlockId::Number(Latest),
+            };
+            if let Some(since) = since {
+                let since = since.resolve(&provider).await?;
+                let block_time = Cast::new(provider).average_block_time(since, block).await?;
+                println!("{:.2} seconds per block", block_time);
+            } else if human {
+                let timestamp = Cast::new(provider).timestamp(block).await?.as_u64();
+                let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+                println!("{}", SimpleCast::relative_time(timestamp, now));
+            } else {
+                println!("{}", Cast::new(provider).age(block).await?);
+            }
         }
-        Subcommands::Balance { block, who, rpc_url } => {
+        Subcommands::Balance { block, who, erc20, raw, rpc_url } => {
             let rpc_url = consume_config_rpc_url(rpc_url);
             let provider = get_http_provider(rpc_url);
-            println!("{}", Cast::new(provider).balance(who, block).await?);
+            let block = match block {
+                Some(block) => Some(block.resolve(&provider).await?),
+                None => None,
+            };
+            let who = resolve_name_or_address(&provider, who).await?;
+            let erc20 = match erc20 {
+                Some(token) => Some(resolve_name_or_address(&provider, token).await?),
+                None => None,
+            };
+            let cast = Cast::new(provider);
+            match erc20 {
+                Some(token) => {
+                    let balance = cast.erc20_balance(token, who, block).await?;
+                    let decimals = if raw { 0 } else { cast.erc20_decimals(token, block).await? };
+                    if decimals == 0 {
+                        println!("{balance}");
+                    } else {
+                        println!("{}", ethers::utils::format_units(balance, decimals as u32)?);
+                    }
+                }
+                None => println!("{}", cast.balance(who, block).await?),
+            }
         }
         Subcommands::BaseFee { block, rpc_url } => {
             let rpc_url = consume_config_rpc_url(rpc_url);
 
             let provider = get_http_provider(rpc_url);
-            println!(
-                "{}",
-                Cast::new(provider).base_fee(block.unwrap_or(BlockId::Number(Latest))).await?
-            );
+            let block = match block {
+                Some(block) => block.resolve(&provider).await?,
+                None => BlockId::Number(Latest),
+            };
+            println!("{}", Cast::new(provider).base_fee(block).await?);
         }
-        Subcommands::GasPrice { rpc_url } => {
+        Subcommands::GasPrice { rpc_url, history, percentiles, to_json } => {
             let rpc_url = consume_config_rpc_url(rpc_url);
             let provider = get_http_provider(rpc_url);
-            println!("{}", Cast::new(provider).gas_price().await?);
+            let cast = Cast::new(provider);
+            match history {
+                Some(blocks) => {
+                    println!("{}", cast.fee_history(blocks, &percentiles, to_json).await?)
+                }
+                None if to_json => println!("{}", serde_json::json!(cast.gas_price().await?)),
+                None => println!("{}", cast.gas_price().await?),
+            }
         }
-        Subcommands::Keccak { data } => {
-            println!("{}", SimpleCast::keccak(&data)?);
+        Subcommands::Keccak { data, file, hex, stdin } => {
+            if stdin {
+                map_stdin_lines(|line| {
+                    if hex {
+                        SimpleCast::keccak_hex(line)
+                    } else {
+                        SimpleCast::keccak(line)
+                    }
+                })?;
+                return Ok(())
+            }
+            let hash = match (file, data) {
+                (Some(path), _) => {
+                    let file = std::fs::File::open(&path)
+                        .wrap_err_with(|| format!("Failed to open {}", path.display()))?;
+                    SimpleCast::keccak_reader(file)?
+                }
+                (None, Some(data)) if hex => SimpleCast::keccak_hex(&data)?,
+                (None, Some(data)) => SimpleCast::keccak(&data)?,
+                (None, None) => unreachable!("clap requires either data or a file"),
+            };
+            println!("{hash}");
         }
 
         Subcommands::Interface {
-            path_or_address,
+            paths_or_addresses,
             name,
             pragma,
             chain,
             output_location,
             etherscan_api_key,
+            allow_duplicate_names,
         } => {
-            let interfaces = if Path::new(&path_or_address).exists() {
-                SimpleCast::generate_interface(InterfacePath::Local { path: path_or_address, name })
-                    .await?
-            } else {
-                let api_key = match etherscan_api_key {
-                    Some(inner) => inner,
-                    _ => {
-                        if let Some(etherscan_api_key) = Config::load().etherscan_api_key {
-                            etherscan_api_key
-                        } else {
+            let single = paths_or_addresses.len() == 1;
+            let mut api_key = etherscan_api_key;
+            let mut targets = Vec::with_capacity(paths_or_addresses.len());
+            for path_or_address in paths_or_addresses {
+                let target = if Path::new(&path_or_address).exists() {
+                    // with several local files, fall back to the file name so that the generated
+                    // interfaces don't all end up being called `Interface`
+                    let name = name.clone().or_else(|| {
+                        (!single)
+                            .then(|| Path::new(&path_or_address).file_stem())
+                            .flatten()
+                            .map(|stem| stem.to_string_lossy().into_owned())
+                    });
+                    InterfacePath::Local { path: path_or_address, name }
+                } else {
+                    if api_key.is_none() {
+                        api_key = Config::load().etherscan_api_key;
+                    }
+                    let api_key = match api_key.clone() {
+                        Some(inner) => inner,
+                        None => {
                             eyre::bail!("No Etherscan API Key is set. Consider using the ETHERSCAN_API_KEY env var, or setting the -e CLI argument or etherscan-api-key in foundry.toml")
                         }
+                    };
+                    InterfacePath::Etherscan {
+                        chain: chain.inner,
+                        api_key,
+                        address: path_or_address.parse::<Address>().wrap_err_with(|| {
+                            format!(
+                                "Invalid address provided: {path_or_address}. Did you make a typo?"
+                            )
+                        })?,
                     }
                 };
-                SimpleCast::generate_interface(InterfacePath::Etherscan {
-                    chain: chain.inner,
-                    api_key,
-                    address: path_or_address
-                        .parse::<Address>()
-                        .wrap_err("Invalid address provided. Did you make a typo?")?,
-                })
-                .await?
-            };
+                targets.push(target);
+            }
+
+            let fetched =
+                futures::future::try_join_all(targets.into_iter().map(|target| async move {
+                    match target {
+                        InterfacePath::Local { .. } => SimpleCast::generate_interface(target).await,
+                        InterfacePath::Etherscan { .. } => {
+                            Retry::new(3, Some(1))
+                                .run_async(|| {
+                                    Box::pin(SimpleCast::generate_interface(target.clone()))
+                                })
+                                .await
+                        }
+                    }
+                }))
+                .await?;
+
+            // merge the results, skipping identical interfaces that were requested more than once
+            let mut interfaces: Vec<InterfaceSource> = Vec::new();
+            for iface in fetched.into_iter().flatten() {
+                match interfaces.iter().find(|existing| existing.name == iface.name) {
+                    Some(existing) if existing.source == iface.source => {}
+                    Some(_) if allow_duplicate_names => {
+                        eprintln!(
+                            "Warning: skipping duplicate interface `{}`, keeping the first one",
+                            iface.name
+                        );
+                    }
+                    Some(_) => eyre::bail!(
+                        "Multiple different interfaces are named `{}`. Use --name to rename local ABIs, or pass --allow-duplicate-names to keep only the first one.",
+                        iface.name
+                    ),
+                    None => interfaces.push(iface),
+                }
+            }
 
             // put it all together
             let pragma = format!("pragma solidity {pragma};");
@@ -605,37 +1044,35 @@ async fn main() -> eyre::Result<()> {
             }
             println!("{name}");
         }
-        Subcommands::Storage { address, slot, rpc_url, block } => {
-            let rpc_url = consume_config_rpc_url(rpc_url);
-
-            let provider = get_http_provider(rpc_url);
-            let value = provider.get_storage_at(address, slot, block).await?;
-            println!("{:?}", value);
-        }
+        Subcommands::Storage(cmd) => cmd.run()?.await?,
+        Subcommands::StorageLayout(cmd) => cmd.run()?,
         Subcommands::Proof { address, slots, rpc_url, block } => {
             let rpc_url = consume_config_rpc_url(rpc_url);
 
             let provider = get_http_provider(rpc_url);
+            let address = resolve_name_or_address(&provider, address).await?;
             let value = provider.get_proof(address, slots, block).await?;
             println!("{}", serde_json::to_string(&value)?);
         }
-        Subcommands::Receipt { hash, field, to_json, rpc_url, cast_async, confirmations } => {
+        Subcommands::Receipt { hash, field, to_json, rpc_url, cast_async, confirmations, cost } => {
             let rpc_url = consume_config_rpc_url(rpc_url);
             let provider = get_http_provider(rpc_url);
             println!(
                 "{}",
                 Cast::new(provider)
-                    .receipt(hash, field, confirmations, cast_async, to_json)
+                    .receipt(hash, field, confirmations, cast_async, cost, to_json)
                     .await?
             );
         }
-        Subcommands::Nonce { block, who, rpc_url } => {
+        Subcommands::Nonce { block, who, pending, rpc_url } => {
             let rpc_url = consume_config_rpc_url(rpc_url);
+            let block = if pending { Some(BlockNumber::Pending.into()) } else { block };
 
             let provider = get_http_provider(rpc_url);
+            let who = resolve_name_or_address(&provider, who).await?;
             println!("{}", Cast::new(provider).nonce(who, block).await?);
         }
-        Subcommands::EtherscanSource { chain, address, directory, etherscan_api_key } => {
+        Subcommands::EtherscanSource { chain, address, directory, flatten, etherscan_api_key } => {
             let api_key = match etherscan_api_key {
                 Some(inner) => inner,
                 _ => {
@@ -656,6 +1093,13 @@ async fn main() -> eyre::Result<()> {
                     )
                     .await?
                 }
+                None if flatten => {
+                    println!(
+                        "{}",
+                        SimpleCast::etherscan_source_flattened(chain.inner, address, api_key)
+                            .await?
+                    );
+                }
                 None => {
                     println!(
                         "{}",
@@ -668,9 +1112,18 @@ async fn main() -> eyre::Result<()> {
             let selector = HumanReadableParser::parse_function(&sig)?.short_signature();
             println!("0x{}", hex::encode(selector));
         }
+        Subcommands::SigEvent { event_sig } => {
+            let event = HumanReadableParser::parse_event(&event_sig)?;
+            println!("{:?}", event.signature());
+        }
         Subcommands::FindBlock(cmd) => cmd.run()?.await?,
         Subcommands::Estimate(cmd) => cmd.run().await?,
+        Subcommands::Cache(cmd) => match cmd.sub {
+            CacheSubcommands::Clean(cmd) => cmd.run()?,
+            CacheSubcommands::Ls(cmd) => cmd.run()?,
+        },
         Subcommands::Wallet { command } => command.run().await?,
+        Subcommands::TxPool { command } => command.run().await?,
         Subcommands::Completions { shell } => {
             generate(shell, &mut Opts::command(), "cast", &mut std::io::stdout())
         }
@@ -681,7 +1134,45 @@ async fn main() -> eyre::Result<()> {
             &mut std::io::stdout(),
         ),
         Subcommands::Run(cmd) => cmd.run()?,
+        Subcommands::Logs(cmd) => cmd.run()?.await?,
         Subcommands::Rpc(cmd) => cmd.run()?.await?,
+        Subcommands::Selectors(cmd) => cmd.run()?.await?,
+        Subcommands::Disassemble { bytecode, block, raw, rpc_url } => {
+            let who = if raw {
+                None
+            } else if let Ok(address) = bytecode.parse::<Address>() {
+                Some(NameOrAddress::Address(address))
+            } else if !bytecode.starts_with("0x") && bytecode.contains('.') {
+                Some(NameOrAddress::Name(bytecode.clone()))
+            } else {
+                None
+            };
+            let code = match who {
+                Some(who) => {
+                    let is_name = matches!(who, NameOrAddress::Name(_));
+                    let rpc_url = consume_config_rpc_url(rpc_url);
+                    let provider = get_http_provider(rpc_url);
+                    let block = match block {
+                        Some(block) => Some(block.resolve(&provider).await?),
+                        None => None,
+                    };
+                    let code = Cast::new(provider).code(who, block).await?;
+                    match code.as_str() {
+                        "0x" if is_name => eyre::bail!("No code deployed at {bytecode}"),
+                        // 20 bytes of bytecode look like an address
+                        "0x" => {
+                            eprintln!(
+                                "No code deployed at {bytecode}, disassembling it as bytecode"
+                            );
+                            bytecode
+                        }
+                        _ => code,
+                    }
+                }
+                None => bytecode,
+            };
+            println!("{}", SimpleCast::disassemble(&code)?);
+        }
         Subcommands::FormatBytes32String { string } => {
             let val = unwrap_or_stdin(string)?;
             println!("{}", SimpleCast::format_bytes32_string(&val)?);
@@ -694,6 +1185,268 @@ async fn main() -> eyre::Result<()> {
     Ok(())
 }
 
+/// Decodes and prints the input data of the transaction.
+///
+/// The signature is taken from `sig`, the ABI of the verified contract or, as a last resort, all
+/// decodable candidates of the selector are printed.
+async fn decode_tx_input(
+    provider: &RetryProvider,
+    hash: &str,
+    sig: Option<String>,
+    etherscan_api_key: Option<String>,
+) -> eyre::Result<()> {
+    let tx = provider
+        .get_transaction(H256::from_str(hash)?)
+        .await?
+        .ok_or_else(|| eyre::eyre!("transaction {:?} not found", hash))?;
+    if tx.input.len() < 4 {
+        println!("\nNo input data to decode.");
+        return Ok(())
+    }
+    let selector: [u8; 4] = tx.input[..4].try_into()?;
+
+    let sigs = if let Some(sig) = sig {
+        vec![sig]
+    } else if let Some(sig) =
+        verified_function_signature(provider, tx.to, selector, etherscan_api_key).await
+    {
+        vec![sig]
+    } else {
+        SignaturesIdentifier::new(Config::foundry_cache_dir(), false)?
+            .decode_selector(&format!("0x{}", hex::encode(selector)), SelectorType::Function)
+            .await?
+    };
+
+    print_decoded_input(&tx.input, sigs)
+}
+
+/// Prints the input data decoded with every signature in `sigs` that fits it
+fn print_decoded_input(input: &Bytes, sigs: Vec<String>) -> eyre::Result<()> {
+    let calldata = format!("0x{}", hex::encode(input));
+    let mut decoded = sigs
+        .into_iter()
+        .filter_map(|sig| {
+            SimpleCast::abi_decode(&sig, &calldata, true).ok().map(|tokens| (sig, tokens))
+        })
+        .peekable();
+    if decoded.peek().is_none() {
+        eyre::bail!("Could not decode the input data with any known signature")
+    }
+    for (sig, tokens) in decoded {
+        println!("\nDecoded input ({sig}):");
+        format_tokens(&tokens).for_each(|token| println!("  {token}"));
+    }
+    Ok(())
+}
+
+/// Decodes a hex encoded, signed legacy, EIP-2930 or EIP-1559 transaction and recovers its
+/// sender.
+fn decode_raw_transaction(raw_tx: &str) -> eyre::Result<Transaction> {
+    let raw_tx = raw_tx.trim();
+    let data = hex::decode(raw_tx.strip_prefix("0x").unwrap_or(raw_tx))
+        .wrap_err("Invalid raw transaction hex")?;
+    match data.first() {
+        None => eyre::bail!("The raw transaction is empty"),
+        Some(0x03) => {
+            eyre::bail!("EIP-4844 blob transactions (type 3) are not supported")
+        }
+        Some(_) => {}
+    }
+
+    let (tx, signature) = TypedTransaction::decode_signed(&rlp::Rlp::new(&data))
+        .wrap_err("Invalid raw transaction")?;
+    let from = signature
+        .recover(tx.sighash())
+        .wrap_err("Failed to recover the sender of the transaction")?;
+
+    let mut decoded = Transaction {
+        hash: keccak256(&data).into(),
+        nonce: tx.nonce().copied().unwrap_or_default(),
+        from,
+        to: tx.to().and_then(|to| to.as_address()).copied(),
+        value: tx.value().copied().unwrap_or_default(),
+        gas: tx.gas().copied().unwrap_or_default(),
+        input: tx.data().cloned().unwrap_or_default(),
+        v: signature.v.into(),
+        r: signature.r,
+        s: signature.s,
+        chain_id: tx.chain_id().map(|id| id.as_u64().into()),
+        ..Default::default()
+    };
+    match tx {
+        TypedTransaction::Legacy(tx) => {
+            decoded.transaction_type = Some(0u64.into());
+            decoded.gas_price = tx.gas_price;
+        }
+        TypedTransaction::Eip2930(tx) => {
+            decoded.transaction_type = Some(1u64.into());
+            decoded.gas_price = tx.tx.gas_price;
+            decoded.access_list = Some(tx.access_list);
+        }
+        TypedTransaction::Eip1559(tx) => {
+            decoded.transaction_type = Some(2u64.into());
+            decoded.max_fee_per_gas = tx.max_fee_per_gas;
+            decoded.max_priority_fee_per_gas = tx.max_priority_fee_per_gas;
+            decoded.access_list = Some(tx.access_list);
+        }
+    }
+    Ok(decoded)
+}
+
+/// Formats the fields of a decoded raw transaction, with the fee fields of its type
+fn pretty_raw_transaction(tx: &Transaction) -> String {
+    let typed = tx.transaction_type.map_or(false, |ty| !ty.is_zero());
+    let fees = if tx.transaction_type == Some(2u64.into()) {
+        format!(
+            "maxFeePerGas         {}\nmaxPriorityFeePerGas {}",
+            tx.max_fee_per_gas.pretty(),
+            tx.max_priority_fee_per_gas.pretty()
+        )
+    } else {
+        format!("gasPrice             {}", tx.gas_price.pretty())
+    };
+    let access_list = if typed {
+        format!("\naccessList           {}", serde_json::to_string(&tx.access_list).unwrap())
+    } else {
+        String::new()
+    };
+    format!(
+        "type                 {}
+chainId              {}
+hash                 {}
+from                 {}
+to                   {}
+nonce                {}
+value                {}
+gas                  {}
+{fees}
+input                {}{access_list}
+r                    {}
+s                    {}
+v                    {}",
+        tx.transaction_type.pretty(),
+        tx.chain_id.pretty(),
+        tx.hash.pretty(),
+        tx.from.pretty(),
+        tx.to.pretty(),
+        tx.nonce.pretty(),
+        tx.value.pretty(),
+        tx.gas.pretty(),
+        tx.input.pretty(),
+        to_bytes(tx.r).pretty(),
+        to_bytes(tx.s).pretty(),
+        tx.v.pretty(),
+    )
+}
+
+/// Returns the signature of the function with the `selector` of the contract at `to`, if it's
+/// verified on etherscan
+async fn verified_function_signature(
+    provider: &RetryProvider,
+    to: Option<Address>,
+    selector: [u8; 4],
+    etherscan_api_key: Option<String>,
+) -> Option<String> {
+    let (to, api_key) = (to?, etherscan_api_key.or_else(|| Config::load().etherscan_api_key)?);
+    let chain = ethers::types::Chain::try_from(provider.get_chainid().await.ok()?.as_u64()).ok()?;
+    let client = ethers::etherscan::Client::new(chain, api_key).ok()?;
+    let abi = client.contract_abi(to).await.ok()?;
+    let function = abi.functions().find(|function| function.short_signature() == selector)?;
+    Some(format!(
+        "{}({})",
+        function.name,
+        function.inputs.iter().map(|param| param.kind.to_string()).collect::<Vec<_>>().join(",")
+    ))
+}
+
+/// Prints the numbered signatures and returns the index of the one to use, asking the user to
+/// select one if there are several.
+fn select_signature(sigs: &[String], kind: &str) -> eyre::Result<usize> {
+    sigs.iter().enumerate().for_each(|(i, sig)| println!("{}) \"{}\"", i + 1, sig));
+
+    match sigs.len() {
+        0 => Err(eyre::eyre!("No signatures found")),
+        1 => Ok(0),
+        _ => {
+            print!("Select {kind} signature by number: ");
+            io::stdout().flush()?;
+            let mut input = String::new();
+            io::stdin().read_line(&mut input)?;
+            let i: usize = input.trim().parse()?;
+            if i == 0 || i > sigs.len() {
+                eyre::bail!("Invalid signature index: {i}")
+            }
+            Ok(i - 1)
+        }
+    }
+}
+
+/// Prints the name and value of every decoded event parameter on its own line
+fn print_event_params(params: &[(String, Token)], indent: &str) {
+    for (name, value) in params {
+        println!("{indent}{name}: {}", format_token(value));
+    }
+}
+
+/// Decodes `calldata`, or every non-empty line of `file`, and prints the decoded tokens.
+///
+/// The results of the lines of a file are separated by `---`.
+fn abi_decode(
+    sig: &str,
+    calldata: Option<String>,
+    file: Option<&Path>,
+    input: bool,
+) -> eyre::Result<()> {
+    let file = match file {
+        Some(file) => file,
+        None => {
+            let tokens = SimpleCast::abi_decode(sig, &calldata.unwrap_or_default(), input)?;
+            format_tokens(&tokens).for_each(|t| println!("{t}"));
+            return Ok(())
+        }
+    };
+
+    let content = fs::read_to_string(file)?;
+    let lines = content.lines().enumerate().filter(|(_, line)| !line.trim().is_empty());
+    for (n, (i, line)) in lines.enumerate() {
+        let tokens = SimpleCast::abi_decode(sig, line.trim(), input)
+            .wrap_err_with(|| format!("Failed to decode line {}", i + 1))?;
+        if n > 0 {
+            println!("---");
+        }
+        format_tokens(&tokens).for_each(|t| println!("{t}"));
+    }
+    Ok(())
+}
+
+/// Checksums `address`, warning if it is mixed-case but its checksum is invalid
+fn checksum_address(address: &str, chain_id: Option<u64>) -> eyre::Result<String> {
+    let address = address.trim();
+    if SimpleCast::has_invalid_checksum(address, chain_id)? {
+        eprintln!("Warning: {address} has an invalid checksum");
+    }
+    Ok(SimpleCast::checksum_address_with_chain_id(&address.parse()?, chain_id))
+}
+
+/// Applies `f` to every line of stdin and prints the results, one per line.
+///
+/// The lines are processed one at a time and stdout is line buffered, so large inputs are streamed
+/// instead of being read into memory.
+fn map_stdin_lines<F>(mut f: F) -> eyre::Result<()>
+where
+    F: FnMut(&str) -> eyre::Result<String>,
+{
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    for (i, line) in io::stdin().lock().lines().enumerate() {
+        let line = line?;
+        let output = f(line.trim_end_matches('\r'))
+            .wrap_err_with(|| format!("Failed to process line {}", i + 1))?;
+        writeln!(stdout, "{}", output)?;
+    }
+    Ok(())
+}
+
 fn unwrap_or_stdin<T>(what: Option<T>) -> eyre::Result<T>
 where
     T: FromStr + Send + Sync,
@@ -710,45 +1463,82 @@ where
     })
 }
 
-fn det_base_in(value: &str, base_in: Option<String>) -> eyre::Result<u32> {
+fn det_base_in(value: &str, base_in: Option<u32>) -> eyre::Result<u32> {
     match base_in {
-        Some(base_in) => match base_in.as_str() {
-            "10" | "dec" => Ok(10),
-            "16" | "hex" => Ok(16),
-            _ => eyre::bail!("Unknown input base: {base_in}"),
-        },
+        Some(base_in) => Ok(base_in),
         None if value.starts_with("0x") => Ok(16),
-        None => match U256::from_str_radix(value, 10) {
+        None if value.starts_with("0o") => Ok(8),
+        None if value.starts_with("0b") => Ok(2),
+        None => match SimpleCast::parse_uint(value, 10) {
             Ok(_) => {
                 eyre::bail!("Could not autodetect input base: input could be decimal or hexadecimal. Please prepend with 0x if the input is hexadecimal, or specify a --base-in parameter.");
             }
             Err(_) => {
-                U256::from_str_radix(value, 16).expect("Could not autodetect input base.");
+                SimpleCast::parse_uint(value, 16).wrap_err("Could not autodetect input base")?;
                 Ok(16)
             }
         },
     }
 }
 
-fn det_base_out(base_out: &str) -> eyre::Result<u32> {
-    match base_out {
-        "10" | "dec" => Ok(10),
-        "16" | "hex" => Ok(16),
-        _ => eyre::bail!("Provided base is not a valid."),
-    }
+/// Builds the transaction and signs it with the signer of `client`, returning the RLP encoded
+/// signed transaction.
+///
+/// Fields that aren't set in `tx` are filled once via the client's provider.
+async fn cast_mktx<S: Signer + 'static, T: Into<NameOrAddress>>(
+    client: &SignerMiddleware<Arc<RetryProvider>, S>,
+    to: T,
+    args: (String, Vec<String>),
+    tx: TransactionOpts,
+    chain: Chain,
+    etherscan_api_key: Option<String>,
+) -> eyre::Result<Bytes> {
+    let sig = args.0;
+    let params = args.1;
+    let params = if !sig.is_empty() { Some((&sig[..], params)) } else { None };
+    let from = client.address();
+    let mut builder = TxBuilder::new(client, from, Some(to), chain, tx.legacy).await?;
+    builder
+        .etherscan_api_key(etherscan_api_key)
+        .args(params)
+        .await?
+        .gas(tx.gas_limit)
+        .gas_price(tx.gas_price)
+        .priority_gas_price(tx.priority_gas_price)
+        .value(tx.value)
+        .nonce(tx.nonce);
+    let (mut tx, _) = builder.build();
+
+    client.fill_transaction(&mut tx, None).await?;
+    let signature = client.signer().sign_transaction(&tx).await?;
+
+    Ok(tx.rlp_signed(&signature))
 }
 
-fn format_uint(val: U256, base_out: u32) -> eyre::Result<String> {
-    match base_out {
-        10 => Ok(val.to_string()),
-        16 => Ok(format!("0x{:x}", val)),
-        _ => Err(eyre::eyre!("Unknown output base: {base_out}")),
-    }
+/// Formats the result of a successful simulation of `cast send`
+fn simulation_summary(output: &[u8], gas: u64) -> String {
+    format!("Simulation succeeded.\nReturn data: 0x{}\nGas used: {gas}", hex::encode(output))
+}
+
+/// How `cast send` handles the transaction before and after submitting it
+#[derive(Debug, Clone, Copy, Default)]
+struct SendOptions {
+    /// Print the receipt as JSON
+    to_json: bool,
+    /// Execute the transaction against the pending state before submitting it
+    simulate: bool,
+    /// Only simulate the transaction, without submitting it
+    dry_run: bool,
+    /// Attach the access list of the transaction if it lowers the gas estimate
+    access_list: bool,
+    /// Attach the access list regardless of the gas estimate
+    force_access_list: bool,
 }
 
 #[allow(clippy::too_many_arguments)]
 async fn cast_send<M: Middleware, F: Into<NameOrAddress>, T: Into<NameOrAddress>>(
     provider: M,
+    rpc_url: &str,
     from: F,
     to: T,
     args: (String, Vec<String>),
@@ -762,7 +1552,7 @@ async fn cast_send<M: Middleware, F: Into<NameOrAddress>, T: Into<NameOrAddress>
     cast_async: bool,
     legacy: bool,
     confs: usize,
-    to_json: bool,
+    options: SendOptions,
 ) -> eyre::Result<()>
 where
     M::Error: 'static,
@@ -780,9 +1570,30 @@ where
         .priority_gas_price(priority_gas_price)
         .value(value)
         .nonce(nonce);
-    let builder_output = builder.build();
+    let mut builder_output = builder.build();
 
     let cast = Cast::new(provider);
+    let SendOptions { to_json, simulate, dry_run, access_list, force_access_list } = options;
+
+    if access_list {
+        let (without, with, attached) =
+            cast.attach_access_list(&mut builder_output.0, force_access_list).await?;
+        if attached {
+            eprintln!("Attached access list, gas estimate: {with} (without: {without})");
+        } else {
+            eprintln!("Access list not attached, gas estimate: {without} (with: {with})");
+        }
+    }
+
+    if simulate {
+        let (output, gas) = simulate_tx(rpc_url.to_string(), &builder_output.0).await?;
+        let summary = simulation_summary(&output, gas);
+        if dry_run {
+            println!("{summary}");
+            return Ok(())
+        }
+        eprintln!("{summary}");
+    }
 
     let pending_tx = cast.send(builder_output).await?;
     let tx_hash = *pending_tx;
@@ -790,7 +1601,8 @@ where
     if cast_async {
         println!("{:#x}", tx_hash);
     } else {
-        let receipt = cast.receipt(format!("{:#x}", tx_hash), None, confs, false, to_json).await?;
+        let receipt =
+            cast.receipt(format!("{:#x}", tx_hash), None, confs, false, false, to_json).await?;
         println!("{receipt}");
     }
 
@@ -0,0 +1,217 @@
+//! cast creation-code, runtime-code and codesize subcommands
+
+use crate::{
+    cmd::{forge::build::CoreBuildArgs, utils, Cmd},
+    compile::{self, CONTRACT_SIZE_LIMIT},
+    opts::cast::parse_block_id,
+    utils::consume_config_rpc_url,
+};
+use clap::Parser;
+use ethers::{
+    prelude::{BlockId, Middleware},
+    solc::{
+        artifacts::{BytecodeObject, CompactBytecode},
+        info::ContractInfo,
+        utils::canonicalized,
+    },
+    types::{Address, Bytes},
+};
+use eyre::Result;
+use foundry_common::get_http_provider;
+use futures::future::BoxFuture;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Parser)]
+pub struct CreationCodeArgs {
+    #[clap(
+        help = "The identifier of the contract in the form `(<path>:)?<contractname>`.",
+        value_name = "CONTRACT"
+    )]
+    contract: ContractInfo,
+
+    #[clap(flatten)]
+    build: CoreBuildArgs,
+}
+
+impl Cmd for CreationCodeArgs {
+    type Output = ();
+
+    fn run(self) -> Result<Self::Output> {
+        let CreationCodeArgs { contract, build } = self;
+        let (bytecode, _) = local_bytecode(&build, contract)?;
+        println!("{}", linked_bytecode(bytecode)?);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Parser)]
+pub struct RuntimeCodeArgs {
+    #[clap(
+        help = "The address of a deployed contract, or the identifier of a contract of the current project in the form `(<path>:)?<contractname>`.",
+        long_help = "The address of a deployed contract, or the identifier of a contract of the current project in the form `(<path>:)?<contractname>`.\n\nThe runtime code of a local contract is taken from its artifact, so immutable variables are zeroed.",
+        value_name = "CONTRACT"
+    )]
+    contract: String,
+
+    #[clap(
+        long,
+        short = 'B',
+        help = "The block height you want to query at.",
+        long_help = "The block height you want to query at. Can also be the tags earliest, latest, or pending.",
+        parse(try_from_str = parse_block_id),
+        value_name = "BLOCK"
+    )]
+    block: Option<BlockId>,
+
+    #[clap(long, env = "ETH_RPC_URL", value_name = "URL")]
+    rpc_url: Option<String>,
+
+    #[clap(flatten)]
+    build: CoreBuildArgs,
+}
+
+impl Cmd for RuntimeCodeArgs {
+    type Output = BoxFuture<'static, Result<()>>;
+
+    fn run(self) -> Result<Self::Output> {
+        Ok(Box::pin(async move {
+            let code = self.runtime_code().await?;
+            println!("{}", linked_bytecode(code)?);
+            Ok(())
+        }))
+    }
+}
+
+impl RuntimeCodeArgs {
+    /// Returns the runtime code of the deployed or local contract
+    ///
+    /// The runtime code of a local contract may still reference unlinked libraries.
+    async fn runtime_code(self) -> Result<CompactBytecode> {
+        let RuntimeCodeArgs { contract, block, rpc_url, build } = self;
+
+        if let Ok(address) = Address::from_str(&contract) {
+            let rpc_url = consume_config_rpc_url(rpc_url);
+            let provider = get_http_provider(rpc_url);
+            let code = provider.get_code(address, block).await?;
+            if code.is_empty() {
+                eyre::bail!("No contract deployed at {:?}", address)
+            }
+            return Ok(CompactBytecode {
+                object: BytecodeObject::Bytecode(code),
+                source_map: None,
+                link_references: Default::default(),
+            })
+        }
+
+        let contract = ContractInfo::from_str(&contract).map_err(|err| eyre::eyre!(err))?;
+        let (_, deployed) = local_bytecode(&build, contract)?;
+        Ok(deployed)
+    }
+}
+
+#[derive(Debug, Clone, Parser)]
+pub struct CodeSizeArgs {
+    #[clap(flatten)]
+    code: RuntimeCodeArgs,
+}
+
+impl Cmd for CodeSizeArgs {
+    type Output = BoxFuture<'static, Result<()>>;
+
+    fn run(self) -> Result<Self::Output> {
+        Ok(Box::pin(self.code_size()))
+    }
+}
+
+impl CodeSizeArgs {
+    async fn code_size(self) -> Result<()> {
+        let code = self.code.runtime_code().await?;
+        // unlinked library placeholders take up as much space as the linked addresses
+        let size = match code.object {
+            BytecodeObject::Bytecode(bytes) => bytes.len(),
+            BytecodeObject::Unlinked(hex) => hex.trim_start_matches("0x").len() / 2,
+        };
+
+        println!("{}", code_size_summary(size)?);
+        Ok(())
+    }
+}
+
+/// Formats the code size relative to the EIP-170 limit, failing if the limit is exceeded
+fn code_size_summary(size: usize) -> Result<String> {
+    if size > CONTRACT_SIZE_LIMIT {
+        eyre::bail!(
+            "{} bytes exceeds the EIP-170 limit of {} bytes by {} bytes",
+            size,
+            CONTRACT_SIZE_LIMIT,
+            size - CONTRACT_SIZE_LIMIT
+        )
+    }
+    Ok(format!(
+        "{} bytes, {} bytes below the limit of {} bytes",
+        size,
+        CONTRACT_SIZE_LIMIT - size,
+        CONTRACT_SIZE_LIMIT
+    ))
+}
+
+/// Compiles the project and returns the creation and runtime bytecode of the `contract`
+fn local_bytecode(
+    build: &CoreBuildArgs,
+    mut contract: ContractInfo,
+) -> Result<(CompactBytecode, CompactBytecode)> {
+    let project = build.project()?;
+    let mut output = compile::suppress_compile(&project)?;
+
+    if let Some(ref mut path) = contract.path {
+        // paths are absolute in the project's output
+        *path = canonicalized(project.root().join(&path)).to_string_lossy().to_string();
+    }
+
+    let (_, bytecode, deployed) = utils::remove_contract(&mut output, &contract)?;
+    let deployed = deployed
+        .bytecode
+        .ok_or_else(|| eyre::eyre!("contract {} does not contain deployed bytecode", contract))?;
+    Ok((bytecode, deployed))
+}
+
+/// Returns the bytecode, failing if it still references libraries that are not linked
+fn linked_bytecode(bytecode: CompactBytecode) -> Result<Bytes> {
+    match bytecode.object {
+        BytecodeObject::Bytecode(bytes) => Ok(bytes),
+        BytecodeObject::Unlinked(_) => {
+            let link_refs = bytecode
+                .link_references
+                .iter()
+                .flat_map(|(path, names)| {
+                    names.keys().map(move |name| format!("\t{}: {}", name, path))
+                })
+                .collect::<Vec<String>>()
+                .join("\n");
+            eyre::bail!("The bytecode references libraries that are not linked - provide their addresses with `--libraries <path>:<name>:<address>`\n{}", link_refs)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summarizes_code_size() {
+        assert_eq!(
+            code_size_summary(24_000).unwrap(),
+            "24000 bytes, 576 bytes below the limit of 24576 bytes"
+        );
+        assert_eq!(
+            code_size_summary(CONTRACT_SIZE_LIMIT).unwrap(),
+            "24576 bytes, 0 bytes below the limit of 24576 bytes"
+        );
+
+        let err = code_size_summary(CONTRACT_SIZE_LIMIT + 10).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "24586 bytes exceeds the EIP-170 limit of 24576 bytes by 10 bytes"
+        );
+    }
+}
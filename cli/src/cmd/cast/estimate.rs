@@ -1,16 +1,20 @@
 // cast estimate subcommands
 use crate::{
-    opts::{cast::parse_name_or_address, EthereumOpts},
+    opts::{
+        cast::{parse_block_id, parse_name_or_address},
+        EthereumOpts,
+    },
     utils::parse_ether_value,
 };
 use cast::{Cast, TxBuilder};
 use clap::Parser;
 use ethers::{
     providers::Middleware,
-    types::{NameOrAddress, U256},
+    types::{transaction::eip2930::AccessList, BlockId, BlockNumber, NameOrAddress, U256},
 };
 use foundry_common::get_http_provider;
 use foundry_config::{Chain, Config};
+use std::path::Path;
 
 #[derive(Debug, Parser)]
 pub struct EstimateArgs {
@@ -30,6 +34,46 @@ Examples: 1ether, 10gwei, 0.01ether"#,
         value_name = "VALUE"
     )]
     value: Option<U256>,
+    #[clap(
+        long = "gas-price",
+        help = "Gas price for legacy transactions, or max fee per gas for EIP1559 transactions.",
+        env = "ETH_GAS_PRICE",
+        parse(try_from_str = parse_ether_value),
+        value_name = "PRICE"
+    )]
+    gas_price: Option<U256>,
+    #[clap(
+        long = "priority-gas-price",
+        help = "Max priority fee per gas for EIP1559 transactions.",
+        env = "ETH_PRIORITY_GAS_PRICE",
+        parse(try_from_str = parse_ether_value),
+        value_name = "PRICE"
+    )]
+    priority_gas_price: Option<U256>,
+    #[clap(
+        long,
+        help = "The access list of the transaction, as JSON or the path to a JSON file.",
+        long_help = r#"The access list of the transaction, as JSON or the path to a JSON file.
+
+Both a plain access list and the output of `cast access-list --json` are accepted."#,
+        parse(try_from_str = parse_access_list),
+        value_name = "ACCESS_LIST"
+    )]
+    access_list: Option<AccessList>,
+    #[clap(
+        long,
+        short = 'B',
+        help = "The block height you want to estimate at.",
+        long_help = "The block height you want to estimate at. Can also be the tags earliest, latest, or pending.",
+        parse(try_from_str = parse_block_id),
+        value_name = "BLOCK"
+    )]
+    block: Option<BlockId>,
+    #[clap(
+        long,
+        help = "Also print the maximum fee of the transaction in wei, based on the base fee of the block."
+    )]
+    fee: bool,
     #[clap(flatten)]
     // TODO: We only need RPC URL and Etherscan API key here.
     eth: EthereumOpts,
@@ -61,7 +105,19 @@ Examples: 1ether, 10gwei, 0.01ether"#,
 }
 impl EstimateArgs {
     pub async fn run(self) -> eyre::Result<()> {
-        let EstimateArgs { to, sig, args, value, eth, command } = self;
+        let EstimateArgs {
+            to,
+            sig,
+            args,
+            value,
+            gas_price,
+            priority_gas_price,
+            access_list,
+            block,
+            fee,
+            eth,
+            command,
+        } = self;
         let mut config = Config::from(&eth);
         let provider = get_http_provider(
             config.eth_rpc_url.take().unwrap_or_else(|| "http://localhost:8545".to_string()),
@@ -72,7 +128,11 @@ impl EstimateArgs {
 
         let from = eth.sender().await;
         let mut builder = TxBuilder::new(&provider, from, to, chain, false).await?;
-        builder.etherscan_api_key(config.get_etherscan_api_key(Some(chain)));
+        builder
+            .etherscan_api_key(config.get_etherscan_api_key(Some(chain)))
+            .gas_price(gas_price)
+            .priority_gas_price(priority_gas_price)
+            .access_list(access_list);
         match command {
             Some(EstimateSubcommands::Create { code, sig, args, value }) => {
                 builder.value(value);
@@ -92,8 +152,84 @@ impl EstimateArgs {
         };
 
         let builder_output = builder.peek();
-        let gas = Cast::new(&provider).estimate(builder_output).await?;
+        let gas = Cast::new(&provider).estimate(builder_output, block).await?;
         println!("{gas}");
+
+        if fee {
+            let fee_per_gas =
+                max_fee_per_gas(&provider, block, gas_price, priority_gas_price).await?;
+            println!("max fee: {} wei", gas * fee_per_gas);
+        }
         Ok(())
     }
 }
+
+/// Returns the maximum fee per gas the transaction pays at the base fee of the block.
+///
+/// This is the base fee plus the priority fee, capped at the max fee per gas if given. Legacy
+/// transactions pay the gas price.
+async fn max_fee_per_gas<M: Middleware>(
+    provider: &M,
+    block: Option<BlockId>,
+    gas_price: Option<U256>,
+    priority_gas_price: Option<U256>,
+) -> eyre::Result<U256>
+where
+    M::Error: 'static,
+{
+    let block = provider
+        .get_block(block.unwrap_or_else(|| BlockNumber::Latest.into()))
+        .await?
+        .ok_or_else(|| eyre::eyre!("block not found"))?;
+
+    let base_fee = match block.base_fee_per_gas {
+        Some(base_fee) => base_fee,
+        None => {
+            return Ok(match gas_price {
+                Some(gas_price) => gas_price,
+                None => provider.get_gas_price().await?,
+            })
+        }
+    };
+
+    let priority_gas_price = match priority_gas_price {
+        Some(priority_gas_price) => priority_gas_price,
+        None => provider.estimate_eip1559_fees(None).await?.1,
+    };
+    let fee_per_gas = base_fee + priority_gas_price;
+    Ok(gas_price.map_or(fee_per_gas, |max_fee| fee_per_gas.min(max_fee)))
+}
+
+/// Parses an access list from JSON or a JSON file
+fn parse_access_list(s: &str) -> eyre::Result<AccessList> {
+    let path = Path::new(s);
+    let json = if path.is_file() { std::fs::read_to_string(path)? } else { s.to_string() };
+    let value: serde_json::Value = serde_json::from_str(&json)?;
+
+    // the output of `cast access-list --json` contains the access list and the gas used
+    let value = match value {
+        serde_json::Value::Object(mut object) if object.contains_key("accessList") => {
+            object.remove("accessList").unwrap_or_default()
+        }
+        value => value,
+    };
+    Ok(serde_json::from_value(value)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_parse_access_list() {
+        let list = r#"[{"address":"0x6b175474e89094c44da98b954eedeac495271d0f","storageKeys":["0x0000000000000000000000000000000000000000000000000000000000000001"]}]"#;
+        let access_list = parse_access_list(list).unwrap();
+        assert_eq!(access_list.0.len(), 1);
+        assert_eq!(access_list.0[0].storage_keys.len(), 1);
+
+        let with_gas = format!(r#"{{"accessList":{list},"gasUsed":"0x5208"}}"#);
+        assert_eq!(parse_access_list(&with_gas).unwrap(), access_list);
+
+        assert!(parse_access_list("not json").is_err());
+    }
+}
@@ -0,0 +1,280 @@
+//! cast logs subcommand
+
+use crate::{cmd::Cmd, opts::cast::parse_block_id, utils::consume_config_rpc_url};
+use clap::Parser;
+use ethers::{
+    abi::{self, Event, HumanReadableParser, ParamType, RawLog, Token},
+    prelude::*,
+    utils::keccak256,
+};
+use eyre::{Result, WrapErr};
+use foundry_common::get_http_provider;
+use foundry_utils::{format_token, parse_tokens};
+use futures::future::BoxFuture;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Parser)]
+pub struct LogsArgs {
+    #[clap(
+        help = "The event signature, e.g. `Transfer(address indexed from, address indexed to, uint256 value)`.",
+        long_help = "The event signature, e.g. `Transfer(address indexed from, address indexed to, uint256 value)`.\n\nThe signature is used to filter the logs by their first topic and to decode them. Parameters must be marked as `indexed` to be matched against the topics.",
+        value_name = "SIG"
+    )]
+    sig: String,
+    #[clap(
+        help = "The values of the indexed parameters to filter by.",
+        long_help = r#"The values of the indexed parameters to filter by, in the order of the indexed parameters.
+
+Values are encoded according to the parameter type, e.g. an address is padded to 32 bytes and strings are hashed. A 32-byte hex value is used as topic as is. For example:
+
+logs "Transfer(address indexed from, address indexed to, uint256 value)" 0x0000000000000000000000000000000000000000
+    => all mints of the token"#,
+        value_name = "TOPICS"
+    )]
+    topics: Vec<String>,
+    #[clap(
+        long,
+        help = "The block height to start querying at.",
+        long_help = "The block height to start querying at. Can also be the tags earliest, latest, or pending.\n\nA block hash queries the logs of that block only, and can't be combined with --to-block.",
+        parse(try_from_str = parse_block_id),
+        value_name = "BLOCK"
+    )]
+    from_block: Option<BlockId>,
+    #[clap(
+        long,
+        help = "The block height to stop querying at.",
+        long_help = "The block height to stop querying at. Can also be the tags earliest, latest, or pending.",
+        parse(try_from_str = parse_block_id),
+        value_name = "BLOCK"
+    )]
+    to_block: Option<BlockId>,
+    #[clap(long, help = "The contract address to filter on.", value_name = "ADDRESS")]
+    address: Option<Address>,
+    #[clap(long = "json", short = 'j', help_heading = "DISPLAY OPTIONS")]
+    to_json: bool,
+    #[clap(long, env = "ETH_RPC_URL", value_name = "URL")]
+    rpc_url: Option<String>,
+}
+
+impl Cmd for LogsArgs {
+    type Output = BoxFuture<'static, Result<()>>;
+
+    fn run(self) -> Result<Self::Output> {
+        Ok(Box::pin(self.query_logs()))
+    }
+}
+
+impl LogsArgs {
+    async fn query_logs(self) -> Result<()> {
+        let LogsArgs { sig, topics, from_block, to_block, address, to_json, rpc_url } = self;
+
+        let event = HumanReadableParser::parse_event(&sig)
+            .wrap_err_with(|| format!("Could not parse event signature `{sig}`"))?;
+        let filter = build_filter(&event, &topics, from_block, to_block, address)?;
+
+        let rpc_url = consume_config_rpc_url(rpc_url);
+        let provider = get_http_provider(rpc_url);
+        let logs = provider.get_logs(&filter).await?;
+
+        let mut decoded = Vec::with_capacity(logs.len());
+        for log in logs {
+            let params = event
+                .parse_log(RawLog { topics: log.topics.clone(), data: log.data.to_vec() })
+                .wrap_err_with(|| {
+                    format!(
+                        "Could not decode log {} of transaction {:?} as `{}`",
+                        log.log_index.unwrap_or_default(),
+                        log.transaction_hash.unwrap_or_default(),
+                        event.name
+                    )
+                })?
+                .params
+                .into_iter()
+                .enumerate()
+                .map(|(i, param)| {
+                    let name = if param.name.is_empty() { i.to_string() } else { param.name };
+                    (name, param.value)
+                })
+                .collect::<Vec<_>>();
+            decoded.push((log, params));
+        }
+
+        if to_json {
+            let logs = decoded
+                .iter()
+                .map(|(log, params)| {
+                    serde_json::json!({
+                        "address": log.address,
+                        "blockNumber": log.block_number,
+                        "transactionHash": log.transaction_hash,
+                        "logIndex": log.log_index,
+                        "event": event.name,
+                        "params": params
+                            .iter()
+                            .map(|(name, value)| (name.clone(), serde_json::Value::String(format_token(value))))
+                            .collect::<serde_json::Map<_, _>>(),
+                    })
+                })
+                .collect::<Vec<_>>();
+            println!("{}", serde_json::to_string_pretty(&logs)?);
+        } else {
+            for (log, params) in &decoded {
+                println!(
+                    "{} @ {:?} (block {}, tx {:?})",
+                    event.name,
+                    log.address,
+                    log.block_number.unwrap_or_default(),
+                    log.transaction_hash.unwrap_or_default()
+                );
+                for (name, value) in params {
+                    println!("  {name}: {}", format_token(value));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Builds the `eth_getLogs` filter for the event and the given values of its indexed parameters
+fn build_filter(
+    event: &Event,
+    topics: &[String],
+    from_block: Option<BlockId>,
+    to_block: Option<BlockId>,
+    address: Option<Address>,
+) -> Result<Filter> {
+    let indexed = event.inputs.iter().filter(|param| param.indexed).collect::<Vec<_>>();
+    if topics.len() > indexed.len() {
+        eyre::bail!(
+            "Got {} topics, but `{}` only has {} indexed parameter(s)",
+            topics.len(),
+            event.name,
+            indexed.len()
+        )
+    }
+
+    // the signature is the first topic, unless the event is anonymous
+    let mut filter_topics = if event.anonymous { vec![] } else { vec![event.signature()] };
+    for (param, value) in indexed.iter().zip(topics) {
+        let topic = encode_topic(&param.kind, value)
+            .wrap_err_with(|| format!("Invalid value for indexed parameter `{}`", param.name))?;
+        filter_topics.push(topic);
+    }
+    if filter_topics.len() > 4 {
+        eyre::bail!("Logs can't be filtered by more than 4 topics")
+    }
+
+    let mut filter = Filter::new();
+    for (i, topic) in filter_topics.into_iter().enumerate() {
+        filter.topics[i] = Some(topic.into());
+    }
+    match (from_block, to_block) {
+        (Some(BlockId::Hash(hash)), None) => filter = filter.at_block_hash(hash),
+        (Some(BlockId::Hash(_)), Some(_)) | (_, Some(BlockId::Hash(_))) => {
+            eyre::bail!("A block hash can only be passed as --from-block, without --to-block")
+        }
+        (from_block, to_block) => {
+            if let Some(BlockId::Number(from_block)) = from_block {
+                filter = filter.from_block(from_block);
+            }
+            if let Some(BlockId::Number(to_block)) = to_block {
+                filter = filter.to_block(to_block);
+            }
+        }
+    }
+    if let Some(address) = address {
+        filter = filter.address(address);
+    }
+
+    Ok(filter)
+}
+
+/// Encodes the value of an indexed parameter as topic.
+///
+/// Value types are ABI encoded, strings and bytes are hashed. Topics of other dynamic types (arrays
+/// and tuples) must be passed as their 32-byte hash.
+fn encode_topic(kind: &ParamType, value: &str) -> Result<H256> {
+    if value.starts_with("0x") && value.len() == 66 {
+        return Ok(H256::from_str(value)?)
+    }
+
+    let token = parse_tokens(std::iter::once((kind, value)), true)?
+        .pop()
+        .ok_or_else(|| eyre::eyre!("Could not parse `{value}` as {kind}"))?;
+    let topic = match token {
+        Token::String(s) => keccak256(s.as_bytes()).into(),
+        Token::Bytes(bytes) => keccak256(bytes).into(),
+        Token::Array(_) | Token::FixedArray(_) | Token::Tuple(_) => {
+            eyre::bail!("Topics of type {kind} must be passed as their 32-byte hash")
+        }
+        token => H256::from_slice(&abi::encode(&[token])),
+    };
+    Ok(topic)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRANSFER: &str = "Transfer(address indexed from, address indexed to, uint256 value)";
+
+    #[test]
+    fn parses_block_range() {
+        let args = LogsArgs::parse_from([
+            "logs",
+            TRANSFER,
+            "--from-block",
+            "100",
+            "--to-block",
+            "latest",
+        ]);
+        assert_eq!(args.from_block, Some(BlockId::Number(BlockNumber::Number(100.into()))));
+        assert_eq!(args.to_block, Some(BlockId::Number(BlockNumber::Latest)));
+
+        let hash = "0xc3b2c7bbd0dd6ab8d1f5ba3e6e8d1c4c4d69d1e7a1f4c5e4a0b8c3d1e2f3a4b5";
+        let args = LogsArgs::parse_from(["logs", TRANSFER, "--from-block", hash]);
+        assert_eq!(args.from_block, Some(BlockId::Hash(hash.parse().unwrap())));
+
+        assert!(LogsArgs::try_parse_from(["logs", TRANSFER, "--from-block", "soon"]).is_err());
+    }
+
+    #[test]
+    fn builds_filter() {
+        let event = HumanReadableParser::parse_event(TRANSFER).unwrap();
+        let zero = format!("{:?}", Address::zero());
+        let filter = build_filter(
+            &event,
+            &[zero],
+            Some(BlockId::Number(BlockNumber::Number(100.into()))),
+            Some(BlockId::Number(BlockNumber::Latest)),
+            None,
+        )
+        .unwrap();
+        assert_eq!(filter.topics[0], Some(event.signature().into()));
+        assert_eq!(filter.topics[1], Some(H256::zero().into()));
+        assert_eq!(filter.topics[2], None);
+        assert_eq!(
+            filter.block_option,
+            FilterBlockOption::Range {
+                from_block: Some(BlockNumber::Number(100.into())),
+                to_block: Some(BlockNumber::Latest)
+            }
+        );
+
+        let hash = H256::repeat_byte(1);
+        let filter = build_filter(&event, &[], Some(BlockId::Hash(hash)), None, None).unwrap();
+        assert_eq!(filter.block_option, FilterBlockOption::AtBlockHash(hash));
+        assert!(build_filter(
+            &event,
+            &[],
+            Some(BlockId::Hash(hash)),
+            Some(BlockId::Number(BlockNumber::Latest)),
+            None
+        )
+        .is_err());
+
+        let too_many = vec![format!("{:?}", Address::zero()); 3];
+        assert!(build_filter(&event, &too_many, None, None, None).is_err());
+    }
+}
@@ -5,8 +5,14 @@
 //! implement `figment::Provider` which allows the subcommand to override the config's defaults, see
 //! [`foundry_config::Config`].
 
+pub mod code;
 pub mod estimate;
 pub mod find_block;
+pub mod logs;
 pub mod rpc;
 pub mod run;
+pub mod selectors;
+pub mod storage;
+pub mod storage_layout;
+pub mod txpool;
 pub mod wallet;
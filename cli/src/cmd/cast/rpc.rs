@@ -1,10 +1,22 @@
 use crate::{cmd::Cmd, utils::consume_config_rpc_url};
 use cast::Cast;
 use clap::Parser;
-use eyre::Result;
+use ethers::{
+    providers::{JsonRpcClient, PubsubClient, Ws},
+    types::U256,
+};
+use eyre::{Result, WrapErr};
 use foundry_common::get_http_provider;
-use futures::future::BoxFuture;
+use futures::{future::BoxFuture, StreamExt};
 use itertools::Itertools;
+use serde::Deserialize;
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// How often in a row `cast rpc --subscribe` tries to reconnect before giving up
+const MAX_RECONNECT_ATTEMPTS: u32 = 5;
 
 #[derive(Debug, Clone, Parser)]
 pub struct RpcArgs {
@@ -22,8 +34,26 @@ rpc eth_getBlockByNumber '["0x123", false]' --raw
     => {"method": "eth_getBlockByNumber", "params": ["0x123", false] ... }"#
     )]
     raw: bool,
-    #[clap(value_name = "METHOD", help = "RPC method name")]
-    method: String,
+    #[clap(
+        long,
+        help = "Subscribe to the notifications of METHOD over a websocket, e.g. newHeads or logs.",
+        long_help = r#"Subscribe to the notifications of METHOD over a websocket, e.g. newHeads or logs.
+
+Requires a ws:// or wss:// RPC URL. Every notification is printed as JSON on its own line until interrupted with Ctrl-C. If the connection drops, the subscription is renewed. For example:
+
+rpc --subscribe logs '{"address": "0x6B175474E89094C44Da98b954EedeAC495271d0F"}'
+    => {"method": "eth_subscribe", "params": ["logs", {"address": "0x6B17..."}] ... }"#,
+        requires = "method",
+        conflicts_with = "raw"
+    )]
+    subscribe: bool,
+    #[clap(
+        value_name = "METHOD",
+        help = "RPC method name",
+        required_unless_present_any = &["batch_method", "batch_file"],
+        conflicts_with_all = &["batch_method", "batch_file"]
+    )]
+    method: Option<String>,
     #[clap(
         value_name = "PARAMS",
         help = "RPC parameters",
@@ -35,13 +65,69 @@ rpc eth_getBlockByNumber 0x123 false
     => {"method": "eth_getBlockByNumber", "params": ["0x123", false] ... }"#
     )]
     params: Vec<String>,
+    #[clap(
+        long = "method",
+        value_name = "METHOD",
+        help = "The method of a request of a batch. Can be passed multiple times.",
+        long_help = r#"The method of a request of a batch. Can be passed multiple times.
+
+All requests are sent as a single JSON-RPC batch and the responses are printed in order, one per line. For example:
+
+rpc --method eth_blockNumber --params '[]' --method eth_getBalance --params '["0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045", "latest"]'"#,
+        multiple_occurrences = true,
+        help_heading = "BATCH"
+    )]
+    batch_method: Vec<String>,
+    #[clap(
+        long = "params",
+        value_name = "PARAMS",
+        help = "The JSON params of a request of a batch, one for every --method.",
+        multiple_occurrences = true,
+        requires = "batch_method",
+        help_heading = "BATCH"
+    )]
+    batch_params: Vec<String>,
+    #[clap(
+        long,
+        value_name = "PATH",
+        help = "Path to a JSON file with an array of requests to send as a batch.",
+        long_help = r#"Path to a JSON file with an array of requests to send as a batch.
+
+Every request is an object with a "method" and optional "params", for example:
+
+[{"method": "eth_blockNumber"}, {"method": "eth_getStorageAt", "params": ["0x6B175474E89094C44Da98b954EedeAC495271d0F", "0x0", "latest"]}]"#,
+        conflicts_with = "batch_method",
+        help_heading = "BATCH"
+    )]
+    batch_file: Option<PathBuf>,
 }
 
 impl Cmd for RpcArgs {
     type Output = BoxFuture<'static, Result<()>>;
     fn run(self) -> eyre::Result<Self::Output> {
-        let RpcArgs { rpc_url, raw, method, params } = self;
-        Ok(Box::pin(Self::do_rpc(rpc_url, raw, method, params)))
+        let RpcArgs {
+            rpc_url,
+            raw,
+            subscribe,
+            method,
+            params,
+            batch_method,
+            batch_params,
+            batch_file,
+        } = self;
+        if let Some(method) = method {
+            if subscribe {
+                let params = Self::subscription_params(method, params);
+                return Ok(Box::pin(Self::do_subscribe(rpc_url, params)))
+            }
+            return Ok(Box::pin(Self::do_rpc(rpc_url, raw, method, params)))
+        }
+
+        let requests = match batch_file {
+            Some(path) => Self::read_batch_file(&path)?,
+            None => Self::batch_from_args(batch_method, batch_params)?,
+        };
+        Ok(Box::pin(Self::do_batch(rpc_url, requests)))
     }
 }
 
@@ -70,7 +156,274 @@ impl RpcArgs {
         println!("{}", Cast::new(provider).rpc(&method, params).await?);
         Ok(())
     }
+
+    /// Subscribes with the `params` of `eth_subscribe` and prints every notification until
+    /// interrupted.
+    ///
+    /// If the connection drops, the subscription is renewed on a new connection, giving up after
+    /// [`MAX_RECONNECT_ATTEMPTS`] failed attempts in a row.
+    async fn do_subscribe(rpc_url: Option<String>, params: Vec<serde_json::Value>) -> Result<()> {
+        let rpc_url = consume_config_rpc_url(rpc_url);
+        if !Self::is_ws_url(&rpc_url) {
+            eyre::bail!(
+                "Subscriptions require a websocket RPC URL (ws:// or wss://), got {rpc_url}"
+            )
+        }
+
+        let mut subscribed = false;
+        let mut failed_attempts = 0;
+        loop {
+            let result = tokio::select! {
+                result = Self::print_notifications(&rpc_url, &params, &mut subscribed) => result,
+                _ = tokio::signal::ctrl_c() => return Ok(()),
+            };
+            match result {
+                Ok(()) => {
+                    failed_attempts = 0;
+                    eprintln!("The connection to {rpc_url} was closed, reconnecting...");
+                }
+                // errors before the first subscription are not transient, e.g. an unknown method
+                Err(err) if !subscribed => return Err(err),
+                Err(err) => {
+                    failed_attempts += 1;
+                    if failed_attempts > MAX_RECONNECT_ATTEMPTS {
+                        return Err(err.wrap_err(format!(
+                            "Giving up after {MAX_RECONNECT_ATTEMPTS} attempts to reconnect"
+                        )))
+                    }
+                    eprintln!(
+                        "{err}, reconnecting ({failed_attempts}/{MAX_RECONNECT_ATTEMPTS})..."
+                    );
+                }
+            }
+
+            let backoff = Duration::from_secs(1 << failed_attempts.min(4));
+            tokio::select! {
+                _ = tokio::time::sleep(backoff) => {}
+                _ = tokio::signal::ctrl_c() => return Ok(()),
+            }
+        }
+    }
+
+    /// Opens a connection, subscribes and prints the notifications until the connection drops.
+    ///
+    /// Sets `subscribed` once the subscription was accepted.
+    async fn print_notifications(
+        rpc_url: &str,
+        params: &[serde_json::Value],
+        subscribed: &mut bool,
+    ) -> Result<()> {
+        let ws = Ws::connect(rpc_url)
+            .await
+            .wrap_err_with(|| format!("Failed to connect to {rpc_url}"))?;
+        let id: U256 = ws.request("eth_subscribe", params).await?;
+        let mut notifications = ws.subscribe(id)?;
+        *subscribed = true;
+
+        while let Some(notification) = notifications.next().await {
+            println!("{}", notification.get());
+        }
+        Ok(())
+    }
+
+    /// Returns the params of `eth_subscribe` for the subscription `kind` and its `params`
+    fn subscription_params(kind: String, params: Vec<String>) -> Vec<serde_json::Value> {
+        std::iter::once(serde_json::Value::String(kind))
+            .chain(params.into_iter().map(Self::to_json_or_string))
+            .collect()
+    }
+
+    fn is_ws_url(url: &str) -> bool {
+        url.starts_with("ws://") || url.starts_with("wss://")
+    }
+
+    /// Sends all requests as a single batch and prints the responses in the order of the requests.
+    ///
+    /// Failed requests don't abort the batch, their error objects are printed in place of the
+    /// result.
+    async fn do_batch(rpc_url: Option<String>, requests: Vec<BatchRequest>) -> Result<()> {
+        let rpc_url = consume_config_rpc_url(rpc_url);
+        let body = requests
+            .iter()
+            .enumerate()
+            .map(|(id, request)| {
+                serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "method": request.method,
+                    "params": request.params,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let response = reqwest::Client::new().post(&rpc_url).json(&body).send().await?;
+        let status = response.status();
+        let response: serde_json::Value = response
+            .json()
+            .await
+            .wrap_err_with(|| format!("Invalid batch response from {rpc_url} ({status})"))?;
+
+        let responses = Self::order_responses(response, requests.len())?;
+        let mut failed = 0usize;
+        for (request, response) in requests.iter().zip(responses) {
+            match response {
+                Ok(result) => println!("{result}"),
+                Err(error) => {
+                    failed += 1;
+                    eprintln!("Request `{}` failed: {error}", request.method);
+                    println!("{}", serde_json::json!({ "error": error }));
+                }
+            }
+        }
+
+        if failed > 0 {
+            eyre::bail!("{failed} of {} request(s) failed", requests.len())
+        }
+        Ok(())
+    }
+
+    /// Matches the responses of a batch to the requests by their id, returning the result or
+    /// error object of every request.
+    fn order_responses(
+        response: serde_json::Value,
+        len: usize,
+    ) -> Result<Vec<std::result::Result<serde_json::Value, serde_json::Value>>> {
+        let responses = match response {
+            serde_json::Value::Array(responses) => responses,
+            // servers respond with a single error object if the whole batch was rejected
+            serde_json::Value::Object(response) => {
+                let error = response.get("error").cloned().unwrap_or_default();
+                eyre::bail!("The batch was rejected: {error}")
+            }
+            response => eyre::bail!("Unexpected batch response: {response}"),
+        };
+
+        let mut ordered = vec![None; len];
+        for mut response in responses {
+            let id = response.get("id").and_then(|id| id.as_u64()).map(|id| id as usize);
+            let slot = match id.and_then(|id| ordered.get_mut(id)) {
+                Some(slot) => slot,
+                None => eyre::bail!("Unexpected response in batch: {response}"),
+            };
+            *slot = Some(match response.get_mut("error").map(serde_json::Value::take) {
+                Some(error) if !error.is_null() => Err(error),
+                _ => {
+                    Ok(response.get_mut("result").map(serde_json::Value::take).unwrap_or_default())
+                }
+            });
+        }
+
+        Ok(ordered
+            .into_iter()
+            .map(|response| response.unwrap_or_else(|| Err(serde_json::json!("missing response"))))
+            .collect())
+    }
+
+    fn read_batch_file(path: &Path) -> Result<Vec<BatchRequest>> {
+        let content = std::fs::read_to_string(path)
+            .wrap_err_with(|| format!("Failed to read batch file {}", path.display()))?;
+        let requests: Vec<BatchRequest> = serde_json::from_str(&content)
+            .wrap_err_with(|| format!("Invalid batch file {}", path.display()))?;
+        if requests.is_empty() {
+            eyre::bail!("Batch file {} contains no requests", path.display())
+        }
+        Ok(requests)
+    }
+
+    fn batch_from_args(methods: Vec<String>, params: Vec<String>) -> Result<Vec<BatchRequest>> {
+        if !params.is_empty() && params.len() != methods.len() {
+            eyre::bail!(
+                "Got {} --method but {} --params, every method requires its params",
+                methods.len(),
+                params.len()
+            )
+        }
+        let mut params = params.into_iter().map(Self::to_json_or_string);
+        Ok(methods
+            .into_iter()
+            .map(|method| BatchRequest {
+                method,
+                params: params.next().unwrap_or_else(|| serde_json::json!([])),
+            })
+            .collect())
+    }
+
     fn to_json_or_string(value: String) -> serde_json::Value {
         serde_json::from_str(&value).unwrap_or(serde_json::Value::String(value))
     }
 }
+
+/// A single request of a batch
+#[derive(Debug, Clone, Deserialize)]
+struct BatchRequest {
+    method: String,
+    #[serde(default = "empty_params")]
+    params: serde_json::Value,
+}
+
+fn empty_params() -> serde_json::Value {
+    serde_json::json!([])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn orders_batch_responses() {
+        let response = json!([
+            {"jsonrpc": "2.0", "id": 2, "error": {"code": -32601, "message": "not found"}},
+            {"jsonrpc": "2.0", "id": 0, "result": "0x1"},
+            {"jsonrpc": "2.0", "id": 1, "result": null},
+        ]);
+        let responses = RpcArgs::order_responses(response, 4).unwrap();
+        assert_eq!(responses[0], Ok(json!("0x1")));
+        assert_eq!(responses[1], Ok(json!(null)));
+        assert_eq!(responses[2], Err(json!({"code": -32601, "message": "not found"})));
+        assert!(responses[3].is_err());
+
+        let rejected = json!({"jsonrpc": "2.0", "id": null, "error": {"code": -32600}});
+        assert!(RpcArgs::order_responses(rejected, 1).is_err());
+    }
+
+    #[test]
+    fn builds_subscription_params() {
+        let params = RpcArgs::subscription_params(
+            "logs".to_string(),
+            vec![r#"{"address": "0x6B175474E89094C44Da98b954EedeAC495271d0F"}"#.to_string()],
+        );
+        assert_eq!(
+            params,
+            vec![json!("logs"), json!({"address": "0x6B175474E89094C44Da98b954EedeAC495271d0F"})]
+        );
+        assert_eq!(
+            RpcArgs::subscription_params("newHeads".to_string(), vec![]),
+            [json!("newHeads")]
+        );
+
+        assert!(RpcArgs::is_ws_url("wss://eth-mainnet.example.com"));
+        assert!(RpcArgs::is_ws_url("ws://127.0.0.1:8545"));
+        assert!(!RpcArgs::is_ws_url("http://127.0.0.1:8545"));
+    }
+
+    #[test]
+    fn builds_batch_from_args() {
+        let requests = RpcArgs::batch_from_args(
+            vec!["eth_blockNumber".to_string(), "eth_getBalance".to_string()],
+            vec!["[]".to_string(), r#"["0x0", "latest"]"#.to_string()],
+        )
+        .unwrap();
+        assert_eq!(requests[1].params, json!(["0x0", "latest"]));
+
+        let requests =
+            RpcArgs::batch_from_args(vec!["eth_blockNumber".to_string()], vec![]).unwrap();
+        assert_eq!(requests[0].params, json!([]));
+
+        assert!(RpcArgs::batch_from_args(
+            vec!["eth_blockNumber".to_string(), "eth_chainId".to_string()],
+            vec!["[]".to_string()]
+        )
+        .is_err());
+    }
+}
@@ -1,27 +1,44 @@
-use crate::{cmd::Cmd, init_progress, update_progress, utils::consume_config_rpc_url};
+use crate::{
+    cmd::{cast::storage::etherscan_storage_layout, Cmd},
+    compile, init_progress, update_progress,
+    utils::{consume_config_rpc_url, parse_u256},
+};
 use cast::{
-    revm::TransactTo,
+    decode,
+    revm::{CreateScheme, Env, Filth, TransactTo},
     trace::{identifier::SignaturesIdentifier, CallTraceDecoder},
 };
 use clap::Parser;
 use ethers::{
     abi::Address,
+    etherscan::Client,
     prelude::Middleware,
-    solc::utils::RuntimeOrHandle,
-    types::{Transaction, H256},
+    solc::{artifacts::CompactContractBytecode, utils::RuntimeOrHandle, Artifact},
+    types::{
+        transaction::eip2718::TypedTransaction, BlockId, BlockNumber, Bytes, NameOrAddress,
+        Transaction, H256, U256,
+    },
 };
 use eyre::WrapErr;
 use forge::{
     debug::DebugArena,
-    executor::{opts::EvmOpts, Backend, DeployResult, ExecutorBuilder, RawCallResult},
-    trace::{identifier::EtherscanIdentifier, CallTraceArena, CallTraceDecoderBuilder, TraceKind},
-    utils::h256_to_u256_be,
+    executor::{
+        opts::EvmOpts, Backend, DatabaseRef, DeployResult, Executor, ExecutorBuilder,
+        RawCallResult, StateChangeset,
+    },
+    trace::{
+        identifier::{EtherscanIdentifier, LocalTraceIdentifier},
+        CallTrace, CallTraceArena, CallTraceDecoderBuilder, LogCallOrder, RawOrDecodedCall,
+        RawOrDecodedLog, RawOrDecodedReturnData, TraceKind,
+    },
+    utils::{h256_to_u256_be, u256_to_h256_be},
 };
-use foundry_common::get_http_provider;
+use foundry_common::{get_http_provider, ContractsByArtifact};
 use foundry_config::{find_project_root_path, Config};
 use indicatif::{ProgressBar, ProgressStyle};
 use std::{
     collections::{BTreeMap, HashMap},
+    fmt::Write,
     str::FromStr,
 };
 use ui::{TUIExitReason, Tui, Ui};
@@ -33,6 +50,13 @@ pub struct RunArgs {
     tx: String,
     #[clap(short, long, env = "ETH_RPC_URL", value_name = "URL")]
     rpc_url: Option<String>,
+    #[clap(
+        long,
+        help = "Forks the state from this node instead of the one the transaction is fetched from.",
+        long_help = "Forks the state from this node instead of the one the transaction is fetched from.\n\nThis is useful to replay the transaction against an archive node, or against the state of another node.",
+        value_name = "URL"
+    )]
+    fork_url: Option<String>,
     #[clap(long, short = 'd', help = "Debugs the transaction.")]
     debug: bool,
     #[clap(
@@ -43,12 +67,60 @@ pub struct RunArgs {
     quick: bool,
     #[clap(long, short = 'v', help = "Prints full address")]
     verbose: bool,
+    #[clap(
+        long,
+        help = "Also prints the events emitted by the transaction and the gas used by every call.",
+        long_help = "Also prints the events emitted by the transaction and the gas used by every call.\n\nThe events are listed in the order they were emitted, together with the contract that emitted them, and the calls are ordered by the gas they used, the most expensive first."
+    )]
+    trace: bool,
     #[clap(
         long,
         help = "Labels address in the trace. 0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045:vitalik.eth",
         value_name = "LABEL"
     )]
     label: Vec<String>,
+    #[clap(
+        long,
+        help = "Prints the storage slots, balances and nonces that were changed by the transaction.",
+        long_help = "Prints the storage slots, balances and nonces that were changed by the transaction.\n\nIf an etherscan API key is configured, the storage slots of verified contracts are annotated with the names of their state variables."
+    )]
+    state_diff: bool,
+    #[clap(
+        long,
+        help = "Replays the transaction with this block timestamp.",
+        long_help = "Replays the transaction with this block timestamp.\n\nThe transactions before it in the block are executed with the real block environment, so only the replayed transaction sees the timestamp. The result is hypothetical and may differ from what happened on chain.",
+        value_name = "TIMESTAMP"
+    )]
+    block_time: Option<u64>,
+    #[clap(
+        long,
+        help = "Replays the transaction with this block number.",
+        long_help = "Replays the transaction with this block number.\n\nThe state is still fetched at the block of the transaction, only the `block.number` seen by the replayed transaction changes. The result is hypothetical and may differ from what happened on chain.",
+        value_name = "BLOCK"
+    )]
+    block_number: Option<u64>,
+    #[clap(
+        long,
+        help = "Replays the transaction with this base fee.",
+        long_help = "Replays the transaction with this base fee.\n\nOnly the replayed transaction sees the base fee, and its gas price must not be lower than it. The result is hypothetical and may differ from what happened on chain.",
+        parse(try_from_str = parse_u256),
+        value_name = "FEE"
+    )]
+    base_fee: Option<U256>,
+    #[clap(
+        long,
+        help = "Replays the transaction at the start of the block this many blocks after its own.",
+        long_help = "Replays the transaction at the start of the block this many blocks after its own.\n\nThe state is forked at the end of the previous block, so it includes the effects of every transaction mined since, including the replayed transaction itself, whose nonce is therefore not checked. The result is hypothetical and may differ from what happened on chain.",
+        default_value = "0",
+        value_name = "BLOCKS"
+    )]
+    block_offset: u64,
+    #[clap(
+        long,
+        help = "Identifies contracts in the trace with the artifacts of the local project.",
+        long_help = "Identifies contracts in the trace with the artifacts of the local project.\n\nThe project is compiled if needed. Contracts whose deployed bytecode matches a local artifact are labeled with its name, and their calls, events and errors are decoded with its ABI."
+    )]
+    with_local_artifacts: bool,
 }
 
 impl Cmd for RunArgs {
@@ -59,13 +131,24 @@ impl Cmd for RunArgs {
 }
 
 impl RunArgs {
+    /// Whether any value of the block environment is overridden
+    fn has_block_overrides(&self) -> bool {
+        self.block_time.is_some() ||
+            self.block_number.is_some() ||
+            self.base_fee.is_some() ||
+            self.block_offset > 0
+    }
+
     async fn run_tx(self) -> eyre::Result<()> {
+        let has_block_overrides = self.has_block_overrides();
         let figment = Config::figment_with_root(find_project_root_path().unwrap());
         let mut evm_opts = figment.extract::<EvmOpts>()?;
         let config = Config::from_provider(figment).sanitized();
 
         let rpc_url = consume_config_rpc_url(self.rpc_url);
         let provider = get_http_provider(rpc_url.as_str());
+        let fork_url = self.fork_url.unwrap_or(rpc_url);
+        let fork_provider = get_http_provider(fork_url.as_str());
 
         if let Some(tx) = provider
             .get_transaction(
@@ -76,10 +159,10 @@ impl RunArgs {
         {
             let tx_block_number = tx.block_number.expect("no block number").as_u64();
             let tx_hash = tx.hash();
-            evm_opts.fork_url = Some(rpc_url);
-            // we need to set the fork block to the previous block, because that's the state at
-            // which we access the data in order to execute the transaction(s)
-            evm_opts.fork_block_number = Some(tx_block_number - 1);
+            let (replay_block_number, fork_block_number) =
+                replay_block_numbers(tx_block_number, self.block_offset);
+            evm_opts.fork_url = Some(fork_url);
+            evm_opts.fork_block_number = Some(fork_block_number);
 
             // Set up the execution environment
             let env = evm_opts.evm_env().await;
@@ -92,9 +175,12 @@ impl RunArgs {
             let mut executor = builder.build(db);
 
             let mut env = executor.env().clone();
-            env.block.number = tx_block_number.into();
+            env.block.number = replay_block_number.into();
 
-            let block = provider.get_block_with_txs(tx_block_number).await?;
+            let block = fork_provider.get_block_with_txs(replay_block_number).await?;
+            if block.is_none() && self.block_offset > 0 {
+                eyre::bail!("Block {replay_block_number} has not been mined yet")
+            }
             if let Some(ref block) = block {
                 env.block.timestamp = block.timestamp;
                 env.block.coinbase = block.author.unwrap_or_default();
@@ -103,8 +189,9 @@ impl RunArgs {
                 env.block.gas_limit = block.gas_limit;
             }
 
-            // Set the state to the moment right before the transaction
-            if !self.quick {
+            // Set the state to the moment right before the transaction, with an offset the
+            // transaction is replayed at the start of the block
+            if !self.quick && self.block_offset == 0 {
                 println!("Executing previous transactions from the block.");
 
                 if let Some(block) = block {
@@ -130,14 +217,51 @@ impl RunArgs {
                 }
             }
 
+            // Override the block environment of the replayed transaction only, the state before it
+            // was computed with the real one
+            if has_block_overrides {
+                println!(
+                    "{}",
+                    Paint::yellow(
+                        "Replaying with an overridden block environment, the result is hypothetical."
+                    )
+                );
+            }
+            if let Some(block_time) = self.block_time {
+                env.block.timestamp = block_time.into();
+            }
+            if let Some(block_number) = self.block_number {
+                env.block.number = block_number.into();
+            }
+            if let Some(base_fee) = self.base_fee {
+                env.block.basefee = base_fee;
+            }
+
+            configure_tx_env(&mut env, &tx);
+            if self.block_offset > 0 {
+                // the forked state already includes the transaction
+                env.tx.nonce = None;
+            }
+            env.tx.transact_to = match tx.to {
+                Some(to) => TransactTo::Call(to),
+                None => TransactTo::Create(CreateScheme::Create),
+            };
+
+            // Execute the transaction without committing it first, in order to compare the
+            // changed accounts with the state before the transaction
+            let state_diff = if self.state_diff {
+                let changeset =
+                    executor.call_raw_with_env(env.clone())?.state_changeset.unwrap_or_default();
+                Some(StateDiff::new(executor.backend(), changeset))
+            } else {
+                None
+            };
+
             // Execute our transaction
             let mut result = {
                 executor.set_tracing(true).set_debugger(self.debug);
 
-                configure_tx_env(&mut env, &tx);
-
-                if let Some(to) = tx.to {
-                    env.tx.transact_to = TransactTo::Call(to);
+                if tx.to.is_some() {
                     let RawCallResult {
                         reverted, gas, traces, debug: run_debug, status: _, ..
                     } = executor.commit_tx_with_env(env).unwrap();
@@ -161,41 +285,202 @@ impl RunArgs {
                 }
             };
 
-            let etherscan_identifier =
-                EtherscanIdentifier::new(&config, evm_opts.get_remote_chain_id())?;
+            let decoder = trace_decoder(
+                &mut result.traces,
+                &self.label,
+                &config,
+                &evm_opts,
+                self.with_local_artifacts,
+            )?;
 
-            let labeled_addresses: BTreeMap<Address, String> = self
-                .label
-                .iter()
-                .filter_map(|label_str| {
-                    let mut iter = label_str.split(':');
+            if self.debug {
+                run_debugger(result, decoder)?;
+            } else {
+                print_traces(&mut result, decoder, self.verbose, self.trace).await?;
+            }
 
-                    if let Some(addr) = iter.next() {
-                        if let (Ok(address), Some(label)) = (Address::from_str(addr), iter.next()) {
-                            return Some((address, label.to_string()))
-                        }
-                    }
-                    None
-                })
-                .collect();
+            if let Some(state_diff) = state_diff {
+                let etherscan = config
+                    .get_etherscan_config_with_chain(evm_opts.get_remote_chain_id())?
+                    .map(|config| config.into_client())
+                    .transpose()?;
+                state_diff.print(etherscan.as_ref()).await;
+            }
+        }
+        Ok(())
+    }
+}
 
-            let mut decoder = CallTraceDecoderBuilder::new().with_labels(labeled_addresses).build();
+/// Executes a call on a fork of the given block without committing it, and prints its decoded
+/// trace.
+///
+/// Like `eth_call`, the call is executed without a base fee and gas price, so the sender does not
+/// need to pay for gas.
+pub async fn trace_call(
+    rpc_url: String,
+    block: Option<BlockId>,
+    tx: &TypedTransaction,
+    labels: &[String],
+    with_local_artifacts: bool,
+    verbose: bool,
+) -> eyre::Result<()> {
+    let ForkedCall { mut executor, env, config, evm_opts } =
+        ForkedCall::new(rpc_url, block, tx).await?;
+    executor.set_tracing(true);
+
+    let RawCallResult { reverted, gas, traces, .. } = executor.call_raw_with_env(env)?;
+    let mut result = RunResult {
+        success: !reverted,
+        traces: vec![(TraceKind::Execution, traces.unwrap_or_default())],
+        debug: Default::default(),
+        gas,
+    };
+
+    let decoder =
+        trace_decoder(&mut result.traces, labels, &config, &evm_opts, with_local_artifacts)?;
+    print_traces(&mut result, decoder, verbose, false).await
+}
 
-            decoder
-                .add_signature_identifier(SignaturesIdentifier::new(Config::foundry_cache_dir())?);
+/// Simulates the transaction on a fork of the latest block without committing it.
+///
+/// The transaction is executed once, like `eth_call` without a base fee and gas price. Returns its
+/// return data and the gas it used, or an error with the decoded revert reason if it reverts.
+pub async fn simulate_tx(rpc_url: String, tx: &TypedTransaction) -> eyre::Result<(Bytes, u64)> {
+    let ForkedCall { mut executor, env, .. } = ForkedCall::new(rpc_url, None, tx).await?;
+    let RawCallResult { reverted, result, gas, .. } = executor.call_raw_with_env(env)?;
+    if reverted {
+        let mut identifier = SignaturesIdentifier::new(Config::foundry_cache_dir(), false).ok();
+        let reason = decode::decode_revert_data(&result, identifier.as_mut())
+            .await
+            .unwrap_or_else(|_| format!("0x{}", hex::encode(&result)));
+        eyre::bail!("Simulation failed, the transaction would revert: {reason}")
+    }
+    Ok((result, gas))
+}
 
-            for (_, trace) in &mut result.traces {
-                decoder.identify(trace, &etherscan_identifier);
-            }
+/// A call prepared for execution on a fork
+struct ForkedCall {
+    executor: Executor,
+    env: Env,
+    config: Config,
+    evm_opts: EvmOpts,
+}
 
-            if self.debug {
-                run_debugger(result, decoder)?;
-            } else {
-                print_traces(&mut result, decoder, self.verbose).await?;
+impl ForkedCall {
+    /// Forks the given block, or the latest one, and prepares the environment of the call `tx`.
+    ///
+    /// Like `eth_call`, the call is executed without a base fee and gas price, so the sender does
+    /// not need to pay for gas.
+    async fn new(
+        rpc_url: String,
+        block: Option<BlockId>,
+        tx: &TypedTransaction,
+    ) -> eyre::Result<Self> {
+        let figment = Config::figment_with_root(find_project_root_path().unwrap());
+        let mut evm_opts = figment.extract::<EvmOpts>()?;
+        let config = Config::from_provider(figment).sanitized();
+
+        let provider = get_http_provider(rpc_url.as_str());
+        let block_number = provider
+            .get_block(block.unwrap_or_else(|| BlockNumber::Latest.into()))
+            .await?
+            .and_then(|block| block.number)
+            .ok_or_else(|| eyre::eyre!("block {:?} not found", block))?;
+
+        evm_opts.fork_url = Some(rpc_url);
+        // the call sees the state after the block
+        evm_opts.fork_block_number = Some(block_number.as_u64());
+
+        let env = evm_opts.evm_env().await;
+        let db = Backend::spawn(evm_opts.get_fork(&config, env.clone()));
+        let executor = ExecutorBuilder::default()
+            .with_config(env)
+            .with_spec(crate::utils::evm_spec(&config.evm_version))
+            .build(db);
+
+        let mut env = executor.env().clone();
+        env.block.basefee = U256::zero();
+        env.tx.caller = tx.from().copied().unwrap_or_default();
+        env.tx.transact_to = match tx.to() {
+            Some(NameOrAddress::Address(to)) => TransactTo::Call(*to),
+            Some(NameOrAddress::Name(name)) => eyre::bail!("unresolved ENS name {name}"),
+            None => TransactTo::Create(CreateScheme::Create),
+        };
+        env.tx.data = tx.data().cloned().unwrap_or_default().0;
+        env.tx.value = tx.value().copied().unwrap_or_default();
+        env.tx.gas_price = U256::zero();
+        env.tx.gas_priority_fee = None;
+        if let Some(gas) = tx.gas() {
+            env.tx.gas_limit = gas.as_u64();
+        } else {
+            env.tx.gas_limit = env.block.gas_limit.as_u64();
+        }
+
+        Ok(Self { executor, env, config, evm_opts })
+    }
+}
+
+/// Returns the decoder of the traces, after identifying their contracts with the `labels` and the
+/// configured labels file, the local artifacts if enabled and etherscan
+fn trace_decoder(
+    traces: &mut [(TraceKind, CallTraceArena)],
+    labels: &[String],
+    config: &Config,
+    evm_opts: &EvmOpts,
+    with_local_artifacts: bool,
+) -> eyre::Result<CallTraceDecoder> {
+    let etherscan_identifier = EtherscanIdentifier::new(config, evm_opts.get_remote_chain_id())?;
+
+    let labeled_addresses: BTreeMap<Address, String> = labels
+        .iter()
+        .filter_map(|label_str| {
+            let mut iter = label_str.split(':');
+
+            if let Some(addr) = iter.next() {
+                if let (Ok(address), Some(label)) = (Address::from_str(addr), iter.next()) {
+                    return Some((address, label.to_string()))
+                }
             }
+            None
+        })
+        .collect();
+
+    let mut decoder = CallTraceDecoderBuilder::new()
+        .with_labels(config.labels()?)
+        .with_labels(labeled_addresses)
+        .build();
+
+    decoder
+        .add_signature_identifier(SignaturesIdentifier::new(Config::foundry_cache_dir(), false)?);
+
+    if with_local_artifacts {
+        let local_contracts = local_contracts(config)?;
+        let local_identifier = LocalTraceIdentifier::new(&local_contracts);
+        for (_, trace) in traces.iter_mut() {
+            decoder.identify(trace, &local_identifier);
         }
-        Ok(())
     }
+
+    for (_, trace) in traces.iter_mut() {
+        decoder.identify(trace, &etherscan_identifier);
+    }
+    Ok(decoder)
+}
+
+/// Compiles the local project and returns its contracts, to identify them in the trace
+fn local_contracts(config: &Config) -> eyre::Result<ContractsByArtifact> {
+    let output = compile::suppress_compile(&config.project()?)?;
+    Ok(ContractsByArtifact(
+        output
+            .into_artifacts()
+            .filter_map(|(id, artifact)| {
+                let CompactContractBytecode { abi, deployed_bytecode, .. } =
+                    artifact.into_contract_bytecode();
+                let code = deployed_bytecode?.bytecode?.object.into_bytes()?;
+                Some((id, (abi?, code.to_vec())))
+            })
+            .collect(),
+    ))
 }
 
 /// Configures the env for the transaction
@@ -227,10 +512,13 @@ fn run_debugger(result: RunResult, decoder: CallTraceDecoder) -> eyre::Result<()
     }
 }
 
+/// Decodes and prints the traces and the outcome of the execution, followed by the
+/// [trace_summary] of every trace if `summary` is set
 async fn print_traces(
     result: &mut RunResult,
     decoder: CallTraceDecoder,
     verbose: bool,
+    summary: bool,
 ) -> eyre::Result<()> {
     if result.traces.is_empty() {
         eyre::bail!("Unexpected error: No traces. Please report this as a bug: https://github.com/foundry-rs/foundry/issues/new?assignees=&labels=T-bug&template=BUG-FORM.yml");
@@ -247,19 +535,354 @@ async fn print_traces(
     }
     println!();
 
+    if summary {
+        for (_, trace) in &result.traces {
+            println!("{}", trace_summary(trace, verbose));
+        }
+    }
+
     if result.success {
         println!("{}", Paint::green("Transaction successfully executed."));
     } else {
         println!("{}", Paint::red("Transaction failed."));
+        let reason =
+            result.traces.iter().find_map(|(_, trace)| match &trace.arena.first()?.trace.output {
+                RawOrDecodedReturnData::Decoded(reason) => Some(reason.clone()),
+                RawOrDecodedReturnData::Raw(_) => None,
+            });
+        if let Some(reason) = reason {
+            println!("Reason: {reason}");
+        }
     }
 
     println!("Gas used: {}", result.gas);
     Ok(())
 }
 
+/// Formats the events emitted in the trace, in the order they were emitted and with the contract
+/// that emitted them, and the calls of the trace ordered by the gas they used, the most expensive
+/// first
+fn trace_summary(arena: &CallTraceArena, full_addresses: bool) -> String {
+    fn target(trace: &CallTrace, full_addresses: bool) -> String {
+        trace.label.clone().unwrap_or_else(|| {
+            if full_addresses {
+                format!("{:?}", trace.address)
+            } else {
+                trace.address.to_string()
+            }
+        })
+    }
+
+    fn events(arena: &CallTraceArena, idx: usize, full_addresses: bool, out: &mut String) {
+        let node = &arena.arena[idx];
+        for child in &node.ordering {
+            match child {
+                LogCallOrder::Log(index) => {
+                    let event = match &node.logs[*index] {
+                        RawOrDecodedLog::Decoded(name, params) => {
+                            let params = params
+                                .iter()
+                                .map(|(name, value)| format!("{name}: {value}"))
+                                .collect::<Vec<_>>()
+                                .join(", ");
+                            format!("{name}({params})")
+                        }
+                        RawOrDecodedLog::Raw(log) => format!(
+                            "{} data: 0x{}",
+                            log.topics
+                                .iter()
+                                .map(|topic| format!("{topic:?}"))
+                                .collect::<Vec<_>>()
+                                .join(" "),
+                            hex::encode(&log.data)
+                        ),
+                    };
+                    writeln!(out, "  {}: {}", target(&node.trace, full_addresses), event).unwrap();
+                }
+                LogCallOrder::Call(index) => {
+                    events(arena, node.children[*index], full_addresses, out)
+                }
+            }
+        }
+    }
+
+    let mut out = String::from("Events:\n");
+    if !arena.arena.is_empty() {
+        events(arena, 0, full_addresses, &mut out);
+    }
+
+    writeln!(out, "Gas used by call:").unwrap();
+    let mut calls = arena.arena.iter().map(|node| &node.trace).collect::<Vec<_>>();
+    calls.sort_by_key(|trace| std::cmp::Reverse(trace.gas_cost));
+    for trace in calls {
+        let function = match &trace.data {
+            _ if trace.created() => "new".to_string(),
+            RawOrDecodedCall::Decoded(function, ..) => function.clone(),
+            RawOrDecodedCall::Raw(data) if data.len() >= 4 => {
+                format!("0x{}", hex::encode(&data[..4]))
+            }
+            RawOrDecodedCall::Raw(_) => "fallback".to_string(),
+        };
+        writeln!(out, "  [{}] {}::{}", trace.gas_cost, target(trace, full_addresses), function)
+            .unwrap();
+    }
+    out
+}
+
 struct RunResult {
     pub success: bool,
     pub traces: Vec<(TraceKind, CallTraceArena)>,
     pub debug: DebugArena,
     pub gas: u64,
 }
+
+/// The accounts that were changed by a transaction
+struct StateDiff {
+    accounts: BTreeMap<Address, AccountDiff>,
+}
+
+/// The changes of a single account
+struct AccountDiff {
+    balance: (U256, U256),
+    nonce: (u64, u64),
+    /// Changed storage slots with their value before and after the transaction
+    storage: BTreeMap<U256, (U256, U256)>,
+    destroyed: bool,
+}
+
+impl StateDiff {
+    /// Compares the changeset of a transaction with the state of the `backend` before the
+    /// transaction
+    fn new(backend: &Backend, changeset: StateChangeset) -> Self {
+        let accounts = changeset
+            .into_iter()
+            .filter_map(|(address, account)| {
+                let before = DatabaseRef::basic(backend, address);
+                let storage = account
+                    .storage
+                    .into_iter()
+                    .filter_map(|(slot, value)| {
+                        let prev = DatabaseRef::storage(backend, address, slot);
+                        (prev != value).then_some((slot, (prev, value)))
+                    })
+                    .collect::<BTreeMap<_, _>>();
+                let diff = AccountDiff {
+                    balance: (before.balance, account.info.balance),
+                    nonce: (before.nonce, account.info.nonce),
+                    storage,
+                    destroyed: matches!(account.filth, Filth::Destroyed),
+                };
+                diff.is_changed().then_some((address, diff))
+            })
+            .collect();
+        Self { accounts }
+    }
+
+    /// Prints the changes of every account, annotating the storage slots of contracts verified on
+    /// etherscan with the names of their state variables
+    async fn print(&self, etherscan: Option<&Client>) {
+        let mut labels = BTreeMap::new();
+        if let Some(client) = etherscan {
+            for (address, diff) in &self.accounts {
+                if !diff.storage.is_empty() {
+                    labels.insert(*address, slot_labels(client, *address).await);
+                }
+            }
+        }
+        println!("{}", self.format(&labels));
+    }
+
+    /// Formats the changes of every account, with the given names of the storage slots of each
+    /// account
+    fn format(&self, labels: &BTreeMap<Address, BTreeMap<U256, String>>) -> String {
+        let mut out = String::from("State diff:\n");
+        if self.accounts.is_empty() {
+            out.push_str("  No state changes.\n");
+        }
+
+        for (address, diff) in &self.accounts {
+            writeln!(out, "{}", Paint::cyan(format!("{:?}", address))).unwrap();
+            if diff.destroyed {
+                writeln!(out, "  {}", Paint::red("destroyed")).unwrap();
+            }
+            let (before, after) = diff.balance;
+            if before != after {
+                let delta = if after > before {
+                    Paint::green(format!("+{}", after - before))
+                } else {
+                    Paint::red(format!("-{}", before - after))
+                };
+                writeln!(out, "  balance: {} -> {} ({})", before, after, delta).unwrap();
+            }
+            let (before, after) = diff.nonce;
+            if before != after {
+                writeln!(out, "  nonce: {} -> {}", before, after).unwrap();
+            }
+
+            for (slot, (before, after)) in &diff.storage {
+                let slot_name = match labels.get(address).and_then(|labels| labels.get(slot)) {
+                    Some(label) => format!("{:?} ({})", u256_to_h256_be(*slot), label),
+                    None => format!("{:?}", u256_to_h256_be(*slot)),
+                };
+                writeln!(out, "  slot {}:", slot_name).unwrap();
+                writeln!(out, "    {} {:?}", Paint::red("-"), u256_to_h256_be(*before)).unwrap();
+                writeln!(out, "    {} {:?}", Paint::green("+"), u256_to_h256_be(*after)).unwrap();
+            }
+        }
+        out
+    }
+}
+
+impl AccountDiff {
+    fn is_changed(&self) -> bool {
+        self.balance.0 != self.balance.1 ||
+            self.nonce.0 != self.nonce.1 ||
+            !self.storage.is_empty() ||
+            self.destroyed
+    }
+}
+
+/// Returns the number of the block the transaction is replayed in and the number of the block whose
+/// state is forked.
+///
+/// The fork is at the previous block, because that's the state at which we access the data in
+/// order to execute the transaction(s).
+fn replay_block_numbers(tx_block_number: u64, block_offset: u64) -> (u64, u64) {
+    let replay_block_number = tx_block_number + block_offset;
+    (replay_block_number, replay_block_number - 1)
+}
+
+/// Returns the names of the state variables stored in each slot of the contract at `address`.
+///
+/// Returns an empty map if the contract is not verified or its source code fails to compile.
+async fn slot_labels(client: &Client, address: Address) -> BTreeMap<U256, String> {
+    let layout = match etherscan_storage_layout(client, address).await {
+        Ok(layout) => layout,
+        Err(err) => {
+            tracing::trace!(?address, ?err, "failed to get storage layout");
+            return BTreeMap::new()
+        }
+    };
+
+    let mut labels = BTreeMap::<U256, String>::new();
+    for variable in layout.storage {
+        if let Ok(slot) = U256::from_dec_str(&variable.slot) {
+            labels
+                .entry(slot)
+                .and_modify(|label| {
+                    label.push_str(", ");
+                    label.push_str(&variable.label);
+                })
+                .or_insert(variable.label);
+        }
+    }
+    labels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use forge::trace::node::CallTraceNode;
+
+    #[test]
+    fn summarizes_trace() {
+        let token = Address::from_low_u64_be(1);
+        let node = |idx, address, label: Option<&str>, function: &str, gas_cost| CallTraceNode {
+            idx,
+            trace: CallTrace {
+                address,
+                label: label.map(str::to_string),
+                data: RawOrDecodedCall::Decoded(function.to_string(), String::new(), vec![]),
+                gas_cost,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let mut router = node(0, Address::from_low_u64_be(2), None, "swap", 50_000);
+        router.children = vec![1];
+        router.logs = vec![RawOrDecodedLog::Decoded(
+            "Swap".to_string(),
+            vec![("amount".to_string(), "5".to_string())],
+        )];
+        router.ordering = vec![LogCallOrder::Call(0), LogCallOrder::Log(0)];
+        let mut transfer = node(1, token, Some("Token"), "transfer", 30_000);
+        transfer.parent = Some(0);
+        transfer.logs = vec![RawOrDecodedLog::Decoded(
+            "Transfer".to_string(),
+            vec![("value".to_string(), "5".to_string())],
+        )];
+        transfer.ordering = vec![LogCallOrder::Log(0)];
+
+        let summary = trace_summary(&CallTraceArena { arena: vec![router, transfer] }, true);
+        // the event of the nested call is emitted before the event of its caller
+        assert_eq!(
+            summary,
+            concat!(
+                "Events:\n",
+                "  Token: Transfer(value: 5)\n",
+                "  0x0000000000000000000000000000000000000002: Swap(amount: 5)\n",
+                "Gas used by call:\n",
+                "  [50000] 0x0000000000000000000000000000000000000002::swap\n",
+                "  [30000] Token::transfer\n",
+            )
+        );
+    }
+
+    #[test]
+    fn formats_state_diff() {
+        let token = Address::from_low_u64_be(1);
+        let sender = Address::from_low_u64_be(2);
+        let diff = StateDiff {
+            accounts: BTreeMap::from([
+                (
+                    token,
+                    AccountDiff {
+                        balance: (0.into(), 0.into()),
+                        nonce: (1, 1),
+                        storage: BTreeMap::from([
+                            (0.into(), (1.into(), 2.into())),
+                            (5.into(), (0.into(), 7.into())),
+                        ]),
+                        destroyed: false,
+                    },
+                ),
+                (
+                    sender,
+                    AccountDiff {
+                        balance: (100.into(), 40.into()),
+                        nonce: (3, 4),
+                        storage: BTreeMap::new(),
+                        destroyed: false,
+                    },
+                ),
+            ]),
+        };
+        let labels = BTreeMap::from([(token, BTreeMap::from([(0.into(), "owner".to_string())]))]);
+
+        let out = diff.format(&labels);
+        assert!(out.starts_with("State diff:\n"), "{out}");
+        assert!(out.contains(
+            "  slot 0x0000000000000000000000000000000000000000000000000000000000000000 (owner):\n"
+        ));
+        assert!(out.contains(
+            "  slot 0x0000000000000000000000000000000000000000000000000000000000000005:\n"
+        ));
+        assert!(out.contains(
+            "0x0000000000000000000000000000000000000000000000000000000000000007\n"
+        ));
+        assert!(out.contains("  balance: 100 -> 40 ("), "{out}");
+        assert!(out.contains("  nonce: 3 -> 4\n"), "{out}");
+        // unchanged values are not listed
+        assert!(!out.contains("nonce: 1 -> 1"), "{out}");
+
+        let empty = StateDiff { accounts: BTreeMap::new() };
+        assert_eq!(empty.format(&BTreeMap::new()), "State diff:\n  No state changes.\n");
+    }
+
+    #[test]
+    fn forks_at_the_block_before_the_replay() {
+        assert_eq!(replay_block_numbers(15_000_000, 0), (15_000_000, 14_999_999));
+        assert_eq!(replay_block_numbers(15_000_000, 3), (15_000_003, 15_000_002));
+    }
+}
@@ -0,0 +1,253 @@
+//! cast selectors subcommand
+
+use crate::{cmd::Cmd, utils::consume_config_rpc_url};
+use cast::trace::identifier::SignaturesIdentifier;
+use clap::Parser;
+use ethers::{
+    prelude::{Address, Middleware, H256},
+    utils::hex,
+};
+use eyre::Result;
+use foundry_common::get_http_provider;
+use foundry_config::Config;
+use foundry_utils::selectors::SelectorType;
+use futures::future::BoxFuture;
+use serde_json::json;
+use std::collections::BTreeSet;
+
+/// `PUSH1`, the first of the `PUSH` opcodes
+const PUSH1: u8 = 0x60;
+/// `PUSH4`, used by the dispatcher to push function selectors
+const PUSH4: u8 = 0x63;
+/// `PUSH32`, the last of the `PUSH` opcodes, used to push event topics
+const PUSH32: u8 = 0x7f;
+const EQ: u8 = 0x14;
+const DUP1: u8 = 0x80;
+const DUP16: u8 = 0x8f;
+
+#[derive(Debug, Clone, Parser)]
+pub struct SelectorsArgs {
+    #[clap(
+        help = "The runtime bytecode, or the address of a deployed contract.",
+        value_name = "BYTECODE_OR_ADDRESS"
+    )]
+    bytecode_or_address: String,
+    #[clap(
+        long,
+        help = "Look up the candidate signatures of every selector on https://sig.eth.samczsun.com."
+    )]
+    resolve: bool,
+    #[clap(
+        long = "json",
+        short = 'j',
+        alias = "to-json",
+        help = "Print the selectors as JSON.",
+        help_heading = "DISPLAY OPTIONS"
+    )]
+    to_json: bool,
+    #[clap(long, env = "ETH_RPC_URL", value_name = "URL")]
+    rpc_url: Option<String>,
+}
+
+impl Cmd for SelectorsArgs {
+    type Output = BoxFuture<'static, Result<()>>;
+
+    fn run(self) -> Result<Self::Output> {
+        Ok(Box::pin(self.list_selectors()))
+    }
+}
+
+impl SelectorsArgs {
+    async fn list_selectors(self) -> Result<()> {
+        let SelectorsArgs { bytecode_or_address, resolve, to_json, rpc_url } = self;
+
+        let input = bytecode_or_address.strip_prefix("0x").unwrap_or(&bytecode_or_address);
+        let code = if input.len() == 40 {
+            let address: Address = bytecode_or_address.parse()?;
+            let provider = get_http_provider(consume_config_rpc_url(rpc_url));
+            let code = provider.get_code(address, None).await?;
+            if code.as_ref().is_empty() {
+                eyre::bail!("No code deployed at {address:?}")
+            }
+            code.to_vec()
+        } else {
+            hex::decode(input).map_err(|_| eyre::eyre!("Invalid bytecode or address provided"))?
+        };
+
+        let Selectors { functions, events } = extract_selectors(&code);
+
+        let mut identifier = if resolve {
+            Some(SignaturesIdentifier::new(Config::foundry_cache_dir(), false)?)
+        } else {
+            None
+        };
+        let functions = resolve_selectors(
+            identifier.as_mut(),
+            functions.iter().map(|selector| format!("0x{}", hex::encode(selector))).collect(),
+            SelectorType::Function,
+        )
+        .await;
+        let events = resolve_selectors(
+            identifier.as_mut(),
+            events.iter().map(|topic| format!("{topic:?}")).collect(),
+            SelectorType::Event,
+        )
+        .await;
+
+        if to_json {
+            let output = json!({ "functions": to_json_entries(functions), "events": to_json_entries(events) });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+            return Ok(())
+        }
+
+        let mut sections = Vec::new();
+        if !functions.is_empty() {
+            sections.push(format!("Functions:\n{}", pretty_entries(functions)));
+        }
+        if !events.is_empty() {
+            sections.push(format!("Events:\n{}", pretty_entries(events)));
+        }
+        println!("{}", sections.join("\n\n"));
+
+        Ok(())
+    }
+}
+
+/// A selector along with its candidate signatures, if they were looked up
+type ResolvedSelector = (String, Option<Vec<String>>);
+
+/// Looks up the candidate signatures of every selector if an identifier is given.
+///
+/// Selectors that could not be looked up are returned without any candidate signatures.
+async fn resolve_selectors(
+    mut identifier: Option<&mut SignaturesIdentifier>,
+    selectors: Vec<String>,
+    selector_type: SelectorType,
+) -> Vec<ResolvedSelector> {
+    let mut resolved = Vec::with_capacity(selectors.len());
+    for selector in selectors {
+        let signatures = match identifier.as_mut() {
+            Some(identifier) => {
+                Some(identifier.decode_selector(&selector, selector_type).await.unwrap_or_default())
+            }
+            None => None,
+        };
+        resolved.push((selector, signatures));
+    }
+    resolved
+}
+
+fn to_json_entries(resolved: Vec<ResolvedSelector>) -> Vec<serde_json::Value> {
+    resolved
+        .into_iter()
+        .map(|(selector, signatures)| match signatures {
+            Some(signatures) => json!({ "selector": selector, "signatures": signatures }),
+            None => json!({ "selector": selector }),
+        })
+        .collect()
+}
+
+fn pretty_entries(resolved: Vec<ResolvedSelector>) -> String {
+    resolved
+        .into_iter()
+        .map(|(selector, signatures)| match signatures {
+            Some(signatures) if !signatures.is_empty() => {
+                format!("{selector}\t{}", signatures.join(", "))
+            }
+            _ => selector,
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Selectors found in runtime bytecode
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Selectors {
+    /// Function selectors compared against the calldata by the dispatcher
+    functions: BTreeSet<[u8; 4]>,
+    /// 32 byte constants that look like event topics
+    events: BTreeSet<H256>,
+}
+
+/// Scans `code` for the selectors pushed by the function dispatcher and for `PUSH32` constants that
+/// look like event topics.
+///
+/// A `PUSH4` is only considered a function selector if it is compared with `EQ`, possibly after
+/// a `DUP`, which is how both solc and vyper dispatch calls. `PUSH32` constants are only
+/// considered event topics if they do not start with four `0x00` or `0xff` bytes, which rules out
+/// most numeric constants and masks.
+fn extract_selectors(code: &[u8]) -> Selectors {
+    let mut selectors = Selectors::default();
+    let mut pc = 0;
+    while pc < code.len() {
+        let op = code[pc];
+        if !(PUSH1..=PUSH32).contains(&op) {
+            pc += 1;
+            continue
+        }
+
+        let size = (op - PUSH1 + 1) as usize;
+        let immediate = match code.get(pc + 1..pc + 1 + size) {
+            Some(immediate) => immediate,
+            None => break,
+        };
+        let next = pc + 1 + size;
+        match op {
+            PUSH4 => {
+                let compared = match code.get(next) {
+                    Some(&EQ) => true,
+                    Some(dup) if (DUP1..=DUP16).contains(dup) => code.get(next + 1) == Some(&EQ),
+                    _ => false,
+                };
+                if compared {
+                    selectors.functions.insert(immediate.try_into().expect("4 bytes"));
+                }
+            }
+            PUSH32 => {
+                let prefix = &immediate[..4];
+                if prefix != [0u8; 4] && prefix != [0xffu8; 4] {
+                    selectors.events.insert(H256::from_slice(immediate));
+                }
+            }
+            _ => {}
+        }
+        pc = next;
+    }
+    selectors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_dispatcher_selectors() {
+        let code = hex::decode(concat!(
+            // DUP1 PUSH4 transfer(address,uint256) EQ PUSH2 0x0037 JUMPI
+            "8063a9059cbb1461003757",
+            // PUSH4 balanceOf(address) DUP2 EQ
+            "6370a082318114",
+            // PUSH4 0xffffffff AND, a mask that is not a selector
+            "63ffffffff16",
+            // PUSH32 Transfer(address,address,uint256)
+            "7fddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef",
+            // PUSH32 type(uint256).max
+            "7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+            // a truncated PUSH4 at the end of the code
+            "6312"
+        ))
+        .unwrap();
+
+        let selectors = extract_selectors(&code);
+        assert_eq!(
+            selectors.functions,
+            BTreeSet::from([[0x70, 0xa0, 0x82, 0x31], [0xa9, 0x05, 0x9c, 0xbb]])
+        );
+        assert_eq!(
+            selectors.events,
+            BTreeSet::from(["0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"
+                .parse()
+                .unwrap()])
+        );
+    }
+}
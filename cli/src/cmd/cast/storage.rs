@@ -0,0 +1,367 @@
+//! cast storage subcommand
+
+use crate::{
+    cmd::Cmd,
+    compile,
+    opts::cast::{parse_block_id, parse_name_or_address, parse_slot},
+    utils::{consume_config_rpc_url, resolve_name_or_address},
+};
+use clap::Parser;
+use comfy_table::Table;
+use ethers::{
+    abi::ethabi::ethereum_types::BigEndianHash,
+    etherscan::Client,
+    prelude::*,
+    solc::{
+        artifacts::{
+            output_selection::ContractOutputSelection, Optimizer, Settings, Source, StorageLayout,
+        },
+        CompilerInput, EvmVersion, Solc,
+    },
+    utils::keccak256,
+};
+use eyre::{Result, WrapErr};
+use foundry_common::{get_http_provider, RetryProvider};
+use foundry_config::{find_project_root_path, Config};
+use futures::future::BoxFuture;
+use semver::Version;
+use std::{collections::BTreeMap, str::FromStr};
+
+#[derive(Debug, Clone, Parser)]
+pub struct StorageArgs {
+    #[clap(help = "The contract address.", parse(try_from_str = parse_name_or_address), value_name = "ADDRESS")]
+    address: NameOrAddress,
+    #[clap(
+        help = "The storage slot number (hex or decimal)",
+        long_help = "The storage slot number (hex or decimal).\n\nIf no slot is given, all state variables of the contract are read and decoded using its storage layout, which is taken from the artifacts of the current project or compiled from the source code verified on etherscan.",
+        parse(try_from_str = parse_slot),
+        value_name = "SLOT"
+    )]
+    slot: Option<H256>,
+    #[clap(short, long, env = "ETH_RPC_URL", value_name = "URL")]
+    rpc_url: Option<String>,
+    #[clap(
+        long,
+        short = 'B',
+        help = "The block height you want to query at.",
+        long_help = "The block height you want to query at. Can also be the tags earliest, latest, or pending.",
+        parse(try_from_str = parse_block_id),
+        value_name = "BLOCK"
+    )]
+    block: Option<BlockId>,
+    #[clap(
+        long,
+        short = 'e',
+        help = "The etherscan API key, used to fetch the storage layout of verified contracts.",
+        env = "ETHERSCAN_API_KEY",
+        value_name = "KEY"
+    )]
+    etherscan_api_key: Option<String>,
+}
+
+impl Cmd for StorageArgs {
+    type Output = BoxFuture<'static, Result<()>>;
+
+    fn run(self) -> Result<Self::Output> {
+        Ok(Box::pin(self.query_storage()))
+    }
+}
+
+impl StorageArgs {
+    async fn query_storage(self) -> Result<()> {
+        let StorageArgs { address, slot, rpc_url, block, etherscan_api_key } = self;
+        let rpc_url = consume_config_rpc_url(rpc_url);
+        let provider = get_http_provider(rpc_url);
+        let address = resolve_name_or_address(&provider, address).await?;
+
+        if let Some(slot) = slot {
+            let value = provider.get_storage_at(address, slot, block).await?;
+            println!("{:?}", value);
+            return Ok(())
+        }
+
+        let layout = match local_storage_layout(&provider, address, block).await? {
+            Some(layout) => layout,
+            None => {
+                let api_key = etherscan_api_key.or_else(|| Config::load().etherscan_api_key).ok_or_else(|| {
+                    eyre::eyre!("Could not find the contract in the current project. Pass an etherscan API key via `--etherscan-api-key` to fetch its storage layout")
+                })?;
+                let chain = ethers::types::Chain::try_from(provider.get_chainid().await?.as_u64())?;
+                let client = Client::new(chain, api_key)?;
+                etherscan_storage_layout(&client, address).await?
+            }
+        };
+
+        let mut reader = StorageReader::new(&provider, address, block);
+        let mut table = Table::new();
+        table.set_header(vec!["Name", "Type", "Slot", "Offset", "Value"]);
+        for variable in &layout.storage {
+            let ty = layout.types.get(&variable.storage_type);
+            let value = reader.read_variable(&layout, variable).await?;
+            table.add_row(vec![
+                variable.label.clone(),
+                ty.map_or_else(|| "?".to_string(), |ty| ty.label.clone()),
+                variable.slot.clone(),
+                variable.offset.to_string(),
+                value,
+            ]);
+        }
+        println!("{table}");
+
+        Ok(())
+    }
+}
+
+/// Returns the storage layout of the contract of the current project whose runtime code is
+/// deployed at `address`, if any
+async fn local_storage_layout(
+    provider: &RetryProvider,
+    address: Address,
+    block: Option<BlockId>,
+) -> Result<Option<StorageLayout>> {
+    let root = find_project_root_path()?;
+    if !root.join(Config::FILE_NAME).exists() {
+        return Ok(None)
+    }
+
+    let code = provider.get_code(address, block).await?;
+    if code.is_empty() {
+        eyre::bail!("No contract deployed at {:?}", address)
+    }
+
+    let mut config = Config::load_with_root(&root);
+    if !config.extra_output.contains(&ContractOutputSelection::StorageLayout) {
+        config.extra_output.push(ContractOutputSelection::StorageLayout);
+    }
+    let output = compile::suppress_compile(&config.project()?)?;
+
+    Ok(output.into_artifacts().find_map(|(_, artifact)| {
+        let deployed = artifact.deployed_bytecode?.bytecode?.object.into_bytes()?;
+        (deployed == code).then_some(artifact.storage_layout).flatten()
+    }))
+}
+
+/// Compiles the source code verified on etherscan for the contract at `address` and returns its
+/// storage layout
+pub(crate) async fn etherscan_storage_layout(
+    client: &Client,
+    address: Address,
+) -> Result<StorageLayout> {
+    let metadata = client.contract_source_code(address).await?;
+    let item = metadata.items.first().ok_or_else(|| eyre::eyre!("Unverified contract"))?;
+    if item.abi == "Contract source code not verified" {
+        eyre::bail!("Contract source code at {:?} is not verified", address)
+    }
+
+    // strip the commit and build metadata, e.g. `v0.8.10+commit.fc410830`
+    let version = Version::parse(item.compiler_version.trim_start_matches('v'))
+        .wrap_err_with(|| format!("Invalid compiler version `{}`", item.compiler_version))?;
+    let version = Version::new(version.major, version.minor, version.patch);
+    let solc = if let Some(solc) = Solc::find_svm_installed_version(version.to_string())? {
+        solc
+    } else {
+        Solc::blocking_install(&version)?
+    };
+
+    let sources = metadata
+        .source_tree()?
+        .entries
+        .into_iter()
+        .map(|entry| (entry.path, Source { content: entry.contents }))
+        .collect();
+    let settings = Settings {
+        optimizer: Optimizer {
+            enabled: Some(item.optimization_used == "1"),
+            runs: item.runs.parse().ok(),
+            details: None,
+        },
+        evm_version: EvmVersion::from_str(&item.evm_version.to_lowercase()).ok(),
+        ..Default::default()
+    }
+    .with_extra_output([ContractOutputSelection::StorageLayout]);
+    let input = CompilerInput { language: "Solidity".to_string(), sources, settings };
+
+    let output = solc.compile(&input)?;
+    if output.has_error() {
+        eyre::bail!("Failed to compile the verified source code of {:?}", address)
+    }
+
+    output
+        .contracts
+        .into_values()
+        .flat_map(|contracts| contracts.into_iter())
+        .find(|(name, _)| *name == item.contract_name)
+        .map(|(_, contract)| contract.storage_layout)
+        .ok_or_else(|| {
+            eyre::eyre!("Could not find contract {} in the compiled source", item.contract_name)
+        })
+}
+
+/// Reads and decodes state variables of a contract, caching the slots that were read
+struct StorageReader<'a> {
+    provider: &'a RetryProvider,
+    address: Address,
+    block: Option<BlockId>,
+    slots: BTreeMap<U256, H256>,
+}
+
+impl<'a> StorageReader<'a> {
+    fn new(provider: &'a RetryProvider, address: Address, block: Option<BlockId>) -> Self {
+        Self { provider, address, block, slots: Default::default() }
+    }
+
+    async fn read_slot(&mut self, slot: U256) -> Result<H256> {
+        if let Some(value) = self.slots.get(&slot) {
+            return Ok(*value)
+        }
+        let location = H256::from_uint(&slot);
+        let value = self.provider.get_storage_at(self.address, location, self.block).await?;
+        self.slots.insert(slot, value);
+        Ok(value)
+    }
+
+    /// Reads the value of the state variable and formats it according to its type
+    async fn read_variable(
+        &mut self,
+        layout: &StorageLayout,
+        variable: &ethers::solc::artifacts::Storage,
+    ) -> Result<String> {
+        let ty = match layout.types.get(&variable.storage_type) {
+            Some(ty) => ty,
+            None => return Ok("?".to_string()),
+        };
+        let slot = U256::from_dec_str(&variable.slot)?;
+        let size: usize = ty.number_of_bytes.parse()?;
+
+        match ty.encoding.as_str() {
+            "mapping" | "dynamic_array" => Ok("requires key".to_string()),
+            "bytes" => self.read_bytes(slot, &ty.label).await,
+            _ if size > 32 => Ok(format!("{size} bytes starting at slot {slot}")),
+            _ => {
+                let word = self.read_slot(slot).await?;
+                let offset = variable.offset as usize;
+                let value = &word.as_bytes()[32 - offset - size..32 - offset];
+                Ok(format_value(&ty.label, value))
+            }
+        }
+    }
+
+    /// Reads a `string` or `bytes` value
+    async fn read_bytes(&mut self, slot: U256, label: &str) -> Result<String> {
+        let word = self.read_slot(slot).await?;
+        let word = U256::from_big_endian(word.as_bytes());
+
+        let data = match BytesSlot::new(word) {
+            BytesSlot::Inline(data) => data,
+            BytesSlot::Long(len) => {
+                let start = U256::from_big_endian(&keccak256(H256::from_uint(&slot)));
+                let mut data = Vec::with_capacity(len);
+                for i in 0..(len + 31) / 32 {
+                    data.extend_from_slice(self.read_slot(start + i).await?.as_bytes());
+                }
+                data.truncate(len);
+                data
+            }
+        };
+        Ok(format_bytes(label, &data))
+    }
+}
+
+/// The content of the slot of a `string` or `bytes` value
+///
+/// Values shorter than 32 bytes are stored in the slot itself together with their length,
+/// longer values are stored starting at `keccak256(slot)`.
+#[derive(Debug, PartialEq, Eq)]
+enum BytesSlot {
+    /// The value stored in the slot itself
+    Inline(Vec<u8>),
+    /// The length of a value stored starting at `keccak256(slot)`
+    Long(usize),
+}
+
+impl BytesSlot {
+    fn new(word: U256) -> Self {
+        if word.bit(0) {
+            BytesSlot::Long(((word - 1) / 2).as_usize())
+        } else {
+            let len = (word.low_u64() & 0xff) as usize / 2;
+            let mut bytes = [0u8; 32];
+            word.to_big_endian(&mut bytes);
+            BytesSlot::Inline(bytes[..len].to_vec())
+        }
+    }
+}
+
+/// Formats a `string` or `bytes` value
+fn format_bytes(label: &str, data: &[u8]) -> String {
+    if label == "string" {
+        format!("{:?}", String::from_utf8_lossy(data))
+    } else {
+        format!("0x{}", hex::encode(data))
+    }
+}
+
+/// Formats the big endian `value` of a value type
+fn format_value(label: &str, value: &[u8]) -> String {
+    if label == "bool" {
+        return (value.iter().any(|byte| *byte != 0)).to_string()
+    }
+    if label == "address" || label.starts_with("contract ") {
+        return format!("{:?}", Address::from_slice(&value[value.len() - 20..]))
+    }
+    if label.starts_with("uint") || label.starts_with("enum ") {
+        return U256::from_big_endian(value).to_string()
+    }
+    if label.starts_with("int") {
+        // sign extend to 32 bytes
+        let fill = if value[0] & 0x80 != 0 { 0xff } else { 0 };
+        let mut word = [fill; 32];
+        word[32 - value.len()..].copy_from_slice(value);
+        return I256::from_raw(U256::from_big_endian(&word)).to_string()
+    }
+    format!("0x{}", hex::encode(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_value_types() {
+        let mut word = [0u8; 32];
+        word[31] = 1;
+        assert_eq!(format_value("bool", &word[31..]), "true");
+        assert_eq!(format_value("uint8", &word[31..]), "1");
+        assert_eq!(format_value("enum Status", &word[31..]), "1");
+        assert_eq!(format_value("bytes1", &word[31..]), "0x01");
+
+        word[12..].copy_from_slice(&[0x11; 20]);
+        assert_eq!(
+            format_value("address", &word[12..]),
+            "0x1111111111111111111111111111111111111111"
+        );
+        assert_eq!(
+            format_value("contract IERC20", &word),
+            "0x1111111111111111111111111111111111111111"
+        );
+
+        assert_eq!(format_value("int16", &[0xff, 0xfe]), "-2");
+        assert_eq!(format_value("int16", &[0x00, 0x02]), "2");
+    }
+
+    #[test]
+    fn decodes_bytes_slots() {
+        // "hello" is stored in the slot, followed by twice its length
+        let mut word = [0u8; 32];
+        word[..5].copy_from_slice(b"hello");
+        word[31] = 10;
+        let slot = BytesSlot::new(U256::from_big_endian(&word));
+        assert_eq!(slot, BytesSlot::Inline(b"hello".to_vec()));
+        assert_eq!(format_bytes("string", b"hello"), "\"hello\"");
+        assert_eq!(format_bytes("bytes", b"hello"), "0x68656c6c6f");
+
+        // a 40 byte value only stores twice its length plus one
+        assert_eq!(BytesSlot::new(U256::from(81)), BytesSlot::Long(40));
+        assert_eq!(BytesSlot::new(U256::zero()), BytesSlot::Inline(vec![]));
+    }
+}
@@ -0,0 +1,121 @@
+//! cast storage-layout subcommand
+
+use crate::{
+    cmd::{
+        forge::build::{self, CoreBuildArgs},
+        Cmd,
+    },
+    compile,
+    opts::forge::CompilerArgs,
+};
+use clap::Parser;
+use comfy_table::Table;
+use ethers::solc::{
+    artifacts::{output_selection::ContractOutputSelection, StorageLayout},
+    info::ContractInfo,
+    utils::canonicalize,
+};
+use eyre::Result;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Parser)]
+pub struct StorageLayoutArgs {
+    #[clap(
+        help = "The identifier of the contract in the form `(<path>:)?<contractname>`.",
+        value_name = "CONTRACT"
+    )]
+    contract: ContractInfo,
+
+    #[clap(
+        long,
+        help = "Print the storage layout as JSON.",
+        long_help = "Print the storage layout as JSON.\n\nEvery state variable is an object with the fields `label`, `slot`, `offset`, `type`, `bytes` and `contract`, in the order of their slots. Compiler specific type identifiers are omitted, so the output of two versions of a contract can be diffed."
+    )]
+    to_json: bool,
+
+    #[clap(flatten)]
+    build: build::CoreBuildArgs,
+}
+
+impl Cmd for StorageLayoutArgs {
+    type Output = ();
+
+    fn run(self) -> Result<Self::Output> {
+        let StorageLayoutArgs { mut contract, to_json, build } = self;
+
+        let mut extra_output = build.compiler.extra_output;
+        if !extra_output.contains(&ContractOutputSelection::StorageLayout) {
+            extra_output.push(ContractOutputSelection::StorageLayout);
+        }
+        let build =
+            CoreBuildArgs { compiler: CompilerArgs { extra_output, ..build.compiler }, ..build };
+
+        let project = build.project()?;
+        let output = if let Some(ref mut contract_path) = contract.path {
+            let target_path = canonicalize(&*contract_path)?;
+            *contract_path = target_path.to_string_lossy().to_string();
+            compile::compile_files(&project, vec![target_path], true)
+        } else {
+            compile::suppress_compile(&project)
+        }?;
+
+        let artifact = output.find_contract(&contract).ok_or_else(|| {
+            eyre::eyre!("Could not find artifact `{contract}` in the compiled artifacts")
+        })?;
+        let layout = artifact.storage_layout.as_ref().ok_or_else(|| {
+            eyre::eyre!("The compiler did not output a storage layout for `{contract}`")
+        })?;
+
+        let variables = storage_variables(layout);
+        if to_json {
+            println!("{}", serde_json::to_string_pretty(&variables)?);
+        } else {
+            let mut table = Table::new();
+            table.set_header(vec!["Name", "Type", "Slot", "Offset", "Bytes", "Contract"]);
+            for variable in variables {
+                table.add_row(vec![
+                    variable.label,
+                    variable.ty,
+                    variable.slot,
+                    variable.offset.to_string(),
+                    variable.bytes,
+                    variable.contract,
+                ]);
+            }
+            println!("{table}");
+        }
+
+        Ok(())
+    }
+}
+
+/// A state variable of a [`StorageLayout`], with its type resolved
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StorageVariable {
+    pub label: String,
+    pub slot: String,
+    pub offset: i64,
+    #[serde(rename = "type")]
+    pub ty: String,
+    pub bytes: String,
+    pub contract: String,
+}
+
+/// Returns the state variables of the `layout` in the order of their slots
+pub fn storage_variables(layout: &StorageLayout) -> Vec<StorageVariable> {
+    layout
+        .storage
+        .iter()
+        .map(|storage| {
+            let ty = layout.types.get(&storage.storage_type);
+            StorageVariable {
+                label: storage.label.clone(),
+                slot: storage.slot.clone(),
+                offset: storage.offset,
+                ty: ty.map_or_else(|| "?".to_string(), |ty| ty.label.clone()),
+                bytes: ty.map_or_else(|| "?".to_string(), |ty| ty.number_of_bytes.clone()),
+                contract: storage.contract.clone(),
+            }
+        })
+        .collect()
+}
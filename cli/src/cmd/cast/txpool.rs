@@ -0,0 +1,173 @@
+//! cast tx-pool subcommand
+
+use crate::utils::consume_config_rpc_url;
+use clap::Parser;
+use ethers::{
+    providers::{Middleware, ProviderError},
+    types::{Address, Transaction, TxpoolContent},
+};
+use eyre::Result;
+use foundry_common::get_http_provider;
+use std::{collections::BTreeMap, fmt::Write};
+
+#[derive(Debug, Parser)]
+pub enum TxPoolSubcommands {
+    #[clap(
+        name = "status",
+        visible_alias = "s",
+        about = "Get the number of pending and queued transactions of the node's transaction pool."
+    )]
+    Status {
+        #[clap(short, long, env = "ETH_RPC_URL", value_name = "URL")]
+        rpc_url: Option<String>,
+        #[clap(long = "json", short = 'j', help_heading = "DISPLAY OPTIONS")]
+        to_json: bool,
+    },
+    #[clap(
+        name = "content",
+        visible_alias = "c",
+        about = "Get the pending and queued transactions of the node's transaction pool."
+    )]
+    Content {
+        #[clap(
+            long,
+            help = "Only show the transactions sent from or to this address.",
+            value_name = "ADDRESS"
+        )]
+        address: Option<Address>,
+        #[clap(short, long, env = "ETH_RPC_URL", value_name = "URL")]
+        rpc_url: Option<String>,
+        #[clap(long = "json", short = 'j', help_heading = "DISPLAY OPTIONS")]
+        to_json: bool,
+    },
+}
+
+impl TxPoolSubcommands {
+    pub async fn run(self) -> Result<()> {
+        match self {
+            TxPoolSubcommands::Status { rpc_url, to_json } => {
+                let provider = get_http_provider(consume_config_rpc_url(rpc_url));
+                let status = provider.txpool_status().await.map_err(txpool_error)?;
+                if to_json {
+                    println!("{}", serde_json::to_string(&status)?);
+                } else {
+                    println!("pending: {}", status.pending);
+                    println!("queued: {}", status.queued);
+                }
+            }
+            TxPoolSubcommands::Content { address, rpc_url, to_json } => {
+                let provider = get_http_provider(consume_config_rpc_url(rpc_url));
+                let mut content = provider.txpool_content().await.map_err(txpool_error)?;
+                if let Some(address) = address {
+                    content = filter_content(content, address);
+                }
+                if to_json {
+                    println!("{}", serde_json::to_string(&content)?);
+                } else {
+                    print!("{}", format_content(&content));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Turns the error of a request for a method the node doesn't expose into a readable one
+fn txpool_error(err: ProviderError) -> eyre::Report {
+    let msg = err.to_string();
+    let lower = msg.to_lowercase();
+    if lower.contains("-32601") ||
+        lower.contains("method not found") ||
+        lower.contains("does not exist") ||
+        lower.contains("not available") ||
+        lower.contains("not supported")
+    {
+        eyre::eyre!("The node does not expose the txpool namespace: {msg}")
+    } else {
+        err.into()
+    }
+}
+
+/// Only keeps the transactions sent from or to `address`
+fn filter_content(content: TxpoolContent, address: Address) -> TxpoolContent {
+    let filter = |pool: BTreeMap<Address, BTreeMap<String, Transaction>>| {
+        pool.into_iter()
+            .filter_map(|(sender, txs)| {
+                let txs = txs
+                    .into_iter()
+                    .filter(|(_, tx)| sender == address || tx.to == Some(address))
+                    .collect::<BTreeMap<_, _>>();
+                (!txs.is_empty()).then_some((sender, txs))
+            })
+            .collect()
+    };
+    TxpoolContent { pending: filter(content.pending), queued: filter(content.queued) }
+}
+
+/// Formats the pending and queued transactions, grouped by sender and ordered by nonce
+fn format_content(content: &TxpoolContent) -> String {
+    let mut out = String::new();
+    for (name, pool) in [("pending", &content.pending), ("queued", &content.queued)] {
+        let count = pool.values().map(BTreeMap::len).sum::<usize>();
+        writeln!(out, "{name}: {count} transactions from {} senders", pool.len()).unwrap();
+        for (sender, txs) in pool {
+            writeln!(out, "  {sender:?}").unwrap();
+            let mut txs = txs.values().collect::<Vec<_>>();
+            txs.sort_by_key(|tx| tx.nonce);
+            for tx in txs {
+                let to = tx.to.map(|to| format!("{to:?}")).unwrap_or_else(|| "create".to_string());
+                let gas_price = tx.max_fee_per_gas.or(tx.gas_price).unwrap_or_default();
+                writeln!(
+                    out,
+                    "    nonce {} {:?} to {} value {} gas price {}",
+                    tx.nonce, tx.hash, to, tx.value, gas_price
+                )
+                .unwrap();
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::U256;
+
+    fn tx(from: Address, to: Address, nonce: u64) -> (String, Transaction) {
+        let tx = Transaction {
+            from,
+            to: Some(to),
+            nonce: nonce.into(),
+            gas_price: Some(U256::from(10)),
+            ..Default::default()
+        };
+        (nonce.to_string(), tx)
+    }
+
+    #[test]
+    fn filters_and_formats_content() {
+        let alice = Address::from_low_u64_be(1);
+        let bob = Address::from_low_u64_be(2);
+        let carol = Address::from_low_u64_be(3);
+        let content = TxpoolContent {
+            pending: BTreeMap::from([
+                (alice, BTreeMap::from([tx(alice, carol, 9), tx(alice, carol, 10)])),
+                (bob, BTreeMap::from([tx(bob, alice, 0), tx(bob, carol, 1)])),
+            ]),
+            queued: BTreeMap::from([(carol, BTreeMap::from([tx(carol, bob, 5)]))]),
+        };
+
+        let filtered = filter_content(content, alice);
+        assert_eq!(filtered.pending[&alice].len(), 2);
+        assert_eq!(filtered.pending[&bob].len(), 1);
+        assert!(filtered.queued.is_empty());
+
+        let formatted = format_content(&filtered);
+        assert!(formatted.starts_with("pending: 3 transactions from 2 senders\n"));
+        assert!(formatted.ends_with("queued: 0 transactions from 0 senders\n"));
+        let nine = formatted.find("nonce 9 ").unwrap();
+        let ten = formatted.find("nonce 10 ").unwrap();
+        assert!(nine < ten);
+    }
+}
@@ -1,17 +1,29 @@
 //! cast wallet subcommand
 
-use crate::opts::{EthereumOpts, Wallet, WalletType};
+use crate::opts::{
+    wallet_from_mnemonic, EthereumOpts, Wallet, WalletType, DEFAULT_DERIVATION_PATH,
+};
 use cast::SimpleCast;
-use clap::Parser;
+use clap::{Parser, ValueHint};
 use ethers::{
     core::rand::thread_rng,
     signers::{LocalWallet, Signer},
-    types::{Address, Chain, Signature},
+    types::{transaction::eip712::TypedData, Address, Chain, Signature, H256},
     utils::get_contract_address,
 };
+use eyre::WrapErr;
+use foundry_common::fs;
 use rayon::prelude::*;
 use regex::RegexSet;
-use std::{str::FromStr, time::Instant};
+use std::{
+    path::PathBuf,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
 
 #[derive(Debug, Parser)]
 pub enum WalletSubcommands {
@@ -40,7 +52,14 @@ pub enum WalletSubcommands {
         )]
         unsafe_password: Option<String>,
     },
-    #[clap(name = "vanity", visible_alias = "va", about = "Generate a vanity address.")]
+    #[clap(
+        name = "vanity",
+        visible_alias = "va",
+        about = "Generate a vanity address.",
+        long_about = r#"Generate a vanity address.
+
+Searches for a private key whose address matches the given prefix and/or suffix on all available threads. The search can be stopped with Ctrl-C."#
+    )]
     Vanity {
         #[clap(
             long,
@@ -51,13 +70,65 @@ pub enum WalletSubcommands {
         starts_with: Option<String>,
         #[clap(long, help = "Suffix for the vanity address.", value_name = "HEX")]
         ends_with: Option<String>,
+        #[clap(
+            long,
+            help = "Match the patterns against the EIP-55 checksummed address, instead of ignoring the case."
+        )]
+        case_sensitive: bool,
+        #[clap(
+            long,
+            help = "Generate a vanity address for the contract created by the generated keypair, instead of the keypair itself."
+        )]
+        contract: bool,
         #[clap(
             long,
             help = "Generate a vanity contract address created by the generated keypair with the specified nonce.",
+            long_help = "Generate a vanity contract address created by the generated keypair with the specified nonce. Implies --contract, defaults to 0 if only --contract is set.",
             value_name = "NONCE"
         )]
         nonce: Option<u64>, /* 2^64-1 is max possible nonce per https://eips.ethereum.org/EIPS/eip-2681 */
     },
+    #[clap(
+        name = "derive",
+        visible_alias = "d",
+        about = "Derive a range of accounts from a mnemonic.",
+        long_about = r#"Derive a range of accounts from a mnemonic.
+
+Prints the index, derivation path and address of every account, using the same derivation as the mnemonic signers of the other commands."#
+    )]
+    Derive {
+        #[clap(long, help = "The BIP-39 mnemonic phrase.", value_name = "PHRASE")]
+        mnemonic: String,
+        #[clap(
+            long,
+            help = "The index of the first account.",
+            default_value = "0",
+            value_name = "INDEX"
+        )]
+        start: u32,
+        #[clap(
+            long,
+            help = "The number of accounts to derive.",
+            default_value = "10",
+            value_name = "COUNT"
+        )]
+        count: u32,
+        #[clap(
+            long,
+            help = "The derivation path without the account index.",
+            default_value = DEFAULT_DERIVATION_PATH,
+            value_name = "PATH"
+        )]
+        path: String,
+        #[clap(
+            long,
+            help = "The BIP-39 passphrase protecting the mnemonic.",
+            value_name = "PASSWORD"
+        )]
+        password: Option<String>,
+        #[clap(long, help = "Also print the private key of every account.")]
+        private_keys: bool,
+    },
     #[clap(name = "address", visible_aliases = &["a", "addr"], about = "Convert a private key to an address.")]
     Address {
         #[clap(
@@ -65,6 +136,12 @@ pub enum WalletSubcommands {
             value_name = "PRIVATE_KEY"
         )]
         private_key_override: Option<String>,
+        #[clap(
+            long,
+            help = "Also print the private key of the wallet, after confirming the prompt.",
+            long_help = "Also print the private key of the wallet, after confirming the prompt. Only available for wallets with a local key, e.g. a keystore."
+        )]
+        show_private_key: bool,
         #[clap(flatten)]
         wallet: Wallet,
     },
@@ -72,6 +149,38 @@ pub enum WalletSubcommands {
     Sign {
         #[clap(help = "message to sign", value_name = "MESSAGE")]
         message: String,
+        #[clap(
+            long,
+            help = "Sign the message as a 32-byte hex digest, without hashing it.",
+            long_help = "Sign the message as a 32-byte hex digest, without hashing it. The digest is signed as is, without the EIP-191 prefix. Only available for wallets with a local key."
+        )]
+        no_hash: bool,
+        #[clap(flatten)]
+        wallet: Wallet,
+    },
+    #[clap(
+        name = "sign-typed-data",
+        visible_alias = "st",
+        about = "Sign EIP-712 typed data.",
+        long_about = r#"Sign EIP-712 typed data.
+
+The typed data is a JSON object with the `types`, `primaryType`, `domain` and `message` fields, as used by `eth_signTypedData_v4`. It can be passed inline or read from a file via --data-path."#
+    )]
+    SignTypedData {
+        #[clap(
+            help = "The EIP-712 typed data as JSON.",
+            value_name = "JSON",
+            required_unless_present = "data_path",
+            conflicts_with = "data_path"
+        )]
+        data: Option<String>,
+        #[clap(
+            long,
+            help = "Read the EIP-712 typed data from a JSON file.",
+            value_hint = ValueHint::FilePath,
+            value_name = "PATH"
+        )]
+        data_path: Option<PathBuf>,
         #[clap(flatten)]
         wallet: Wallet,
     },
@@ -126,57 +235,101 @@ impl WalletSubcommands {
                     );
                 }
             }
-            WalletSubcommands::Vanity { starts_with, ends_with, nonce } => {
-                let mut regexs = vec![];
-                if let Some(prefix) = starts_with {
-                    let pad_width = prefix.len() + prefix.len() % 2;
-                    hex::decode(format!("{:0>width$}", prefix, width = pad_width))
-                        .expect("invalid prefix hex provided");
-                    regexs.push(format!(r"^{}", prefix));
-                }
-                if let Some(suffix) = ends_with {
-                    let pad_width = suffix.len() + suffix.len() % 2;
-                    hex::decode(format!("{:0>width$}", suffix, width = pad_width))
-                        .expect("invalid suffix hex provided");
-                    regexs.push(format!(r"{}$", suffix));
-                }
-
-                assert!(
-                    regexs.iter().map(|p| p.len() - 1).sum::<usize>() <= 40,
-                    "vanity patterns length exceeded. cannot be more than 40 characters",
-                );
+            WalletSubcommands::Vanity {
+                starts_with,
+                ends_with,
+                case_sensitive,
+                contract,
+                nonce,
+            } => {
+                let matcher = VanityMatcher::new(starts_with, ends_with, case_sensitive)?;
+                // looking for contract address created by wallet with CREATE + nonce
+                let nonce = if contract { Some(nonce.unwrap_or_default()) } else { nonce };
 
-                let regex = RegexSet::new(regexs)?;
-                let match_contract = nonce.is_some();
+                let stop = Arc::new(AtomicBool::new(false));
+                let attempts = Arc::new(AtomicU64::new(0));
 
                 println!("Starting to generate vanity address...");
                 let timer = Instant::now();
-                let wallet = std::iter::repeat_with(move || LocalWallet::new(&mut thread_rng()))
-                    .par_bridge()
-                    .find_any(|wallet| {
-                        let addr = if match_contract {
-                            // looking for contract address created by wallet with CREATE + nonce
-                            let contract_addr =
-                                get_contract_address(wallet.address(), nonce.unwrap());
-                            hex::encode(contract_addr.to_fixed_bytes())
-                        } else {
-                            // looking for wallet address
-                            hex::encode(wallet.address().to_fixed_bytes())
-                        };
-                        regex.matches(&addr).into_iter().count() == regex.patterns().len()
-                    })
-                    .expect("failed to generate vanity wallet");
+                let mut search = tokio::task::spawn_blocking({
+                    let (stop, attempts) = (stop.clone(), attempts.clone());
+                    move || find_vanity_wallet(&matcher, nonce, &stop, &attempts)
+                });
+
+                let mut report = tokio::time::interval(Duration::from_secs(5));
+                report.tick().await;
+                let wallet = loop {
+                    tokio::select! {
+                        wallet = &mut search => break wallet?,
+                        _ = tokio::signal::ctrl_c() => {
+                            // wait for all workers to stop
+                            stop.store(true, Ordering::Relaxed);
+                            search.await?;
+                            break None
+                        }
+                        _ = report.tick() => {
+                            eprintln!(
+                                "{} attempts ({:.0} attempts/s)",
+                                attempts.load(Ordering::Relaxed),
+                                attempts_per_sec(&attempts, &timer)
+                            );
+                        }
+                    }
+                };
+
+                let wallet = match wallet {
+                    Some(wallet) => wallet,
+                    None => {
+                        eprintln!(
+                            "Stopped after {} attempts ({:.0} attempts/s).",
+                            attempts.load(Ordering::Relaxed),
+                            attempts_per_sec(&attempts, &timer)
+                        );
+                        return Ok(())
+                    }
+                };
 
                 println!(
-                    "Successfully found vanity address in {} seconds.{}{}\nAddress: {}\nPrivate Key: 0x{}",
+                    "Successfully found vanity address in {} seconds ({} attempts, {:.0} attempts/s).",
                     timer.elapsed().as_secs(),
-                    if match_contract {"\nContract address: "} else {""},
-                    if match_contract {SimpleCast::checksum_address(&get_contract_address(wallet.address(), nonce.unwrap()))?} else {"".to_string()},
+                    attempts.load(Ordering::Relaxed),
+                    attempts_per_sec(&attempts, &timer)
+                );
+                if let Some(nonce) = nonce {
+                    println!(
+                        "Contract address: {}",
+                        SimpleCast::checksum_address(&get_contract_address(
+                            wallet.address(),
+                            nonce
+                        ))?
+                    );
+                }
+                println!(
+                    "Address: {}\nPrivate Key: 0x{}",
                     SimpleCast::checksum_address(&wallet.address())?,
                     hex::encode(wallet.signer().to_bytes()),
                 );
             }
-            WalletSubcommands::Address { wallet, private_key_override } => {
+            WalletSubcommands::Derive { mnemonic, start, count, path, password, private_keys } => {
+                let end = start
+                    .checked_add(count)
+                    .ok_or_else(|| eyre::eyre!("Account index out of range: {start} + {count}"))?;
+                for index in start..end {
+                    let wallet =
+                        wallet_from_mnemonic(&mnemonic, Some(&path), index, password.as_deref())?;
+                    let address = SimpleCast::checksum_address(&wallet.address())?;
+                    let path = format!("{}/{index}", path.trim_end_matches('/'));
+                    if private_keys {
+                        println!(
+                            "{index} {path} {address} 0x{}",
+                            hex::encode(wallet.signer().to_bytes())
+                        );
+                    } else {
+                        println!("{index} {path} {address}");
+                    }
+                }
+            }
+            WalletSubcommands::Address { wallet, private_key_override, show_private_key } => {
                 let wallet = EthereumOpts {
                     wallet: private_key_override
                         .map(|pk| Wallet { private_key: Some(pk), ..Default::default() })
@@ -190,13 +343,69 @@ impl WalletSubcommands {
                 .unwrap();
 
                 let addr = match wallet {
-                    WalletType::Ledger(signer) => signer.address(),
-                    WalletType::Local(signer) => signer.address(),
-                    WalletType::Trezor(signer) => signer.address(),
+                    WalletType::Ledger(ref signer) => signer.address(),
+                    WalletType::Local(ref signer) => signer.address(),
+                    WalletType::Trezor(ref signer) => signer.address(),
                 };
-                println!("Address: {}", SimpleCast::checksum_address(&addr)?);
+                let addr = SimpleCast::checksum_address(&addr)?;
+                println!("Address: {addr}");
+
+                if show_private_key {
+                    let signer = match wallet {
+                        WalletType::Local(signer) => signer,
+                        _ => eyre::bail!("The private key of a hardware wallet cannot be revealed"),
+                    };
+                    eprint!("Reveal the private key of {addr}? [y/N] ");
+                    let mut input = String::new();
+                    std::io::stdin().read_line(&mut input)?;
+                    if !matches!(input.trim(), "y" | "Y" | "yes") {
+                        eyre::bail!("Aborted, the private key was not revealed")
+                    }
+                    println!("Private Key: 0x{}", hex::encode(signer.signer().signer().to_bytes()));
+                }
             }
-            WalletSubcommands::Sign { message, wallet } => {
+            WalletSubcommands::Sign { message, no_hash, wallet } => {
+                let digest = if no_hash { Some(parse_digest(&message)?) } else { None };
+
+                let wallet = EthereumOpts {
+                    wallet,
+                    rpc_url: Some("http://localhost:8545".to_string()),
+                    chain: Some(Chain::Mainnet.into()),
+                    ..Default::default()
+                }
+                .signer(0u64.into())
+                .await?
+                .unwrap();
+
+                let sig = match (wallet, digest) {
+                    (WalletType::Local(wallet), Some(digest)) => wallet.signer().sign_hash(digest),
+                    (_, Some(_)) => {
+                        eyre::bail!("Signing a digest without hashing requires a local wallet")
+                    }
+                    (WalletType::Ledger(wallet), None) => {
+                        wallet.signer().sign_message(&message).await?
+                    }
+                    (WalletType::Local(wallet), None) => {
+                        wallet.signer().sign_message(&message).await?
+                    }
+                    (WalletType::Trezor(wallet), None) => {
+                        wallet.signer().sign_message(&message).await?
+                    }
+                };
+                println!("Signature: 0x{sig}");
+            }
+            WalletSubcommands::SignTypedData { data, data_path, wallet } => {
+                let typed_data = match data_path {
+                    Some(path) => {
+                        let data = fs::read_to_string(&path)?;
+                        serde_json::from_str::<TypedData>(&data).wrap_err_with(|| {
+                            format!("Failed to parse EIP-712 typed data from `{}`", path.display())
+                        })?
+                    }
+                    None => serde_json::from_str::<TypedData>(&data.unwrap_or_default())
+                        .wrap_err("Failed to parse EIP-712 typed data")?,
+                };
+
                 let wallet = EthereumOpts {
                     wallet,
                     rpc_url: Some("http://localhost:8545".to_string()),
@@ -208,9 +417,15 @@ impl WalletSubcommands {
                 .unwrap();
 
                 let sig = match wallet {
-                    WalletType::Ledger(wallet) => wallet.signer().sign_message(&message).await?,
-                    WalletType::Local(wallet) => wallet.signer().sign_message(&message).await?,
-                    WalletType::Trezor(wallet) => wallet.signer().sign_message(&message).await?,
+                    WalletType::Ledger(wallet) => {
+                        wallet.signer().sign_typed_data(&typed_data).await?
+                    }
+                    WalletType::Local(wallet) => {
+                        wallet.signer().sign_typed_data(&typed_data).await?
+                    }
+                    WalletType::Trezor(wallet) => {
+                        wallet.signer().sign_typed_data(&typed_data).await?
+                    }
                 };
                 println!("Signature: 0x{sig}");
             }
@@ -232,3 +447,126 @@ impl WalletSubcommands {
         Ok(())
     }
 }
+
+/// Parses a 32-byte hex digest, as signed by `cast wallet sign --no-hash`
+fn parse_digest(digest: &str) -> eyre::Result<H256> {
+    let bytes = hex::decode(digest.strip_prefix("0x").unwrap_or(digest))
+        .wrap_err_with(|| format!("invalid digest hex provided: {digest}"))?;
+    if bytes.len() != 32 {
+        eyre::bail!("Expected a 32-byte digest, got {} bytes", bytes.len())
+    }
+    Ok(H256::from_slice(&bytes))
+}
+
+/// Matches addresses against the prefix and suffix of a vanity address
+#[derive(Debug)]
+struct VanityMatcher {
+    regex: RegexSet,
+    case_sensitive: bool,
+}
+
+impl VanityMatcher {
+    fn new(
+        starts_with: Option<String>,
+        ends_with: Option<String>,
+        case_sensitive: bool,
+    ) -> eyre::Result<Self> {
+        let mut regexs = vec![];
+        if let Some(prefix) = starts_with {
+            regexs.push(format!(r"^{}", Self::pattern(&prefix, case_sensitive, "prefix")?));
+        }
+        if let Some(suffix) = ends_with {
+            regexs.push(format!(r"{}$", Self::pattern(&suffix, case_sensitive, "suffix")?));
+        }
+
+        if regexs.iter().map(|p| p.len() - 1).sum::<usize>() > 40 {
+            eyre::bail!("vanity patterns length exceeded. cannot be more than 40 characters")
+        }
+
+        Ok(Self { regex: RegexSet::new(regexs)?, case_sensitive })
+    }
+
+    /// Validates the hex `pattern`, lowercasing it unless the match is case sensitive
+    fn pattern(pattern: &str, case_sensitive: bool, kind: &str) -> eyre::Result<String> {
+        let pattern = pattern.strip_prefix("0x").unwrap_or(pattern);
+        let pad_width = pattern.len() + pattern.len() % 2;
+        hex::decode(format!("{:0>width$}", pattern, width = pad_width))
+            .wrap_err_with(|| format!("invalid {kind} hex provided: {pattern}"))?;
+        Ok(if case_sensitive { pattern.to_string() } else { pattern.to_lowercase() })
+    }
+
+    fn is_match(&self, address: Address) -> bool {
+        let address = if self.case_sensitive {
+            // the checksummed address without the `0x` prefix
+            ethers::utils::to_checksum(&address, None)[2..].to_string()
+        } else {
+            hex::encode(address.to_fixed_bytes())
+        };
+        self.regex.matches(&address).into_iter().count() == self.regex.patterns().len()
+    }
+}
+
+/// Generates random wallets on all threads until one matches or `stop` is set.
+///
+/// If `nonce` is set, the address of the contract created by the wallet at that nonce is matched
+/// instead of the wallet's address.
+fn find_vanity_wallet(
+    matcher: &VanityMatcher,
+    nonce: Option<u64>,
+    stop: &AtomicBool,
+    attempts: &AtomicU64,
+) -> Option<LocalWallet> {
+    (0..rayon::current_num_threads()).into_par_iter().find_map_any(|_| {
+        let mut rng = thread_rng();
+        while !stop.load(Ordering::Relaxed) {
+            let wallet = LocalWallet::new(&mut rng);
+            attempts.fetch_add(1, Ordering::Relaxed);
+            let address = match nonce {
+                Some(nonce) => get_contract_address(wallet.address(), nonce),
+                None => wallet.address(),
+            };
+            if matcher.is_match(address) {
+                stop.store(true, Ordering::Relaxed);
+                return Some(wallet)
+            }
+        }
+        None
+    })
+}
+
+fn attempts_per_sec(attempts: &AtomicU64, timer: &Instant) -> f64 {
+    attempts.load(Ordering::Relaxed) as f64 / timer.elapsed().as_secs_f64().max(f64::EPSILON)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_vanity_patterns() {
+        let address: Address = "0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045".parse().unwrap();
+
+        let matcher = VanityMatcher::new(Some("D8DA".to_string()), None, false).unwrap();
+        assert!(matcher.is_match(address));
+
+        let matcher =
+            VanityMatcher::new(Some("d8dA".to_string()), Some("96045".to_string()), true).unwrap();
+        assert!(matcher.is_match(address));
+
+        let matcher = VanityMatcher::new(Some("D8DA".to_string()), None, true).unwrap();
+        assert!(!matcher.is_match(address));
+
+        assert!(VanityMatcher::new(Some("xyz".to_string()), None, false).is_err());
+    }
+
+    #[test]
+    fn parses_digest() {
+        let digest = format!("0x{}", "ab".repeat(32));
+        assert_eq!(parse_digest(&digest).unwrap(), H256::repeat_byte(0xab));
+        assert_eq!(parse_digest(&"ab".repeat(32)).unwrap(), H256::repeat_byte(0xab));
+
+        assert!(parse_digest("0xabcd").is_err());
+        assert!(parse_digest(&"ab".repeat(33)).is_err());
+        assert!(parse_digest("0xzz").is_err());
+    }
+}
@@ -8,7 +8,7 @@ use crate::{
     utils::{self, p_println},
 };
 use cast::trace::identifier::TraceIdentifier;
-use clap::{AppSettings, ArgEnum, Parser};
+use clap::{AppSettings, ArgEnum, Parser, ValueHint};
 use ethers::{
     abi::Address,
     prelude::{
@@ -33,7 +33,7 @@ use forge::{
 use foundry_common::{evm::EvmArgs, fs};
 use foundry_config::Config;
 use semver::Version;
-use std::{collections::HashMap, sync::mpsc::channel, thread};
+use std::{collections::HashMap, path::PathBuf, sync::mpsc::channel, thread};
 
 // Loads project's figment and merges the build cli arguments into it
 foundry_config::impl_figment_convert!(CoverageArgs, opts, evm_opts);
@@ -50,6 +50,15 @@ pub struct CoverageArgs {
     )]
     report: CoverageReportKind,
 
+    #[clap(
+        long,
+        help = "The path to write the LCOV report to.",
+        long_help = "The path to write the LCOV report to. Defaults to lcov.info in the project root.",
+        value_hint = ValueHint::FilePath,
+        value_name = "PATH"
+    )]
+    report_file: Option<PathBuf>,
+
     #[clap(flatten, next_help_heading = "TEST FILTERING")]
     filter: Filter,
 
@@ -71,6 +80,9 @@ impl Cmd for CoverageArgs {
     type Output = ();
 
     fn run(self) -> eyre::Result<Self::Output> {
+        if self.report_file.is_some() && !matches!(self.report, CoverageReportKind::Lcov) {
+            eyre::bail!("--report-file can only be used with --report lcov")
+        }
         let (config, evm_opts) = self.configure()?;
         let (project, output) = self.build(&config)?;
         p_println!(!self.opts.silent => "Analysing contracts...");
@@ -273,6 +285,8 @@ impl CoverageArgs {
                 fuzz_runs: config.fuzz_runs,
                 fuzz_max_local_rejects: config.fuzz_max_local_rejects,
                 fuzz_max_global_rejects: config.fuzz_max_global_rejects,
+                fuzz_dictionary: config.fuzz_dictionary,
+                fuzz_dictionary_weight: config.fuzz_dictionary_weight,
                 ..Default::default()
             })
             .set_coverage(true)
@@ -324,9 +338,13 @@ impl CoverageArgs {
         // Output final report
         match self.report {
             CoverageReportKind::Summary => SummaryReporter::default().report(report),
-            // TODO: Sensible place to put the LCOV file
             CoverageReportKind::Lcov => {
-                LcovReporter::new(&mut fs::create_file(root.join("lcov.info"))?).report(report)
+                let path = self.report_file.unwrap_or_else(|| root.join("lcov.info"));
+                if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty())
+                {
+                    fs::create_dir_all(parent)?;
+                }
+                LcovReporter::new(&mut fs::create_file(path)?).report(report)
             }
             CoverageReportKind::Debug => DebugReporter::default().report(report),
         }
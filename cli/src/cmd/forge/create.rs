@@ -10,14 +10,11 @@ use clap::{Parser, ValueHint};
 use ethers::{
     abi::{Abi, Constructor, Token},
     prelude::{artifacts::BytecodeObject, ContractFactory, Middleware},
-    solc::{
-        info::ContractInfo,
-        utils::{canonicalized, read_json_file},
-    },
+    solc::{info::ContractInfo, utils::canonicalized},
     types::{transaction::eip2718::TypedTransaction, Chain},
 };
 use eyre::Context;
-use foundry_common::{fs, get_http_provider};
+use foundry_common::get_http_provider;
 use foundry_utils::parse_tokens;
 use rustc_hex::ToHex;
 use serde_json::json;
@@ -82,10 +79,19 @@ pub struct CreateArgs {
 
     #[clap(
         long = "verification-provider",
-        help = "Contract verification provider to use `sourcify` or `etherscan`",
+        help = "Contract verification provider to use `etherscan`, `sourcify` or `blockscout`",
         default_value = "etherscan"
     )]
     verification_provider: verify::VerificationProviderType,
+
+    #[clap(
+        long,
+        help = "The verifier API url, e.g. of a custom etherscan compatible explorer or the API base of a Blockscout instance.",
+        env = "VERIFIER_URL",
+        value_name = "VERIFIER_URL",
+        required_if_eq("verification_provider", "blockscout")
+    )]
+    verifier_url: Option<String>,
 }
 
 impl CreateArgs {
@@ -131,25 +137,7 @@ impl CreateArgs {
             Some(ref v) => {
                 let constructor_args =
                     if let Some(ref constructor_args_path) = self.constructor_args_path {
-                        if !constructor_args_path.exists() {
-                            eyre::bail!(
-                                "Constructor args file \"{}\" not found",
-                                constructor_args_path.display()
-                            );
-                        }
-                        if constructor_args_path.extension() == Some(std::ffi::OsStr::new("json")) {
-                            match read_json_file(constructor_args_path) {
-                                Ok(args) => args,
-                                Err(err) => eyre::bail!(
-                                    "Constructor args file \"{}\" must encode a json array: \"{}\"",
-                                    constructor_args_path.display(),
-                                    err
-                                ),
-                            }
-                        } else {
-                            let file = fs::read_to_string(constructor_args_path)?;
-                            file.split_whitespace().map(str::to_string).collect::<Vec<String>>()
-                        }
+                        utils::read_constructor_args_file(constructor_args_path)?
                     } else {
                         self.constructor_args.clone()
                     };
@@ -289,16 +277,23 @@ impl CreateArgs {
             contract: self.contract,
             compiler_version: None,
             constructor_args,
+            constructor_args_path: None,
+            guess_constructor_args: false,
+            from_broadcast: None,
             num_of_optimizations,
             chain: chain.into(),
             etherscan_key: self.eth.etherscan_api_key,
             flatten: false,
             force: false,
             watch: true,
+            json: false,
+            proxy: false,
+            rpc_url: None,
             retry: RETRY_VERIFY_ON_CREATE,
             libraries: vec![],
             root: None,
             verifier: self.verification_provider,
+            verifier_url: self.verifier_url,
         };
         println!("Waiting for etherscan to detect contract deployment...");
         verify.run().await
@@ -0,0 +1,199 @@
+//! Gas diff command
+use crate::cmd::Cmd;
+use clap::{Parser, ValueHint};
+use forge::gas_report::{JsonFunctionGas, JsonGasReport};
+use foundry_common::fs;
+use std::{cmp::Ordering, path::PathBuf};
+use yansi::Paint;
+
+/// Compare two JSON gas reports written by `forge test --gas-report-json`.
+#[derive(Debug, Clone, Parser)]
+pub struct GasDiffArgs {
+    #[clap(help = "The gas report to compare against.", value_hint = ValueHint::FilePath, value_name = "BASELINE")]
+    baseline: PathBuf,
+
+    #[clap(help = "The new gas report.", value_hint = ValueHint::FilePath, value_name = "CURRENT")]
+    current: PathBuf,
+
+    /// The maximum increase of a function's average gas, in percent.
+    ///
+    /// If any function's average gas increased by more than this, the command exits with a
+    /// non-zero code.
+    #[clap(long, default_value = "0", value_name = "PERCENT")]
+    tolerance: f64,
+}
+
+impl Cmd for GasDiffArgs {
+    type Output = ();
+
+    fn run(self) -> eyre::Result<Self::Output> {
+        let baseline: JsonGasReport = serde_json::from_str(&fs::read_to_string(&self.baseline)?)?;
+        let current: JsonGasReport = serde_json::from_str(&fs::read_to_string(&self.current)?)?;
+
+        let diff = GasDiff::new(&baseline, &current);
+        diff.print(self.tolerance);
+
+        let regressions = diff.regressions(self.tolerance).count();
+        if regressions > 0 {
+            eyre::bail!(
+                "{regressions} function(s) increased their average gas by more than {}%",
+                self.tolerance
+            )
+        }
+        Ok(())
+    }
+}
+
+/// The change in gas of a single function that is in both reports.
+#[derive(Debug, Clone, PartialEq)]
+struct FunctionGasChange {
+    contract: String,
+    function: String,
+    baseline: JsonFunctionGas,
+    current: JsonFunctionGas,
+}
+
+impl FunctionGasChange {
+    /// Returns the change of the average gas
+    fn gas_change(&self) -> i128 {
+        self.current.avg as i128 - self.baseline.avg as i128
+    }
+
+    /// Returns the change of the average gas in percent
+    fn pct_change(&self) -> f64 {
+        if self.baseline.avg == 0 {
+            return if self.current.avg == 0 { 0. } else { f64::INFINITY }
+        }
+        self.gas_change() as f64 / self.baseline.avg as f64 * 100.
+    }
+}
+
+/// The differences between two gas reports.
+#[derive(Debug, Default)]
+struct GasDiff {
+    /// Functions that are in both reports and whose average gas changed
+    changed: Vec<FunctionGasChange>,
+    /// `contract:function` entries that are only in the current report
+    added: Vec<String>,
+    /// `contract:function` entries that are only in the baseline report
+    removed: Vec<String>,
+}
+
+impl GasDiff {
+    fn new(baseline: &JsonGasReport, current: &JsonGasReport) -> Self {
+        let mut diff = GasDiff::default();
+
+        for (contract, current_contract) in &current.contracts {
+            let baseline_contract = baseline.contracts.get(contract);
+            for (function, current_gas) in &current_contract.functions {
+                match baseline_contract.and_then(|c| c.functions.get(function)) {
+                    Some(baseline_gas) if baseline_gas.avg != current_gas.avg => {
+                        diff.changed.push(FunctionGasChange {
+                            contract: contract.clone(),
+                            function: function.clone(),
+                            baseline: baseline_gas.clone(),
+                            current: current_gas.clone(),
+                        })
+                    }
+                    Some(_) => {}
+                    None => diff.added.push(format!("{contract}:{function}")),
+                }
+            }
+        }
+
+        for (contract, baseline_contract) in &baseline.contracts {
+            let current_contract = current.contracts.get(contract);
+            for function in baseline_contract.functions.keys() {
+                if current_contract.and_then(|c| c.functions.get(function)).is_none() {
+                    diff.removed.push(format!("{contract}:{function}"));
+                }
+            }
+        }
+
+        diff.changed
+            .sort_by(|a, b| b.pct_change().partial_cmp(&a.pct_change()).unwrap_or(Ordering::Equal));
+        diff
+    }
+
+    /// Returns the changes that increased the average gas by more than `tolerance` percent
+    fn regressions(&self, tolerance: f64) -> impl Iterator<Item = &FunctionGasChange> {
+        self.changed.iter().filter(move |change| change.pct_change() > tolerance)
+    }
+
+    fn print(&self, tolerance: f64) {
+        if self.changed.is_empty() && self.added.is_empty() && self.removed.is_empty() {
+            println!("No gas changes.");
+            return
+        }
+
+        if !self.changed.is_empty() {
+            println!("Changed:");
+            for change in &self.changed {
+                let line = format!(
+                    "{}:{} (avg gas: {} -> {} ({:+}, {:+.3}%))",
+                    change.contract,
+                    change.function,
+                    change.baseline.avg,
+                    change.current.avg,
+                    change.gas_change(),
+                    change.pct_change()
+                );
+                match change.pct_change() {
+                    pct if pct > tolerance => println!("  {}", Paint::red(line)),
+                    pct if pct < 0. => println!("  {}", Paint::green(line)),
+                    _ => println!("  {line}"),
+                }
+            }
+        }
+        if !self.added.is_empty() {
+            println!("Added:");
+            self.added.iter().for_each(|function| println!("  {function}"));
+        }
+        if !self.removed.is_empty() {
+            println!("Removed:");
+            self.removed.iter().for_each(|function| println!("  {function}"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use forge::gas_report::JsonContractGas;
+
+    fn report(functions: &[(&str, u64)]) -> JsonGasReport {
+        let functions = functions
+            .iter()
+            .map(|(name, avg)| {
+                let gas =
+                    JsonFunctionGas { calls: 1, min: *avg, avg: *avg, median: *avg, max: *avg };
+                (name.to_string(), gas)
+            })
+            .collect();
+        let contract = JsonContractGas {
+            deployment_cost: 0,
+            deployment_cost_min: 0,
+            deployment_size: 0,
+            functions,
+        };
+        JsonGasReport { contracts: [("src/Counter.sol:Counter".to_string(), contract)].into() }
+    }
+
+    #[test]
+    fn diffs_gas_reports() {
+        let baseline = report(&[("increment()", 1000), ("reset()", 500), ("old()", 100)]);
+        let current = report(&[("increment()", 1100), ("reset()", 400), ("new()", 100)]);
+
+        let diff = GasDiff::new(&baseline, &current);
+        assert_eq!(diff.added, vec!["src/Counter.sol:Counter:new()"]);
+        assert_eq!(diff.removed, vec!["src/Counter.sol:Counter:old()"]);
+        assert_eq!(
+            diff.changed.iter().map(|c| c.function.as_str()).collect::<Vec<_>>(),
+            vec!["increment()", "reset()"]
+        );
+        assert_eq!(diff.changed[0].pct_change(), 10.);
+
+        assert_eq!(diff.regressions(5.).count(), 1);
+        assert_eq!(diff.regressions(10.).count(), 0);
+    }
+}
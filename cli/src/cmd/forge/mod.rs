@@ -49,6 +49,7 @@ pub mod debug;
 pub mod flatten;
 pub mod fmt;
 pub mod fourbyte;
+pub mod gas_diff;
 pub mod init;
 pub mod inspect;
 pub mod install;
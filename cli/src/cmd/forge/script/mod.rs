@@ -169,10 +169,15 @@ impl ScriptArgs {
         )?;
 
         let local_identifier = LocalTraceIdentifier::new(known_contracts);
-        let mut decoder =
-            CallTraceDecoderBuilder::new().with_labels(result.labeled_addresses.clone()).build();
-
-        decoder.add_signature_identifier(SignaturesIdentifier::new(Config::foundry_cache_dir())?);
+        let mut decoder = CallTraceDecoderBuilder::new()
+            .with_labels(script_config.config.labels()?)
+            .with_labels(result.labeled_addresses.clone())
+            .build();
+
+        decoder.add_signature_identifier(SignaturesIdentifier::new(
+            Config::foundry_cache_dir(),
+            false,
+        )?);
 
         for (_, trace) in &mut result.traces {
             decoder.identify(trace, &local_identifier);
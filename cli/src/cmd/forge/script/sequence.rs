@@ -199,16 +199,23 @@ impl ScriptSequence {
                                 contract,
                                 compiler_version: Some(version.to_string()),
                                 constructor_args: Some(hex::encode(&constructor_args)),
+                                constructor_args_path: None,
+                                guess_constructor_args: false,
+                                from_broadcast: None,
                                 num_of_optimizations: verify.num_of_optimizations,
                                 chain: chain.into(),
                                 etherscan_key: Some(etherscan_key.clone()),
                                 flatten: false,
                                 force: false,
                                 watch: true,
+                                json: false,
+                                proxy: false,
+                                rpc_url: None,
                                 retry: verify.retry.clone(),
                                 libraries: self.libraries.clone(),
                                 root: None,
                                 verifier: VerificationProviderType::Etherscan,
+                                verifier_url: None,
                             };
 
                             future_verifications.push(verify.run());
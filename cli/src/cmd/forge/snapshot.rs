@@ -5,7 +5,7 @@ use crate::cmd::{
         test,
         test::{custom_run, Test, TestOutcome},
     },
-    Cmd,
+    Cmd, LoadConfig,
 };
 use clap::{Parser, ValueHint};
 use ethers::types::U256;
@@ -16,7 +16,7 @@ use regex::Regex;
 use std::{
     cmp::Ordering,
     collections::HashMap,
-    fmt::Write,
+    fmt::{self, Write},
     fs,
     io::{self, BufRead},
     path::{Path, PathBuf},
@@ -26,9 +26,9 @@ use watchexec::config::{InitConfig, RuntimeConfig};
 use yansi::Paint;
 
 /// A regex that matches a basic snapshot entry like
-/// `Test:testDeposit() (gas: 58804)`
+/// `Test:testDeposit() (gas: 58804)`, as well as the entries of fuzz and invariant tests
 pub static RE_BASIC_SNAPSHOT_ENTRY: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r"(?P<file>(.*?)):(?P<sig>(\w+)\s*\((.*?)\))\s*\(((gas:)?\s*(?P<gas>\d+)|(runs:\s*(?P<runs>\d+),\s*μ:\s*(?P<avg>\d+),\s*~:\s*(?P<med>\d+)))\)").unwrap()
+    Regex::new(r"(?P<file>(.*?)):(?P<sig>(\w+)\s*\((.*?)\))\s*\(((gas:)?\s*(?P<gas>\d+)|(runs:\s*(?P<runs>\d+),\s*μ:\s*(?P<avg>\d+),\s*~:\s*(?P<med>\d+))|(runs:\s*(?P<iruns>\d+),\s*calls:\s*(?P<calls>\d+),\s*reverts:\s*(?P<reverts>\d+)))\)").unwrap()
 });
 
 /// Deterministic fuzzer seed used for gas snapshots.
@@ -73,6 +73,12 @@ pub struct SnapshotArgs {
     )]
     check: Option<Option<PathBuf>>,
 
+    /// Tolerated gas change in percent before `--check` fails.
+    ///
+    /// By default, any change in gas fails the check.
+    #[clap(long, requires = "check", value_name = "PERCENT")]
+    tolerance: Option<f64>,
+
     // Hidden because there is only one option
     #[clap(help = "How to format the output.", long, hide(true))]
     format: Option<Format>,
@@ -111,6 +117,9 @@ impl Cmd for SnapshotArgs {
         // Set fuzz seed so gas snapshots are deterministic
         self.test.fuzz_seed = Some(U256::from_big_endian(&SNAPSHOT_FUZZ_SEED));
 
+        // a filtered run only covers a part of the snapshot
+        let filtered = !self.test.filter(&(&self.test).load_config()).is_empty();
+
         let outcome = custom_run(self.test)?;
         outcome.ensure_ok()?;
         let tests = self.config.apply(outcome);
@@ -122,13 +131,18 @@ impl Cmd for SnapshotArgs {
         } else if let Some(path) = self.check {
             let snap = path.as_ref().unwrap_or(&self.snap);
             let snaps = read_snapshot(snap)?;
-            if check(tests, snaps) {
+            let entries = tests.iter().map(SnapshotEntry::from).collect();
+            if check(entries, snaps, self.tolerance.unwrap_or_default(), filtered) {
                 std::process::exit(0)
             } else {
                 std::process::exit(1)
             }
         } else {
-            write_to_snapshot_file(&tests, self.snap, self.format)?;
+            let mut entries = tests.iter().map(SnapshotEntry::from).collect::<Vec<_>>();
+            if filtered && self.snap.exists() {
+                entries = merge_snapshot(read_snapshot(&self.snap)?, entries);
+            }
+            write_to_snapshot_file(&entries, self.snap, self.format)?;
         }
         Ok(())
     }
@@ -245,6 +259,24 @@ impl FromStr for SnapshotEntry {
                                         mean_gas: avg.as_str().parse().unwrap(),
                                     },
                                 })
+                                .or_else(|| {
+                                    cap.name("iruns")
+                                        .and_then(|runs| {
+                                            cap.name("calls").and_then(|calls| {
+                                                cap.name("reverts")
+                                                    .map(|reverts| (runs, calls, reverts))
+                                            })
+                                        })
+                                        .map(|(runs, calls, reverts)| SnapshotEntry {
+                                            contract_name: file.as_str().to_string(),
+                                            signature: sig.as_str().to_string(),
+                                            gas_used: TestKindReport::Invariant {
+                                                runs: runs.as_str().parse().unwrap(),
+                                                calls: calls.as_str().parse().unwrap(),
+                                                reverts: reverts.as_str().parse().unwrap(),
+                                            },
+                                        })
+                                })
                         }
                     })
                 })
@@ -253,6 +285,22 @@ impl FromStr for SnapshotEntry {
     }
 }
 
+impl From<&Test> for SnapshotEntry {
+    fn from(test: &Test) -> Self {
+        SnapshotEntry {
+            contract_name: test.contract_name().to_string(),
+            signature: test.signature.clone(),
+            gas_used: test.result.kind.report(),
+        }
+    }
+}
+
+impl fmt::Display for SnapshotEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{} {}", self.contract_name, self.signature, self.gas_used)
+    }
+}
+
 /// Reads a list of snapshot entries from a snapshot file
 fn read_snapshot(path: impl AsRef<Path>) -> eyre::Result<Vec<SnapshotEntry>> {
     let path = path.as_ref();
@@ -268,19 +316,40 @@ fn read_snapshot(path: impl AsRef<Path>) -> eyre::Result<Vec<SnapshotEntry>> {
     Ok(entries)
 }
 
-/// Writes a series of tests to a snapshot file
+/// Writes a series of snapshot entries to a snapshot file
 fn write_to_snapshot_file(
-    tests: &[Test],
+    entries: &[SnapshotEntry],
     path: impl AsRef<Path>,
     _format: Option<Format>,
 ) -> eyre::Result<()> {
     let mut out = String::new();
-    for test in tests {
-        writeln!(out, "{}:{} {}", test.contract_name(), test.signature, test.result.kind.report())?;
+    for entry in entries {
+        writeln!(out, "{entry}")?;
     }
     Ok(fs::write(path, out)?)
 }
 
+/// Merges the entries of a filtered run into an existing snapshot
+///
+/// The entries of tests that were run replace their existing entries, new tests are appended and
+/// the entries of tests that were not run are kept.
+fn merge_snapshot(snaps: Vec<SnapshotEntry>, mut tests: Vec<SnapshotEntry>) -> Vec<SnapshotEntry> {
+    let mut merged = snaps
+        .into_iter()
+        .map(|snap| {
+            tests
+                .iter()
+                .position(|test| {
+                    test.contract_name == snap.contract_name && test.signature == snap.signature
+                })
+                .map(|pos| tests.remove(pos))
+                .unwrap_or(snap)
+        })
+        .collect::<Vec<_>>();
+    merged.extend(tests);
+    merged
+}
+
 /// A Snapshot entry diff
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct SnapshotDiff {
@@ -302,37 +371,67 @@ impl SnapshotDiff {
     fn gas_diff(&self) -> f64 {
         self.gas_change() as f64 / self.target_gas_used.gas() as f64
     }
+
+    /// Returns whether the gas changed by more than `tolerance` percent
+    fn exceeds(&self, tolerance: f64) -> bool {
+        self.gas_change() != 0 && self.gas_diff().abs() * 100.0 > tolerance
+    }
 }
 
 /// Compares the set of tests with an existing snapshot
 ///
+/// A test fails the check if its gas changed by more than `tolerance` percent or if it has no
+/// entry in the snapshot. Unless the run was `filtered`, entries of tests that no longer exist
+/// fail the check as well.
+///
 /// Returns true all tests match
-fn check(tests: Vec<Test>, snaps: Vec<SnapshotEntry>) -> bool {
-    let snaps = snaps
+fn check(
+    tests: Vec<SnapshotEntry>,
+    snaps: Vec<SnapshotEntry>,
+    tolerance: f64,
+    filtered: bool,
+) -> bool {
+    let mut snaps = snaps
         .into_iter()
         .map(|s| ((s.contract_name, s.signature), s.gas_used))
         .collect::<HashMap<_, _>>();
     let mut has_diff = false;
     for test in tests {
-        if let Some(target_gas) =
-            snaps.get(&(test.contract_name().to_string(), test.signature.clone())).cloned()
+        if let Some(target_gas_used) =
+            snaps.remove(&(test.contract_name.clone(), test.signature.clone()))
         {
-            let source_gas = test.result.kind.report();
-            if source_gas.gas() != target_gas.gas() {
+            let diff = SnapshotDiff {
+                signature: test.signature,
+                source_gas_used: test.gas_used,
+                target_gas_used,
+            };
+            if diff.exceeds(tolerance) {
                 eprintln!(
-                    "Diff in \"{}::{}\": consumed \"{}\" gas, expected \"{}\" gas ",
-                    test.contract_name(),
-                    test.signature,
-                    source_gas,
-                    target_gas
+                    "Diff in \"{}::{}\": {} → {} ({})",
+                    test.contract_name,
+                    diff.signature,
+                    diff.target_gas_used,
+                    diff.source_gas_used,
+                    fmt_pct_change(diff.gas_diff())
                 );
                 has_diff = true;
             }
         } else {
             eprintln!(
                 "No matching snapshot entry found for \"{}::{}\" in snapshot file",
-                test.contract_name(),
-                test.signature
+                test.contract_name, test.signature
+            );
+            has_diff = true;
+        }
+    }
+
+    if !filtered {
+        let mut removed = snaps.into_keys().collect::<Vec<_>>();
+        removed.sort();
+        for (contract_name, signature) in removed {
+            eprintln!(
+                "Snapshot entry \"{}::{}\" does not match any test, it was removed or renamed",
+                contract_name, signature
             );
             has_diff = true;
         }
@@ -377,8 +476,10 @@ fn diff(tests: Vec<Test>, snaps: Vec<SnapshotEntry>) -> eyre::Result<()> {
         let gas_diff = diff.gas_diff();
         overall_gas_diff += gas_diff;
         println!(
-            "{} (gas: {} ({})) ",
+            "{} (gas: {} → {} | {} {})",
             diff.signature,
+            diff.target_gas_used.gas(),
+            diff.source_gas_used.gas(),
             fmt_change(gas_change),
             fmt_pct_change(gas_diff)
         );
@@ -444,4 +545,60 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn can_parse_invariant_snapshot_entry() {
+        let s = "Test:invariantBalance() (runs: 256, calls: 3840, reverts: 12)";
+        let entry = SnapshotEntry::from_str(s).unwrap();
+        assert_eq!(
+            entry,
+            SnapshotEntry {
+                contract_name: "Test".to_string(),
+                signature: "invariantBalance()".to_string(),
+                gas_used: TestKindReport::Invariant { runs: 256, calls: 3840, reverts: 12 }
+            }
+        );
+        assert_eq!(entry.to_string(), s);
+    }
+
+    fn entry(signature: &str, gas: u64) -> SnapshotEntry {
+        SnapshotEntry {
+            contract_name: "Test".to_string(),
+            signature: signature.to_string(),
+            gas_used: TestKindReport::Standard { gas },
+        }
+    }
+
+    #[test]
+    fn can_check_snapshot_with_tolerance() {
+        let snaps = vec![entry("a()", 1000), entry("b()", 2000)];
+
+        assert!(check(snaps.clone(), snaps.clone(), 0.0, false));
+        assert!(!check(vec![entry("a()", 1001), entry("b()", 2000)], snaps.clone(), 0.0, false));
+        assert!(check(vec![entry("a()", 1010), entry("b()", 1990)], snaps.clone(), 1.0, false));
+        assert!(!check(vec![entry("a()", 1011), entry("b()", 2000)], snaps.clone(), 1.0, false));
+
+        // new tests have to be added to the snapshot
+        assert!(!check(vec![entry("a()", 1000), entry("c()", 10)], snaps, 1.0, true));
+    }
+
+    #[test]
+    fn can_check_removed_snapshot_entries() {
+        let snaps = vec![entry("a()", 1000), entry("b()", 2000)];
+
+        assert!(!check(vec![entry("a()", 1000)], snaps.clone(), 0.0, false));
+        // a filtered run doesn't cover all entries
+        assert!(check(vec![entry("a()", 1000)], snaps, 0.0, true));
+    }
+
+    #[test]
+    fn can_merge_filtered_snapshot() {
+        let snaps = vec![entry("a()", 1000), entry("b()", 2000), entry("c()", 3000)];
+        let tests = vec![entry("d()", 4000), entry("b()", 2500)];
+
+        assert_eq!(
+            merge_snapshot(snaps, tests),
+            vec![entry("a()", 1000), entry("b()", 2500), entry("c()", 3000), entry("d()", 4000)]
+        );
+    }
 }
@@ -3,7 +3,11 @@ use clap::Parser;
 use ethers::solc::FileFilter;
 use forge::TestFilter;
 use foundry_config::Config;
-use std::{fmt, path::Path, str::FromStr};
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
 /// The filter to use during testing
 ///
@@ -70,9 +74,24 @@ pub struct Filter {
         value_name = "GLOB"
     )]
     pub path_pattern_inverse: Option<GlobMatcher>,
+
+    /// The root of the project, path patterns are also matched against paths relative to it
+    #[clap(skip)]
+    pub root: Option<PathBuf>,
 }
 
 impl Filter {
+    /// Returns whether no pattern is set, in which case all tests are run
+    pub fn is_empty(&self) -> bool {
+        self.pattern.is_none() &&
+            self.test_pattern.is_none() &&
+            self.test_pattern_inverse.is_none() &&
+            self.contract_pattern.is_none() &&
+            self.contract_pattern_inverse.is_none() &&
+            self.path_pattern.is_none() &&
+            self.path_pattern_inverse.is_none()
+    }
+
     /// Merges the set filter globs with the config's values
     pub fn with_merged_config(&self, config: &Config) -> Self {
         let mut filter = self.clone();
@@ -95,8 +114,21 @@ impl Filter {
         if filter.path_pattern_inverse.is_none() {
             filter.path_pattern_inverse = config.path_pattern_inverse.clone().map(Into::into);
         }
+        filter.root = Some(config.__root.0.clone());
         filter
     }
+
+    /// Returns whether `glob` matches the `path` or the `path` relative to the project root.
+    ///
+    /// Test contracts are identified by their absolute source path, so this allows patterns like
+    /// `test/unit/**`.
+    fn is_path_match(&self, glob: &GlobMatcher, path: &str) -> bool {
+        glob.is_match(path) ||
+            self.root
+                .as_ref()
+                .and_then(|root| Path::new(path).strip_prefix(root).ok())
+                .map_or(false, |relative| glob.is_match(&relative.to_string_lossy()))
+    }
 }
 
 impl fmt::Debug for Filter {
@@ -121,10 +153,10 @@ impl FileFilter for Filter {
     fn is_match(&self, file: &Path) -> bool {
         if let Some(file) = file.as_os_str().to_str() {
             if let Some(ref glob) = self.path_pattern {
-                return glob.is_match(file)
+                return self.is_path_match(glob, file)
             }
             if let Some(ref glob) = self.path_pattern_inverse {
-                return !glob.is_match(file)
+                return !self.is_path_match(glob, file)
             }
         }
         file.is_sol_test()
@@ -164,10 +196,10 @@ impl TestFilter for Filter {
         let mut ok = true;
         let path = path.as_ref();
         if let Some(ref glob) = self.path_pattern {
-            ok &= glob.is_match(path);
+            ok &= self.is_path_match(glob, path);
         }
         if let Some(ref glob) = self.path_pattern_inverse {
-            ok &= !glob.is_match(path);
+            ok &= !self.is_path_match(glob, path);
         }
         ok
     }
@@ -263,4 +295,29 @@ mod tests {
         assert!(matcher.is_match("test/Contract.sol"));
         assert!(matcher.is_match("./test/Contract.sol"));
     }
+
+    #[test]
+    fn can_match_paths_relative_to_root() {
+        let mut filter = Filter::parse_from([
+            "filter",
+            "--match-path",
+            "test/unit/**",
+            "--no-match-path",
+            "**/Slow*",
+            "--match-contract",
+            "Unit",
+        ]);
+        assert!(!filter.matches_path("/project/test/unit/A.t.sol"));
+
+        filter.root = Some(PathBuf::from("/project"));
+        assert!(filter.matches_path("/project/test/unit/A.t.sol"));
+        assert!(filter.matches_path("/project/test/unit/nested/B.t.sol"));
+        assert!(!filter.matches_path("/project/test/integration/A.t.sol"));
+        assert!(!filter.matches_path("/project/test/unit/SlowA.t.sol"));
+        assert!(!filter.matches_path("/other/test/unit/A.t.sol"));
+
+        // path and name filters are combined
+        assert!(filter.matches_contract("UnitTest"));
+        assert!(!filter.matches_contract("IntegrationTest"));
+    }
 }
@@ -9,23 +9,34 @@ use crate::{
     suggestions, utils,
 };
 use cast::fuzz::CounterExample;
-use clap::{AppSettings, Parser};
-use ethers::{solc::utils::RuntimeOrHandle, types::U256};
+use clap::{AppSettings, Parser, ValueHint};
+use ethers::{
+    solc::utils::RuntimeOrHandle,
+    types::{Address, U256},
+};
+use eyre::WrapErr;
 use forge::{
     decode::decode_console_logs,
     executor::inspector::CheatsConfig,
     gas_report::GasReport,
-    result::{SuiteResult, TestKind, TestResult},
+    result::{junit_xml, FinishedTest, SuiteResult, TestKind, TestResult},
     trace::{
         identifier::{EtherscanIdentifier, LocalTraceIdentifier},
         CallTraceDecoderBuilder, TraceKind,
     },
-    MultiContractRunner, MultiContractRunnerBuilder, TestOptions,
+    MultiContractRunner, MultiContractRunnerBuilder, Shard, TestOptions,
 };
-use foundry_common::evm::EvmArgs;
-use foundry_config::{figment, Config};
+use foundry_common::{evm::EvmArgs, fs};
+use foundry_config::{figment, Config, FuzzRngAlgorithm};
+use proptest::test_runner::RngAlgorithm;
 use regex::Regex;
-use std::{collections::BTreeMap, path::PathBuf, sync::mpsc::channel, thread, time::Duration};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+    sync::mpsc::channel,
+    thread,
+    time::Duration,
+};
 use tracing::trace;
 use watchexec::config::{InitConfig, RuntimeConfig};
 use yansi::Paint;
@@ -67,6 +78,29 @@ pub struct TestArgs {
     #[clap(long, env = "FORGE_GAS_REPORT")]
     gas_report: bool,
 
+    /// Write a machine readable gas report to the given file.
+    ///
+    /// The report contains the min, avg, median and max gas of every function, keyed by contract
+    /// and function signature. Two reports can be compared with `forge gas-diff`.
+    #[clap(long, value_hint = ValueHint::FilePath, value_name = "PATH")]
+    gas_report_json: Option<PathBuf>,
+
+    /// Only include contracts whose source path matches the glob pattern in the gas report.
+    ///
+    /// Can be passed multiple times. If not set, contracts at all paths are included.
+    #[clap(long, multiple_occurrences = true, value_name = "GLOB")]
+    gas_report_include_path: Vec<String>,
+
+    /// Exclude contracts whose source path matches the glob pattern from the gas report.
+    ///
+    /// Can be passed multiple times.
+    #[clap(long, multiple_occurrences = true, value_name = "GLOB")]
+    gas_report_exclude_path: Vec<String>,
+
+    /// Write the test results as a JUnit XML report to the given file.
+    #[clap(long, value_hint = ValueHint::FilePath, value_name = "PATH")]
+    junit: Option<PathBuf>,
+
     /// Exit with code 0 even if a test fails.
     #[clap(long, env = "FORGE_ALLOW_FAILURE")]
     allow_failure: bool,
@@ -75,6 +109,19 @@ pub struct TestArgs {
     #[clap(long, short, help_heading = "DISPLAY OPTIONS")]
     json: bool,
 
+    /// Print the result of every test as soon as it completed, with the progress of the run.
+    ///
+    /// Logs, traces and the summary of a test contract are still printed once all of its tests
+    /// completed.
+    #[clap(long, help_heading = "DISPLAY OPTIONS", conflicts_with = "json")]
+    stream: bool,
+
+    /// Print the N slowest tests after the run.
+    ///
+    /// The duration of fuzz and invariant tests includes all of their runs.
+    #[clap(long, help_heading = "DISPLAY OPTIONS", conflicts_with = "json", value_name = "N")]
+    slowest: Option<usize>,
+
     #[clap(flatten, next_help_heading = "EVM OPTIONS")]
     evm_opts: EvmArgs,
 
@@ -102,6 +149,50 @@ pub struct TestArgs {
         parse(try_from_str = utils::parse_u256)
     )]
     pub fuzz_seed: Option<U256>,
+
+    #[clap(
+        long,
+        help = "Fail a fuzz test once fuzzing it took longer than this many seconds",
+        value_name = "SECONDS"
+    )]
+    pub fuzz_timeout: Option<u64>,
+
+    #[clap(
+        long,
+        help = "Write a Solidity test replaying the call sequence of every broken invariant to this directory",
+        value_hint = ValueHint::DirPath,
+        value_name = "PATH"
+    )]
+    pub invariant_replay_dir: Option<PathBuf>,
+
+    /// Persist the call sequences of invariant runs that hit new code to this directory, and
+    /// replay them at the start of the next run.
+    ///
+    /// The corpus of a test contract is discarded when the code of the contract or of its targets
+    /// changed.
+    #[clap(long, value_hint = ValueHint::DirPath, value_name = "PATH")]
+    pub invariant_corpus_dir: Option<PathBuf>,
+
+    /// Only call the targeted contracts from this address during invariant runs.
+    ///
+    /// Can be passed multiple times. Overrides the senders returned by `targetSenders()`.
+    #[clap(long, multiple_occurrences = true, value_name = "ADDRESS")]
+    pub invariant_sender: Vec<Address>,
+
+    /// Only run a shard of the tests, e.g. `2/4` runs the second of four shards.
+    ///
+    /// The matching test functions are split into shards deterministically, so running every shard
+    /// of the same suite runs every test exactly once. Useful for splitting a test suite across
+    /// parallel CI jobs.
+    #[clap(long, value_name = "INDEX/COUNT")]
+    pub shard: Option<Shard>,
+
+    /// Only run the tests that failed in the last run.
+    ///
+    /// The failing tests of every run are recorded in the cache directory. If the last run had no
+    /// failing tests, nothing is run.
+    #[clap(long)]
+    pub rerun_failed: bool,
 }
 
 impl TestArgs {
@@ -141,10 +232,53 @@ impl Provider for TestArgs {
             dict.insert("fuzz_seed".to_string(), fuzz_seed.to_string().into());
         }
 
+        if let Some(fuzz_timeout) = self.fuzz_timeout {
+            dict.insert("fuzz_timeout".to_string(), fuzz_timeout.into());
+        }
+
+        if let Some(ref invariant_replay_dir) = self.invariant_replay_dir {
+            dict.insert(
+                "invariant_replay_dir".to_string(),
+                invariant_replay_dir.to_string_lossy().to_string().into(),
+            );
+        }
+
+        if let Some(ref invariant_corpus_dir) = self.invariant_corpus_dir {
+            dict.insert(
+                "invariant_corpus_dir".to_string(),
+                invariant_corpus_dir.to_string_lossy().to_string().into(),
+            );
+        }
+
+        if !self.invariant_sender.is_empty() {
+            dict.insert(
+                "invariant_senders".to_string(),
+                self.invariant_sender
+                    .iter()
+                    .map(|sender| format!("{:?}", sender))
+                    .collect::<Vec<_>>()
+                    .into(),
+            );
+        }
+
         if let Some(ref etherscan_api_key) = self.etherscan_api_key {
             dict.insert("etherscan_api_key".to_string(), etherscan_api_key.to_string().into());
         }
 
+        if !self.gas_report_include_path.is_empty() {
+            dict.insert(
+                "gas_reports_include_paths".to_string(),
+                self.gas_report_include_path.clone().into(),
+            );
+        }
+
+        if !self.gas_report_exclude_path.is_empty() {
+            dict.insert(
+                "gas_reports_exclude_paths".to_string(),
+                self.gas_report_exclude_path.clone().into(),
+            );
+        }
+
         Ok(Map::from([(Config::selected_profile(), dict)]))
     }
 }
@@ -262,6 +396,20 @@ impl TestOutcome {
             .fold(Duration::ZERO, |acc, SuiteResult { duration, .. }| acc + *duration)
     }
 
+    /// Returns the `n` slowest tests, slowest first, with the identifier of their contract
+    pub fn slowest(&self, n: usize) -> Vec<(&String, &String, &TestResult)> {
+        let mut tests = self
+            .results
+            .iter()
+            .flat_map(|(contract, suite)| {
+                suite.test_results.iter().map(move |(name, result)| (contract, name, result))
+            })
+            .collect::<Vec<_>>();
+        tests.sort_by(|(_, _, a), (_, _, b)| b.duration.cmp(&a.duration));
+        tests.truncate(n);
+        tests
+    }
+
     pub fn summary(&self) -> String {
         let failed = self.failures().count();
         let result = if failed == 0 { Paint::green("ok") } else { Paint::red("FAILED") };
@@ -305,6 +453,10 @@ fn short_test_result(name: &str, result: &TestResult) {
     };
 
     println!("{} {} {}", status, name, result.kind.report());
+
+    if let Some(seed) = result.counterexample_seed {
+        println!("{}", Paint::yellow(format!("  re-run with --fuzz-seed {seed:#x}")));
+    }
 }
 
 pub fn custom_run(args: TestArgs) -> eyre::Result<TestOutcome> {
@@ -315,17 +467,46 @@ pub fn custom_run(args: TestArgs) -> eyre::Result<TestOutcome> {
         fuzz_runs: config.fuzz_runs,
         fuzz_max_local_rejects: config.fuzz_max_local_rejects,
         fuzz_max_global_rejects: config.fuzz_max_global_rejects,
+        fuzz_dictionary: config.fuzz_dictionary,
+        fuzz_dictionary_weight: config.fuzz_dictionary_weight,
         fuzz_seed: config.fuzz_seed,
+        fuzz_rng_algorithm: config.fuzz_rng_algorithm.map(|algorithm| match algorithm {
+            FuzzRngAlgorithm::XorShift => RngAlgorithm::XorShift,
+            FuzzRngAlgorithm::ChaCha => RngAlgorithm::ChaCha,
+        }),
+        fuzz_failure_persist_dir: config.fuzz_failure_persist_dir.clone(),
+        fuzz_timeout: config.fuzz_timeout.map(Duration::from_secs),
+        fuzz_addresses: config.fuzz_addresses.clone(),
+        fuzz_addresses_deployed: config.fuzz_addresses_deployed,
+        fuzz_uint256_min: config.fuzz_uint256_min,
+        fuzz_uint256_max: config.fuzz_uint256_max,
+        fuzz_exclude_zero: config.fuzz_exclude_zero,
         invariant_runs: config.invariant_runs,
         invariant_depth: config.invariant_depth,
         invariant_fail_on_revert: config.invariant_fail_on_revert,
         invariant_call_override: config.invariant_call_override,
+        invariant_replay_dir: config.invariant_replay_dir.clone(),
+        invariant_corpus_dir: config.invariant_corpus_dir.clone(),
+        invariant_selector_weights: config.invariant_selector_weights.clone(),
+        invariant_senders: config.invariant_senders.clone(),
     };
 
     let mut filter = args.filter(&config);
 
     trace!(target: "forge::test", ?filter, "using filter");
 
+    let failures_path = config.cache_path.join(TEST_FAILURES_FILENAME);
+    let only_tests = if args.rerun_failed {
+        let failures = read_failures(&failures_path)?;
+        if failures.is_empty() {
+            println!("No tests failed in the last run.");
+            return Ok(TestOutcome::new(BTreeMap::new(), args.allow_failure))
+        }
+        Some(failures)
+    } else {
+        None
+    };
+
     // Set up the project
     let project = config.project()?;
     let compiler = ProjectCompiler::default();
@@ -339,7 +520,8 @@ pub fn custom_run(args: TestArgs) -> eyre::Result<TestOutcome> {
 
     // Determine print verbosity and executor verbosity
     let verbosity = evm_opts.verbosity;
-    if args.gas_report && evm_opts.verbosity < 3 {
+    let gas_reporting = args.gas_report || args.gas_report_json.is_some();
+    if gas_reporting && evm_opts.verbosity < 3 {
         evm_opts.verbosity = 3;
     }
 
@@ -354,7 +536,11 @@ pub fn custom_run(args: TestArgs) -> eyre::Result<TestOutcome> {
         .sender(evm_opts.sender)
         .with_fork(evm_opts.get_fork(&config, env.clone()))
         .with_cheats_config(CheatsConfig::new(&config, &evm_opts))
-        .with_test_options(test_options)
+        .with_test_options(test_options.clone())
+        // Report the results of seeded runs in the same order, so they can be compared
+        .sorted(test_options.configured_fuzz_seed().is_some())
+        .with_shard(args.shard)
+        .with_only_tests(only_tests)
         .build(project.paths.root, output, env, evm_opts)?;
 
     if args.debug.is_some() {
@@ -407,17 +593,73 @@ pub fn custom_run(args: TestArgs) -> eyre::Result<TestOutcome> {
     } else if args.list {
         list(runner, filter, args.json)
     } else {
-        test(
+        let outcome = test(
             config,
             runner,
             verbosity,
             filter,
             args.json,
+            args.stream,
             args.allow_failure,
             test_options,
             args.gas_report,
-        )
+            args.gas_report_json,
+        )?;
+        if let Some(n) = args.slowest {
+            let slowest = outcome.slowest(n);
+            if !slowest.is_empty() {
+                println!();
+                println!("Slowest tests:");
+                for (contract, name, result) in slowest {
+                    println!("{:>10.2?} {}:{}", result.duration, contract, name);
+                }
+            }
+        }
+        if let Some(path) = args.junit {
+            if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&path, junit_xml(&outcome.results))?;
+        }
+        write_failures(&failures_path, &outcome)?;
+        Ok(outcome)
+    }
+}
+
+/// The file in the cache directory that records the tests that failed in the last run
+const TEST_FAILURES_FILENAME: &str = "test-failures.json";
+
+/// Returns the tests that failed in the last run, by contract identifier
+fn read_failures(path: &Path) -> eyre::Result<BTreeMap<String, BTreeSet<String>>> {
+    if !path.exists() {
+        return Ok(BTreeMap::new())
+    }
+    serde_json::from_str(&fs::read_to_string(path)?)
+        .wrap_err_with(|| format!("Failed to read the failed tests from {}", path.display()))
+}
+
+/// Records the tests that failed in the `outcome`, by contract identifier
+fn write_failures(path: &Path, outcome: &TestOutcome) -> eyre::Result<()> {
+    let failures = outcome
+        .results
+        .iter()
+        .map(|(contract, suite)| {
+            let tests = suite
+                .test_results
+                .iter()
+                .filter(|(_, result)| !result.success)
+                .map(|(test, _)| test.clone())
+                .collect::<BTreeSet<_>>();
+            (contract.clone(), tests)
+        })
+        .filter(|(_, tests)| !tests.is_empty())
+        .collect::<BTreeMap<_, _>>();
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
     }
+    fs::write(path, serde_json::to_string_pretty(&failures)?)?;
+    Ok(())
 }
 
 /// Lists all matching tests
@@ -446,10 +688,13 @@ fn test(
     verbosity: u8,
     filter: Filter,
     json: bool,
+    stream: bool,
     allow_failure: bool,
     test_options: TestOptions,
-    gas_reporting: bool,
+    gas_report_table: bool,
+    gas_report_json: Option<PathBuf>,
 ) -> eyre::Result<TestOutcome> {
+    let gas_reporting = gas_report_table || gas_report_json.is_some();
     trace!(target: "forge::test", "running all tests");
     if runner.count_filtered_tests(&filter) == 0 {
         let filter_str = filter.to_string();
@@ -481,15 +726,38 @@ fn test(
         let remote_chain_id = runner.evm_opts.get_remote_chain_id();
         // Do not re-query etherscan for contracts that you've already queried today.
         let etherscan_identifier = EtherscanIdentifier::new(&config, remote_chain_id)?;
+        let config_labels = config.labels()?;
 
         // Set up test reporter channel
         let (tx, rx) = channel::<(String, SuiteResult)>();
 
+        // Print every test result as soon as the test completed
+        let printer = if stream {
+            let total = runner.count_tests_to_run(&filter);
+            let (test_tx, test_rx) = channel::<FinishedTest>();
+            runner = runner.with_test_stream(test_tx);
+            Some(thread::spawn(move || {
+                for (completed, finished) in test_rx.into_iter().enumerate() {
+                    // hold the lock so the progress and the result end up on the same line
+                    let _stdout = std::io::stdout().lock();
+                    print!("[{}/{}] ", completed + 1, total);
+                    short_test_result(
+                        &format!("{}:{}", finished.contract, finished.test),
+                        &finished.result,
+                    );
+                }
+            }))
+        } else {
+            None
+        };
+
         // Run tests
         let handle = thread::spawn(move || runner.test(&filter, Some(tx), test_options).unwrap());
 
         let mut results: BTreeMap<String, SuiteResult> = BTreeMap::new();
-        let mut gas_report = GasReport::new(config.gas_reports, config.gas_reports_ignore);
+        let mut gas_report = GasReport::new(config.gas_reports, config.gas_reports_ignore)
+            .with_path_filter(&config.gas_reports_include_paths, &config.gas_reports_exclude_paths)
+            .wrap_err("Invalid gas report path pattern")?;
         for (contract_name, suite_result) in rx {
             let mut tests = suite_result.test_results.clone();
             println!();
@@ -501,7 +769,9 @@ fn test(
                 println!("Running {} {} for {}", tests.len(), term, contract_name);
             }
             for (name, result) in &mut tests {
-                short_test_result(name, result);
+                if !stream {
+                    short_test_result(name, result);
+                }
 
                 // We only display logs at level 2 and above
                 if verbosity >= 2 {
@@ -519,6 +789,7 @@ fn test(
                 if !result.traces.is_empty() {
                     // Identify addresses in each trace
                     let mut decoder = CallTraceDecoderBuilder::new()
+                        .with_labels(config_labels.clone())
                         .with_labels(result.labeled_addresses.clone())
                         .with_events(local_identifier.events())
                         .build();
@@ -574,11 +845,23 @@ fn test(
         }
 
         if gas_reporting {
-            println!("{}", gas_report.finalize());
+            let gas_report = gas_report.finalize();
+            if gas_report_table {
+                println!("{}", gas_report);
+            }
+            if let Some(path) = gas_report_json {
+                if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(&path, serde_json::to_string_pretty(&gas_report.to_json_report())?)?;
+            }
         }
 
         // reattach the thread
         let _ = handle.join();
+        if let Some(printer) = printer {
+            let _ = printer.join();
+        }
 
         trace!(target: "forge::test", "received {} results", results.len());
         Ok(TestOutcome::new(results, allow_failure))
@@ -0,0 +1,60 @@
+use async_trait::async_trait;
+use ethers::etherscan::Client;
+use eyre::Context;
+
+use super::{
+    etherscan::EtherscanVerificationProvider, VerificationProvider, VerificationResult,
+    VerificationStatus, VerifyArgs, VerifyCheckArgs,
+};
+
+/// Verifies contracts on a Blockscout instance.
+///
+/// Blockscout exposes an etherscan compatible API, so this reuses the etherscan verification
+/// logic but submits to the API at `--verifier-url` instead.
+pub struct BlockscoutVerificationProvider;
+
+#[async_trait]
+impl VerificationProvider for BlockscoutVerificationProvider {
+    async fn verify(&self, args: VerifyArgs) -> eyre::Result<VerificationResult> {
+        let client =
+            blockscout_client(args.verifier_url.as_deref(), args.etherscan_key.as_deref())?;
+        EtherscanVerificationProvider.verify_with_client(&client, args).await
+    }
+
+    async fn check(&self, args: VerifyCheckArgs) -> eyre::Result<VerificationResult> {
+        let client =
+            blockscout_client(args.verifier_url.as_deref(), args.etherscan_key.as_deref())?;
+        EtherscanVerificationProvider.check_with_client(&client, args).await
+    }
+
+    async fn status(&self, args: &VerifyCheckArgs, id: &str) -> eyre::Result<VerificationStatus> {
+        let client =
+            blockscout_client(args.verifier_url.as_deref(), args.etherscan_key.as_deref())?;
+        EtherscanVerificationProvider.status_with_client(&client, id).await
+    }
+
+    async fn is_verified(&self, args: &VerifyArgs) -> eyre::Result<bool> {
+        let client =
+            blockscout_client(args.verifier_url.as_deref(), args.etherscan_key.as_deref())?;
+        EtherscanVerificationProvider.is_verified_with_client(&client, args.address).await
+    }
+}
+
+/// Returns a client for the Blockscout API at `verifier_url`, e.g.
+/// `https://blockscout.com/xdai/mainnet/api`
+///
+/// Blockscout does not require an API key, but will use it if one is provided.
+fn blockscout_client(verifier_url: Option<&str>, api_key: Option<&str>) -> eyre::Result<Client> {
+    let api_url = verifier_url.ok_or_else(|| {
+        eyre::eyre!("Blockscout verification requires the API url to be set via `--verifier-url`")
+    })?;
+    // the explorer itself is served from the parent of the api endpoint
+    let browser_url = api_url.trim_end_matches('/').trim_end_matches("/api");
+
+    Client::builder()
+        .with_api_key(api_key.unwrap_or_default())
+        .with_api_url(api_url)?
+        .with_url(browser_url)?
+        .build()
+        .wrap_err("Failed to create blockscout client")
+}
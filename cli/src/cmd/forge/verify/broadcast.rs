@@ -0,0 +1,179 @@
+use super::{VerificationResult, VerificationStatus, VerifyArgs};
+use crate::{
+    cmd::{forge::script::TransactionWithMetadata, LoadConfig},
+    compile,
+};
+use ethers::{
+    abi::Address,
+    prelude::{ArtifactId, TransactionReceipt},
+    solc::{info::ContractInfo, utils::read_json_file},
+};
+use semver::Version;
+use serde::Deserialize;
+use std::path::Path;
+use tracing::trace;
+
+/// The parts of a `forge script` broadcast file that are needed to verify the created contracts
+#[derive(Deserialize)]
+struct Broadcast {
+    transactions: Vec<TransactionWithMetadata>,
+    #[serde(default)]
+    receipts: Vec<TransactionReceipt>,
+    #[serde(default)]
+    libraries: Vec<String>,
+}
+
+impl VerifyArgs {
+    /// Verifies every contract that was created in the given broadcast file.
+    ///
+    /// Verification failures of individual contracts do not abort the batch, they're collected and
+    /// reported once all contracts have been processed.
+    pub(super) async fn verify_broadcast(self, path: &Path) -> eyre::Result<()> {
+        let broadcast: Broadcast = read_json_file(path).map_err(|err| {
+            eyre::eyre!("Failed to read broadcast file \"{}\": {}", path.display(), err)
+        })?;
+
+        let project = self.load_config_emit_warnings().project()?;
+        let output = compile::suppress_compile(&project)?;
+        let artifacts = output
+            .into_artifacts()
+            .filter_map(|(id, artifact)| {
+                let bytecode = artifact.bytecode?.object.into_bytes()?;
+                Some((id, bytecode.to_vec()))
+            })
+            .filter(|(_, bytecode)| !bytecode.is_empty())
+            .collect::<Vec<_>>();
+
+        let provider = self.verifier.client();
+        let mut failures = vec![];
+        let mut verified = 0usize;
+        let mut pending = 0usize;
+
+        for tx in
+            broadcast.transactions.iter().filter(|tx| tx.opcode == "CREATE" || tx.is_create2())
+        {
+            let name = tx.contract_name.clone().unwrap_or_default();
+
+            let address = match created_address(tx, &broadcast.receipts) {
+                Some(address) => address,
+                None => {
+                    failures.push((name, None, eyre::eyre!("Could not find the contract address")));
+                    continue
+                }
+            };
+
+            let (artifact, constructor_args) = match find_artifact(tx, &name, &artifacts) {
+                Some(found) => found,
+                None => {
+                    failures.push((
+                        name,
+                        Some(address),
+                        eyre::eyre!("Could not find a matching artifact in the project"),
+                    ));
+                    continue
+                }
+            };
+
+            let args = VerifyArgs {
+                address,
+                contract: ContractInfo {
+                    path: Some(artifact.source.to_string_lossy().to_string()),
+                    name: artifact.name.clone(),
+                },
+                constructor_args: Some(hex::encode(constructor_args)),
+                // We strip the build metadata, since it can lead to etherscan not identifying it
+                // correctly. eg: `v0.8.10+commit.fc410830.Linux.gcc` != `v0.8.10+commit.fc410830`
+                compiler_version: self.compiler_version.clone().or_else(|| {
+                    let version = &artifact.version;
+                    Some(Version::new(version.major, version.minor, version.patch).to_string())
+                }),
+                libraries: if self.libraries.is_empty() {
+                    broadcast.libraries.clone()
+                } else {
+                    self.libraries.clone()
+                },
+                ..self.clone()
+            };
+
+            trace!(?address, contract = ?args.contract, "verifying broadcast contract");
+            match provider.is_verified(&args).await {
+                Ok(true) => {
+                    println!("Skipping [{}] {:?}: already verified", artifact.name, address);
+                    continue
+                }
+                Ok(false) => {}
+                Err(err) => {
+                    failures.push((artifact.name.clone(), Some(address), err));
+                    continue
+                }
+            }
+
+            match provider.verify(args).await {
+                Ok(VerificationResult { status: VerificationStatus::Failed(reason), .. }) => {
+                    failures.push((artifact.name.clone(), Some(address), eyre::eyre!(reason)))
+                }
+                Ok(VerificationResult { status: VerificationStatus::Pending, .. }) => pending += 1,
+                Ok(_) => verified += 1,
+                Err(err) => failures.push((artifact.name.clone(), Some(address), err)),
+            }
+        }
+
+        println!("\nVerified {} contract(s) from {}", verified, path.display());
+        if pending > 0 {
+            println!("{} submission(s) are still pending", pending);
+        }
+
+        if failures.is_empty() {
+            return Ok(())
+        }
+
+        eprintln!("\nFailed to verify {} contract(s):", failures.len());
+        for (name, address, err) in &failures {
+            match address {
+                Some(address) => eprintln!("  [{name}] {address:?}: {err}"),
+                None => eprintln!("  [{name}]: {err}"),
+            }
+        }
+        eyre::bail!("Verification failed for {} contract(s)", failures.len())
+    }
+}
+
+/// Returns the address of the contract created by the transaction.
+///
+/// For `CREATE2` the address is part of the broadcast, otherwise it's taken from the receipt.
+fn created_address(
+    tx: &TransactionWithMetadata,
+    receipts: &[TransactionReceipt],
+) -> Option<Address> {
+    if !tx.is_create2() {
+        let receipt_address = tx.hash.and_then(|hash| {
+            receipts
+                .iter()
+                .find(|receipt| receipt.transaction_hash == hash)
+                .and_then(|receipt| receipt.contract_address)
+        });
+        if receipt_address.is_some() {
+            return receipt_address
+        }
+    }
+
+    tx.contract_address.filter(|address| !address.is_zero())
+}
+
+/// Finds the artifact whose creation code the transaction deployed and returns it together with
+/// the constructor arguments appended to the creation code.
+fn find_artifact<'a>(
+    tx: &'a TransactionWithMetadata,
+    name: &str,
+    artifacts: &'a [(ArtifactId, Vec<u8>)],
+) -> Option<(&'a ArtifactId, &'a [u8])> {
+    let data = tx.typed_tx().data()?;
+    // If it's a CREATE2, the tx.data comes with a 32-byte salt in the beginning of the transaction
+    let data = if tx.is_create2() { data.0.get(32..)? } else { &data.0[..] };
+
+    artifacts
+        .iter()
+        .filter(|(id, _)| name.is_empty() || id.name == name)
+        .find(|(_, bytecode)| data.starts_with(bytecode))
+        .map(|(id, bytecode)| (id, &data[bytecode.len()..]))
+}
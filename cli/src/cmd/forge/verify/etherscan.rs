@@ -1,13 +1,14 @@
-use crate::cmd::LoadConfig;
+use crate::{cmd::LoadConfig, utils::consume_config_rpc_url};
 use async_trait::async_trait;
-use cast::SimpleCast;
+use cast::{proxy, SimpleCast};
 use ethers::{
+    abi::Address,
     etherscan::{
         contract::{CodeFormat, VerifyContract},
         utils::lookup_compiler_version,
         Client,
     },
-    prelude::artifacts::StandardJsonCompilerInput,
+    prelude::{artifacts::StandardJsonCompilerInput, Middleware, H256},
     solc::{
         artifacts::{BytecodeHash, Source},
         cache::CacheEntry,
@@ -15,19 +16,26 @@ use ethers::{
     },
 };
 use eyre::{eyre, Context};
-use foundry_config::{Config, SolcReq};
+use foundry_common::get_http_provider;
+use foundry_config::{Chain, Config, SolcReq};
 use foundry_utils::Retry;
 use futures::FutureExt;
 use once_cell::sync::Lazy;
 use regex::Regex;
 use semver::{BuildMetadata, Version};
+use serde::Deserialize;
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, BTreeSet},
     path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
 };
 use tracing::{trace, warn};
 
-use super::{VerificationProvider, VerifyArgs, VerifyCheckArgs, RETRY_CHECK_ON_VERIFY};
+use super::{
+    VerificationProvider, VerificationResult, VerificationStatus, VerifyArgs, VerifyCheckArgs,
+    RETRY_CHECK_ON_VERIFY,
+};
 
 pub static RE_BUILD_COMMIT: Lazy<Regex> =
     Lazy::new(|| Regex::new(r#"(?P<commit>commit\.[0-9,a-f]{8})"#).unwrap());
@@ -36,11 +44,104 @@ pub struct EtherscanVerificationProvider;
 
 #[async_trait]
 impl VerificationProvider for EtherscanVerificationProvider {
-    async fn verify(&self, mut args: VerifyArgs) -> eyre::Result<()> {
-        let etherscan_key = args.etherscan_key.take().expect("ETHERSCAN_API_KEY must be set");
-        let etherscan = Client::new(args.chain.try_into()?, &etherscan_key)
-            .wrap_err("Failed to create etherscan client")?;
+    async fn verify(&self, args: VerifyArgs) -> eyre::Result<VerificationResult> {
+        let etherscan = self.client(
+            args.chain,
+            args.verifier_url.as_deref(),
+            args.etherscan_key.as_deref().expect("ETHERSCAN_API_KEY must be set"),
+        )?;
+
+        if args.proxy {
+            return self.verify_proxy_with_client(&etherscan, args).await
+        }
+
+        self.verify_with_client(&etherscan, args).await
+    }
+
+    /// Executes the command to check verification status on Etherscan
+    async fn check(&self, args: VerifyCheckArgs) -> eyre::Result<VerificationResult> {
+        let etherscan = self.client(
+            args.chain,
+            args.verifier_url.as_deref(),
+            args.etherscan_key.as_deref().expect("ETHERSCAN_API_KEY must be set"),
+        )?;
+
+        self.check_with_client(&etherscan, args).await
+    }
+
+    async fn status(&self, args: &VerifyCheckArgs, id: &str) -> eyre::Result<VerificationStatus> {
+        let etherscan = self.client(
+            args.chain,
+            args.verifier_url.as_deref(),
+            args.etherscan_key.as_deref().expect("ETHERSCAN_API_KEY must be set"),
+        )?;
+
+        self.status_with_client(&etherscan, id).await
+    }
+
+    async fn is_verified(&self, args: &VerifyArgs) -> eyre::Result<bool> {
+        let etherscan = self.client(
+            args.chain,
+            args.verifier_url.as_deref(),
+            args.etherscan_key.as_deref().expect("ETHERSCAN_API_KEY must be set"),
+        )?;
+
+        self.is_verified_with_client(&etherscan, args.address).await
+    }
+}
+
+impl EtherscanVerificationProvider {
+    /// Returns the etherscan client for the given chain.
+    ///
+    /// If a `verifier_url` is provided, it is used as the API endpoint and the chain is only used
+    /// to look up the explorer's url. This allows verifying on etherscan compatible explorers of
+    /// chains that are unknown to us.
+    pub(super) fn client(
+        &self,
+        chain: Chain,
+        verifier_url: Option<&str>,
+        etherscan_key: &str,
+    ) -> eyre::Result<Client> {
+        let client = if let Some(api_url) = verifier_url {
+            let browser_url = ethers::types::Chain::try_from(chain)
+                .ok()
+                .and_then(|chain| chain.etherscan_urls())
+                .map(|(_, browser_url)| browser_url)
+                .unwrap_or_else(|| api_url.trim_end_matches('/').trim_end_matches("/api"));
+
+            Client::builder()
+                .with_api_key(etherscan_key)
+                .with_api_url(api_url)?
+                .with_url(browser_url)?
+                .build()
+        } else {
+            Client::new(chain.try_into()?, etherscan_key)
+        };
+
+        client.wrap_err("Failed to create etherscan client")
+    }
+
+    /// Returns whether the source code of the contract at `address` is already verified on the
+    /// given etherscan compatible API
+    pub(super) async fn is_verified_with_client(
+        &self,
+        etherscan: &Client,
+        address: Address,
+    ) -> eyre::Result<bool> {
+        let metadata = etherscan
+            .contract_source_code(address)
+            .await
+            .wrap_err("Failed to fetch the contract's source code")?;
+
+        Ok(metadata.items.iter().any(|item| item.abi != "Contract source code not verified"))
+    }
 
+    /// Submits the verification request to the given etherscan compatible API
+    pub(super) async fn verify_with_client(
+        &self,
+        etherscan: &Client,
+        args: VerifyArgs,
+    ) -> eyre::Result<VerificationResult> {
         let verify_args = self.create_verify_request(&args).await?;
 
         trace!("submitting verification request {:?}", verify_args);
@@ -48,7 +149,7 @@ impl VerificationProvider for EtherscanVerificationProvider {
         let retry: Retry = args.retry.into();
         let resp = retry.run_async(|| {
             async {
-                println!("\nSubmitting verification for [{}] {:?}.", verify_args.contract_name, SimpleCast::checksum_address(&verify_args.address));
+                eprintln!("\nSubmitting verification for [{}] {:?}.", verify_args.contract_name, SimpleCast::checksum_address(&verify_args.address));
                 let resp = etherscan
                     .submit_contract_verification(&verify_args)
                     .await
@@ -65,91 +166,218 @@ impl VerificationProvider for EtherscanVerificationProvider {
                     }
 
                     warn!("Failed verify submission: {:?}", resp);
-                    eprintln!(
-                        "Encountered an error verifying this contract:\nResponse: `{}`\nDetails: `{}`",
-                        resp.message, resp.result
-                    );
-                    std::process::exit(1);
+                    // rejected submissions are not retried
+                    return Ok(Some(Err(resp.result)))
                 }
 
-                Ok(Some(resp))
+                Ok(Some(Ok(resp)))
             }
                 .boxed()
         }).await?;
 
-        if let Some(resp) = resp {
-            println!(
-                "Submitted contract for verification:\n\tResponse: `{}`\n\tGUID: `{}`\n\tURL:
-        {}",
-                resp.message,
-                resp.result,
-                etherscan.address_url(args.address)
-            );
-
-            if args.watch {
-                let check_args = VerifyCheckArgs {
-                    id: resp.result,
-                    chain: args.chain,
-                    retry: RETRY_CHECK_ON_VERIFY,
-                    etherscan_key: Some(etherscan_key),
-                    verifier: args.verifier,
-                };
-                // return check_args.run().await
-                return self.check(check_args).await
+        let url = Some(etherscan.address_url(args.address));
+        let resp = match resp {
+            Some(Ok(resp)) => resp,
+            Some(Err(reason)) => {
+                return Ok(VerificationResult {
+                    guid: None,
+                    status: VerificationStatus::Failed(reason),
+                    url,
+                    already_verified: false,
+                })
             }
-        } else {
-            println!("Contract source code already verified");
+            None => return Ok(VerificationResult::already_verified(url)),
+        };
+
+        eprintln!(
+            "Submitted contract for verification:\n\tResponse: `{}`\n\tGUID: `{}`",
+            resp.message, resp.result
+        );
+
+        if !args.watch {
+            return Ok(VerificationResult::pending(resp.result, url))
         }
 
-        Ok(())
+        let check_args = VerifyCheckArgs {
+            ids: vec![resp.result],
+            chain: args.chain,
+            retry: RETRY_CHECK_ON_VERIFY,
+            etherscan_key: args.etherscan_key,
+            verifier: args.verifier,
+            verifier_url: args.verifier_url,
+            json: args.json,
+        };
+        let result = self.check_with_client(etherscan, check_args).await?;
+        Ok(VerificationResult { url, ..result })
     }
 
-    /// Executes the command to check verification status on Etherscan
-    async fn check(&self, args: VerifyCheckArgs) -> eyre::Result<()> {
-        let etherscan = Client::new(
-            args.chain.try_into()?,
-            &args.etherscan_key.expect("ETHERSCAN_API_KEY must be set"),
-        )
-        .wrap_err("Failed to create etherscan client")?;
+    /// Verifies the implementation of the EIP-1967 proxy at `args.address` and links the proxy to
+    /// it
+    async fn verify_proxy_with_client(
+        &self,
+        etherscan: &Client,
+        mut args: VerifyArgs,
+    ) -> eyre::Result<VerificationResult> {
+        let proxy = args.address;
+        let provider = get_http_provider(consume_config_rpc_url(args.rpc_url.clone()));
+        let implementation = proxy_implementation(&provider, proxy).await?;
+        eprintln!(
+            "Found implementation {:?} of proxy {:?}",
+            SimpleCast::checksum_address(&implementation)?,
+            SimpleCast::checksum_address(&proxy)?
+        );
 
-        println!("Waiting for verification result...");
-        let retry: Retry = args.retry.into();
-        retry
+        // etherscan only links a proxy to a verified implementation
+        args.address = implementation;
+        args.watch = true;
+        let result = self.verify_with_client(etherscan, args.clone()).await?;
+        if result.status != VerificationStatus::Verified {
+            return Ok(result)
+        }
+
+        let api_url = api_url(args.chain, args.verifier_url.as_deref())?;
+        let api_key = args.etherscan_key.as_deref().unwrap_or_default();
+        let client = reqwest::Client::new();
+
+        eprintln!(
+            "\nSubmitting proxy verification for {:?}.",
+            SimpleCast::checksum_address(&proxy)?
+        );
+        let resp: EtherscanProxyResponse = client
+            .post(&api_url)
+            .form(&[
+                ("module", "contract"),
+                ("action", "verifyproxycontract"),
+                ("address", &format!("{:?}", proxy)),
+                ("expectedimplementation", &format!("{:?}", implementation)),
+                ("apikey", api_key),
+            ])
+            .send()
+            .await
+            .wrap_err("Failed to submit proxy verification")?
+            .json()
+            .await
+            .wrap_err("Failed to parse the proxy verification response")?;
+
+        if resp.status == "0" {
+            warn!("Failed proxy verification submission: {:?}", resp);
+            eyre::bail!(
+                "Encountered an error verifying the proxy:\nResponse: `{}`\nDetails: `{}`",
+                resp.message,
+                resp.result
+            )
+        }
+        eprintln!("Submitted proxy for verification:\n\tGUID: `{}`", resp.result);
+
+        let guid = resp.result;
+        let retry: Retry = RETRY_CHECK_ON_VERIFY.into();
+        let status = retry
             .run_async(|| {
                 async {
-                    let resp = etherscan
-                        .check_contract_verification_status(args.id.clone())
+                    let resp: EtherscanProxyResponse = client
+                        .get(&api_url)
+                        .query(&[
+                            ("module", "contract"),
+                            ("action", "checkproxyverification"),
+                            ("guid", &guid),
+                            ("apikey", api_key),
+                        ])
+                        .send()
+                        .await
+                        .wrap_err("Failed to request proxy verification status")?
+                        .json()
                         .await
-                        .wrap_err("Failed to request verification status")?;
+                        .wrap_err("Failed to parse the proxy verification status")?;
 
                     if resp.status == "0" {
-                        if resp.result == "Already Verified" {
-                            println!("Contract source code already verified");
-                            return Ok(())
-                        }
-
-                        if resp.result == "Pending in queue" {
-                            return Err(eyre!("Verification is still pending...",))
+                        if resp.result.contains("Pending") {
+                            return Err(eyre!("Proxy verification is still pending..."))
                         }
-
-                        eprintln!(
-                            "Contract verification failed:\nResponse: `{}`\nDetails: `{}`",
-                            resp.message, resp.result
-                        );
-                        std::process::exit(1);
+                        return Ok(VerificationStatus::Failed(format!(
+                            "Proxy verification failed: {}",
+                            resp.result
+                        )))
                     }
 
-                    println!("Contract successfully verified");
-                    Ok(())
+                    eprintln!("Proxy successfully verified: {}", resp.result);
+                    Ok(VerificationStatus::Verified)
                 }
                 .boxed()
             })
             .await
-            .wrap_err("Checking verification result failed:")
+            .wrap_err("Checking proxy verification result failed:")?;
+
+        Ok(VerificationResult {
+            guid: Some(guid),
+            status,
+            url: Some(etherscan.address_url(proxy)),
+            already_verified: false,
+        })
+    }
+
+    /// Requests the status of the submission `id` from the given etherscan compatible API
+    pub(super) async fn status_with_client(
+        &self,
+        etherscan: &Client,
+        id: &str,
+    ) -> eyre::Result<VerificationStatus> {
+        Ok(self.result_with_client(etherscan, id).await?.status)
+    }
+
+    /// Requests the status of the submission `id` once and returns it as a
+    /// [VerificationResult]
+    async fn result_with_client(
+        &self,
+        etherscan: &Client,
+        id: &str,
+    ) -> eyre::Result<VerificationResult> {
+        let resp = etherscan
+            .check_contract_verification_status(id.to_string())
+            .await
+            .wrap_err("Failed to request verification status")?;
+
+        let (status, already_verified) = match (resp.status.as_str(), resp.result.as_str()) {
+            ("0", "Already Verified") => (VerificationStatus::Verified, true),
+            ("0", "Pending in queue") => (VerificationStatus::Pending, false),
+            ("0", _) => (VerificationStatus::Failed(resp.result), false),
+            _ => (VerificationStatus::Verified, false),
+        };
+        Ok(VerificationResult { guid: Some(id.to_string()), status, url: None, already_verified })
+    }
+
+    /// Checks the verification status on the given etherscan compatible API
+    pub(super) async fn check_with_client(
+        &self,
+        etherscan: &Client,
+        args: VerifyCheckArgs,
+    ) -> eyre::Result<VerificationResult> {
+        eprintln!("Waiting for verification result...");
+        for attempt in 1..=args.retry.retries {
+            match self.result_with_client(etherscan, args.id()).await {
+                // a failed verification is final and not retried
+                Ok(result) if result.status != VerificationStatus::Pending => return Ok(result),
+                Ok(_) => warn!("verification of {} is still pending", args.id()),
+                // failed requests are retried like pending submissions
+                Err(err) if attempt < args.retry.retries => {
+                    warn!("failed to request the status of {}: {}", args.id(), err)
+                }
+                Err(err) => return Err(err),
+            }
+            if attempt < args.retry.retries {
+                tokio::time::sleep(Duration::from_secs(args.retry.delay.into())).await;
+            }
+        }
+        // only JSON output reports a pending result, with its own exit code
+        if !args.json {
+            eyre::bail!(
+                "Verification of {} is still pending after {} attempts",
+                args.id(),
+                args.retry.retries
+            )
+        }
+        Ok(VerificationResult::pending(args.id(), None))
     }
-}
 
-impl EtherscanVerificationProvider {
     /// Creates the `VerifyContract` etherescan request in order to verify the contract
     ///
     /// If `--flatten` is set to `true` then this will send with [`CodeFormat::SingleFile`]
@@ -198,6 +426,7 @@ impl EtherscanVerificationProvider {
             self.standard_json_source(args, &project, &contract_path, &compiler_version)?
         };
 
+        let version = compiler_version.clone();
         let compiler_version = ensure_solc_build_metadata(compiler_version).await?;
         let compiler_version = format!("v{}", compiler_version);
         let mut verify_args =
@@ -205,14 +434,41 @@ impl EtherscanVerificationProvider {
                 .constructor_arguments(args.constructor_args.clone())
                 .code_format(code_format);
 
+        // the standard json input contains the settings of the project, a single file needs them to
+        // be submitted separately
         if code_format == CodeFormat::SingleFile {
             verify_args = if let Some(optimizations) = args.num_of_optimizations {
                 verify_args.optimized().runs(optimizations as u32)
             } else if config.optimizer {
+                eprintln!(
+                    "Using the optimizer runs of the project config: {}",
+                    config.optimizer_runs
+                );
                 verify_args.optimized().runs(config.optimizer_runs.try_into()?)
             } else {
+                eprintln!("Using the optimizer settings of the project config: not optimized");
                 verify_args.not_optimized()
             };
+
+            if let Some(evm_version) = config.evm_version.normalize_version(&version) {
+                eprintln!("Using the EVM version of the project config: {}", evm_version);
+                verify_args = verify_args.evm_version(evm_version.to_string());
+            }
+
+            // etherscan expects the linked libraries as numbered `libraryname`/`libraryaddress`
+            // pairs
+            let libraries =
+                config.parsed_libraries()?.libs.into_values().flatten().collect::<Vec<_>>();
+            if libraries.len() > 10 {
+                eyre::bail!(
+                    "Etherscan supports at most 10 linked libraries for flattened sources, found {}",
+                    libraries.len()
+                )
+            }
+            for (i, (name, address)) in libraries.into_iter().enumerate() {
+                verify_args.other.insert(format!("libraryname{}", i + 1), name);
+                verify_args.other.insert(format!("libraryaddress{}", i + 1), address);
+            }
         }
 
         Ok(verify_args)
@@ -222,6 +478,7 @@ impl EtherscanVerificationProvider {
     /// The priority desc:
     ///     1. Through CLI arg `--compiler-version`
     ///     2. `solc` defined in foundry.toml
+    ///     3. The version the contract was compiled with, from the cache
     fn compiler_version(
         &self,
         args: &VerifyArgs,
@@ -234,32 +491,48 @@ impl EtherscanVerificationProvider {
 
         if let Some(ref solc) = config.solc {
             match solc {
-                SolcReq::Version(version) => return Ok(version.to_owned()),
+                SolcReq::Version(version) => {
+                    eprintln!("Using the compiler version of the project config: v{}", version);
+                    return Ok(version.to_owned())
+                }
                 SolcReq::Local(solc) => {
                     if solc.is_file() {
-                        return Ok(Solc::new(solc).version()?)
+                        let version = Solc::new(solc).version()?;
+                        eprintln!("Using the compiler version of the project config: v{}", version);
+                        return Ok(version)
                     }
                 }
             }
         }
 
         if let Some((_, entry)) = entry {
-            let artifacts = entry.artifacts_versions().collect::<Vec<_>>();
-            if artifacts.len() == 1 {
-                let mut version = artifacts[0].0.to_owned();
+            // the versions the contract was compiled with, or if it has no artifacts, the versions
+            // of all artifacts of its source file
+            let versions = match entry.artifacts.get(&args.contract.name) {
+                Some(artifacts) if !artifacts.is_empty() => {
+                    artifacts.keys().collect::<BTreeSet<_>>()
+                }
+                _ => entry.artifacts_versions().map(|(version, _)| version).collect(),
+            };
+            if versions.len() == 1 {
+                let mut version = versions.into_iter().next().unwrap().to_owned();
                 version.build = match RE_BUILD_COMMIT.captures(version.build.as_str()) {
                     Some(cap) => BuildMetadata::new(cap.name("commit").unwrap().as_str())?,
                     _ => BuildMetadata::EMPTY,
                 };
+                eprintln!(
+                    "Auto-detected compiler version v{} from the artifact of {}",
+                    version, args.contract.name
+                );
                 return Ok(version)
             }
 
-            if artifacts.is_empty() {
+            if versions.is_empty() {
                 warn!("no artifacts detected")
             } else {
                 warn!(
                     "ambiguous compiler versions found in cache: {}",
-                    artifacts.iter().map(|a| a.0.to_string()).collect::<Vec<_>>().join(", ")
+                    versions.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(", ")
                 );
             }
         }
@@ -364,6 +637,16 @@ To skip this solc dry, pass `--force`.
         target: &Path,
         version: &Version,
     ) -> eyre::Result<(String, String, CodeFormat)> {
+        if let Some(runs) = args.num_of_optimizations {
+            let project_runs = project.solc_config.settings.optimizer.runs;
+            if project_runs != Some(runs) {
+                warn!(
+                    "ignoring `--num-of-optimizations {}`, the standard json input uses the project's optimizer settings",
+                    runs
+                );
+            }
+        }
+
         let mut input: StandardJsonCompilerInput = project
             .standard_json_input(target)
             .wrap_err("Failed to get standard json input")?
@@ -401,6 +684,43 @@ fn strip_build_meta(version: Version) -> Version {
     }
 }
 
+/// Response of etherscan's proxy verification endpoints
+#[derive(Debug, Deserialize)]
+struct EtherscanProxyResponse {
+    status: String,
+    message: String,
+    result: String,
+}
+
+/// Reads the implementation address of the EIP-1967 proxy at `proxy`
+async fn proxy_implementation<M: Middleware>(provider: &M, proxy: Address) -> eyre::Result<Address>
+where
+    M::Error: 'static,
+{
+    let slot = H256::from_str(proxy::EIP1967_IMPLEMENTATION_SLOT)?;
+    let value = provider.get_storage_at(proxy, slot, None).await?;
+    let implementation = Address::from_slice(&value.as_bytes()[12..]);
+    if implementation.is_zero() {
+        eyre::bail!(
+            "{:?} doesn't look like a supported proxy: its EIP-1967 implementation slot is empty",
+            proxy
+        )
+    }
+    Ok(implementation)
+}
+
+/// Returns the url of the etherscan compatible API to use for `chain`
+fn api_url(chain: Chain, verifier_url: Option<&str>) -> eyre::Result<String> {
+    if let Some(api_url) = verifier_url {
+        return Ok(api_url.to_string())
+    }
+    ethers::types::Chain::try_from(chain)
+        .ok()
+        .and_then(|chain| chain.etherscan_urls())
+        .map(|(api_url, _)| api_url.to_string())
+        .ok_or_else(|| eyre!("No etherscan API url known for chain {}", chain))
+}
+
 /// Given any solc [Version] return a [Version] with build metadata
 ///
 /// # Example
@@ -418,3 +738,16 @@ async fn ensure_solc_build_metadata(version: Version) -> eyre::Result<Version> {
         Ok(lookup_compiler_version(&version).await?)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::{types::U256, utils::keccak256};
+
+    #[test]
+    fn eip1967_implementation_slot_matches_preimage() {
+        let mut slot = [0u8; 32];
+        (U256::from(keccak256("eip1967.proxy.implementation")) - 1).to_big_endian(&mut slot);
+        assert_eq!(H256::from_str(proxy::EIP1967_IMPLEMENTATION_SLOT).unwrap(), H256(slot));
+    }
+}
@@ -1,19 +1,42 @@
 //! Verify contract source
 
-use crate::cmd::RetryArgs;
+use crate::{
+    cmd::{utils, LoadConfig, RetryArgs},
+    compile,
+};
 use async_trait::async_trait;
 use clap::{Parser, ValueHint};
-use ethers::{abi::Address, solc::info::ContractInfo};
-use foundry_config::{impl_figment_convert_basic, Chain};
+use comfy_table::Table;
+use ethers::{
+    abi::{Abi, Address},
+    solc::{artifacts::CompactBytecode, info::ContractInfo, utils::canonicalized},
+};
+use eyre::Context;
+use foundry_config::{
+    figment,
+    figment::{
+        value::{Dict, Map, Value},
+        Metadata, Profile, Provider,
+    },
+    impl_figment_convert, Chain, Config,
+};
+use foundry_utils::parse_tokens;
+use futures::future::join_all;
+use serde::Serialize;
 use std::{
     fmt::{Display, Formatter},
     path::PathBuf,
     str::FromStr,
+    time::Duration,
 };
+use tracing::warn;
 
+use blockscout::BlockscoutVerificationProvider;
 use etherscan::EtherscanVerificationProvider;
 use sourcify::SourcifyVerificationProvider;
 
+mod blockscout;
+mod broadcast;
 mod etherscan;
 mod sourcify;
 
@@ -22,18 +45,60 @@ pub const RETRY_CHECK_ON_VERIFY: RetryArgs = RetryArgs { retries: 6, delay: 10 }
 /// Verification arguments
 #[derive(Debug, Clone, Parser)]
 pub struct VerifyArgs {
-    #[clap(help = "The address of the contract to verify.", value_name = "ADDRESS")]
+    // Both positionals are ignored if `--from-broadcast` is set, in which case they are taken from
+    // the broadcast file for every created contract.
+    #[clap(
+        help = "The address of the contract to verify.",
+        value_name = "ADDRESS",
+        default_value_if(
+            "from_broadcast",
+            None,
+            Some("0x0000000000000000000000000000000000000000")
+        )
+    )]
     pub address: Address,
 
     #[clap(
         help = "The contract identifier in the form `<path>:<contractname>`.",
-        value_name = "CONTRACT"
+        value_name = "CONTRACT",
+        default_value_if("from_broadcast", None, Some("Broadcast"))
     )]
     pub contract: ContractInfo,
 
-    #[clap(long, help = "the encoded constructor arguments", value_name = "ARGS")]
+    #[clap(
+        long,
+        help = "Verify all contracts created in a broadcast file of `forge script`.",
+        long_help = "Verify all contracts created in a broadcast file of `forge script`, e.g. `broadcast/Deploy.s.sol/1/run-latest.json`.\n\nThe address, contract and constructor arguments of every created contract are taken from the broadcast file. Contracts that are already verified are skipped.",
+        value_hint = ValueHint::FilePath,
+        value_name = "PATH",
+        conflicts_with_all = &["constructor_args", "constructor_args_path", "guess_constructor_args", "json"]
+    )]
+    pub from_broadcast: Option<PathBuf>,
+
+    #[clap(
+        long,
+        help = "the encoded constructor arguments",
+        value_name = "ARGS",
+        conflicts_with_all = &["constructor_args_path", "guess_constructor_args"]
+    )]
     pub constructor_args: Option<String>,
 
+    #[clap(
+        long,
+        help = "The path to a file containing the constructor arguments.",
+        value_hint = ValueHint::FilePath,
+        value_name = "FILE",
+        conflicts_with_all = &["constructor_args", "guess_constructor_args"]
+    )]
+    pub constructor_args_path: Option<PathBuf>,
+
+    #[clap(
+        long,
+        help = "Try to extract the constructor arguments from the transaction that created the contract, using etherscan.",
+        conflicts_with_all = &["constructor_args", "constructor_args_path"]
+    )]
+    pub guess_constructor_args: bool,
+
     #[clap(
         long,
         help = "The compiler version used to build the smart contract.",
@@ -67,7 +132,11 @@ pub struct VerifyArgs {
     )]
     pub etherscan_key: Option<String>,
 
-    #[clap(help = "Flatten the source code before verifying.", long = "flatten")]
+    #[clap(
+        help = "Flatten the source code before verifying.",
+        long_help = "Flatten the source code before verifying.\n\nWithout this flag the Solidity Standard JSON Input of the project is submitted. It contains all sources the contract depends on and the project's compiler settings (optimizer, evm version, remappings, libraries), which avoids mismatches in import resolution.",
+        long = "flatten"
+    )]
     pub flatten: bool,
 
     #[clap(
@@ -80,6 +149,29 @@ pub struct VerifyArgs {
     #[clap(long, help = "Wait for verification result after submission")]
     pub watch: bool,
 
+    #[clap(
+        long,
+        help = "Print the verification result as JSON.",
+        long_help = "Print the verification result as JSON.\n\nThe exit code is 0 if the contract is verified, 2 if the verification is still pending and 1 if it failed."
+    )]
+    pub json: bool,
+
+    #[clap(
+        long,
+        help = "Verify the implementation of the EIP-1967 proxy at ADDRESS and link it to the proxy.",
+        long_help = "Verify the implementation of the EIP-1967 proxy at ADDRESS and link it to the proxy.\n\nThe implementation address is read from the proxy's storage, its source code is verified as CONTRACT and the proxy is then marked as a proxy of it on etherscan.",
+        conflicts_with = "from_broadcast"
+    )]
+    pub proxy: bool,
+
+    #[clap(
+        long,
+        env = "ETH_RPC_URL",
+        help = "The RPC endpoint, used to read the implementation address of a proxy.",
+        value_name = "URL"
+    )]
+    pub rpc_url: Option<String>,
+
     #[clap(flatten, help = "Allows to use retry arguments for contract verification")]
     pub retry: RetryArgs,
 
@@ -105,19 +197,161 @@ pub struct VerifyArgs {
         arg_enum,
         long = "verifier",
         help_heading = "Verification Provider",
-        help = "Contract verification provider to use `sourcify` or `etherscan`",
+        help = "Contract verification provider to use `etherscan`, `sourcify` or `blockscout`",
         default_value = "etherscan"
     )]
     pub verifier: VerificationProviderType,
+
+    #[clap(
+        long,
+        help_heading = "Verification Provider",
+        help = "The verifier API url, e.g. of a custom etherscan compatible explorer or the API base of a Blockscout instance.",
+        env = "VERIFIER_URL",
+        value_name = "VERIFIER_URL",
+        required_if_eq("verifier", "blockscout")
+    )]
+    pub verifier_url: Option<String>,
 }
 
-impl_figment_convert_basic!(VerifyArgs);
+impl_figment_convert!(VerifyArgs);
+
+// Make this args a `figment::Provider` so that the linked libraries are merged into the `Config`
+// and applied to the verified source
+impl Provider for VerifyArgs {
+    fn metadata(&self) -> Metadata {
+        Metadata::named("Verify Args Provider")
+    }
+
+    fn data(&self) -> Result<Map<Profile, Dict>, figment::Error> {
+        let mut dict = Dict::new();
+        if let Some(ref root) = self.root {
+            dict.insert("root".to_string(), Value::serialize(root)?);
+        }
+        if !self.libraries.is_empty() {
+            dict.insert("libraries".to_string(), Value::serialize(&self.libraries)?);
+        }
+        Ok(Map::from([(Config::selected_profile(), dict)]))
+    }
+}
 
 impl VerifyArgs {
     /// Run the verify command to submit the contract's source code for verification on etherscan
-    pub async fn run(self) -> eyre::Result<()> {
+    pub async fn run(mut self) -> eyre::Result<()> {
+        if let Some(broadcast) = self.from_broadcast.take() {
+            return self.verify_broadcast(&broadcast).await
+        }
+
+        let json = self.json;
+        self.verify().await?.report(json)
+    }
+
+    /// Submits the contract's source code for verification and returns the result
+    pub async fn verify(mut self) -> eyre::Result<VerificationResult> {
+        if self.proxy && !matches!(self.verifier, VerificationProviderType::Etherscan) {
+            eyre::bail!("Proxies can only be verified with the etherscan verifier")
+        }
+
+        self.constructor_args = self.resolve_constructor_args().await?;
         self.verifier.client().verify(self).await
     }
+
+    /// Returns the ABI encoded constructor arguments.
+    ///
+    /// These are either passed directly, read from `--constructor-args-path` or, if
+    /// `--guess-constructor-args` is set, extracted from the contract's creation transaction.
+    async fn resolve_constructor_args(&self) -> eyre::Result<Option<String>> {
+        if let Some(ref constructor_args_path) = self.constructor_args_path {
+            let (abi, _) = self.compile_contract()?;
+            let constructor = abi.constructor.ok_or_else(|| {
+                eyre::eyre!("Contract {} does not have a constructor", self.contract.name)
+            })?;
+
+            let args = utils::read_constructor_args_file(constructor_args_path)?;
+            if args.len() != constructor.inputs.len() {
+                eyre::bail!(
+                    "Expected {} constructor arguments, but found {} in \"{}\"",
+                    constructor.inputs.len(),
+                    args.len(),
+                    constructor_args_path.display()
+                );
+            }
+
+            let params = constructor
+                .inputs
+                .iter()
+                .zip(&args)
+                .map(|(input, arg)| (&input.kind, arg.as_str()))
+                .collect::<Vec<_>>();
+            let tokens = parse_tokens(params, true)?;
+
+            // we only need the encoded arguments, so we pass an empty code to `encode_input`
+            return Ok(Some(hex::encode(constructor.encode_input(Vec::new(), &tokens)?)))
+        }
+
+        if self.guess_constructor_args {
+            return Ok(Some(self.guess_constructor_args().await?))
+        }
+
+        Ok(self.constructor_args.clone())
+    }
+
+    /// Fetches the transaction that created the contract from etherscan and returns the
+    /// constructor arguments, which are appended to the contract's creation code.
+    async fn guess_constructor_args(&self) -> eyre::Result<String> {
+        let (_, bytecode) = self.compile_contract()?;
+        let bytecode = bytecode.object.into_bytes().ok_or_else(|| {
+            eyre::eyre!("Contract {} has unlinked library references", self.contract.name)
+        })?;
+
+        let etherscan = EtherscanVerificationProvider.client(
+            self.chain,
+            self.verifier_url.as_deref(),
+            self.etherscan_key.as_deref().unwrap_or_default(),
+        )?;
+
+        eprintln!("Fetching the creation transaction of {:?}...", self.address);
+        let creation_tx = etherscan
+            .get_transactions(&self.address, None)
+            .await
+            .wrap_err("Failed to fetch the transactions of the contract")?
+            .into_iter()
+            .find(|tx| tx.contract_address == Some(self.address))
+            .ok_or_else(|| {
+                eyre::eyre!(
+                    "Could not find the transaction that created {:?}. Contracts created by other contracts are not supported.",
+                    self.address
+                )
+            })?;
+        let input = creation_tx
+            .input
+            .value()
+            .ok_or_else(|| eyre::eyre!("The creation transaction has no input data"))?;
+
+        if !input.starts_with(&bytecode) {
+            eyre::bail!(
+                "The creation code of {:?} does not match the compiled contract {}, the constructor arguments can not be extracted",
+                self.address,
+                self.contract.name
+            );
+        }
+
+        Ok(hex::encode(&input[bytecode.len()..]))
+    }
+
+    /// Compiles the project and returns the ABI and creation code of the contract to verify
+    fn compile_contract(&self) -> eyre::Result<(Abi, CompactBytecode)> {
+        let project = self.load_config_emit_warnings().project()?;
+        let mut output = compile::suppress_compile(&project)?;
+
+        let mut contract = self.contract.clone();
+        if let Some(ref mut path) = contract.path {
+            // paths are absolute in the project's output
+            *path = canonicalized(project.root().join(path.as_str())).to_string_lossy().to_string();
+        }
+
+        let (abi, bytecode, _) = utils::remove_contract(&mut output, &contract)?;
+        Ok((abi, bytecode))
+    }
 }
 
 /// Check verification status arguments
@@ -125,9 +359,11 @@ impl VerifyArgs {
 pub struct VerifyCheckArgs {
     #[clap(
         help = "The verification ID. For Etherscan - Submission GUID. For Sourcify - Contract Address",
-        value_name = "ID"
+        long_help = "The verification ID. For Etherscan - Submission GUID. For Sourcify - Contract Address\n\nIf multiple IDs are given, all of them are polled concurrently and a summary of their status is printed.",
+        value_name = "ID",
+        required = true
     )]
-    id: String,
+    ids: Vec<String>,
 
     #[clap(
         long,
@@ -154,16 +390,219 @@ pub struct VerifyCheckArgs {
     #[clap(
         long = "verifier",
         help_heading = "Verification Provider",
-        help = "Contract verification provider to use `sourcify` or `etherscan`",
+        help = "Contract verification provider to use `etherscan`, `sourcify` or `blockscout`",
         default_value = "etherscan"
     )]
     pub verifier: VerificationProviderType,
+
+    #[clap(
+        long,
+        help_heading = "Verification Provider",
+        help = "The verifier API url, e.g. of a custom etherscan compatible explorer or the API base of a Blockscout instance.",
+        env = "VERIFIER_URL",
+        value_name = "VERIFIER_URL",
+        required_if_eq("verifier", "blockscout")
+    )]
+    pub verifier_url: Option<String>,
+
+    #[clap(
+        long,
+        help = "Print the verification results as JSON.",
+        long_help = "Print the verification results as JSON.\n\nThe exit code is 0 if all contracts are verified, 2 if a verification is still pending and 1 if one failed."
+    )]
+    pub json: bool,
 }
 
 impl VerifyCheckArgs {
     /// Run the verify command to submit the contract's source code for verification on etherscan
     pub async fn run(self) -> eyre::Result<()> {
-        self.verifier.client().check(self).await
+        let json = self.json;
+        if self.ids.len() > 1 {
+            let results = self.check_all().await?;
+            return report_all(&results, json)
+        }
+        self.verifier.client().check(self).await?.report(json)
+    }
+
+    /// The verification ID of a single submission, checked by [VerificationProvider::check]
+    fn id(&self) -> &str {
+        &self.ids[0]
+    }
+
+    /// Polls the status of all submissions concurrently until none of them is pending or the
+    /// retries are exhausted.
+    async fn check_all(self) -> eyre::Result<Vec<VerificationResult>> {
+        let provider = self.verifier.client();
+        let mut statuses = vec![VerificationStatus::Pending; self.ids.len()];
+
+        eprintln!("Waiting for the verification results of {} submissions...", self.ids.len());
+        for attempt in 1..=self.retry.retries {
+            let pending = (0..self.ids.len())
+                .filter(|i| statuses[*i] == VerificationStatus::Pending)
+                .collect::<Vec<_>>();
+            let results =
+                join_all(pending.iter().map(|i| provider.status(&self, &self.ids[*i]))).await;
+
+            for (i, result) in pending.into_iter().zip(results) {
+                statuses[i] = match result {
+                    Ok(status) => status,
+                    // failed requests are retried like pending submissions
+                    Err(err) if attempt < self.retry.retries => {
+                        warn!("failed to request the status of {}: {}", self.ids[i], err);
+                        VerificationStatus::Pending
+                    }
+                    Err(err) => VerificationStatus::Failed(err.to_string()),
+                };
+            }
+
+            if !statuses.contains(&VerificationStatus::Pending) {
+                break
+            }
+            if attempt < self.retry.retries {
+                tokio::time::sleep(Duration::from_secs(self.retry.delay.into())).await;
+            }
+        }
+
+        Ok(self
+            .ids
+            .into_iter()
+            .zip(statuses)
+            .map(|(id, status)| VerificationResult {
+                guid: Some(id),
+                status,
+                url: None,
+                already_verified: false,
+            })
+            .collect())
+    }
+}
+
+/// Prints the results of multiple submissions, either as a JSON array or as a summary table.
+///
+/// In JSON mode the process exits with the code of the worst result, see
+/// [VerificationResult::exit_code].
+fn report_all(results: &[VerificationResult], json: bool) -> eyre::Result<()> {
+    if json {
+        println!("{}", serde_json::to_string(results)?);
+        let code = results.iter().map(VerificationResult::exit_code).max().unwrap_or_default();
+        if code != 0 {
+            std::process::exit(code)
+        }
+        return Ok(())
+    }
+
+    let mut table = Table::new();
+    table.set_header(vec!["ID", "Status"]);
+    for result in results {
+        table.add_row(vec![result.guid.clone().unwrap_or_default(), result.status.to_string()]);
+    }
+    println!("{}", table);
+
+    let pending =
+        results.iter().filter(|result| result.status == VerificationStatus::Pending).count();
+    if pending > 0 {
+        println!("{} submission(s) are still pending", pending);
+    }
+    let failed = results
+        .iter()
+        .filter(|result| matches!(result.status, VerificationStatus::Failed(_)))
+        .count();
+    if failed > 0 {
+        eyre::bail!("Verification failed for {} submission(s)", failed)
+    }
+    Ok(())
+}
+
+/// The outcome of a verification submission or status check
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct VerificationResult {
+    /// The ID of the submission, the GUID for etherscan and the contract address for sourcify
+    pub guid: Option<String>,
+    #[serde(flatten)]
+    pub status: VerificationStatus,
+    /// The explorer URL of the contract, if known
+    pub url: Option<String>,
+    /// Whether the contract was already verified before it was submitted
+    pub already_verified: bool,
+}
+
+impl VerificationResult {
+    /// The result of a submission that is still pending
+    pub fn pending(guid: impl Into<String>, url: Option<String>) -> Self {
+        Self {
+            guid: Some(guid.into()),
+            status: VerificationStatus::Pending,
+            url,
+            already_verified: false,
+        }
+    }
+
+    /// The result of a contract that was verified before it was submitted
+    pub fn already_verified(url: Option<String>) -> Self {
+        Self { guid: None, status: VerificationStatus::Verified, url, already_verified: true }
+    }
+
+    /// The process exit code for this result: `0` if verified, `1` if failed and `2` if pending
+    pub fn exit_code(&self) -> i32 {
+        match self.status {
+            VerificationStatus::Verified => 0,
+            VerificationStatus::Failed(_) => 1,
+            VerificationStatus::Pending => 2,
+        }
+    }
+
+    /// Prints the result.
+    ///
+    /// In JSON mode the result is printed as a JSON object and the process exits with
+    /// [Self::exit_code] unless the contract is verified. Otherwise a failed verification is
+    /// returned as an error.
+    fn report(&self, json: bool) -> eyre::Result<()> {
+        if json {
+            println!("{}", serde_json::to_string(self)?);
+            if self.exit_code() != 0 {
+                std::process::exit(self.exit_code())
+            }
+            return Ok(())
+        }
+
+        match self.status {
+            VerificationStatus::Verified if self.already_verified => {
+                println!("Contract source code already verified")
+            }
+            VerificationStatus::Verified => println!("Contract successfully verified"),
+            VerificationStatus::Pending => {
+                println!("Verification is pending:");
+                if let Some(ref guid) = self.guid {
+                    println!("\tGUID: `{}`", guid);
+                }
+                if let Some(ref url) = self.url {
+                    println!("\tURL: {}", url);
+                }
+            }
+            VerificationStatus::Failed(ref reason) => {
+                eyre::bail!("Contract verification failed:\nDetails: `{}`", reason)
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The status of a verification submission
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", content = "reason", rename_all = "lowercase")]
+pub enum VerificationStatus {
+    Verified,
+    Pending,
+    Failed(String),
+}
+
+impl Display for VerificationStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VerificationStatus::Verified => f.write_str("verified"),
+            VerificationStatus::Pending => f.write_str("pending"),
+            VerificationStatus::Failed(reason) => write!(f, "failed: {}", reason),
+        }
     }
 }
 
@@ -171,6 +610,7 @@ impl VerifyCheckArgs {
 pub enum VerificationProviderType {
     Etherscan,
     Sourcify,
+    Blockscout,
 }
 
 impl VerificationProviderType {
@@ -178,14 +618,29 @@ impl VerificationProviderType {
         match self {
             VerificationProviderType::Etherscan => Box::new(EtherscanVerificationProvider),
             VerificationProviderType::Sourcify => Box::new(SourcifyVerificationProvider),
+            VerificationProviderType::Blockscout => Box::new(BlockscoutVerificationProvider),
         }
     }
 }
 
 #[async_trait]
 pub trait VerificationProvider {
-    async fn verify(&self, args: VerifyArgs) -> eyre::Result<()>;
-    async fn check(&self, args: VerifyCheckArgs) -> eyre::Result<()>;
+    /// Submits the contract for verification and, if `args.watch` is set, waits for the result.
+    async fn verify(&self, args: VerifyArgs) -> eyre::Result<VerificationResult>;
+
+    /// Polls the status of the submission `args.id()` until it's no longer pending or the retries
+    /// are exhausted.
+    async fn check(&self, args: VerifyCheckArgs) -> eyre::Result<VerificationResult>;
+
+    /// Requests the current status of the submission `id` once, without retrying.
+    async fn status(&self, args: &VerifyCheckArgs, id: &str) -> eyre::Result<VerificationStatus>;
+
+    /// Returns whether the contract at `args.address` is already verified.
+    ///
+    /// Providers that can't tell return `false`, so the contract is submitted again.
+    async fn is_verified(&self, _args: &VerifyArgs) -> eyre::Result<bool> {
+        Ok(false)
+    }
 }
 
 impl FromStr for VerificationProviderType {
@@ -195,6 +650,7 @@ impl FromStr for VerificationProviderType {
         match s {
             "e" | "etherscan" => Ok(VerificationProviderType::Etherscan),
             "s" | "sourcify" => Ok(VerificationProviderType::Sourcify),
+            "b" | "blockscout" => Ok(VerificationProviderType::Blockscout),
             _ => Err(format!("Unknown field: {s}")),
         }
     }
@@ -209,6 +665,9 @@ impl Display for VerificationProviderType {
             VerificationProviderType::Sourcify => {
                 write!(f, "sourcify")?;
             }
+            VerificationProviderType::Blockscout => {
+                write!(f, "blockscout")?;
+            }
         };
         Ok(())
     }
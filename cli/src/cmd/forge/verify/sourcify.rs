@@ -10,7 +10,9 @@ use tracing::{trace, warn};
 
 use crate::cmd::LoadConfig;
 
-use super::{VerificationProvider, VerifyArgs, VerifyCheckArgs};
+use super::{
+    VerificationProvider, VerificationResult, VerificationStatus, VerifyArgs, VerifyCheckArgs,
+};
 
 pub static SOURCIFY_URL: &str = "https://sourcify.dev/server/";
 
@@ -39,7 +41,7 @@ pub struct SourcifyVerificationProvider;
 
 #[async_trait]
 impl VerificationProvider for SourcifyVerificationProvider {
-    async fn verify(&self, args: VerifyArgs) -> eyre::Result<()> {
+    async fn verify(&self, args: VerifyArgs) -> eyre::Result<VerificationResult> {
         let config = args.load_config_emit_warnings();
         let project = config.project()?;
 
@@ -85,7 +87,7 @@ impl VerificationProvider for SourcifyVerificationProvider {
         let resp = retry
             .run_async(|| {
                 async {
-                    println!(
+                    eprintln!(
                         "\nSubmitting verification for [{}] {:?}.",
                         args.contract.name,
                         SimpleCast::checksum_address(&args.address)?
@@ -117,11 +119,10 @@ impl VerificationProvider for SourcifyVerificationProvider {
             })
             .await?;
 
-        self.process_sourcify_response(resp.map(|r| r.result));
-        Ok(())
+        Ok(self.process_sourcify_response(&body.address, resp.map(|r| r.result)))
     }
 
-    async fn check(&self, args: VerifyCheckArgs) -> eyre::Result<()> {
+    async fn check(&self, args: VerifyCheckArgs) -> eyre::Result<VerificationResult> {
         let retry: Retry = args.retry.into();
         let resp = retry
             .run_async(|| {
@@ -129,7 +130,7 @@ impl VerificationProvider for SourcifyVerificationProvider {
                     let url = format!(
                         "{}check-by-addresses?addresses={}&chainIds={}",
                         SOURCIFY_URL,
-                        args.id,
+                        args.id(),
                         args.chain.id(),
                     );
 
@@ -148,27 +149,57 @@ impl VerificationProvider for SourcifyVerificationProvider {
             })
             .await?;
 
-        self.process_sourcify_response(resp);
-        Ok(())
+        Ok(self.process_sourcify_response(args.id(), resp))
+    }
+
+    async fn status(&self, args: &VerifyCheckArgs, id: &str) -> eyre::Result<VerificationStatus> {
+        let url = format!(
+            "{}check-by-addresses?addresses={}&chainIds={}",
+            SOURCIFY_URL,
+            id,
+            args.chain.id(),
+        );
+
+        let response = reqwest::get(url).await?.error_for_status()?;
+        let response = response
+            .json::<Vec<SourcifyResponseElement>>()
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| eyre::eyre!("Empty response from sourcify"))?;
+
+        Ok(sourcify_status(&response.status))
     }
 }
 
 impl SourcifyVerificationProvider {
-    fn process_sourcify_response(&self, response: Option<Vec<SourcifyResponseElement>>) {
+    /// Converts the sourcify response for the contract at `address` into a [VerificationResult]
+    fn process_sourcify_response(
+        &self,
+        address: &str,
+        response: Option<Vec<SourcifyResponseElement>>,
+    ) -> VerificationResult {
         let response = response.unwrap().remove(0);
-        if response.status == "perfect" {
-            if let Some(ts) = response.storage_timestamp {
-                println!("Contract source code already verified. Storage Timestamp: {ts}");
-            } else {
-                println!("Contract successfully verified")
-            }
-        } else if response.status == "partial" {
-            println!("The recompiled contract partially matches the deployed version")
-        } else if response.status == "false" {
-            println!("Contract source code is not verified")
-        } else {
-            eprintln!("Unknown status from sourcify. Status: {}", response.status);
-            std::process::exit(1);
+        if response.status == "partial" {
+            eprintln!("The recompiled contract partially matches the deployed version")
+        }
+        VerificationResult {
+            guid: Some(address.to_string()),
+            status: sourcify_status(&response.status),
+            url: None,
+            // sourcify only reports a storage timestamp for contracts it already knew
+            already_verified: response.status == "perfect" && response.storage_timestamp.is_some(),
         }
     }
 }
+
+/// Maps the match status reported by sourcify to a [VerificationStatus]
+///
+/// Sourcify verifies synchronously, so there are no pending submissions.
+fn sourcify_status(status: &str) -> VerificationStatus {
+    match status {
+        "perfect" | "partial" => VerificationStatus::Verified,
+        "false" => VerificationStatus::Failed("not verified".to_string()),
+        status => VerificationStatus::Failed(format!("unknown status `{}`", status)),
+    }
+}
@@ -10,14 +10,18 @@ use ethers::{
         artifacts::{CompactBytecode, CompactDeployedBytecode, ContractBytecodeSome},
         cache::{CacheEntry, SolFilesCache},
         info::ContractInfo,
+        utils::read_json_file,
         Artifact, ProjectCompileOutput,
     },
 };
 use forge::executor::opts::EvmOpts;
-use foundry_common::{ContractsByArtifact, TestFunctionExt};
+use foundry_common::{fs, ContractsByArtifact, TestFunctionExt};
 use foundry_config::{figment::Figment, Chain as ConfigChain, Config};
 use foundry_utils::Retry;
-use std::{collections::BTreeMap, path::PathBuf};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
 use yansi::Paint;
 
 /// Common trait for all cli commands
@@ -151,6 +155,32 @@ impl From<RetryArgs> for Retry {
     }
 }
 
+/// Reads the constructor arguments from a file.
+///
+/// Files with a `.json` extension must contain a json array of arguments, all other files are
+/// expected to contain whitespace separated arguments.
+pub fn read_constructor_args_file(constructor_args_path: &Path) -> eyre::Result<Vec<String>> {
+    if !constructor_args_path.exists() {
+        eyre::bail!("Constructor args file \"{}\" not found", constructor_args_path.display());
+    }
+    let args = if constructor_args_path.extension() == Some(std::ffi::OsStr::new("json")) {
+        match read_json_file(constructor_args_path) {
+            Ok(args) => args,
+            Err(err) => eyre::bail!(
+                "Constructor args file \"{}\" must encode a json array: \"{}\"",
+                constructor_args_path.display(),
+                err
+            ),
+        }
+    } else {
+        fs::read_to_string(constructor_args_path)?
+            .split_whitespace()
+            .map(str::to_string)
+            .collect::<Vec<String>>()
+    };
+    Ok(args)
+}
+
 /// Returns error if constructor has arguments.
 pub fn ensure_clean_constructor(abi: &Abi) -> eyre::Result<()> {
     if let Some(constructor) = &abi.constructor {
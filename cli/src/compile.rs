@@ -24,7 +24,7 @@ pub fn compile(
 }
 
 // https://eips.ethereum.org/EIPS/eip-170
-const CONTRACT_SIZE_LIMIT: usize = 24576;
+pub const CONTRACT_SIZE_LIMIT: usize = 24576;
 
 pub struct SizeReport {
     pub contracts: BTreeMap<String, ContractInfo>,
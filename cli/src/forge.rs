@@ -109,6 +109,9 @@ fn main() -> eyre::Result<()> {
                 cmd.run()?;
             }
         }
+        Subcommands::GasDiff(cmd) => {
+            cmd.run()?;
+        }
         Subcommands::Fmt(cmd) => {
             cmd.run()?;
         }
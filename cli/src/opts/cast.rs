@@ -1,15 +1,32 @@
 use super::{ClapChain, EthereumOpts, TransactionOpts};
 use crate::{
-    cmd::cast::{
-        estimate::EstimateArgs, find_block::FindBlockArgs, rpc::RpcArgs, run::RunArgs,
-        wallet::WalletSubcommands,
+    cmd::{
+        cast::{
+            code::{CodeSizeArgs, CreationCodeArgs, RuntimeCodeArgs},
+            estimate::EstimateArgs,
+            find_block::FindBlockArgs,
+            logs::LogsArgs,
+            rpc::RpcArgs,
+            run::RunArgs,
+            selectors::SelectorsArgs,
+            storage::StorageArgs,
+            storage_layout::StorageLayoutArgs,
+            txpool::TxPoolSubcommands,
+            wallet::WalletSubcommands,
+        },
+        forge::cache::CacheArgs,
     },
-    utils::parse_u256,
+    utils::{parse_ether_value, parse_u256},
 };
+use cast::SimpleCast;
 use clap::{Parser, Subcommand, ValueHint};
 use ethers::{
     abi::ethabi::ethereum_types::BigEndianHash,
-    types::{serde_helpers::Numeric, Address, BlockId, BlockNumber, NameOrAddress, H256, U256},
+    providers::Middleware,
+    types::{
+        serde_helpers::Numeric, Address, Block, BlockId, BlockNumber, Bytes, NameOrAddress, H256,
+        U256,
+    },
 };
 use std::{path::PathBuf, str::FromStr};
 
@@ -51,6 +68,12 @@ pub enum Subcommands {
     FromUtf8 {
         #[clap(value_name = "TEXT")]
         text: Option<String>,
+        #[clap(
+            long,
+            help = "Read the inputs from stdin, one per line, and print one result per line.",
+            conflicts_with = "text"
+        )]
+        stdin: bool,
     },
     #[clap(name = "--to-hex")]
     #[clap(visible_aliases = &["to-hex", "th", "2h"])]
@@ -58,6 +81,12 @@ pub enum Subcommands {
     ToHex {
         #[clap(value_name = "DECIMAL")]
         decimal: Option<String>,
+        #[clap(
+            long,
+            help = "Read the inputs from stdin, one per line, and print one result per line.",
+            conflicts_with = "decimal"
+        )]
+        stdin: bool,
     },
     #[clap(name = "--concat-hex")]
     #[clap(visible_aliases = &["concat-hex", "ch"])]
@@ -88,10 +117,25 @@ The input can be:
     },
     #[clap(name = "--to-checksum-address")]
     #[clap(visible_aliases = &["to-checksum-address", "--to-checksum", "to-checksum", "ta", "2a"])] // Compatibility with dapptools' cast
-    #[clap(about = "Convert an address to a checksummed format (EIP-55).")]
+    #[clap(
+        about = "Convert an address to a checksummed format (EIP-55).",
+        long_about = "Convert an address to a checksummed format (EIP-55).\n\nWith --chain-id, the checksum is salted with the chain id according to EIP-1191. A warning is printed if the input is mixed-case but its checksum is invalid."
+    )]
     ToCheckSumAddress {
         #[clap(value_name = "ADDRESS")]
-        address: Option<Address>,
+        address: Option<String>,
+        #[clap(
+            long,
+            help = "Salt the checksum with the chain id (EIP-1191).",
+            value_name = "CHAIN_ID"
+        )]
+        chain_id: Option<u64>,
+        #[clap(
+            long,
+            help = "Read the inputs from stdin, one per line, and print one result per line.",
+            conflicts_with = "address"
+        )]
+        stdin: bool,
     },
     #[clap(name = "--to-ascii")]
     #[clap(visible_aliases = &["to-ascii", "tas", "2as"])]
@@ -116,6 +160,12 @@ The input can be:
     ToBytes32 {
         #[clap(value_name = "BYTES")]
         bytes: Option<String>,
+        #[clap(
+            long,
+            help = "Read the inputs from stdin, one per line, and print one result per line.",
+            conflicts_with = "bytes"
+        )]
+        stdin: bool,
     },
     #[clap(name = "--to-dec")]
     #[clap(visible_aliases = &["to-dec", "td", "2d"])]
@@ -123,6 +173,12 @@ The input can be:
     ToDec {
         #[clap(value_name = "HEXVALUE")]
         hexvalue: Option<String>,
+        #[clap(
+            long,
+            help = "Read the inputs from stdin, one per line, and print one result per line.",
+            conflicts_with = "hexvalue"
+        )]
+        stdin: bool,
     },
     #[clap(name = "--to-fix")]
     #[clap(visible_aliases = &["to-fix", "tf", "2f"])]
@@ -155,15 +211,15 @@ The input can be:
         value: String,
         #[clap(value_name = "BITS")]
         bits: String,
-        #[clap(long = "--base-in", help = "The input base")]
-        base_in: Option<String>,
+        #[clap(long = "--base-in", help = "The input base", parse(try_from_str = parse_base))]
+        base_in: Option<u32>,
         #[clap(
             long = "--base-out",
             help = "The output base",
             default_value = "16",
             parse(try_from_str = parse_base)
         )]
-        base_out: String,
+        base_out: u32,
     },
     #[clap(name = "shr")]
     #[clap(about = "Perform a right shifting operation")]
@@ -172,38 +228,74 @@ The input can be:
         value: String,
         #[clap(value_name = "BITS")]
         bits: String,
-        #[clap(long = "--base-in", help = "The input base")]
-        base_in: Option<String>,
+        #[clap(long = "--base-in", help = "The input base", parse(try_from_str = parse_base))]
+        base_in: Option<u32>,
         #[clap(
             long = "--base-out",
             help = "The output base",
             default_value = "16",
             parse(try_from_str = parse_base)
         )]
-        base_out: String,
+        base_out: u32,
+    },
+    #[clap(name = "to-base")]
+    #[clap(visible_aliases = &["--to-base", "--to-radix", "tr", "2r"])]
+    #[clap(
+        about = "Convert a number from one base to another, for bases 2 to 36.",
+        long_about = r#"Convert a number from one base to another, for bases 2 to 36.
+
+Bases may be given as a number or as bin, oct, dec or hex. If no input base is given, it is detected from a 0b, 0o or 0x prefix, and the value is read as decimal otherwise."#
+    )]
+    ToBase {
+        #[clap(value_name = "VALUE")]
+        value: String,
+        #[clap(
+            long = "from",
+            help = "The input base.",
+            value_name = "BASE",
+            parse(try_from_str = parse_base)
+        )]
+        base_in: Option<u32>,
+        #[clap(
+            long = "to",
+            help = "The output base.",
+            value_name = "BASE",
+            parse(try_from_str = parse_base)
+        )]
+        base_out: u32,
     },
     #[clap(name = "--to-unit")]
     #[clap(visible_aliases = &["to-unit", "tun", "2un"])]
     #[clap(
-        about = "Convert an ETH amount into another unit (ether, gwei or wei).",
-        long_about = r#"Convert an ETH amount into another unit (ether, gwei or wei).\
+        about = "Convert an ETH amount into another unit (ether, gwei, wei, ...) or a token amount with --decimals.",
+        long_about = r#"Convert an ETH amount into another unit (ether, finney, szabo, gwei, mwei, kwei or wei) or a token amount with --decimals.\
+
+The amount is in wei, unless it is tagged with a unit.
 
 Examples:
 - 1ether wei
 - "1 ether" wei
 - 1ether
 - 1 gwei
-- 1gwei ether"#
+- 1gwei ether
+- 1.5finney szabo
+- 1234567 --decimals 6"#
     )]
     ToUnit {
         #[clap(value_name = "VALUE")]
         value: Option<String>,
         #[clap(
-            help = "The unit to convert to (ether, gwei, wei).",
+            help = "The unit to convert to (ether, finney, szabo, gwei, mwei, kwei, wei).",
             default_value = "wei",
             value_name = "UNIT"
         )]
         unit: String,
+        #[clap(
+            long,
+            help = "Convert to an amount with the given number of decimals instead of a unit, e.g. 6 for USDC.",
+            value_name = "DECIMALS"
+        )]
+        decimals: Option<u32>,
     },
     #[clap(name = "--to-wei")]
     #[clap(visible_aliases = &["to-wei", "tw", "2w"])]
@@ -212,8 +304,18 @@ Examples:
         #[clap(allow_hyphen_values = true, value_name = "VALUE")]
         // negative values not yet supported internally
         value: Option<String>,
-        #[clap(value_name = "UNIT")]
+        #[clap(
+            help = "The unit of the amount (ether, finney, szabo, gwei, mwei, kwei, wei).",
+            value_name = "UNIT"
+        )]
         unit: Option<String>,
+        #[clap(
+            long,
+            help = "The number of decimals of the amount instead of a unit, e.g. 6 for USDC.",
+            value_name = "DECIMALS",
+            conflicts_with = "unit"
+        )]
+        decimals: Option<u32>,
     },
     #[clap(name = "--from-wei")]
     #[clap(visible_aliases = &["from-wei", "fw"])]
@@ -222,15 +324,42 @@ Examples:
         #[clap(allow_hyphen_values = true, value_name = "VALUE")]
         // negative values not yet supported internally
         value: Option<String>,
-        #[clap(value_name = "UNIT")]
+        #[clap(
+            help = "The unit to convert to (ether, finney, szabo, gwei, mwei, kwei, wei).",
+            value_name = "UNIT"
+        )]
         unit: Option<String>,
+        #[clap(
+            long,
+            help = "The number of decimals to convert to instead of a unit, e.g. 6 for USDC.",
+            value_name = "DECIMALS",
+            conflicts_with = "unit"
+        )]
+        decimals: Option<u32>,
     },
     #[clap(name = "--to-rlp")]
-    #[clap(about = "RLP encodes hex data, or an array of hex data")]
-    ToRlp { value: Option<String> },
+    #[clap(
+        about = "RLP encodes a value, or nested lists of values.",
+        long_about = r#"RLP encodes a value, or nested lists of values.
+
+The input is JSON. Lists are encoded as RLP lists, 0x prefixed strings as raw bytes and numbers or unprefixed decimal strings as integers, i.e. as their big endian bytes without leading zeros. This is the output format of --from-rlp, so its output can be encoded again."#
+    )]
+    ToRlp {
+        #[clap(value_name = "VALUE")]
+        value: Option<String>,
+    },
     #[clap(name = "--from-rlp")]
     #[clap(about = "Decodes RLP encoded data. Input must be hexadecimal.")]
-    FromRlp { value: Option<String> },
+    FromRlp {
+        #[clap(value_name = "VALUE")]
+        value: Option<String>,
+        #[clap(
+            long,
+            help = "Interpret the decoded byte strings as integers.",
+            long_help = "Interpret the decoded byte strings of up to 32 bytes as big endian integers. By default, they are output as hex."
+        )]
+        as_int: bool,
+    },
     #[clap(name = "access-list")]
     #[clap(visible_aliases = &["ac", "acl"])]
     #[clap(about = "Create an access list for a transaction.")]
@@ -250,6 +379,11 @@ Examples:
             value_name = "BLOCK"
         )]
         block: Option<BlockId>,
+        #[clap(
+            long,
+            help = "Also estimate the gas of the transaction with and without the access list."
+        )]
+        with_gas: bool,
         #[clap(flatten)]
         // TODO: We only need RPC URL + etherscan stuff from this struct
         eth: EthereumOpts,
@@ -262,11 +396,11 @@ Examples:
     Block {
         #[clap(
             help = "The block height you want to query at.",
-            long_help = "The block height you want to query at. Can also be the tags earliest, latest, or pending.",
-            parse(try_from_str = parse_block_id),
+            long_help = "The block height you want to query at. Can also be the tags earliest, latest, pending, safe, or finalized.",
+            parse(try_from_str = parse_block_spec),
             value_name = "BLOCK"
         )]
-        block: BlockId,
+        block: BlockSpec,
         #[clap(
             help = "If specified, only get the given field of the block.",
             value_name = "FIELD"
@@ -298,6 +432,87 @@ Examples:
         args: Vec<String>,
         #[clap(long, short, help = "the block you want to query, can also be earliest/latest/pending", parse(try_from_str = parse_block_id), value_name = "BLOCK")]
         block: Option<BlockId>,
+        #[clap(
+            long,
+            help_heading = "BLOCK RANGE",
+            help = "Sample the call at every --step blocks, starting at this block.",
+            long_help = "Sample the call at every --step blocks, starting at this block.\n\nEvery sample is printed as a row of the block number and the decoded return values, separated by tabs. With --json, the samples are printed as an array of objects with the fields `block` and `result`.",
+            requires = "to-block",
+            conflicts_with_all = &["block", "trace"],
+            value_name = "BLOCK"
+        )]
+        from_block: Option<u64>,
+        #[clap(
+            long,
+            help_heading = "BLOCK RANGE",
+            help = "The last block to sample the call at.",
+            requires = "from-block",
+            value_name = "BLOCK"
+        )]
+        to_block: Option<u64>,
+        #[clap(
+            long,
+            help_heading = "BLOCK RANGE",
+            help = "The number of blocks between two samples.",
+            default_value = "1",
+            value_name = "BLOCKS"
+        )]
+        step: u64,
+        #[clap(
+            long,
+            help_heading = "STATE OVERRIDES",
+            help = "Override the balance of an account for the call.",
+            long_help = "Override the balance of an account for the call, e.g. `0x6B175474E89094C44Da98b954EedeAC495271d0F:1ether`.",
+            parse(try_from_str = parse_balance_override),
+            value_name = "ADDRESS:BALANCE"
+        )]
+        override_balance: Vec<(Address, U256)>,
+        #[clap(
+            long,
+            help_heading = "STATE OVERRIDES",
+            help = "Override the code of an account for the call.",
+            parse(try_from_str = parse_code_override),
+            value_name = "ADDRESS:CODE"
+        )]
+        override_code: Vec<(Address, Bytes)>,
+        #[clap(
+            long,
+            help_heading = "STATE OVERRIDES",
+            help = "Override a storage slot of an account for the call.",
+            long_help = "Override a storage slot of an account for the call. The slot and value can be passed as decimal or hex numbers, e.g. `0x6B175474E89094C44Da98b954EedeAC495271d0F:0:0x1`.",
+            parse(try_from_str = parse_state_override),
+            value_name = "ADDRESS:SLOT:VALUE"
+        )]
+        override_state: Vec<(Address, H256, H256)>,
+        #[clap(
+            long,
+            help = "Execute the call on a fork of the block and print its trace.",
+            long_help = "Execute the call on a fork of the block and print its trace.\n\nThe call is executed locally with the state fetched from the RPC, and the calls, events and errors of all contracts in the trace are decoded like in `cast run`.",
+            conflicts_with_all = &["override-balance", "override-code", "override-state"]
+        )]
+        trace: bool,
+        #[clap(
+            long,
+            help = "Labels address in the trace. 0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045:vitalik.eth",
+            requires = "trace",
+            value_name = "LABEL"
+        )]
+        label: Vec<String>,
+        #[clap(
+            long,
+            help = "Identifies contracts in the trace with the artifacts of the local project.",
+            requires = "trace"
+        )]
+        with_local_artifacts: bool,
+        #[clap(
+            long = "json",
+            short = 'j',
+            help_heading = "DISPLAY OPTIONS",
+            help = "Print the decoded return values as JSON.",
+            long_help = "Print the decoded return values as JSON.\n\nThe values are an object keyed by the names of the return values if all of them are named in the signature, e.g. `balanceOf(address)(uint256 balance)`, otherwise an array. Integers are printed as strings, tuples and arrays as nested arrays.",
+            conflicts_with = "trace"
+        )]
+        to_json: bool,
         #[clap(flatten)]
         eth: EthereumOpts,
     },
@@ -336,7 +551,17 @@ Examples:
     },
     #[clap(name = "compute-address")]
     #[clap(visible_alias = "ca")]
-    #[clap(about = "Compute the contract address from a given nonce and deployer address.")]
+    #[clap(
+        about = "Compute the contract address from a given nonce and deployer address.",
+        long_about = r#"Compute the contract address from a given nonce and deployer address.
+
+With --salt and --init-code, the address of a contract deployed with CREATE2 by the deployer is computed instead.
+
+Examples:
+- cast compute-address 0xdead.. --nonce 5
+- cast compute-address 0xdead.. --salt 0x01 --init-code 0x6080..
+- cast compute-address 0xdead.. --salt 0x01 --init-code @out/Counter.bin"#
+    )]
     ComputeAddress {
         #[clap(long, env = "ETH_RPC_URL", value_name = "URL")]
         rpc_url: Option<String>,
@@ -344,6 +569,21 @@ Examples:
         address: String,
         #[clap(long, help = "The nonce of the deployer address.", parse(try_from_str = parse_u256), value_name = "NONCE")]
         nonce: Option<U256>,
+        #[clap(
+            long,
+            help = "The CREATE2 salt, left padded to 32 bytes.",
+            requires = "init-code",
+            conflicts_with = "nonce",
+            value_name = "SALT"
+        )]
+        salt: Option<String>,
+        #[clap(
+            long,
+            help = "The CREATE2 init code as hex, or a file containing it prefixed with @.",
+            requires = "salt",
+            value_name = "HEX|@FILE"
+        )]
+        init_code: Option<String>,
     },
     #[clap(name = "namehash")]
     #[clap(visible_aliases = &["na", "nh"])]
@@ -362,6 +602,30 @@ Examples:
         field: Option<String>,
         #[clap(long = "json", short = 'j', help_heading = "DISPLAY OPTIONS")]
         to_json: bool,
+        #[clap(
+            long,
+            help = "Decode the input data of the transaction.",
+            long_help = r#"Decode the input data of the transaction.
+
+The function signature is taken from --sig, the ABI of the verified contract on Etherscan or, if neither is available, looked up on https://sig.eth.samczsun.com."#,
+            conflicts_with_all = &["field", "to_json"]
+        )]
+        decode: bool,
+        #[clap(
+            long,
+            help = "The function signature to decode the input data with, e.g. transfer(address,uint256). Implies --decode.",
+            value_name = "SIG",
+            conflicts_with_all = &["field", "to_json"]
+        )]
+        sig: Option<String>,
+        #[clap(
+            long,
+            help = "Print the raw, signed transaction reconstructed from its fields and signature.",
+            conflicts_with_all = &["field", "to_json", "decode", "sig"]
+        )]
+        raw: bool,
+        #[clap(long, env = "ETHERSCAN_API_KEY", value_name = "KEY")]
+        etherscan_api_key: Option<String>,
         #[clap(long, env = "ETH_RPC_URL", value_name = "URL")]
         rpc_url: Option<String>,
     },
@@ -389,6 +653,13 @@ Examples:
             help = "Exit immediately if the transaction was not found."
         )]
         cast_async: bool,
+        #[clap(
+            long,
+            help = "Also print the fee paid by the transaction.",
+            long_help = "Also print the fee paid by the transaction, in wei and ETH. For EIP-1559 transactions the fee is split into the base fee and priority fee portions.",
+            conflicts_with = "field"
+        )]
+        cost: bool,
         #[clap(long = "json", short = 'j', help_heading = "DISPLAY OPTIONS")]
         to_json: bool,
         #[clap(long, env = "ETH_RPC_URL", value_name = "URL")]
@@ -436,6 +707,93 @@ Examples:
             conflicts_with = "nonce"
         )]
         resend: bool,
+        #[clap(
+            long,
+            help = "Use the next nonce including the pending transactions of the sender.",
+            long_help = "Use the next nonce including the pending transactions of the sender, so the transaction does not replace one that is still in flight.\n\nThis depends on the node's view of the mempool: transactions the node hasn't seen yet, or has dropped, are not included.",
+            conflicts_with_all = &["nonce", "resend"]
+        )]
+        pending_nonce: bool,
+        #[clap(
+            long,
+            help = "Simulate the transaction on a fork of the latest block before sending it.",
+            long_help = "Simulate the transaction on a fork of the latest block before sending it. The transaction is executed once locally, its return data and the gas it used are printed, and it is not sent if it would revert."
+        )]
+        simulate: bool,
+        #[clap(
+            long,
+            help = "Only simulate the transaction, without sending it. Implies --simulate."
+        )]
+        dry_run: bool,
+        #[clap(
+            long,
+            help = "Attach a generated access list to the transaction if it reduces the gas estimate.",
+            long_help = "Attach a generated access list to the transaction if it reduces the gas estimate.\n\nThe access list is created with `eth_createAccessList`, and the gas of the transaction is estimated both with and without it before signing.",
+            conflicts_with = "legacy"
+        )]
+        access_list_auto: bool,
+        #[clap(
+            long,
+            help = "Attach the generated access list even if it does not reduce the gas estimate. Implies --access-list-auto.",
+            conflicts_with = "legacy"
+        )]
+        force_access_list: bool,
+        #[clap(
+            long,
+            help = "Attach the content of this file as a blob. Can be passed multiple times.",
+            long_help = "Attach the content of this file as a blob. Can be passed multiple times.\n\nBlob transactions (EIP-4844) are not supported yet: the blobs are checked against the size and count limits, and the transaction is rejected before anything is signed.",
+            value_name = "FILE"
+        )]
+        blob: Vec<PathBuf>,
+    },
+    #[clap(name = "mktx")]
+    #[clap(visible_alias = "m")]
+    #[clap(
+        about = "Build and sign a transaction without publishing it.",
+        long_about = r#"Build and sign a transaction without publishing it.
+
+Prints the signed, RLP encoded raw transaction, which can be published later with `cast publish`. Fields that are not specified, e.g. the nonce or the gas limit, are fetched from the node once when the transaction is built."#
+    )]
+    MakeTx {
+        #[clap(
+            help = "The destination of the transaction.",
+            parse(try_from_str = parse_name_or_address),
+            value_name = "TO"
+        )]
+        to: NameOrAddress,
+        #[clap(help = "The signature of the function to call.", value_name = "SIG")]
+        sig: Option<String>,
+        #[clap(help = "The arguments of the function to call.", value_name = "ARGS")]
+        args: Vec<String>,
+        #[clap(flatten, next_help_heading = "TRANSACTION OPTIONS")]
+        tx: TransactionOpts,
+        #[clap(flatten, next_help_heading = "ETHEREUM OPTIONS")]
+        eth: EthereumOpts,
+    },
+    #[clap(name = "decode-transaction")]
+    #[clap(visible_aliases = &["decode-tx", "dt"])]
+    #[clap(
+        about = "Decode a raw signed transaction.",
+        long_about = r#"Decode a raw signed transaction.
+
+Decodes legacy, EIP-2930 and EIP-1559 transactions and recovers the sender from the signature. With --sig, the input data is decoded with the given function signature."#
+    )]
+    DecodeTransaction {
+        #[clap(help = "The raw signed transaction.", value_name = "RAW_TX")]
+        raw_tx: Option<String>,
+        #[clap(
+            long,
+            help = "The function signature to decode the input data with, e.g. transfer(address,uint256).",
+            value_name = "SIG"
+        )]
+        sig: Option<String>,
+        #[clap(
+            long = "json",
+            short = 'j',
+            help_heading = "DISPLAY OPTIONS",
+            conflicts_with = "sig"
+        )]
+        to_json: bool,
     },
     #[clap(name = "publish")]
     #[clap(visible_alias = "p")]
@@ -451,6 +809,14 @@ Examples:
             help = "Only print the transaction hash and exit immediately."
         )]
         cast_async: bool,
+        #[clap(
+            short,
+            long,
+            help = "The number of confirmations until the receipt is fetched.",
+            default_value = "1",
+            value_name = "CONFIRMATIONS"
+        )]
+        confirmations: usize,
         // FIXME: We only need the RPC URL and `--flashbots` options from this.
         #[clap(flatten)]
         eth: EthereumOpts,
@@ -468,8 +834,20 @@ Examples:
             value_name = "SIG"
         )]
         sig: String,
-        #[clap(help = "The ABI-encoded calldata.", value_name = "CALLDATA")]
-        calldata: String,
+        #[clap(
+            help = "The ABI-encoded calldata.",
+            value_name = "CALLDATA",
+            required_unless_present = "file",
+            conflicts_with = "file"
+        )]
+        calldata: Option<String>,
+        #[clap(
+            long,
+            help = "Decode every line of a file, printing a separator between the results.",
+            value_hint = ValueHint::FilePath,
+            value_name = "PATH"
+        )]
+        file: Option<PathBuf>,
     },
     #[clap(name = "--abi-decode")]
     #[clap(visible_alias = "ad")]
@@ -485,8 +863,20 @@ Defaults to decoding output data. To decode input data pass --input or use cast
             value_name = "SIG"
         )]
         sig: String,
-        #[clap(help = "The ABI-encoded calldata.", value_name = "CALLDATA")]
-        calldata: String,
+        #[clap(
+            help = "The ABI-encoded calldata.",
+            value_name = "CALLDATA",
+            required_unless_present = "file",
+            conflicts_with = "file"
+        )]
+        calldata: Option<String>,
+        #[clap(
+            long,
+            help = "Decode every line of a file, printing a separator between the results.",
+            value_hint = ValueHint::FilePath,
+            value_name = "PATH"
+        )]
+        file: Option<PathBuf>,
         #[clap(long, short, help = "Decode input data.")]
         input: bool,
     },
@@ -494,11 +884,21 @@ Defaults to decoding output data. To decode input data pass --input or use cast
     #[clap(visible_alias = "ae")]
     #[clap(about = "ABI encode the given function argument, excluding the selector.")]
     AbiEncode {
-        #[clap(help = "The function signature.", value_name = "SIG")]
+        #[clap(
+            help = "The function signature.",
+            long_help = "The function signature.\n\nThe arguments are encoded without a selector, so a constructor signature like `constructor(address,uint256)` can be used to encode constructor arguments, e.g. for `--constructor-args`.",
+            value_name = "SIG"
+        )]
         sig: String,
         #[clap(help = "The arguments of the function.", value_name = "ARGS")]
         #[clap(allow_hyphen_values = true)]
         args: Vec<String>,
+        #[clap(
+            long,
+            help = "Use non-standard packed encoding, like Solidity's abi.encodePacked.",
+            long_help = "Use non-standard packed encoding, like Solidity's abi.encodePacked. Values are concatenated without padding, except for array elements. Tuples and arrays of dynamic types are not supported."
+        )]
+        packed: bool,
     },
     #[clap(name = "index")]
     #[clap(visible_alias = "in")]
@@ -511,6 +911,24 @@ Defaults to decoding output data. To decode input data pass --input or use cast
         #[clap(help = "The storage slot of the mapping.", value_name = "SLOT_NUMBER")]
         slot_number: String,
     },
+    #[clap(name = "index-erc7201")]
+    #[clap(visible_alias = "index7201")]
+    #[clap(
+        about = "Compute the base storage slot of an ERC-7201 namespace.",
+        long_about = r#"Compute the base storage slot of an ERC-7201 namespace.
+
+The slot is keccak256(abi.encode(uint256(keccak256(NAMESPACE)) - 1)) & ~bytes32(uint256(0xff)).
+
+Examples:
+- cast index-erc7201 openzeppelin.storage.ERC20"#
+    )]
+    IndexErc7201 {
+        #[clap(
+            help = "The namespace id, e.g. `openzeppelin.storage.ERC20`.",
+            value_name = "NAMESPACE"
+        )]
+        namespace: String,
+    },
     #[clap(name = "4byte")]
     #[clap(visible_aliases = &["4", "4b"])]
     #[clap(
@@ -519,6 +937,17 @@ Defaults to decoding output data. To decode input data pass --input or use cast
     FourByte {
         #[clap(help = "The function selector.", value_name = "SELECTOR")]
         selector: String,
+        #[clap(
+            long,
+            help = "Only use the local signature database, without querying https://sig.eth.samczsun.com."
+        )]
+        offline: bool,
+        #[clap(
+            long,
+            help = "Get the custom error signatures for the selector.",
+            long_help = "Get the custom error signatures for the selector.\n\nThe full revert data can be passed instead of the selector, in which case it is decoded with every matching error signature."
+        )]
+        error: bool,
     },
     #[clap(name = "4byte-decode")]
     #[clap(visible_aliases = &["4d", "4bd"])]
@@ -526,15 +955,112 @@ Defaults to decoding output data. To decode input data pass --input or use cast
     FourByteDecode {
         #[clap(help = "The ABI-encoded calldata.", value_name = "CALLDATA")]
         calldata: Option<String>,
+        #[clap(
+            long,
+            help = "Only use the local signature database, without querying https://sig.eth.samczsun.com."
+        )]
+        offline: bool,
+        #[clap(long, help = "Decode the data as revert data of a custom error.")]
+        error: bool,
+    },
+    #[clap(name = "decode-error")]
+    #[clap(visible_alias = "derr")]
+    #[clap(
+        about = "Decode ABI-encoded revert data.",
+        long_about = r#"Decode ABI-encoded revert data.
+
+Error(string) is decoded to its message and Panic(uint256) to its code and the meaning of the code. Any other selector is looked up as custom error using https://sig.eth.samczsun.com."#
+    )]
+    DecodeError {
+        #[clap(help = "The ABI-encoded revert data.", value_name = "DATA")]
+        data: String,
+        #[clap(
+            long,
+            help = "Only use the local signature database, without querying https://sig.eth.samczsun.com."
+        )]
+        offline: bool,
     },
     #[clap(name = "4byte-event")]
     #[clap(visible_aliases = &["4e", "4be"])]
     #[clap(
-        about = "Get the event signature for a given topic 0 from https://sig.eth.samczsun.com."
+        about = "Get the event signature for a given topic 0 from https://sig.eth.samczsun.com.",
+        long_about = r#"Get the event signature for a given topic 0 from https://sig.eth.samczsun.com.
+
+With --decode, the log given by topic 0, --topics and --data is decoded with every signature that fits it. If several signatures decode the log, they are listed by number to select one.
+
+Examples:
+- cast 4byte-event 0xddf2..
+- cast 4byte-event 0xddf2.. --decode --topics 0x..,0x.. --data 0x.."#
     )]
     FourByteEvent {
         #[clap(help = "Topic 0", value_name = "TOPIC_0")]
         topic: String,
+        #[clap(
+            long,
+            help = "Only use the local signature database, without querying https://sig.eth.samczsun.com."
+        )]
+        offline: bool,
+        #[clap(long, help = "Decode the log with the resolved signature.")]
+        decode: bool,
+        #[clap(
+            long,
+            help = "The topics of the log following topic 0, separated by commas.",
+            use_value_delimiter = true,
+            requires = "decode",
+            value_name = "TOPICS"
+        )]
+        topics: Vec<String>,
+        #[clap(
+            long,
+            help = "The data of the log.",
+            default_value = "0x",
+            requires = "decode",
+            value_name = "HEX"
+        )]
+        data: String,
+    },
+    #[clap(name = "decode-event")]
+    #[clap(visible_aliases = &["de"])]
+    #[clap(
+        about = "Decode the topics and data of a log.",
+        long_about = r#"Decode the topics and data of a log.
+
+Indexed parameters are decoded from the topics, all other parameters from the data.
+
+If no event signature is given, the signature is looked up by the first topic on https://sig.eth.samczsun.com and the log is decoded with every matching signature.
+
+Examples:
+- cast decode-event --sig "Transfer(address indexed from, address indexed to, uint256 value)" --topics 0xddf2..,0x..,0x.. --data 0x..
+- cast decode-event --topics 0xddf2..,0x..,0x.. --data 0x..
+- cast decode-event --anonymous --sig "Deposit(address indexed from, uint256 value)" --topics 0x.. --data 0x..""#
+    )]
+    DecodeEvent {
+        #[clap(
+            long,
+            help = "The event signature, e.g. `Transfer(address indexed from, address indexed to, uint256 value)`.",
+            value_name = "EVENT_SIG"
+        )]
+        sig: Option<String>,
+        #[clap(
+            long,
+            help = "The topics of the log, separated by commas.",
+            use_value_delimiter = true,
+            value_name = "TOPICS"
+        )]
+        topics: Vec<String>,
+        #[clap(long, help = "The data of the log.", default_value = "0x", value_name = "HEX")]
+        data: String,
+        #[clap(
+            long,
+            help = "Decode the log as anonymous event, without a signature topic.",
+            requires = "sig"
+        )]
+        anonymous: bool,
+        #[clap(
+            long,
+            help = "Only use the local signature database, without querying https://sig.eth.samczsun.com."
+        )]
+        offline: bool,
     },
     #[clap(name = "upload-signature")]
     #[clap(visible_aliases = &["ups"])]
@@ -576,29 +1102,60 @@ Tries to decode the calldata using https://sig.eth.samczsun.com unless --offline
             long,
             short = 'B',
             help = "The block height you want to query at.",
-            long_help = "The block height you want to query at. Can also be the tags earliest, latest, or pending.",
-            parse(try_from_str = parse_block_id),
+            long_help = "The block height you want to query at. Can also be the tags earliest, latest, pending, safe, or finalized.",
+            parse(try_from_str = parse_block_spec),
             value_name = "BLOCK"
         )]
-        block: Option<BlockId>,
+        block: Option<BlockSpec>,
+        #[clap(
+            long,
+            help = "Print the age of the block relative to now, e.g. \"3 days, 4 hours ago\"."
+        )]
+        human: bool,
+        #[clap(
+            long,
+            help = "Estimate the average block time between this block and the queried block.",
+            parse(try_from_str = parse_block_spec),
+            conflicts_with = "human",
+            value_name = "BLOCK"
+        )]
+        since: Option<BlockSpec>,
         #[clap(short, long, env = "ETH_RPC_URL", value_name = "URL")]
         rpc_url: Option<String>,
     },
     #[clap(name = "balance")]
     #[clap(visible_alias = "b")]
-    #[clap(about = "Get the balance of an account in wei.")]
+    #[clap(
+        about = "Get the balance of an account in wei.",
+        long_about = r#"Get the balance of an account in wei.
+
+With --erc20, the balance of the given token is returned instead, in units of the token's decimals."#
+    )]
     Balance {
         #[clap(
             long,
             short = 'B',
             help = "The block height you want to query at.",
-            long_help = "The block height you want to query at. Can also be the tags earliest, latest, or pending.",
-            parse(try_from_str = parse_block_id),
+            long_help = "The block height you want to query at. Can also be the tags earliest, latest, pending, safe, or finalized.",
+            parse(try_from_str = parse_block_spec),
             value_name = "BLOCK"
         )]
-        block: Option<BlockId>,
+        block: Option<BlockSpec>,
         #[clap(help = "The account you want to query", parse(try_from_str = parse_name_or_address), value_name = "WHO")]
         who: NameOrAddress,
+        #[clap(
+            long,
+            help = "Get the balance of the given ERC20 token instead.",
+            parse(try_from_str = parse_name_or_address),
+            value_name = "TOKEN"
+        )]
+        erc20: Option<NameOrAddress>,
+        #[clap(
+            long,
+            help = "Print the token balance without adjusting it for the token's decimals.",
+            requires = "erc20"
+        )]
+        raw: bool,
         #[clap(short, long, env = "ETH_RPC_URL", value_name = "URL")]
         rpc_url: Option<String>,
     },
@@ -610,11 +1167,11 @@ Tries to decode the calldata using https://sig.eth.samczsun.com unless --offline
             long,
             short = 'B',
             help = "The block height you want to query at.",
-            long_help = "The block height you want to query at. Can also be the tags earliest, latest, or pending.",
-            parse(try_from_str = parse_block_id),
+            long_help = "The block height you want to query at. Can also be the tags earliest, latest, pending, safe, or finalized.",
+            parse(try_from_str = parse_block_spec),
             value_name = "BLOCK"
         )]
-        block: Option<BlockId>,
+        block: Option<BlockSpec>,
         #[clap(short, long, env = "ETH_RPC_URL", value_name = "URL")]
         rpc_url: Option<String>,
     },
@@ -636,19 +1193,113 @@ Tries to decode the calldata using https://sig.eth.samczsun.com unless --offline
         #[clap(short, long, env = "ETH_RPC_URL", value_name = "URL")]
         rpc_url: Option<String>,
     },
+    #[clap(
+        name = "implementation",
+        visible_alias = "impl",
+        about = "Get the implementation address of a proxy.",
+        long_about = "Get the implementation address of a proxy.\n\nRecognizes EIP-1967 proxies, including beacon proxies, EIP-1822 (UUPS) proxies and OpenZeppelin's transparent proxies prior to EIP-1967."
+    )]
+    Implementation {
+        #[clap(
+            long,
+            short = 'B',
+            help = "The block height you want to query at.",
+            long_help = "The block height you want to query at. Can also be the tags earliest, latest, pending, safe, or finalized.",
+            parse(try_from_str = parse_block_spec),
+            value_name = "BLOCK"
+        )]
+        block: Option<BlockSpec>,
+        #[clap(help = "The proxy address.", parse(try_from_str = parse_name_or_address), value_name = "PROXY")]
+        proxy: NameOrAddress,
+        #[clap(long, help = "Get the admin address of the proxy instead.")]
+        admin: bool,
+        #[clap(
+            long,
+            help = "Also print the name of the implementation, if it is verified on Etherscan.",
+            conflicts_with = "admin"
+        )]
+        name: bool,
+        #[clap(flatten)]
+        chain: ClapChain,
+        #[clap(long, env = "ETHERSCAN_API_KEY", value_name = "KEY")]
+        etherscan_api_key: Option<String>,
+        #[clap(short, long, env = "ETH_RPC_URL", value_name = "URL")]
+        rpc_url: Option<String>,
+    },
+    #[clap(
+        name = "creation-code",
+        visible_alias = "cc",
+        about = "Get the creation code of a contract of the current project."
+    )]
+    CreationCode(CreationCodeArgs),
+    #[clap(
+        name = "runtime-code",
+        visible_alias = "rc",
+        about = "Get the runtime code of a deployed contract or a contract of the current project."
+    )]
+    RuntimeCode(RuntimeCodeArgs),
+    #[clap(
+        name = "codesize",
+        visible_alias = "cs",
+        about = "Get the runtime code size of a deployed contract or a contract of the current project.",
+        long_about = "Get the runtime code size of a deployed contract or a contract of the current project.\n\nExits with a non-zero code if the size exceeds the limit of 24576 bytes of EIP-170."
+    )]
+    CodeSize(CodeSizeArgs),
     #[clap(name = "gas-price")]
     #[clap(visible_alias = "g")]
     #[clap(about = "Get the current gas price.")]
     GasPrice {
         #[clap(short, long, env = "ETH_RPC_URL", value_name = "URL")]
         rpc_url: Option<String>,
+        #[clap(
+            long,
+            help = "Print the base fees and priority fees of the last BLOCKS blocks instead.",
+            value_name = "BLOCKS"
+        )]
+        history: Option<u64>,
+        #[clap(
+            long,
+            help = "The priority fee percentiles to report with --history.",
+            use_value_delimiter = true,
+            default_value = "25,50,75",
+            requires = "history",
+            value_name = "PERCENTILES"
+        )]
+        percentiles: Vec<f64>,
+        #[clap(long = "json", short = 'j', help_heading = "DISPLAY OPTIONS")]
+        to_json: bool,
     },
     #[clap(name = "keccak")]
     #[clap(visible_alias = "k")]
     #[clap(about = "Hash arbitrary data using keccak-256.")]
     Keccak {
-        #[clap(value_name = "DATA")]
-        data: String,
+        #[clap(
+            help = "The data to hash.",
+            long_help = "The data to hash. Data with a 0x prefix is hashed as hex, anything else as UTF-8 text.",
+            required_unless_present_any = &["file", "stdin"],
+            conflicts_with_all = &["file", "stdin"],
+            value_name = "DATA"
+        )]
+        data: Option<String>,
+        #[clap(
+            long,
+            help = "Hash the contents of a file instead.",
+            value_hint = ValueHint::FilePath,
+            value_name = "PATH"
+        )]
+        file: Option<PathBuf>,
+        #[clap(
+            long,
+            help = "Hash every line of stdin and print one hash per line.",
+            conflicts_with = "file"
+        )]
+        stdin: bool,
+        #[clap(
+            long,
+            help = "Interpret the data as hex bytes, with or without a 0x prefix.",
+            conflicts_with = "file"
+        )]
+        hex: bool,
     },
     #[clap(name = "resolve-name")]
     #[clap(visible_alias = "rn")]
@@ -679,25 +1330,15 @@ Tries to decode the calldata using https://sig.eth.samczsun.com unless --offline
     #[clap(
         name = "storage",
         visible_alias = "st",
-        about = "Get the raw value of a contract's storage slot."
+        about = "Get the raw value of a contract's storage slot, or decode all of its state variables."
     )]
-    Storage {
-        #[clap(help = "The contract address.", parse(try_from_str = parse_name_or_address), value_name = "ADDRESS")]
-        address: NameOrAddress,
-        #[clap(help = "The storage slot number (hex or decimal)", parse(try_from_str = parse_slot), value_name = "SLOT")]
-        slot: H256,
-        #[clap(short, long, env = "ETH_RPC_URL", value_name = "URL")]
-        rpc_url: Option<String>,
-        #[clap(
-            long,
-            short = 'B',
-            help = "The block height you want to query at.",
-            long_help = "The block height you want to query at. Can also be the tags earliest, latest, or pending.",
-            parse(try_from_str = parse_block_id),
-            value_name = "BLOCK"
-        )]
-        block: Option<BlockId>,
-    },
+    Storage(StorageArgs),
+    #[clap(
+        name = "storage-layout",
+        visible_alias = "sl",
+        about = "Print the storage layout of a contract of the current project."
+    )]
+    StorageLayout(StorageLayoutArgs),
     #[clap(
         name = "proof",
         visible_alias = "pr",
@@ -735,6 +1376,13 @@ Tries to decode the calldata using https://sig.eth.samczsun.com unless --offline
         block: Option<BlockId>,
         #[clap(help = "The address you want to get the nonce for.", parse(try_from_str = parse_name_or_address), value_name = "WHO")]
         who: NameOrAddress,
+        #[clap(
+            long,
+            help = "Get the next nonce including the pending transactions of the address.",
+            long_help = "Get the next nonce including the pending transactions of the address, i.e. the nonce at the pending block.\n\nThis depends on the node's view of the mempool: transactions the node hasn't seen yet, or has dropped, are not included.",
+            conflicts_with = "block"
+        )]
+        pending: bool,
         #[clap(short, long, env = "ETH_RPC_URL", value_name = "URL")]
         rpc_url: Option<String>,
     },
@@ -748,14 +1396,36 @@ Tries to decode the calldata using https://sig.eth.samczsun.com unless --offline
         address: String,
         #[clap(short, help = "The output directory to expand source tree into.", value_hint = ValueHint::DirPath, value_name = "DIRECTORY")]
         directory: Option<PathBuf>,
+        #[clap(
+            long,
+            help = "Flatten the sources into a single file, like `forge flatten`.",
+            long_help = "Flatten the sources into a single file, like `forge flatten`.\n\nThe SPDX license identifiers of all sources are consolidated into a single one, and duplicate pragmas are removed.",
+            conflicts_with = "directory"
+        )]
+        flatten: bool,
         #[clap(long, env = "ETHERSCAN_API_KEY", value_name = "KEY")]
         etherscan_api_key: Option<String>,
     },
+    #[clap(
+        name = "cache",
+        about = "Manage the cache of forked RPC state and etherscan responses.",
+        long_about = "Manage the cache of forked RPC state and etherscan responses.\n\nThis is the same cache in ~/.foundry that is written when forking and managed by `forge cache`."
+    )]
+    Cache(CacheArgs),
     #[clap(name = "wallet", visible_alias = "w", about = "Wallet management utilities.")]
     Wallet {
         #[clap(subcommand)]
         command: WalletSubcommands,
     },
+    #[clap(
+        name = "tx-pool",
+        visible_alias = "txpool",
+        about = "Inspect the pending and queued transactions of the node's transaction pool."
+    )]
+    TxPool {
+        #[clap(subcommand)]
+        command: TxPoolSubcommands,
+    },
     #[clap(
         name = "interface",
         visible_alias = "i",
@@ -764,13 +1434,14 @@ Tries to decode the calldata using https://sig.eth.samczsun.com unless --offline
     )]
     Interface {
         #[clap(
-            help = "The contract address, or the path to an ABI file.",
-            long_help = r#"The contract address, or the path to an ABI file.
+            help = "The contract addresses, or the paths to ABI files.",
+            long_help = r#"The contract addresses, or the paths to ABI files.
 
-If an address is specified, then the ABI is fetched from Etherscan."#,
-            value_name = "PATH_OR_ADDRESS"
+If an address is specified, then the ABI is fetched from Etherscan. All generated interfaces are written to a single file."#,
+            value_name = "PATH_OR_ADDRESS",
+            required = true
         )]
-        path_or_address: String,
+        paths_or_addresses: Vec<String>,
         #[clap(
             long,
             short,
@@ -803,6 +1474,11 @@ If an address is specified, then the ABI is fetched from Etherscan."#,
         etherscan_api_key: Option<String>,
         #[clap(flatten)]
         chain: ClapChain,
+        #[clap(
+            long,
+            help = "Keep the first interface when different contracts share a name instead of erroring."
+        )]
+        allow_duplicate_names: bool,
     },
     #[clap(name = "sig", visible_alias = "si", about = "Get the selector for a function.")]
     Sig {
@@ -812,6 +1488,19 @@ If an address is specified, then the ABI is fetched from Etherscan."#,
         )]
         sig: String,
     },
+    #[clap(
+        name = "sig-event",
+        visible_alias = "se",
+        about = "Get the topic hash of an event.",
+        long_about = "Get the topic hash of an event, which is the first topic of its logs unless the event is anonymous."
+    )]
+    SigEvent {
+        #[clap(
+            help = "The event signature, e.g. Transfer(address indexed from, address indexed to, uint256 amount).",
+            value_name = "EVENT_SIG"
+        )]
+        event_sig: String,
+    },
     #[clap(
         name = "find-block",
         visible_alias = "f",
@@ -831,10 +1520,51 @@ If an address is specified, then the ABI is fetched from Etherscan."#,
         about = "Runs a published transaction in a local environment and prints the trace."
     )]
     Run(RunArgs),
+    #[clap(name = "logs")]
+    #[clap(about = "Get logs by signature or topic.")]
+    Logs(LogsArgs),
     #[clap(name = "rpc")]
     #[clap(visible_alias = "rp")]
     #[clap(about = "Perform a raw JSON-RPC request")]
     Rpc(RpcArgs),
+    #[clap(
+        name = "selectors",
+        visible_alias = "sel",
+        about = "Extract the function selectors and event topics from a contract's bytecode."
+    )]
+    Selectors(SelectorsArgs),
+    #[clap(name = "disassemble")]
+    #[clap(visible_alias = "da")]
+    #[clap(
+        about = "Disassemble EVM bytecode into opcodes.",
+        long_about = r#"Disassemble EVM bytecode into opcodes.
+
+Prints one `offset: OPCODE [immediate]` line per instruction. If an address or ENS name is given instead of bytecode, the deployed code is fetched from the RPC. If no code is deployed at the address, or `--raw` is passed, the 20 bytes of the address are disassembled instead."#
+    )]
+    Disassemble {
+        #[clap(
+            help = "The runtime bytecode, or the address or ENS name of a deployed contract.",
+            value_name = "BYTECODE_OR_ADDRESS"
+        )]
+        bytecode: String,
+        #[clap(
+            long,
+            short = 'B',
+            help = "The block height to fetch the code at, if an address is given.",
+            long_help = "The block height to fetch the code at, if an address is given. Can also be the tags earliest, latest, pending, safe, or finalized.",
+            parse(try_from_str = parse_block_spec),
+            value_name = "BLOCK"
+        )]
+        block: Option<BlockSpec>,
+        #[clap(
+            long,
+            help = "Disassemble the input as bytecode, even if it is a valid address.",
+            conflicts_with = "block"
+        )]
+        raw: bool,
+        #[clap(short, long, env = "ETH_RPC_URL", value_name = "URL")]
+        rpc_url: Option<String>,
+    },
     #[clap(name = "--format-bytes32-string")]
     #[clap(about = "Formats a string into bytes32 encoding.")]
     FormatBytes32String {
@@ -867,16 +1597,79 @@ pub fn parse_block_id(s: &str) -> eyre::Result<BlockId> {
     })
 }
 
-fn parse_slot(s: &str) -> eyre::Result<H256> {
+/// A block id which may also use the `safe` and `finalized` tags of post-merge clients.
+///
+/// These tags can't be represented as `BlockNumber`, so they're resolved to the number of the
+/// tagged block by the node before querying.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockSpec {
+    Id(BlockId),
+    Safe,
+    Finalized,
+}
+
+impl BlockSpec {
+    /// Returns the id of the block, resolving the `safe` and `finalized` tags using the provider.
+    ///
+    /// If the node doesn't support the tag, its error is returned as is.
+    pub async fn resolve<M: Middleware>(self, provider: &M) -> eyre::Result<BlockId> {
+        let tag = match self {
+            BlockSpec::Id(id) => return Ok(id),
+            BlockSpec::Safe => "safe",
+            BlockSpec::Finalized => "finalized",
+        };
+        let block: Option<Block<H256>> =
+            provider.provider().request("eth_getBlockByNumber", (tag, false)).await?;
+        let number = block
+            .and_then(|block| block.number)
+            .ok_or_else(|| eyre::eyre!("No {tag} block found"))?;
+        Ok(BlockId::Number(BlockNumber::Number(number)))
+    }
+}
+
+impl From<BlockId> for BlockSpec {
+    fn from(id: BlockId) -> Self {
+        BlockSpec::Id(id)
+    }
+}
+
+pub fn parse_block_spec(s: &str) -> eyre::Result<BlockSpec> {
+    Ok(match s {
+        "safe" => BlockSpec::Safe,
+        "finalized" => BlockSpec::Finalized,
+        s => BlockSpec::Id(parse_block_id(s)?),
+    })
+}
+
+pub fn parse_slot(s: &str) -> eyre::Result<H256> {
     Ok(H256::from_uint(&U256::from(
         Numeric::from_str(s).map_err(|e| eyre::eyre!("Could not parse slot number: {e}"))?,
     )))
 }
 
-fn parse_base(s: &str) -> eyre::Result<String> {
-    Ok(match s {
-        "10" | "dec" => "10".to_string(),
-        "16" | "hex" => "16".to_string(),
-        _ => eyre::bail!("Provided base is not a valid."),
-    })
+fn parse_balance_override(s: &str) -> eyre::Result<(Address, U256)> {
+    let (address, balance) = s
+        .split_once(':')
+        .ok_or_else(|| eyre::eyre!("Expected `<ADDRESS>:<BALANCE>`, got `{s}`"))?;
+    Ok((Address::from_str(address)?, parse_ether_value(balance)?))
+}
+
+fn parse_code_override(s: &str) -> eyre::Result<(Address, Bytes)> {
+    let (address, code) =
+        s.split_once(':').ok_or_else(|| eyre::eyre!("Expected `<ADDRESS>:<CODE>`, got `{s}`"))?;
+    Ok((Address::from_str(address)?, Bytes::from_str(code)?))
+}
+
+fn parse_state_override(s: &str) -> eyre::Result<(Address, H256, H256)> {
+    let mut parts = s.splitn(3, ':');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(address), Some(slot), Some(value)) => {
+            Ok((Address::from_str(address)?, parse_slot(slot)?, parse_slot(value)?))
+        }
+        _ => eyre::bail!("Expected `<ADDRESS>:<SLOT>:<VALUE>`, got `{s}`"),
+    }
+}
+
+fn parse_base(s: &str) -> eyre::Result<u32> {
+    SimpleCast::parse_base(s)
 }
@@ -13,6 +13,7 @@ use crate::cmd::forge::{
     flatten,
     fmt::FmtArgs,
     fourbyte::UploadSelectorsArgs,
+    gas_diff::GasDiffArgs,
     init::InitArgs,
     inspect,
     install::InstallArgs,
@@ -142,6 +143,12 @@ pub enum Subcommands {
     #[clap(visible_alias = "s", about = "Create a snapshot of each test's gas usage.")]
     Snapshot(snapshot::SnapshotArgs),
 
+    #[clap(
+        about = "Compare two JSON gas reports and fail on regressions.",
+        long_about = "Compare two JSON gas reports written by `forge test --gas-report-json`, and exit with a non-zero code if a function's average gas increased by more than the tolerance."
+    )]
+    GasDiff(GasDiffArgs),
+
     #[clap(visible_alias = "co", about = "Display the current config.")]
     Config(config::ConfigArgs),
 
@@ -1,10 +1,11 @@
 use std::{str::FromStr, sync::Arc};
 
 use clap::Parser;
+use eth_keystore::KeystoreError;
 use ethers::{
     middleware::SignerMiddleware,
     prelude::Signer,
-    signers::{coins_bip39::English, Ledger, LocalWallet, MnemonicBuilder, Trezor},
+    signers::{coins_bip39::English, Ledger, LocalWallet, MnemonicBuilder, Trezor, WalletError},
     types::Address,
 };
 use eyre::{eyre, Result};
@@ -48,6 +49,30 @@ impl WalletType {
     }
 }
 
+/// The BIP-44 derivation path of Ethereum accounts, without the account index
+pub const DEFAULT_DERIVATION_PATH: &str = "m/44'/60'/0'/0";
+
+/// Derives the wallet at `index` below the derivation path `path` from a BIP-39 mnemonic,
+/// protected by the optional `password`.
+///
+/// Without a path, the account is derived at [DEFAULT_DERIVATION_PATH], the path used for all
+/// mnemonic signers.
+pub fn wallet_from_mnemonic(
+    phrase: &str,
+    path: Option<&str>,
+    index: u32,
+    password: Option<&str>,
+) -> Result<LocalWallet> {
+    let path = path.unwrap_or(DEFAULT_DERIVATION_PATH).trim_end_matches('/');
+    let mut builder = MnemonicBuilder::<English>::default()
+        .phrase(phrase.trim())
+        .derivation_path(&format!("{path}/{index}"))?;
+    if let Some(password) = password {
+        builder = builder.password(password);
+    }
+    Ok(builder.build()?)
+}
+
 #[derive(Parser, Debug, Default, Clone, Serialize)]
 #[cfg_attr(not(doc), allow(missing_docs))]
 #[cfg_attr(
@@ -114,6 +139,16 @@ pub struct Wallet {
     )]
     pub keystore_password: Option<String>,
 
+    #[clap(
+        long = "password-file",
+        help_heading = "WALLET OPTIONS - KEYSTORE",
+        help = "The file containing the keystore password. Used with --keystore.",
+        requires = "keystore-path",
+        conflicts_with = "keystore-password",
+        value_name = "PATH"
+    )]
+    pub keystore_password_file: Option<String>,
+
     #[clap(
         short,
         long = "ledger",
@@ -163,7 +198,13 @@ impl Wallet {
     }
 
     pub fn keystore(&self) -> Result<Option<LocalWallet>> {
-        self.get_from_keystore(self.keystore_path.as_ref(), self.keystore_password.as_ref())
+        let password = match self.keystore_password_file {
+            Some(ref path) => {
+                Some(fs::read_to_string(path)?.trim_end_matches(&['\r', '\n']).to_string())
+            }
+            None => self.keystore_password.clone(),
+        };
+        self.get_from_keystore(self.keystore_path.as_ref(), password.as_ref())
     }
 
     pub fn mnemonic(&self) -> Result<Option<LocalWallet>> {
@@ -193,7 +234,7 @@ pub trait WalletTrait {
 
     fn get_from_mnemonic(&self, path: &str, index: u32) -> Result<LocalWallet> {
         let mnemonic = fs::read_to_string(path)?.replace('\n', "");
-        Ok(MnemonicBuilder::<English>::default().phrase(mnemonic.as_str()).index(index)?.build()?)
+        wallet_from_mnemonic(&mnemonic, None, index, None)
     }
 
     fn get_from_keystore(
@@ -202,17 +243,28 @@ pub trait WalletTrait {
         keystore_password: Option<&String>,
     ) -> Result<Option<LocalWallet>> {
         Ok(match (keystore_path, keystore_password) {
-            (Some(path), Some(password)) => Some(LocalWallet::decrypt_keystore(path, password)?),
+            (Some(path), Some(password)) => Some(decrypt_keystore(path, password)?),
             (Some(path), None) => {
                 println!("Insert keystore password:");
-                let password = rpassword::read_password().unwrap();
-                Some(LocalWallet::decrypt_keystore(path, password)?)
+                let password = rpassword::read_password()?;
+                Some(decrypt_keystore(path, password)?)
             }
             (None, _) => None,
         })
     }
 }
 
+/// Decrypts the JSON keystore at `path`, with either the scrypt or the pbkdf2 key derivation.
+fn decrypt_keystore(path: &str, password: impl AsRef<[u8]>) -> Result<LocalWallet> {
+    LocalWallet::decrypt_keystore(path, password).map_err(|err| match err {
+        // a MAC mismatch means that the key derived from the password is wrong
+        WalletError::EthKeystoreError(KeystoreError::MacMismatch) => {
+            eyre!("Failed to decrypt keystore `{path}`: incorrect password")
+        }
+        err => eyre!("Failed to decrypt keystore `{path}`: {err}"),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -225,6 +277,7 @@ mod tests {
             private_key: Some("123".to_string()),
             keystore_path: None,
             keystore_password: None,
+            keystore_password_file: None,
             mnemonic_path: None,
             ledger: false,
             trezor: false,
@@ -1,10 +1,10 @@
 use console::Emoji;
 use ethers::{
     abi::token::{LenientTokenizer, Tokenizer},
-    prelude::TransactionReceipt,
+    prelude::{Middleware, TransactionReceipt},
     solc::EvmVersion,
-    types::U256,
-    utils::format_units,
+    types::{Address, NameOrAddress, U256},
+    utils::{format_units, to_checksum},
 };
 use forge::executor::SpecId;
 use foundry_config::Config;
@@ -134,6 +134,38 @@ pub fn consume_config_rpc_url(rpc_url: Option<String>) -> String {
     }
 }
 
+/// The ids of the chains with an ENS registry: mainnet, ropsten, rinkeby, goerli and sepolia
+const ENS_CHAIN_IDS: [u64; 5] = [1, 3, 4, 5, 11155111];
+
+/// Resolves `who` to an address, looking up ENS names with the `provider`.
+///
+/// Names are only resolved on chains with an ENS registry. The resolved address is printed to
+/// stderr, so the output of the command stays the same.
+pub async fn resolve_name_or_address<M: Middleware>(
+    provider: &M,
+    who: NameOrAddress,
+) -> eyre::Result<Address>
+where
+    M::Error: 'static,
+{
+    let name = match who {
+        NameOrAddress::Address(address) => return Ok(address),
+        NameOrAddress::Name(name) => name,
+    };
+
+    let chain_id = provider.get_chainid().await?.as_u64();
+    if !ENS_CHAIN_IDS.contains(&chain_id) {
+        eyre::bail!("Cannot resolve ENS name `{}`, chain {} has no ENS registry", name, chain_id)
+    }
+
+    let address = provider
+        .resolve_name(&name)
+        .await
+        .map_err(|err| eyre::eyre!("Failed to resolve ENS name `{}`: {}", name, err))?;
+    eprintln!("Resolved {} to {}", name, to_checksum(&address, None));
+    Ok(address)
+}
+
 /// Parses an ether value from a string.
 ///
 /// The amount can be tagged with a unit, e.g. "1ether".
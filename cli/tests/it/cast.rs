@@ -1,11 +1,14 @@
 //! Contains various tests for checking cast commands
 
+use anvil::{spawn, NodeConfig};
+use ethers::solc::{Artifact, ConfigurableContractArtifact};
 use foundry_cli_test_utils::{
-    casttest,
+    casttest, forgetest_async,
     util::{TestCommand, TestProject},
 };
+use foundry_config::{Chain, Config};
 use foundry_utils::rpc::next_http_rpc_endpoint;
-use std::{io::Write, path::PathBuf};
+use std::{io::Write, path::PathBuf, time::Duration};
 
 // tests that the `cast block` command works correctly
 casttest!(latest_block, |_: TestProject, mut cmd: TestCommand| {
@@ -23,6 +26,18 @@ casttest!(latest_block, |_: TestProject, mut cmd: TestCommand| {
     assert_eq!(output.trim(), "0x950091817a57e22b6c1f3b951a15f52d41ac89b299cc8f9c89bb6d185f80c415")
 });
 
+// tests that the `cast block` command resolves the post-merge block tags
+casttest!(finalized_block, |_: TestProject, mut cmd: TestCommand| {
+    let eth_rpc_url = next_http_rpc_endpoint();
+
+    cmd.args(["block", "finalized", "number", "--rpc-url", eth_rpc_url.as_str()]);
+    let finalized = cmd.stdout_lossy().trim().parse::<u64>().unwrap();
+
+    cmd.cast_fuse().args(["block", "safe", "number", "--rpc-url", eth_rpc_url.as_str()]);
+    let safe = cmd.stdout_lossy().trim().parse::<u64>().unwrap();
+    assert!(safe >= finalized);
+});
+
 // tests that the `cast find-block` command works correctly
 casttest!(finds_block, |_: TestProject, mut cmd: TestCommand| {
     // Construct args
@@ -41,6 +56,34 @@ casttest!(finds_block, |_: TestProject, mut cmd: TestCommand| {
     assert!(output.contains("14428082"), "{}", output);
 });
 
+// tests that `cast implementation` reads the implementation of a proxy and rejects other contracts
+casttest!(proxy_implementation, |_: TestProject, mut cmd: TestCommand| {
+    let eth_rpc_url = next_http_rpc_endpoint();
+
+    // USDC, an OpenZeppelin transparent proxy predating EIP-1967
+    cmd.args([
+        "implementation",
+        "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48",
+        "--block",
+        "15007840",
+        "--rpc-url",
+        eth_rpc_url.as_str(),
+    ]);
+    let output = cmd.stdout_lossy();
+    assert_eq!(output.trim(), "0xa2327a938Febf5FEC13baCFb16Ae10EcBc4cbDCF");
+
+    // WETH is not a proxy
+    cmd.cast_fuse().args([
+        "implementation",
+        "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2",
+        "--rpc-url",
+        eth_rpc_url.as_str(),
+    ]);
+    let output = cmd.unchecked_output();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("not a recognized proxy"));
+});
+
 // tests that we can create a new wallet with keystore
 casttest!(new_wallet_keystore_with_password, |_: TestProject, mut cmd: TestCommand| {
     cmd.args(["wallet", "new", ".", "--unsafe-password", "test"]);
@@ -49,6 +92,96 @@ casttest!(new_wallet_keystore_with_password, |_: TestProject, mut cmd: TestComma
     assert!(out.contains("Public Address of the key"));
 });
 
+// tests that we can sign EIP-712 typed data, using the example of the EIP
+// <https://github.com/ethereum/EIPs/blob/master/assets/eip-712/Example.js>
+casttest!(wallet_sign_typed_data, |_: TestProject, mut cmd: TestCommand| {
+    let typed_data = r#"{
+        "types": {
+            "EIP712Domain": [
+                { "name": "name", "type": "string" },
+                { "name": "version", "type": "string" },
+                { "name": "chainId", "type": "uint256" },
+                { "name": "verifyingContract", "type": "address" }
+            ],
+            "Person": [
+                { "name": "name", "type": "string" },
+                { "name": "wallet", "type": "address" }
+            ],
+            "Mail": [
+                { "name": "from", "type": "Person" },
+                { "name": "to", "type": "Person" },
+                { "name": "contents", "type": "string" }
+            ]
+        },
+        "primaryType": "Mail",
+        "domain": {
+            "name": "Ether Mail",
+            "version": "1",
+            "chainId": 1,
+            "verifyingContract": "0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC"
+        },
+        "message": {
+            "from": { "name": "Cow", "wallet": "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826" },
+            "to": { "name": "Bob", "wallet": "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB" },
+            "contents": "Hello, Bob!"
+        }
+    }"#;
+
+    // the private key is `keccak256("cow")`
+    cmd.args([
+        "wallet",
+        "sign-typed-data",
+        typed_data,
+        "--private-key",
+        "c85ef7d79691fe79573b1a7064c19c1a9819ebdbd1faaab1a8ec92344438aaf4",
+    ]);
+    let out = cmd.stdout_lossy();
+    assert_eq!(
+        out.trim(),
+        "Signature: 0x4355c47d63924e8a72e509b65029052eb6c299d53a04e167c5775fd466751c9d07299936d304c153f6443dfa05f40ff007d72911b6f72307f996231605b915621c"
+    );
+
+    cmd.cast_fuse().args([
+        "wallet",
+        "sign-typed-data",
+        "{\"types\": {}",
+        "--private-key",
+        "c85ef7d79691fe79573b1a7064c19c1a9819ebdbd1faaab1a8ec92344438aaf4",
+    ]);
+    let err = cmd.stderr_lossy();
+    assert!(err.contains("Failed to parse EIP-712 typed data"), "{}", err);
+});
+
+// tests that `cast mktx` signs a transaction without a node if all fields are provided
+casttest!(mktx_without_node, |_: TestProject, mut cmd: TestCommand| {
+    let args = [
+        "mktx",
+        "0x0000000000000000000000000000000000000001",
+        "--value",
+        "1gwei",
+        "--nonce",
+        "0",
+        "--gas-limit",
+        "21000",
+        "--gas-price",
+        "10gwei",
+        "--legacy",
+        "--chain",
+        "1",
+        "--rpc-url",
+        "http://127.0.0.1:1",
+        "--private-key",
+        "c85ef7d79691fe79573b1a7064c19c1a9819ebdbd1faaab1a8ec92344438aaf4",
+    ];
+    cmd.args(args);
+    let raw_tx = cmd.stdout_lossy();
+    assert!(raw_tx.trim().starts_with("0x"), "{}", raw_tx);
+
+    // the same inputs result in the same signed transaction
+    cmd.cast_fuse().args(args);
+    assert_eq!(cmd.stdout_lossy(), raw_tx);
+});
+
 // tests that `cast estimate` is working correctly.
 casttest!(estimate_function_gas, |_: TestProject, mut cmd: TestCommand| {
     let eth_rpc_url = next_http_rpc_endpoint();
@@ -138,7 +271,7 @@ casttest!(upload_signatures, |_: TestProject, mut cmd: TestCommand| {
 
 // tests that the `cast to-rlp` and `cast from-rlp` commands work correctly
 casttest!(cast_rlp, |_: TestProject, mut cmd: TestCommand| {
-    cmd.args(["--to-rlp", "[\"0xaa\", [[\"bb\"]], \"0xcc\"]"]);
+    cmd.args(["--to-rlp", "[\"0xaa\", [[187]], \"0xcc\"]"]);
     let out = cmd.stdout_lossy();
     assert!(out.contains("0xc881aac3c281bb81cc"), "{}", out);
 
@@ -222,3 +355,754 @@ casttest!(cast_run_succeeds, |_: TestProject, mut cmd: TestCommand| {
     assert!(output.contains("Transaction successfully executed"));
     assert!(!output.contains("Revert"));
 });
+
+// tests that `cast run` rejects negative block offsets
+casttest!(cast_run_rejects_negative_block_offset, |_: TestProject, mut cmd: TestCommand| {
+    cmd.args([
+        "run",
+        "0x2d951c5c95d374263ca99ad9c20c9797fc714330a8037429a3aa4c83d456f845",
+        "--block-offset=-1",
+    ]);
+    let output = cmd.unchecked_output();
+    assert!(!output.status.success());
+    let err = String::from_utf8_lossy(&output.stderr);
+    assert!(err.contains("Invalid value \"-1\" for '--block-offset <BLOCKS>'"), "{err}");
+});
+
+// tests that `cast decode-event` decodes the topics and data of a log
+casttest!(cast_decode_event, |_: TestProject, mut cmd: TestCommand| {
+    cmd.args([
+        "decode-event",
+        "--sig",
+        "Transfer(address indexed from, address indexed to, uint256 value)",
+        "--topics",
+        "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef,0x0000000000000000000000008dbd1b711dc621e1404633da156fcc779e1c6f3e,0x000000000000000000000000d9f3c9cc99548bf3b44a43e0a2d07399eb918adc",
+        "--data",
+        "0x000000000000000000000000000000000000000000000000000000000000002a",
+    ]);
+    let out = cmd.stdout_lossy();
+    assert_eq!(
+        out.trim(),
+        "from: 0x8dbd1b711dc621e1404633da156fcc779e1c6f3e\nto: 0xd9f3c9cc99548bf3b44a43e0a2d07399eb918adc\nvalue: 42"
+    );
+});
+
+// tests that `cast 4byte-event --decode` decodes the log with the signature found for topic 0
+casttest!(cast_4byte_event_decode, |_: TestProject, mut cmd: TestCommand| {
+    cmd.args([
+        "4byte-event",
+        "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef",
+        "--decode",
+        "--topics",
+        "0x0000000000000000000000008dbd1b711dc621e1404633da156fcc779e1c6f3e,0x000000000000000000000000d9f3c9cc99548bf3b44a43e0a2d07399eb918adc",
+        "--data",
+        "0x000000000000000000000000000000000000000000000000000000000000002a",
+    ]);
+    let out = cmd.stdout_lossy();
+    assert!(out.contains("1) \"Transfer(address,address,uint256)\"\n"), "{out}");
+    assert!(out.contains("0x8dbd1b711dc621e1404633da156fcc779e1c6f3e\n"), "{out}");
+    assert!(out.contains("0xd9f3c9cc99548bf3b44a43e0a2d07399eb918adc\n"), "{out}");
+    assert!(out.trim_end().ends_with(": 42"), "{out}");
+
+    // the topics and data are only accepted together with --decode
+    cmd.cast_fuse().args([
+        "4byte-event",
+        "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef",
+        "--topics",
+        "0x0000000000000000000000008dbd1b711dc621e1404633da156fcc779e1c6f3e",
+    ]);
+    assert!(!cmd.unchecked_output().status.success());
+});
+
+// tests that `cast to-base` converts between arbitrary bases and reports invalid digits
+casttest!(cast_to_base, |_: TestProject, mut cmd: TestCommand| {
+    cmd.args(["to-base", "0xff", "--to", "2"]);
+    assert_eq!(cmd.stdout_lossy().trim(), "0b11111111");
+
+    cmd.cast_fuse().args(["to-base", "zz", "--from", "36", "--to", "dec"]);
+    assert_eq!(cmd.stdout_lossy().trim(), "1295");
+
+    cmd.cast_fuse().args(["to-base", "129", "--from", "oct", "--to", "hex"]);
+    let err = cmd.stderr_lossy();
+    assert!(err.contains("Invalid digit `9` at position 2 of `129` for base 8"), "{err}");
+});
+
+// tests that `cast disassemble` prints one line per instruction
+casttest!(cast_disassemble, |_: TestProject, mut cmd: TestCommand| {
+    cmd.args(["disassemble", "0x608060405234801561001057600080fd"]);
+    assert_eq!(
+        cmd.stdout_lossy().trim(),
+        "00000000: PUSH1 0x80
+00000002: PUSH1 0x40
+00000004: MSTORE
+00000005: CALLVALUE
+00000006: DUP1
+00000007: ISZERO
+00000008: PUSH2 0x0010
+0000000b: JUMPI
+0000000c: PUSH1 0x00
+0000000e: DUP1
+0000000f: REVERT"
+    );
+});
+
+// tests that `cast disassemble --raw` disassembles 20 bytes of bytecode instead of fetching the code
+// of the address they look like
+casttest!(cast_disassemble_raw, |_: TestProject, mut cmd: TestCommand| {
+    cmd.args(["disassemble", "--raw", "0x60806040523480156100105760008060006000fd"]);
+    assert_eq!(
+        cmd.stdout_lossy().trim(),
+        "00000000: PUSH1 0x80
+00000002: PUSH1 0x40
+00000004: MSTORE
+00000005: CALLVALUE
+00000006: DUP1
+00000007: ISZERO
+00000008: PUSH2 0x0010
+0000000b: JUMPI
+0000000c: PUSH1 0x00
+0000000e: DUP1
+0000000f: PUSH1 0x00
+00000011: PUSH1 0x00
+00000013: REVERT"
+    );
+});
+
+// tests that `cast wallet derive` derives the same accounts as the default mnemonic signers
+casttest!(wallet_derive, |_: TestProject, mut cmd: TestCommand| {
+    cmd.args([
+        "wallet",
+        "derive",
+        "--mnemonic",
+        "test test test test test test test test test test test junk",
+        "--start",
+        "1",
+        "--count",
+        "2",
+        "--private-keys",
+    ]);
+    assert_eq!(
+        cmd.stdout_lossy().trim(),
+        "1 m/44'/60'/0'/0/1 0x70997970C51812dc3A010C7d01b50e0d17dc79C8 0x59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d
+2 m/44'/60'/0'/0/2 0x3C44CdDdB6a900fa2b585dd299e03d12FA4293BC 0x5de4111afa1a4b94908f83103eb1f1706367c2e68ca870fc3fb9a804cdab365a"
+    );
+});
+
+// tests that `cast wallet address` decrypts scrypt and pbkdf2 keystores
+casttest!(wallet_address_keystore, |prj: TestProject, mut cmd: TestCommand| {
+    let keystore = |kdf: &str| {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join(format!("tests/fixtures/keystore/{kdf}.json"))
+            .into_os_string()
+            .into_string()
+            .unwrap()
+    };
+    let expected = "Address: 0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266";
+
+    cmd.args(["wallet", "address", "--keystore", &keystore("scrypt"), "--password", "foundry"]);
+    assert_eq!(cmd.stdout_lossy().trim(), expected);
+
+    let password_file = prj.root().join("password");
+    std::fs::write(&password_file, "foundry\n").unwrap();
+    cmd.cast_fuse().args([
+        "wallet",
+        "address",
+        "--keystore",
+        &keystore("pbkdf2"),
+        "--password-file",
+        password_file.to_str().unwrap(),
+    ]);
+    assert_eq!(cmd.stdout_lossy().trim(), expected);
+
+    cmd.cast_fuse().args([
+        "wallet",
+        "address",
+        "--keystore",
+        &keystore("pbkdf2"),
+        "--password",
+        "wrong",
+    ]);
+    assert!(cmd.stderr_lossy().contains("incorrect password"));
+
+    cmd.cast_fuse()
+        .args([
+            "wallet",
+            "address",
+            "--keystore",
+            &keystore("scrypt"),
+            "--password",
+            "foundry",
+            "--show-private-key",
+        ])
+        .stdin(|mut stdin| stdin.write_all(b"y\n").unwrap());
+    assert_eq!(
+        cmd.stdout_lossy().trim(),
+        format!("{expected}\nPrivate Key: 0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80")
+    );
+});
+
+// tests that `cast decode-transaction` decodes signed transactions and recovers the sender
+casttest!(decode_transaction, |_: TestProject, mut cmd: TestCommand| {
+    // the EIP-155 example transaction
+    cmd.args([
+        "decode-transaction",
+        "0xf86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83",
+    ]);
+    let out = cmd.stdout_lossy();
+    assert!(out.contains("type                 0\n"), "{out}");
+    assert!(out.contains("chainId              1\n"), "{out}");
+    assert!(
+        out.contains("from                 0x9d8A62f656a8d1615C1294fd71e9CFb3E4855A4F\n"),
+        "{out}"
+    );
+    assert!(
+        out.contains("to                   0x3535353535353535353535353535353535353535\n"),
+        "{out}"
+    );
+    assert!(out.contains("nonce                9\n"), "{out}");
+    assert!(out.contains("gasPrice             20000000000\n"), "{out}");
+    assert!(out.contains(
+        "hash                 0x33469b22e9f636356c4160a87eb19df52b7412e8eac32a4a55ffe88ea8350788\n"
+    ));
+
+    // an EIP-1559 token transfer
+    cmd.cast_fuse().args([
+        "decode-transaction",
+        "0x02f8b00107843b9aca008504a817c80082ea609470997970c51812dc3a010c7d01b50e0d17dc79c880b844a9059cbb00000000000000000000000070997970c51812dc3a010c7d01b50e0d17dc79c800000000000000000000000000000000000000000000000000000000000003e8c001a0bb50e2d89a4ed70663d080659fe0ad4b9bc3e06c17a227433966cb59ceee020da010420595dccb837abb372dd821c96c1d4a54b41f85a4dfc9224de224cf45290c",
+        "--sig",
+        "transfer(address,uint256)",
+    ]);
+    let out = cmd.stdout_lossy();
+    assert!(out.contains("type                 2\n"), "{out}");
+    assert!(
+        out.contains("from                 0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266\n"),
+        "{out}"
+    );
+    assert!(out.contains("maxFeePerGas         20000000000\n"), "{out}");
+    assert!(out.contains("maxPriorityFeePerGas 1000000000\n"), "{out}");
+    assert!(out.contains(
+        "Decoded input (transfer(address,uint256)):\n  0x70997970c51812dc3a010c7d01b50e0d17dc79c8\n  1000"
+    ));
+
+    cmd.cast_fuse().args(["decode-transaction", "0x03f8"]);
+    assert!(cmd.stderr_lossy().contains("blob transactions (type 3) are not supported"));
+});
+
+// tests that `cast publish` only prints the receipt once the transaction has the given number of
+// confirmations
+forgetest_async!(
+    #[serial_test::serial]
+    cast_publish_waits_for_confirmations,
+    |_: TestProject, mut cmd: TestCommand| async move {
+        let node_config = NodeConfig::test().with_blocktime(Some(Duration::from_secs(1)));
+        let (api, handle) = spawn(node_config).await;
+        let rpc = handle.http_endpoint();
+        let wallet = handle.dev_wallets().next().unwrap();
+        let pk = hex::encode(&wallet.signer().to_bytes());
+
+        cmd.cast_fuse().args([
+            "mktx",
+            "--private-key",
+            pk.as_str(),
+            "--rpc-url",
+            rpc.as_str(),
+            "--value",
+            "1",
+            "0x000000000000000000000000000000000000dEaD",
+        ]);
+        let raw_tx = cmd.stdout_lossy().trim().to_string();
+
+        cmd.cast_fuse().args([
+            "publish",
+            "--rpc-url",
+            rpc.as_str(),
+            "--confirmations",
+            "3",
+            raw_tx.as_str(),
+        ]);
+        let receipt: serde_json::Value = serde_json::from_str(cmd.stdout_lossy().trim()).unwrap();
+        let mined = receipt["blockNumber"].as_str().unwrap().trim_start_matches("0x");
+        let mined = u64::from_str_radix(mined, 16).unwrap();
+        assert!(api.block_number().unwrap().as_u64() >= mined + 2);
+    }
+);
+
+// tests that `cast call --trace` executes the call on a fork and prints its trace
+casttest!(call_trace, |_: TestProject, mut cmd: TestCommand| {
+    let eth_rpc_url = next_http_rpc_endpoint();
+    let weth = "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2";
+
+    cmd.args([
+        "call",
+        weth,
+        "balanceOf(address)(uint256)",
+        "0x0000000000000000000000000000000000000000",
+        "--block",
+        "15007840",
+        "--trace",
+        "--rpc-url",
+        eth_rpc_url.as_str(),
+    ]);
+    let output = cmd.stdout_lossy();
+    assert!(output.contains("Traces:"), "{output}");
+    assert!(output.contains("Transaction successfully executed."), "{output}");
+
+    // the amount exceeds the total supply, so the transfer reverts
+    cmd.cast_fuse().args([
+        "call",
+        weth,
+        "transfer(address,uint256)(bool)",
+        weth,
+        "1000000000000000000000000000000",
+        "--block",
+        "15007840",
+        "--trace",
+        "--rpc-url",
+        eth_rpc_url.as_str(),
+    ]);
+    let output = cmd.stdout_lossy();
+    assert!(output.contains("Transaction failed."), "{output}");
+});
+
+// tests that constructor arguments are encoded without a selector
+casttest!(abi_encode_constructor, |_: TestProject, mut cmd: TestCommand| {
+    cmd.args([
+        "abi-encode",
+        "constructor(address,uint256)",
+        "0x5B38Da6a701c568545dCfcB03FcB875f56beddC4",
+        "1000",
+    ]);
+    assert_eq!(
+        cmd.stdout_lossy().trim(),
+        "0x0000000000000000000000005b38da6a701c568545dcfcb03fcb875f56beddc400000000000000000000000000000000000000000000000000000000000003e8"
+    );
+});
+
+// tests unit conversions with all ether units and custom decimals
+casttest!(unit_conversions, |_: TestProject, mut cmd: TestCommand| {
+    cmd.args(["to-unit", "1.5finney", "szabo"]);
+    assert_eq!(cmd.stdout_lossy().trim(), "1500");
+
+    cmd.cast_fuse().args(["to-unit", "1234567", "--decimals", "6"]);
+    assert_eq!(cmd.stdout_lossy().trim(), "1.234567");
+
+    // no floating point rounding
+    cmd.cast_fuse().args(["to-wei", "0.123456789123456789"]);
+    assert_eq!(cmd.stdout_lossy().trim(), "123456789123456789");
+
+    cmd.cast_fuse().args(["to-wei", "12.5", "--decimals", "6"]);
+    assert_eq!(cmd.stdout_lossy().trim(), "12500000");
+
+    cmd.cast_fuse().args(["from-wei", "12500000", "--decimals", "6"]);
+    assert_eq!(cmd.stdout_lossy().trim(), "12.500000");
+
+    cmd.cast_fuse().args(["from-wei", "1000", "kwei"]);
+    assert_eq!(cmd.stdout_lossy().trim(), "1.000");
+});
+
+// tests that `cast to-checksum-address` salts the checksum with `--chain-id` and warns about
+// invalid checksums
+casttest!(checksum_address, |_: TestProject, mut cmd: TestCommand| {
+    cmd.args(["to-checksum-address", "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed"]);
+    assert_eq!(cmd.stdout_lossy().trim(), "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed");
+
+    cmd.cast_fuse().args([
+        "to-checksum-address",
+        "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed",
+        "--chain-id",
+        "30",
+    ]);
+    assert_eq!(cmd.stdout_lossy().trim(), "0x5aaEB6053f3e94c9b9a09f33669435E7ef1bEAeD");
+
+    // valid EIP-55 checksum, but not for chain 30
+    cmd.cast_fuse().args([
+        "to-checksum-address",
+        "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+        "--chain-id",
+        "30",
+    ]);
+    let output = cmd.unchecked_output();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout).trim(),
+        "0x5aaEB6053f3e94c9b9a09f33669435E7ef1bEAeD"
+    );
+    assert!(String::from_utf8_lossy(&output.stderr).contains("has an invalid checksum"));
+});
+
+// tests that `--stdin` transforms every line of stdin
+casttest!(stdin_lines, |_: TestProject, mut cmd: TestCommand| {
+    cmd.args(["keccak", "--stdin"]).stdin(|mut stdin| stdin.write_all(b"\nhello\n").unwrap());
+    assert_eq!(
+        cmd.stdout_lossy(),
+        "0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470\n0x1c8aff950685c2ed4bc3174f3472287b56d9517b9c948127319a09a7a36deac8\n"
+    );
+
+    cmd.cast_fuse().args(["to-checksum-address", "--stdin"]).stdin(|mut stdin| {
+        stdin
+            .write_all(
+                b"0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266\n0x70997970c51812dc3a010c7d01b50e0d17dc79c8\n",
+            )
+            .unwrap()
+    });
+    assert_eq!(
+        cmd.stdout_lossy(),
+        "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266\n0x70997970C51812dc3A010C7d01b50e0d17dc79C8\n"
+    );
+
+    cmd.cast_fuse()
+        .args(["to-hex", "--stdin"])
+        .stdin(|mut stdin| stdin.write_all(b"255\n16").unwrap());
+    assert_eq!(cmd.stdout_lossy(), "0xff\n0x10\n");
+
+    // errors report the failing line
+    cmd.cast_fuse()
+        .args(["to-dec", "--stdin"])
+        .stdin(|mut stdin| stdin.write_all(b"0x1\nzz\n").unwrap());
+    assert!(cmd.stderr_lossy().contains("line 2"));
+});
+
+// tests that the nonce can be queried at the pending block
+casttest!(nonce_pending, |_: TestProject, mut cmd: TestCommand| {
+    let eth_rpc_url = next_http_rpc_endpoint();
+
+    cmd.args([
+        "nonce",
+        "--pending",
+        "--rpc-url",
+        eth_rpc_url.as_str(),
+        "0x0000000000000000000000000000000000000000",
+    ]);
+    assert_eq!(cmd.stdout_lossy().trim(), "0");
+
+    cmd.cast_fuse().args([
+        "nonce",
+        "--pending",
+        "--block",
+        "latest",
+        "0x0000000000000000000000000000000000000000",
+    ]);
+    assert!(cmd.stderr_lossy().contains("cannot be used with"));
+});
+
+// checks that `cast cache` lists and prunes the cached blocks of a chain
+// this test is not isolated and modifies ~ so it is ignored
+casttest!(
+    #[ignore]
+    cache_ls_clean,
+    |_: TestProject, mut cmd: TestCommand| {
+        let chain = Chain::Named(ethers::prelude::Chain::Mainnet);
+        for block in [100, 101] {
+            std::fs::create_dir_all(Config::foundry_block_cache_dir(chain, block).unwrap())
+                .unwrap();
+            std::fs::write(Config::foundry_block_cache_file(chain, block).unwrap(), "{}").unwrap();
+        }
+
+        cmd.args(["cache", "ls", "mainnet"]);
+        let output = cmd.stdout_lossy();
+        assert!(output.contains("-️ mainnet ("), "{}", output);
+        assert!(output.contains("\t-️ Block 100 ("), "{}", output);
+        assert!(output.contains("\t-️ Block 101 ("), "{}", output);
+
+        cmd.cast_fuse().args(["cache", "clean", "mainnet", "--blocks", "100"]);
+        cmd.assert_empty_stdout();
+        assert!(!Config::foundry_block_cache_dir(chain, 100).unwrap().exists());
+        assert!(Config::foundry_block_cache_dir(chain, 101).unwrap().exists());
+
+        Config::clean_foundry_cache().unwrap();
+    }
+);
+
+// tests that `cast call --json` prints the decoded return values as JSON
+casttest!(call_json, |_: TestProject, mut cmd: TestCommand| {
+    let eth_rpc_url = next_http_rpc_endpoint();
+    let weth = "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2";
+
+    cmd.args([
+        "call",
+        weth,
+        "decimals()(uint8 decimals)",
+        "--json",
+        "--block",
+        "15007840",
+        "--rpc-url",
+        eth_rpc_url.as_str(),
+    ]);
+    assert_eq!(cmd.stdout_lossy().trim(), r#"{"decimals":"18"}"#);
+
+    // unnamed return values are printed as an array
+    cmd.cast_fuse().args([
+        "call",
+        weth,
+        "symbol()(string)",
+        "--json",
+        "--block",
+        "15007840",
+        "--rpc-url",
+        eth_rpc_url.as_str(),
+    ]);
+    assert_eq!(cmd.stdout_lossy().trim(), r#"["WETH"]"#);
+});
+
+// tests that `cast storage-layout` prints the storage layout of a local contract
+casttest!(storage_layout, |prj: TestProject, mut cmd: TestCommand| {
+    prj.inner()
+        .add_source(
+            "Vault",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity >0.8.9;
+contract Vault {
+    uint128 a;
+    uint128 b;
+    address owner;
+    mapping(address => uint256) balances;
+}
+   "#,
+        )
+        .unwrap();
+
+    cmd.args(["storage-layout", "Vault", "--to-json"]).root_arg();
+    let layout: serde_json::Value = serde_json::from_str(&cmd.stdout_lossy()).unwrap();
+    assert_eq!(
+        layout,
+        serde_json::json!([
+            {"label": "a", "slot": "0", "offset": 0, "type": "uint128", "bytes": "16", "contract": "src/Vault.sol:Vault"},
+            {"label": "b", "slot": "0", "offset": 16, "type": "uint128", "bytes": "16", "contract": "src/Vault.sol:Vault"},
+            {"label": "owner", "slot": "1", "offset": 0, "type": "address", "bytes": "20", "contract": "src/Vault.sol:Vault"},
+            {"label": "balances", "slot": "2", "offset": 0, "type": "mapping(address => uint256)", "bytes": "32", "contract": "src/Vault.sol:Vault"},
+        ])
+    );
+
+    cmd.cast_fuse().args(["storage-layout", "Vault"]).root_arg();
+    let table = cmd.stdout_lossy();
+    assert!(table.contains("owner"));
+    assert!(table.contains("mapping(address => uint256)"));
+});
+
+// tests that `cast creation-code`, `cast runtime-code` and `cast codesize` work with a local
+// contract
+casttest!(local_contract_code, |prj: TestProject, mut cmd: TestCommand| {
+    prj.inner()
+        .add_source(
+            "Counter",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity >0.8.9;
+contract Counter {
+    uint256 public number;
+    function increment() public {
+        number++;
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    cmd.args(["creation-code", "Counter"]).root_arg();
+    let creation_code = cmd.stdout_lossy().trim().to_string();
+
+    cmd.cast_fuse().args(["runtime-code", "src/Counter.sol:Counter"]).root_arg();
+    let runtime_code = cmd.stdout_lossy().trim().to_string();
+
+    let artifact: ConfigurableContractArtifact =
+        ethers::solc::utils::read_json_file(prj.paths().artifacts.join("Counter.sol/Counter.json"))
+            .unwrap();
+    assert_eq!(creation_code, artifact.get_bytecode_bytes().unwrap().to_string());
+    assert_eq!(runtime_code, artifact.get_deployed_bytecode_bytes().unwrap().to_string());
+
+    cmd.cast_fuse().args(["codesize", "Counter"]).root_arg();
+    let size = artifact.get_deployed_bytecode_bytes().unwrap().len();
+    assert_eq!(
+        cmd.stdout_lossy().trim(),
+        format!("{} bytes, {} bytes below the limit of 24576 bytes", size, 24576 - size)
+    );
+});
+
+// tests that `cast sig-event` computes the topic hash of an event
+casttest!(sig_event, |_: TestProject, mut cmd: TestCommand| {
+    let transfer = "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+
+    cmd.args(["sig-event", "Transfer(address indexed from, address indexed to, uint256 amount)"]);
+    assert_eq!(cmd.stdout_lossy().trim(), transfer);
+
+    cmd.cast_fuse().args(["sig-event", "event Transfer(address,address,uint256)"]);
+    assert_eq!(cmd.stdout_lossy().trim(), transfer);
+});
+
+// tests that commands accepting an address resolve ENS names once and report the address
+casttest!(resolves_ens_names, |_: TestProject, mut cmd: TestCommand| {
+    let eth_rpc_url = next_http_rpc_endpoint();
+
+    cmd.args(["nonce", "vitalik.eth", "--block", "15007840", "--rpc-url", eth_rpc_url.as_str()]);
+    let output = cmd.output();
+    assert!(String::from_utf8_lossy(&output.stdout).trim().parse::<u64>().unwrap() > 0);
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("Resolved vitalik.eth to 0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045"));
+});
+
+// tests that `cast tx --raw` reconstructs signed transactions that decode to the fetched ones
+casttest!(tx_raw_roundtrip, |_: TestProject, mut cmd: TestCommand| {
+    let eth_rpc_url = next_http_rpc_endpoint();
+
+    for hash in [
+        // the first ether transfer, a legacy transaction without a chain id
+        "0x5c504ed432cb51138bcf09aa5e8a410dd4a1e204ef84bfed1be16dfba1b22060",
+        // the transaction replayed by `cast_run_succeeds`
+        "0x2d951c5c95d374263ca99ad9c20c9797fc714330a8037429a3aa4c83d456f845",
+    ] {
+        cmd.cast_fuse().args(["tx", hash, "--raw", "--rpc-url", eth_rpc_url.as_str()]);
+        let raw = cmd.stdout_lossy().trim().to_string();
+
+        cmd.cast_fuse().args(["tx", hash, "--json", "--rpc-url", eth_rpc_url.as_str()]);
+        let tx: serde_json::Value = serde_json::from_str(&cmd.stdout_lossy()).unwrap();
+
+        cmd.cast_fuse().args(["decode-transaction", raw.as_str()]);
+        let decoded = cmd.stdout_lossy();
+        let from = tx["from"].as_str().unwrap();
+        let nonce = u64::from_str_radix(tx["nonce"].as_str().unwrap().trim_start_matches("0x"), 16)
+            .unwrap();
+        assert!(decoded.contains(&format!("hash                 {}\n", hash)), "{decoded}");
+        assert!(decoded.to_lowercase().contains(&format!("from                 {}\n", from)));
+        assert!(decoded.contains(&format!("nonce                {}\n", nonce)), "{decoded}");
+    }
+});
+
+// tests that `cast age` prints relative ages and estimates the block time
+casttest!(age_human_and_block_time, |_: TestProject, mut cmd: TestCommand| {
+    let eth_rpc_url = next_http_rpc_endpoint();
+
+    cmd.args(["age", "-B", "1", "--human", "--rpc-url", eth_rpc_url.as_str()]);
+    let output = cmd.stdout_lossy();
+    assert!(output.contains(" years, "), "{}", output);
+    assert!(output.trim().ends_with(" ago"), "{}", output);
+
+    // the first blocks after the merge are produced in slots of 12 seconds
+    cmd.cast_fuse().args([
+        "age",
+        "-B",
+        "15537494",
+        "--since",
+        "15537394",
+        "--rpc-url",
+        eth_rpc_url.as_str(),
+    ]);
+    let output = cmd.stdout_lossy();
+    let block_time = output.trim().strip_suffix(" seconds per block").unwrap();
+    assert!(block_time.parse::<f64>().unwrap() >= 12.0, "{}", output);
+});
+
+// tests that `cast call` samples the call across a block range
+casttest!(call_block_range, |_: TestProject, mut cmd: TestCommand| {
+    let eth_rpc_url = next_http_rpc_endpoint();
+    let weth = "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2";
+
+    cmd.args([
+        "call",
+        weth,
+        "decimals()(uint8)",
+        "--from-block",
+        "16000000",
+        "--to-block",
+        "16000004",
+        "--step",
+        "2",
+        "--rpc-url",
+        eth_rpc_url.as_str(),
+    ]);
+    assert_eq!(cmd.stdout_lossy().trim(), "16000000\t18\n16000002\t18\n16000004\t18");
+
+    cmd.cast_fuse().args([
+        "call",
+        weth,
+        "decimals()(uint8)",
+        "--from-block",
+        "16000000",
+        "--to-block",
+        "16000001",
+        "--json",
+        "--rpc-url",
+        eth_rpc_url.as_str(),
+    ]);
+    let samples: serde_json::Value = serde_json::from_str(cmd.stdout_lossy().trim()).unwrap();
+    assert_eq!(
+        samples,
+        serde_json::json!([
+            { "block": 16000000, "result": ["18"] },
+            { "block": 16000001, "result": ["18"] }
+        ])
+    );
+});
+
+// tests that `cast decode-error` decodes the builtin revert errors
+casttest!(decode_error, |_: TestProject, mut cmd: TestCommand| {
+    // `Error(string)` with the message "insufficient balance"
+    cmd.args([
+        "decode-error",
+        "0x08c379a000000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000000000000000014696e73756666696369656e742062616c616e6365000000000000000000000000",
+    ]);
+    assert_eq!(cmd.stdout_lossy().trim(), "insufficient balance");
+
+    cmd.cast_fuse().args([
+        "decode-error",
+        "0x4e487b710000000000000000000000000000000000000000000000000000000000000011",
+    ]);
+    assert_eq!(cmd.stdout_lossy().trim(), "Panic(0x11): Arithmetic over/underflow");
+});
+
+// tests that `cast compute-address` computes CREATE2 addresses from hex or file init code
+casttest!(compute_create2_address, |prj: TestProject, mut cmd: TestCommand| {
+    let deployer = "0x00000000000000000000000000000000deadbeef";
+    let salt = "0x00000000000000000000000000000000000000000000000000000000cafebabe";
+    cmd.args(["compute-address", deployer, "--salt", salt, "--init-code", "0xdeadbeef"]);
+    assert_eq!(
+        cmd.stdout_lossy().trim(),
+        "Computed Address: 0x60f3f640a8508fC6a86d45DF051962668E1e8AC7"
+    );
+
+    let init_code = prj.root().join("init_code.hex");
+    std::fs::write(&init_code, "0xdeadbeef\n").unwrap();
+    cmd.cast_fuse().args([
+        "compute-address",
+        deployer,
+        "--salt",
+        "0xcafebabe",
+        "--init-code",
+        &format!("@{}", init_code.display()),
+    ]);
+    assert_eq!(
+        cmd.stdout_lossy().trim(),
+        "Computed Address: 0x60f3f640a8508fC6a86d45DF051962668E1e8AC7"
+    );
+});
+
+// tests that `--file` decodes every line of a file, for inputs and outputs
+casttest!(abi_decode_file, |prj: TestProject, mut cmd: TestCommand| {
+    let word = |n: u8| format!("{:0>64}", format!("{n:02x}"));
+    let calldata = prj.root().join("calldata.txt");
+    std::fs::write(
+        &calldata,
+        format!("0xa9059cbb{}{}\n\n0xa9059cbb{}{}\n", word(1), word(42), word(2), word(7)),
+    )
+    .unwrap();
+    cmd.args([
+        "--calldata-decode",
+        "transfer(address,uint256)",
+        "--file",
+        calldata.to_str().unwrap(),
+    ]);
+    assert_eq!(
+        cmd.stdout_lossy().trim(),
+        "0x0000000000000000000000000000000000000001\n42\n---\n0x0000000000000000000000000000000000000002\n7"
+    );
+
+    let output = prj.root().join("output.txt");
+    std::fs::write(&output, format!("0x{}\n0x{}\n", word(1), word(0))).unwrap();
+    cmd.cast_fuse().args([
+        "--abi-decode",
+        "balanceOf(address)(uint256)",
+        "--file",
+        output.to_str().unwrap(),
+    ]);
+    assert_eq!(cmd.stdout_lossy().trim(), "1\n---\n0");
+});
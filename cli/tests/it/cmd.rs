@@ -1260,6 +1260,62 @@ contract ContractThreeTest is DSTest {
     assert!(third_out.contains("foo") && third_out.contains("bar") && third_out.contains("baz"));
 });
 
+// checks that the gas report includes the cost of every deployment of a contract
+forgetest!(gas_report_deployment_costs, |prj: TestProject, mut cmd: TestCommand| {
+    prj.insert_ds_test();
+    prj.inner()
+        .add_source(
+            "Contracts.sol",
+            r#"
+//SPDX-license-identifier: MIT
+pragma solidity ^0.8.0;
+
+import "./test.sol";
+
+contract Fixed {
+    function foo() public {}
+}
+
+contract Varying {
+    uint256 public n;
+
+    constructor(uint256 _n) {
+        for (uint256 i = 0; i < _n; i++) {
+            n++;
+        }
+    }
+
+    function bar() public {}
+}
+
+contract DeploymentsTest is DSTest {
+    function testDeploy() public {
+        new Fixed().foo();
+        new Fixed().foo();
+        new Varying(1).bar();
+        new Varying(10).bar();
+    }
+}
+    "#,
+        )
+        .unwrap();
+
+    let report = prj.root().join("gas.json");
+    cmd.args(["test", "--gas-report-json"]).arg(&report);
+    cmd.assert_non_empty_stdout();
+
+    let report: forge::gas_report::JsonGasReport =
+        ethers::solc::utils::read_json_file(report).unwrap();
+    let contract = |name: &str| {
+        report.contracts.iter().find(|(id, _)| id.ends_with(name)).map(|(_, gas)| gas).unwrap()
+    };
+    let fixed = contract(":Fixed");
+    assert!(fixed.deployment_cost > 0);
+    assert_eq!(fixed.deployment_cost, fixed.deployment_cost_min);
+    let varying = contract(":Varying");
+    assert!(varying.deployment_cost > varying.deployment_cost_min);
+});
+
 forgetest_init!(can_use_absolute_imports, |prj: TestProject, mut cmd: TestCommand| {
     let remapping = prj.paths().libraries[0].join("myDepdendency");
     let config = Config {
@@ -1309,3 +1365,14 @@ forgetest_init!(can_use_absolute_imports, |prj: TestProject, mut cmd: TestComman
     let stdout = cmd.stdout_lossy();
     assert!(stdout.contains("Compiler run successful"));
 });
+
+// checks that `forge coverage` can write an LCOV report to a custom path
+forgetest_init!(can_write_lcov_report, |prj: TestProject, mut cmd: TestCommand| {
+    cmd.args(["coverage", "--report", "lcov", "--report-file", "reports/lcov.info"]);
+    cmd.assert_non_empty_stdout();
+
+    let lcov = std::fs::read_to_string(prj.root().join("reports/lcov.info")).unwrap();
+    assert!(lcov.contains("SF:"), "{}", lcov);
+    assert!(lcov.contains("DA:"), "{}", lcov);
+    assert!(lcov.trim_end().ends_with("end_of_record"), "{}", lcov);
+});
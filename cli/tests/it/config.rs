@@ -60,11 +60,25 @@ forgetest!(can_extract_config_values, |prj: TestProject, mut cmd: TestCommand| {
         fuzz_runs: 1000,
         fuzz_max_local_rejects: 2000,
         fuzz_max_global_rejects: 100203,
+        fuzz_dictionary: true,
+        fuzz_dictionary_weight: 40,
         fuzz_seed: Some(1000.into()),
+        fuzz_rng_algorithm: None,
+        fuzz_failure_persist_dir: None,
+        fuzz_timeout: None,
+        fuzz_addresses: vec![],
+        fuzz_addresses_deployed: false,
+        fuzz_uint256_min: None,
+        fuzz_uint256_max: None,
+        fuzz_exclude_zero: false,
         invariant_runs: 256,
         invariant_depth: 15,
         invariant_fail_on_revert: false,
         invariant_call_override: false,
+        invariant_replay_dir: None,
+        invariant_corpus_dir: None,
+        invariant_selector_weights: Default::default(),
+        invariant_senders: vec![],
         ffi: true,
         sender: "00a329c0648769A73afAc7F9381D08FB43dBEA72".parse().unwrap(),
         tx_origin: "00a329c0648769A73afAc7F9F81E08FB43dBEA72".parse().unwrap(),
@@ -84,6 +98,7 @@ forgetest!(can_extract_config_values, |prj: TestProject, mut cmd: TestCommand| {
         etherscan_api_key: None,
         etherscan: Default::default(),
         verbosity: 4,
+        labels_file: None,
         remappings: vec![Remapping::from_str("forge-std=lib/forge-std/").unwrap().into()],
         libraries: vec![
             "src/DssSpell.sol:DssExecLib:0x8De6DDbCd5053d32292AAA0D2105A32d108484a6".to_string()
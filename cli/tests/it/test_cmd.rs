@@ -360,3 +360,175 @@ contract ContractTest is Test {
             .join("tests/fixtures/can_use_libs_in_multi_fork.stdout"),
     );
 });
+
+// tests that `--junit` writes the test results as JUnit XML
+forgetest!(can_write_junit_report, |prj: TestProject, mut cmd: TestCommand| {
+    prj.insert_ds_test();
+
+    prj.inner()
+        .add_source(
+            "JunitTest.t.sol",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.10;
+import "./test.sol";
+contract JunitTest is DSTest {
+    function testPass() external {
+        assertTrue(true);
+    }
+
+    function testFailingFuzz(uint256 x) external {
+        require(x < 10, "x <too> big");
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    let report = prj.root().join("reports/junit.xml");
+    cmd.arg("test").arg("--junit").arg(&report);
+    cmd.unchecked_output();
+
+    let xml = std::fs::read_to_string(&report).unwrap();
+    assert!(xml.starts_with(r#"<?xml version="1.0" encoding="UTF-8"?>"#));
+    assert!(xml.contains(r#"<testsuites name="forge" tests="2" failures="1""#));
+    assert!(xml.contains(r#"<testsuite name="src/JunitTest.t.sol:JunitTest" tests="2""#));
+    assert!(
+        xml.contains(r#"<testcase name="testPass()" classname="src/JunitTest.t.sol:JunitTest""#)
+    );
+    assert!(xml.contains(r#"<failure message="x &lt;too&gt; big">Reason: x &lt;too&gt; big"#));
+    assert!(xml.contains("\nCounterexample: "));
+});
+
+// checks that `--stream` prints every test with the progress of the run
+forgetest!(can_stream_test_results, |prj: TestProject, mut cmd: TestCommand| {
+    prj.insert_ds_test();
+
+    prj.inner()
+        .add_source(
+            "StreamTest.t.sol",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.10;
+import "./test.sol";
+contract StreamTest is DSTest {
+    function testFirst() external {
+        assertTrue(true);
+    }
+
+    function testSecond() external {
+        assertTrue(true);
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    cmd.args(["test", "--stream"]);
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("/2] [PASS] src/StreamTest.t.sol:StreamTest:testFirst()"));
+    assert!(stdout.contains("/2] [PASS] src/StreamTest.t.sol:StreamTest:testSecond()"));
+    assert!(stdout.contains("[1/2] "));
+    assert!(stdout.contains("[2/2] "));
+});
+
+// checks that `--slowest` prints the slowest tests after the run
+forgetest!(can_print_slowest_tests, |prj: TestProject, mut cmd: TestCommand| {
+    prj.insert_ds_test();
+
+    prj.inner()
+        .add_source(
+            "SlowTest.t.sol",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.10;
+import "./test.sol";
+contract SlowTest is DSTest {
+    function testFast() external {
+        assertTrue(true);
+    }
+
+    function testSlow(uint256 x) external {
+        assertTrue(x == x);
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    cmd.args(["test", "--slowest", "1"]);
+    let stdout = cmd.stdout_lossy();
+    let (_, slowest) = stdout.split_once("Slowest tests:").unwrap();
+    let slowest = slowest.trim().lines().collect::<Vec<_>>();
+    assert_eq!(slowest.len(), 1);
+    assert!(slowest[0].ends_with("src/SlowTest.t.sol:SlowTest:testSlow(uint256)"));
+});
+
+// checks that `--rerun-failed` only runs the tests that failed in the last run
+forgetest!(can_rerun_failed_tests, |prj: TestProject, mut cmd: TestCommand| {
+    prj.insert_ds_test();
+
+    let source = |value: u64| {
+        format!(
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.10;
+import "./test.sol";
+contract RerunTest is DSTest {{
+    function testPass() external {{
+        assertTrue(true);
+    }}
+
+    function testMaybeFail() external {{
+        assertEq(uint256({value}), 1);
+    }}
+}}
+   "#
+        )
+    };
+    prj.inner().add_source("RerunTest.t.sol", source(2)).unwrap();
+
+    cmd.arg("test");
+    let stdout = String::from_utf8_lossy(&cmd.unchecked_output().stdout).to_string();
+    assert!(stdout.contains("testPass()"));
+    assert!(stdout.contains("testMaybeFail()"));
+
+    cmd.forge_fuse().args(["test", "--rerun-failed"]);
+    let stdout = String::from_utf8_lossy(&cmd.unchecked_output().stdout).to_string();
+    assert!(stdout.contains("[FAIL. Reason: Assertion failed.] testMaybeFail()"));
+    assert!(!stdout.contains("testPass()"));
+
+    prj.inner().add_source("RerunTest.t.sol", source(1)).unwrap();
+    cmd.forge_fuse().args(["test", "--rerun-failed"]);
+    let stdout = cmd.stdout_lossy();
+    assert!(stdout.contains("[PASS] testMaybeFail()"));
+    assert!(!stdout.contains("testPass()"));
+
+    cmd.forge_fuse().args(["test", "--rerun-failed"]);
+    assert!(cmd.stdout_lossy().contains("No tests failed in the last run."));
+});
+
+// checks that the fuzz seed can be pinned with `FOUNDRY_FUZZ_SEED` and is printed for failed tests
+forgetest!(can_set_fuzz_seed_from_env, |prj: TestProject, mut cmd: TestCommand| {
+    prj.insert_ds_test();
+    prj.inner()
+        .add_source(
+            "FuzzSeedTest.t.sol",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.10;
+import "./test.sol";
+contract FuzzSeedTest is DSTest {
+    function testFuzzFail(uint256 x) external {
+        assertTrue(x == x + 1);
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    cmd.set_env("FOUNDRY_FUZZ_SEED", "42");
+    cmd.arg("test");
+    let stdout = String::from_utf8_lossy(&cmd.unchecked_output().stdout).to_string();
+    assert!(stdout.contains("re-run with --fuzz-seed 0x2a"), "{stdout}");
+});
@@ -6,7 +6,13 @@ use foundry_cli_test_utils::{
     forgetest,
     util::{TestCommand, TestProject},
 };
-use foundry_utils::Retry;
+use foundry_utils::{rpc::next_http_rpc_endpoint, Retry};
+use std::{
+    collections::BTreeMap,
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpListener,
+    thread::JoinHandle,
+};
 
 const VERIFICATION_PROVIDERS: &'static [&'static str] = &["etherscan", "sourcify"];
 
@@ -48,6 +54,64 @@ function doStuff() external {}
         .unwrap();
 }
 
+/// Starts a mock etherscan API that answers `requests` requests with `respond`
+///
+/// Returns the url of the API and a handle that resolves to the decoded query and form fields of
+/// every request.
+fn mock_etherscan_api(
+    requests: usize,
+    respond: fn(&BTreeMap<String, String>) -> &'static str,
+) -> (String, JoinHandle<Vec<BTreeMap<String, String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/api", listener.local_addr().unwrap());
+    let handle = std::thread::spawn(move || {
+        let mut fields = Vec::with_capacity(requests);
+        for _ in 0..requests {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let target = request_line.split_whitespace().nth(1).unwrap().to_string();
+
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let line = line.trim_end();
+                if line.is_empty() {
+                    break
+                }
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        content_length = value.trim().parse().unwrap();
+                    }
+                }
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+
+            let url = reqwest::Url::parse(&format!("http://localhost{}", target)).unwrap();
+            let body = format!("http://localhost/?{}", String::from_utf8(body).unwrap());
+            let body = reqwest::Url::parse(&body).unwrap();
+            let request: BTreeMap<_, _> =
+                url.query_pairs().chain(body.query_pairs()).into_owned().collect();
+
+            let response = respond(&request);
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                response.len(),
+                response
+            )
+            .unwrap();
+            fields.push(request);
+        }
+        fields
+    });
+    (url, handle)
+}
+
 fn parse_verification_result(cmd: &mut TestCommand, retries: u32) -> eyre::Result<()> {
     // give etherscan some time to verify the contract
     let retry = Retry::new(retries, Some(30));
@@ -302,3 +366,179 @@ forgetest!(
         }
     }
 );
+
+// tests that `--proxy` rejects contracts without an EIP-1967 implementation slot
+forgetest!(can_reject_non_proxy, |prj: TestProject, mut cmd: TestCommand| {
+    add_unique(&prj);
+    add_verify_target(&prj);
+
+    cmd.arg("verify-contract").root_arg().args([
+        "--proxy",
+        "--rpc-url",
+        next_http_rpc_endpoint().as_str(),
+        // WETH
+        "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2",
+        "src/Verify.sol:Verify",
+        "dummykey",
+    ]);
+
+    let err = cmd.stderr_lossy();
+    assert!(err.contains("doesn't look like a supported proxy"), "{}", err);
+});
+
+// tests that linked libraries are submitted to etherscan, in the standard json input and for
+// flattened sources
+forgetest!(can_verify_with_libraries_mocked, |prj: TestProject, mut cmd: TestCommand| {
+    prj.inner()
+        .add_source(
+            "Lib.sol",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity =0.8.10;
+library Lib {
+    function f() public pure returns (uint256) {
+        return 1;
+    }
+}
+"#,
+        )
+        .unwrap();
+    prj.inner()
+        .add_source(
+            "UsesLib.sol",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity =0.8.10;
+import {Lib} from "./Lib.sol";
+contract UsesLib {
+    function g() external pure returns (uint256) {
+        return Lib.f();
+    }
+}
+"#,
+        )
+        .unwrap();
+
+    let lib = "0x1111111111111111111111111111111111111111";
+    let args = |url: &str| {
+        vec![
+            "--verifier-url".to_string(),
+            url.to_string(),
+            "--compiler-version".to_string(),
+            "0.8.10+commit.fc410830".to_string(),
+            "--libraries".to_string(),
+            format!("src/Lib.sol:Lib:{}", lib),
+            "0x2222222222222222222222222222222222222222".to_string(),
+            "src/UsesLib.sol:UsesLib".to_string(),
+            "dummykey".to_string(),
+        ]
+    };
+
+    let respond =
+        |_: &BTreeMap<String, String>| r#"{"status":"1","message":"OK","result":"mock-guid"}"#;
+
+    let (url, request) = mock_etherscan_api(1, respond);
+    cmd.arg("verify-contract").root_arg().args(args(&url));
+    let out = cmd.stdout_lossy();
+    assert!(out.contains("mock-guid"), "{}", out);
+    let request = request.join().unwrap().remove(0);
+    assert_eq!(request["codeformat"], "solidity-standard-json-input");
+    let input: serde_json::Value = serde_json::from_str(&request["sourceCode"]).unwrap();
+    assert_eq!(input["settings"]["libraries"]["src/Lib.sol"]["Lib"], lib);
+
+    let (url, request) = mock_etherscan_api(1, respond);
+    cmd.forge_fuse()
+        .arg("verify-contract")
+        .root_arg()
+        .args(args(&url))
+        .args(["--flatten", "--force"]);
+    let out = cmd.stdout_lossy();
+    assert!(out.contains("mock-guid"), "{}", out);
+    let request = request.join().unwrap().remove(0);
+    assert_eq!(request["codeformat"], "solidity-single-file");
+    assert_eq!(request["libraryname1"], "Lib");
+    assert_eq!(request["libraryaddress1"], lib);
+});
+
+// tests that `verify-check` polls multiple submissions and reports pending ones as pending
+forgetest!(can_check_multiple_submissions_mocked, |_prj: TestProject, mut cmd: TestCommand| {
+    // the pending submission is requested again in the second attempt
+    let (url, requests) = mock_etherscan_api(4, |request| match request["guid"].as_str() {
+        "guid-verified" => r#"{"status":"1","message":"OK","result":"Pass - Verified"}"#,
+        "guid-pending" => r#"{"status":"0","message":"NOTOK","result":"Pending in queue"}"#,
+        _ => r#"{"status":"0","message":"NOTOK","result":"Fail - Unable to verify"}"#,
+    });
+
+    cmd.args([
+        "verify-check",
+        "--verifier-url",
+        url.as_str(),
+        "--etherscan-key",
+        "dummykey",
+        "--retries",
+        "2",
+        "--delay",
+        "0",
+        "guid-verified",
+        "guid-pending",
+        "guid-failed",
+    ]);
+
+    let output = cmd.unchecked_output();
+    assert!(!output.status.success());
+    let out = String::from_utf8_lossy(&output.stdout);
+    assert!(out.contains("verified"), "{}", out);
+    assert!(out.contains("failed: Fail - Unable to verify"), "{}", out);
+    assert!(out.contains("1 submission(s) are still pending"), "{}", out);
+    let err = String::from_utf8_lossy(&output.stderr);
+    assert!(err.contains("Verification failed for 1 submission(s)"), "{}", err);
+
+    let requests = requests.join().unwrap();
+    assert_eq!(requests.iter().filter(|request| request["guid"] == "guid-pending").count(), 2);
+});
+
+// tests that `--json` prints the verification result and exits with the code of its status
+forgetest!(can_print_verification_result_json_mocked, |prj: TestProject, mut cmd: TestCommand| {
+    add_unique(&prj);
+    add_verify_target(&prj);
+
+    let (url, request) =
+        mock_etherscan_api(1, |_| r#"{"status":"1","message":"OK","result":"mock-guid"}"#);
+    cmd.arg("verify-contract").root_arg().args([
+        "--json",
+        "--verifier-url",
+        url.as_str(),
+        "--compiler-version",
+        "0.8.10+commit.fc410830",
+        "0x2222222222222222222222222222222222222222",
+        "src/Verify.sol:Verify",
+        "dummykey",
+    ]);
+    let output = cmd.unchecked_output();
+    assert_eq!(output.status.code(), Some(2));
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result["guid"], "mock-guid");
+    assert_eq!(result["status"], "pending");
+    assert_eq!(result["already_verified"], false);
+    request.join().unwrap();
+
+    let (url, _) = mock_etherscan_api(
+        1,
+        |_| r#"{"status":"0","message":"NOTOK","result":"Fail - Unable to verify"}"#,
+    );
+    cmd.forge_fuse().args([
+        "verify-check",
+        "--json",
+        "--verifier-url",
+        url.as_str(),
+        "--etherscan-key",
+        "dummykey",
+        "mock-guid",
+    ]);
+    let output = cmd.unchecked_output();
+    assert_eq!(output.status.code(), Some(1));
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result["guid"], "mock-guid");
+    assert_eq!(result["status"], "failed");
+    assert_eq!(result["reason"], "Fail - Unable to verify");
+});
@@ -26,7 +26,7 @@ use semver::Version;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::{
     borrow::Cow,
-    collections::{hash_map::Entry, BTreeSet, HashMap},
+    collections::{hash_map::Entry, BTreeMap, BTreeSet, HashMap},
     fs,
     path::{Path, PathBuf},
     str::FromStr,
@@ -145,6 +145,10 @@ pub struct Config {
     pub gas_reports: Vec<String>,
     /// list of contracts to ignore for gas reports
     pub gas_reports_ignore: Vec<String>,
+    /// glob patterns of contract paths to report gas of, all paths are included if empty
+    pub gas_reports_include_paths: Vec<String>,
+    /// glob patterns of contract paths to ignore for gas reports
+    pub gas_reports_exclude_paths: Vec<String>,
     /// The Solc instance to use if any.
     ///
     /// This takes precedence over `auto_detect_solc`, if a version is set then this overrides
@@ -174,6 +178,11 @@ pub struct Config {
     pub model_checker: Option<ModelCheckerSettings>,
     /// verbosity to use
     pub verbosity: u8,
+    /// Path to a TOML file mapping addresses to the labels they are shown with in traces, e.g.
+    /// `0x6B175474E89094C44Da98b954EedeAC495271d0F = "DAI"`
+    ///
+    /// Labels set with the `label` cheatcode take precedence.
+    pub labels_file: Option<PathBuf>,
     /// url of the rpc server that should be used for any rpc calls
     pub eth_rpc_url: Option<String>,
     /// etherscan API key, or alias for an `EtherscanConfig` in `etherscan` table
@@ -212,6 +221,21 @@ pub struct Config {
     /// Allows overriding an unsafe external call when running invariant tests. eg. reetrancy
     /// checks
     pub invariant_call_override: bool,
+    /// The directory where Solidity tests replaying the call sequences that broke invariants are
+    /// written to
+    pub invariant_replay_dir: Option<PathBuf>,
+    /// The directory of the corpora of invariant test contracts: the call sequences that hit new
+    /// code, which are replayed at the start of the next run
+    pub invariant_corpus_dir: Option<PathBuf>,
+    /// Relative weights of the calls to targeted contracts and selectors during invariant runs,
+    /// keyed by `<contract name>` or `<contract name>:<function signature>`.
+    ///
+    /// Targets without an entry have a weight of 1, a weight of 0 excludes the target.
+    pub invariant_selector_weights: BTreeMap<String, u32>,
+    /// The only addresses that call the targeted contracts during invariant runs.
+    ///
+    /// Takes precedence over `targetSenders()`. If empty, the senders are picked as usual.
+    pub invariant_senders: Vec<Address>,
     /// Whether to allow ffi cheatcodes in test
     pub ffi: bool,
     /// The address which will be executing all tests
@@ -279,11 +303,40 @@ pub struct Config {
     /// by proptest, to be encountered during usage of `vm.assume`
     /// cheatcode.
     pub fuzz_max_global_rejects: u32,
+    /// Whether to generate fuzz inputs from a dictionary of values seeded with the state and the
+    /// bytecode of the deployed contracts
+    pub fuzz_dictionary: bool,
+    /// How often, in percent, fuzz inputs are generated from the dictionary instead of at random
+    pub fuzz_dictionary_weight: u32,
     /// Optional seed for the fuzzing RNG algorithm
     #[serde(
         deserialize_with = "ethers_core::types::serde_helpers::deserialize_stringified_numeric_opt"
     )]
     pub fuzz_seed: Option<U256>,
+    /// The RNG algorithm used by the fuzzer, defaults to proptest's default algorithm
+    pub fuzz_rng_algorithm: Option<FuzzRngAlgorithm>,
+    /// The directory where failing fuzz cases are persisted, so they are replayed first on the
+    /// next run
+    pub fuzz_failure_persist_dir: Option<PathBuf>,
+    /// The maximum number of seconds spent fuzzing a single function, after which the test fails
+    pub fuzz_timeout: Option<u64>,
+    /// Addresses that half of the random `address` fuzz inputs are picked from
+    pub fuzz_addresses: Vec<Address>,
+    /// Whether the contracts deployed before a fuzz test, e.g. in `setUp()`, are added to the
+    /// `fuzz_addresses`
+    pub fuzz_addresses_deployed: bool,
+    /// The inclusive lower bound of `uint256` fuzz inputs
+    #[serde(
+        deserialize_with = "ethers_core::types::serde_helpers::deserialize_stringified_numeric_opt"
+    )]
+    pub fuzz_uint256_min: Option<U256>,
+    /// The inclusive upper bound of `uint256` fuzz inputs
+    #[serde(
+        deserialize_with = "ethers_core::types::serde_helpers::deserialize_stringified_numeric_opt"
+    )]
+    pub fuzz_uint256_max: Option<U256>,
+    /// Whether zero is never generated as `address`, `int` or `uint` fuzz input
+    pub fuzz_exclude_zero: bool,
     /// Print the names of the compiled contracts
     pub names: bool,
     /// Print the sizes of the compiled contracts
@@ -504,6 +557,22 @@ impl Config {
             self.build_info_path = Some(p(&root, &build_info_path));
         }
 
+        if let Some(fuzz_failure_persist_dir) = self.fuzz_failure_persist_dir {
+            self.fuzz_failure_persist_dir = Some(p(&root, &fuzz_failure_persist_dir));
+        }
+
+        if let Some(invariant_replay_dir) = self.invariant_replay_dir {
+            self.invariant_replay_dir = Some(p(&root, &invariant_replay_dir));
+        }
+
+        if let Some(invariant_corpus_dir) = self.invariant_corpus_dir {
+            self.invariant_corpus_dir = Some(p(&root, &invariant_corpus_dir));
+        }
+
+        if let Some(labels_file) = self.labels_file {
+            self.labels_file = Some(p(&root, &labels_file));
+        }
+
         self.libs = self.libs.into_iter().map(|lib| p(&root, &lib)).collect();
 
         self.remappings =
@@ -779,6 +848,27 @@ impl Config {
         Ok(None)
     }
 
+    /// Returns the address labels of the configured `labels_file`, if any
+    pub fn labels(&self) -> eyre::Result<BTreeMap<Address, String>> {
+        let path = match self.labels_file {
+            Some(ref path) => path,
+            None => return Ok(Default::default()),
+        };
+        let content = fs::read_to_string(path)
+            .wrap_err_with(|| format!("Failed to read labels file {}", path.display()))?;
+        let labels: BTreeMap<String, String> = toml::from_str(&content)
+            .wrap_err_with(|| format!("Failed to parse labels file {}", path.display()))?;
+        labels
+            .into_iter()
+            .map(|(address, label)| {
+                let address = address.parse::<Address>().wrap_err_with(|| {
+                    format!("Invalid address `{address}` in labels file {}", path.display())
+                })?;
+                Ok((address, label))
+            })
+            .collect()
+    }
+
     /// Helper function to just get the API key
     pub fn get_etherscan_api_key(&self, chain: Option<impl Into<Chain>>) -> Option<String> {
         self.get_etherscan_config_with_chain(chain).ok().flatten().map(|c| c.key)
@@ -1472,6 +1562,16 @@ impl<P: Into<PathBuf>> From<P> for RootPath {
     }
 }
 
+/// The RNG algorithms the fuzzer can be configured with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FuzzRngAlgorithm {
+    /// The XorShift algorithm, faster but with lower quality randomness
+    XorShift,
+    /// The ChaCha algorithm with 20 rounds
+    ChaCha,
+}
+
 /// Parses a config profile
 ///
 /// All `Profile` date is ignored by serde, however the `Config::to_string_pretty` includes it and
@@ -1535,6 +1635,8 @@ impl Default for Config {
             evm_version: Default::default(),
             gas_reports: vec!["*".to_string()],
             gas_reports_ignore: vec![],
+            gas_reports_include_paths: vec![],
+            gas_reports_exclude_paths: vec![],
             solc: None,
             auto_detect_solc: true,
             offline: false,
@@ -1555,11 +1657,25 @@ impl Default for Config {
             fuzz_runs: 256,
             fuzz_max_local_rejects: 1024,
             fuzz_max_global_rejects: 65536,
+            fuzz_dictionary: true,
+            fuzz_dictionary_weight: 40,
             fuzz_seed: None,
+            fuzz_rng_algorithm: None,
+            fuzz_failure_persist_dir: None,
+            fuzz_timeout: None,
+            fuzz_addresses: vec![],
+            fuzz_addresses_deployed: false,
+            fuzz_uint256_min: None,
+            fuzz_uint256_max: None,
+            fuzz_exclude_zero: false,
             invariant_runs: 256,
             invariant_depth: 15,
             invariant_fail_on_revert: false,
             invariant_call_override: false,
+            invariant_replay_dir: None,
+            invariant_corpus_dir: None,
+            invariant_selector_weights: Default::default(),
+            invariant_senders: vec![],
             ffi: false,
             sender: Config::DEFAULT_SENDER,
             tx_origin: Config::DEFAULT_SENDER,
@@ -1578,6 +1694,7 @@ impl Default for Config {
             eth_rpc_url: None,
             etherscan_api_key: None,
             verbosity: 0,
+            labels_file: None,
             remappings: vec![],
             libraries: vec![],
             ignored_error_codes: vec![
@@ -2514,6 +2631,40 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_labels_file() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [profile.default]
+                labels_file = 'labels.toml'
+            "#,
+            )?;
+            jail.create_file(
+                "labels.toml",
+                r#"
+                0x6B175474E89094C44Da98b954EedeAC495271d0F = "DAI"
+                "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48" = "USDC"
+            "#,
+            )?;
+            let config = Config::load();
+            assert_eq!(config.labels_file, Some(PathBuf::from("labels.toml")));
+            let labels = config.labels().unwrap();
+            assert_eq!(labels.len(), 2);
+            assert_eq!(
+                labels["0x6B175474E89094C44Da98b954EedeAC495271d0F".parse::<Address>().unwrap()],
+                "DAI"
+            );
+
+            jail.create_file("labels.toml", r#"notanaddress = "Foo""#)?;
+            assert!(config.labels().is_err());
+
+            assert!(Config::default().labels().unwrap().is_empty());
+            Ok(())
+        });
+    }
+
     #[test]
     fn test_default_test_path() {
         figment::Jail::expect_with(|_| {
@@ -3006,6 +3157,7 @@ mod tests {
                 fuzz_max_local_rejects = 1024
                 fuzz_runs = 256
                 fuzz_seed = '0x3e8'
+                fuzz_rng_algorithm = 'xorshift'
                 invariant_runs = 256
                 invariant_depth = 15
                 invariant_fail_on_revert = false
@@ -3049,6 +3201,7 @@ mod tests {
             let config = Config::load_with_root(jail.directory());
 
             assert_eq!(config.fuzz_seed, Some(1000.into()));
+            assert_eq!(config.fuzz_rng_algorithm, Some(FuzzRngAlgorithm::XorShift));
             assert_eq!(
                 config.remappings,
                 vec![Remapping::from_str("nested/=lib/nested/").unwrap().into()]
@@ -199,11 +199,7 @@ impl<'a> ContractVisitor<'a> {
                 // branch ID as we do
                 self.branch_id += 1;
 
-                self.push_item(CoverageItem {
-                    kind: CoverageItemKind::Branch { branch_id, path_id: 0 },
-                    loc: self.source_location_for(&node.src),
-                    hits: 0,
-                });
+                self.push_branches(&node.src, branch_id);
                 self.visit_block(*body)?;
 
                 Ok(())
@@ -264,7 +260,25 @@ impl<'a> ContractVisitor<'a> {
                     loc: self.source_location_for(&node.src),
                     hits: 0,
                 });
-                Ok(())
+
+                self.visit_expression(
+                    node.attribute("condition")
+                        .ok_or_else(|| eyre::eyre!("conditional had no condition"))?,
+                )?;
+
+                // Like if statements, a conditional takes one of two paths
+                let branch_id = self.branch_id;
+                self.branch_id += 1;
+                self.push_branches(&node.src, branch_id);
+
+                self.visit_expression(
+                    node.attribute("trueExpression")
+                        .ok_or_else(|| eyre::eyre!("conditional had no true expression"))?,
+                )?;
+                self.visit_expression(
+                    node.attribute("falseExpression")
+                        .ok_or_else(|| eyre::eyre!("conditional had no false expression"))?,
+                )
             }
             // Does not count towards coverage
             NodeType::FunctionCallOptions |
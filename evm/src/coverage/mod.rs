@@ -50,23 +50,47 @@ impl CoverageReport {
     /// Get coverage summaries by source file path
     pub fn summary_by_file(&self) -> impl Iterator<Item = (String, CoverageSummary)> {
         let mut summaries: BTreeMap<String, CoverageSummary> = BTreeMap::new();
+        // The number of paths taken and the total number of paths of every branch, keyed by the
+        // source file path, compiler version, contract and branch ID
+        let mut branches: HashMap<(String, Version, String, usize), (usize, usize)> =
+            HashMap::new();
 
         for (version, items) in self.items.iter() {
             for item in items {
-                let mut summary = summaries
-                    .entry(
-                        self.source_paths
-                            .get(&(version.clone(), item.loc.source_id))
-                            .cloned()
-                            .unwrap_or_else(|| {
-                                format!("Unknown (ID: {}, solc: {})", item.loc.source_id, version)
-                            }),
-                    )
-                    .or_default();
+                let path = self
+                    .source_paths
+                    .get(&(version.clone(), item.loc.source_id))
+                    .cloned()
+                    .unwrap_or_else(|| {
+                        format!("Unknown (ID: {}, solc: {})", item.loc.source_id, version)
+                    });
+                if let CoverageItemKind::Branch { branch_id, .. } = item.kind {
+                    let (taken, total) = branches
+                        .entry((
+                            path.clone(),
+                            version.clone(),
+                            item.loc.contract_name.clone(),
+                            branch_id,
+                        ))
+                        .or_default();
+                    *total += 1;
+                    if item.hits > 0 {
+                        *taken += 1;
+                    }
+                }
+                let mut summary = summaries.entry(path).or_default();
                 summary += item;
             }
         }
 
+        for ((path, ..), (taken, total)) in branches {
+            if taken > 0 && taken < total {
+                if let Some(summary) = summaries.get_mut(&path) {
+                    summary.partial_branch_count += 1;
+                }
+            }
+        }
+
         summaries.into_iter()
     }
 
@@ -93,6 +117,25 @@ impl CoverageReport {
         items_by_source.into_iter()
     }
 
+    /// Get coverage items by the on-disk path of their source file.
+    ///
+    /// Unlike [CoverageReport::items_by_source], this includes source files without any coverage
+    /// items, and skips items whose source ID does not map to a known source file.
+    pub fn items_by_source_path(&self) -> impl Iterator<Item = (String, Vec<CoverageItem>)> {
+        let mut items_by_source: BTreeMap<String, Vec<CoverageItem>> =
+            self.source_paths.values().map(|path| (path.clone(), Vec::new())).collect();
+
+        for (version, items) in self.items.iter() {
+            for item in items {
+                if let Some(path) = self.source_paths.get(&(version.clone(), item.loc.source_id)) {
+                    items_by_source.entry(path.clone()).or_default().push(item.clone());
+                }
+            }
+        }
+
+        items_by_source.into_iter()
+    }
+
     /// Processes data from a [HitMap] and sets hit counts for coverage items in this coverage map.
     ///
     /// This function should only be called *after* all the relevant sources have been processed and
@@ -128,6 +171,11 @@ impl HitMap {
     pub fn hit(&mut self, pc: usize) {
         *self.hits.entry(pc).or_default() += 1;
     }
+
+    /// Returns the program counters that were hit
+    pub fn pcs(&self) -> impl Iterator<Item = usize> + '_ {
+        self.hits.keys().copied()
+    }
 }
 
 /// A unique identifier for a contract
@@ -260,6 +308,8 @@ pub struct CoverageSummary {
     pub branch_count: usize,
     /// The number of branches that were hit.
     pub branch_hits: usize,
+    /// The number of branches where only some of the paths were taken.
+    pub partial_branch_count: usize,
     /// The number of functions in the source file.
     pub function_count: usize,
     /// The number of functions hit.
@@ -274,6 +324,7 @@ impl AddAssign<&Self> for CoverageSummary {
         self.statement_hits += other.statement_hits;
         self.branch_count += other.branch_count;
         self.branch_hits += other.branch_hits;
+        self.partial_branch_count += other.partial_branch_count;
         self.function_count += other.function_count;
         self.function_hits += other.function_hits;
     }
@@ -340,3 +391,51 @@ impl AddAssign<&CoverageItem> for &mut CoverageSummary {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn branch(branch_id: usize, path_id: usize, hits: u64) -> CoverageItem {
+        CoverageItem {
+            kind: CoverageItemKind::Branch { branch_id, path_id },
+            loc: SourceLocation {
+                source_id: 0,
+                contract_name: "Counter".to_string(),
+                start: 0,
+                length: None,
+                line: 1,
+            },
+            hits,
+        }
+    }
+
+    #[test]
+    fn counts_partially_covered_branches() {
+        let version = Version::new(0, 8, 15);
+        let mut report = CoverageReport::default();
+        report.add_source(version.clone(), 0, "src/Counter.sol".to_string());
+        report.add_items(
+            version,
+            vec![
+                // fully covered
+                branch(0, 0, 1),
+                branch(0, 1, 2),
+                // partially covered
+                branch(1, 0, 0),
+                branch(1, 1, 3),
+                // not covered at all
+                branch(2, 0, 0),
+                branch(2, 1, 0),
+            ],
+        );
+
+        let summaries = report.summary_by_file().collect::<Vec<_>>();
+        assert_eq!(summaries.len(), 1);
+        let (path, summary) = &summaries[0];
+        assert_eq!(path, "src/Counter.sol");
+        assert_eq!(summary.branch_count, 6);
+        assert_eq!(summary.branch_hits, 3);
+        assert_eq!(summary.partial_branch_count, 1);
+    }
+}
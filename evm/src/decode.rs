@@ -2,16 +2,24 @@
 use crate::{
     abi::ConsoleEvents::{self, *},
     executor::inspector::cheatcodes::util::ERROR_PREFIX,
+    trace::identifier::SignaturesIdentifier,
 };
 use ethers::{
-    abi::{AbiDecode, Contract as Abi, RawLog},
+    abi::{AbiDecode, Contract as Abi, Function, RawLog, Token},
     contract::EthLogDecode,
     prelude::U256,
     types::Log,
 };
 use foundry_common::SELECTOR_LEN;
+use foundry_utils::{format_token, get_func, selectors::SelectorType};
 use revm::Return;
 
+/// The selector of the builtin `Error(string)` error
+pub const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+
+/// The selector of the builtin `Panic(uint256)` error
+pub const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+
 /// Decode a set of logs, only returning logs from DSTest logging events and Hardhat's `console.log`
 pub fn decode_console_logs(logs: &[Log]) -> Vec<String> {
     logs.iter().filter_map(decode_console_log).collect()
@@ -63,6 +71,46 @@ pub fn decode_console_log(log: &Log) -> Option<String> {
     Some(decoded)
 }
 
+/// Returns the meaning of the code of a Solidity `Panic(uint256)` error.
+///
+/// ref: <https://docs.soliditylang.org/en/latest/control-structures.html#panic-via-assert-and-error-via-require>
+pub fn panic_reason(code: U256) -> Option<&'static str> {
+    if code > U256::from(u8::MAX) {
+        return None
+    }
+    let reason = match code.low_u32() {
+        // generic compiler inserted panic
+        0x00 => "Generic compiler panic",
+        // assert
+        0x01 => "Assertion violated",
+        // safemath over/underflow
+        0x11 => "Arithmetic over/underflow",
+        // divide by 0
+        0x12 => "Division or modulo by 0",
+        // conversion into non-existent enum type
+        0x21 => "Conversion into non-existent enum type",
+        // incorrectly encoded storage byte array
+        0x22 => "Incorrectly encoded storage byte array",
+        // pop() on empty array
+        0x31 => "`pop()` on empty array",
+        // index out of bounds
+        0x32 => "Index out of bounds",
+        // allocating too much memory or creating too large array
+        0x41 => "Memory allocation overflow",
+        // calling a zero initialized variable of internal function type
+        0x51 => "Calling a zero initialized variable of internal function type",
+        _ => return None,
+    };
+    Some(reason)
+}
+
+/// Decodes the code of `Panic(uint256)` revert data
+fn decode_panic_code(err: &[u8]) -> eyre::Result<U256> {
+    err.get(SELECTOR_LEN..SELECTOR_LEN + 32)
+        .map(U256::from_big_endian)
+        .ok_or_else(|| eyre::eyre!("Bad panic decode"))
+}
+
 /// Given an ABI encoded error string with the function signature `Error(string)`, it decodes
 /// it and returns the revert error message.
 pub fn decode_revert(
@@ -78,54 +126,12 @@ pub fn decode_revert(
         }
         eyre::bail!("Not enough error data to decode")
     }
-    match err[..SELECTOR_LEN] {
-        // keccak(Panic(uint256))
-        [78, 72, 123, 113] => {
-            // ref: https://soliditydeveloper.com/solidity-0.8
-            match err[err.len() - 1] {
-                1 => {
-                    // assert
-                    Ok("Assertion violated".to_string())
-                }
-                17 => {
-                    // safemath over/underflow
-                    Ok("Arithmetic over/underflow".to_string())
-                }
-                18 => {
-                    // divide by 0
-                    Ok("Division or modulo by 0".to_string())
-                }
-                33 => {
-                    // conversion into non-existent enum type
-                    Ok("Conversion into non-existent enum type".to_string())
-                }
-                34 => {
-                    // incorrectly encoded storage byte array
-                    Ok("Incorrectly encoded storage byte array".to_string())
-                }
-                49 => {
-                    // pop() on empty array
-                    Ok("`pop()` on empty array".to_string())
-                }
-                50 => {
-                    // index out of bounds
-                    Ok("Index out of bounds".to_string())
-                }
-                65 => {
-                    // allocating too much memory or creating too large array
-                    Ok("Memory allocation overflow".to_string())
-                }
-                81 => {
-                    // calling a zero initialized variable of internal function type
-                    Ok("Calling a zero initialized variable of internal function type".to_string())
-                }
-                _ => {
-                    eyre::bail!("Unsupported solidity builtin panic")
-                }
-            }
-        }
-        // keccak(Error(string))
-        [8, 195, 121, 160] => {
+    let selector: [u8; SELECTOR_LEN] = err[..SELECTOR_LEN].try_into().unwrap();
+    match selector {
+        PANIC_SELECTOR => panic_reason(decode_panic_code(err)?)
+            .map(str::to_string)
+            .ok_or_else(|| eyre::eyre!("Unsupported solidity builtin panic")),
+        ERROR_SELECTOR => {
             String::decode(&err[SELECTOR_LEN..]).map_err(|_| eyre::eyre!("Bad string decode"))
         }
         // keccak(expectRevert(bytes))
@@ -198,3 +204,126 @@ pub fn decode_revert(
         }
     }
 }
+
+/// Decodes ABI encoded revert data, as returned by a reverted call.
+///
+/// `Error(string)` is decoded to its message and `Panic(uint256)` to its code and the meaning of
+/// the code. Any other selector is looked up as custom error with the signature `identifier`, if
+/// any, and otherwise decoded like [decode_revert].
+pub async fn decode_revert_data(
+    data: &[u8],
+    identifier: Option<&mut SignaturesIdentifier>,
+) -> eyre::Result<String> {
+    if data.len() < SELECTOR_LEN {
+        eyre::bail!("Revert data is too short: expected at least a 4 byte selector")
+    }
+    if data[..SELECTOR_LEN] == PANIC_SELECTOR {
+        let code = decode_panic_code(data)?;
+        return Ok(match panic_reason(code) {
+            Some(reason) => format!("Panic({code:#x}): {reason}"),
+            None => format!("Panic({code:#x})"),
+        })
+    }
+    if let Some(identifier) = identifier {
+        if let Some((error, args)) = decode_custom_error(data, identifier).await {
+            let args = args.iter().map(format_token).collect::<Vec<_>>().join(", ");
+            return Ok(format!("{}({args})", error.name))
+        }
+    }
+    decode_revert(data, None, None).map_err(|_| {
+        eyre::eyre!("No error signature found for selector 0x{}", hex::encode(&data[..SELECTOR_LEN]))
+    })
+}
+
+/// Looks up the selector of the revert data as custom error with the signature `identifier` and
+/// decodes the revert data with the first signature that matches it.
+///
+/// Errors are encoded like function calls, so the error is returned as `Function` whose inputs are
+/// the parameters of the error. The builtin `Error(string)` and `Panic(uint256)` are not looked
+/// up.
+pub async fn decode_custom_error(
+    data: &[u8],
+    identifier: &mut SignaturesIdentifier,
+) -> Option<(Function, Vec<Token>)> {
+    // the arguments of a custom error are ABI encoded in 32 byte words
+    if data.len() < SELECTOR_LEN ||
+        (data.len() - SELECTOR_LEN) % 32 != 0 ||
+        data[..SELECTOR_LEN] == ERROR_SELECTOR ||
+        data[..SELECTOR_LEN] == PANIC_SELECTOR
+    {
+        return None
+    }
+
+    let selector = format!("0x{}", hex::encode(&data[..SELECTOR_LEN]));
+    identifier.decode_selector(&selector, SelectorType::Error).await.ok()?.iter().find_map(|sig| {
+        let error = get_func(sig.trim_start_matches("error ")).ok()?;
+        let args = error.decode_input(&data[SELECTOR_LEN..]).ok()?;
+        Some((error, args))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A signature identifier that only knows `InsufficientBalance(uint256,uint256)` and never
+    /// queries `sig.eth.samczsun.com`
+    fn offline_identifier(dir: &std::path::Path, selector: &str) -> SignaturesIdentifier {
+        let cache = serde_json::json!({
+            "events": {},
+            "functions": {},
+            "candidates": {
+                "events": {},
+                "functions": {},
+                "errors": { (selector): ["InsufficientBalance(uint256,uint256)"] },
+            },
+        });
+        std::fs::write(dir.join("signatures"), cache.to_string()).unwrap();
+        SignaturesIdentifier::new(Some(dir.into()), true).unwrap()
+    }
+
+    #[tokio::test]
+    async fn decodes_custom_error_offline() {
+        let mut data = ethers::utils::id("InsufficientBalance(uint256,uint256)").to_vec();
+        data.extend(ethers::abi::encode(&[Token::Uint(10.into()), Token::Uint(20.into())]));
+        let selector = format!("0x{}", hex::encode(&data[..SELECTOR_LEN]));
+
+        let tmp = tempfile::tempdir().unwrap();
+        let mut identifier = offline_identifier(tmp.path(), &selector);
+
+        let (error, args) = decode_custom_error(&data, &mut identifier).await.unwrap();
+        assert_eq!(error.name, "InsufficientBalance");
+        assert_eq!(args, vec![Token::Uint(10.into()), Token::Uint(20.into())]);
+        assert_eq!(
+            decode_revert_data(&data, Some(&mut identifier)).await.unwrap(),
+            "InsufficientBalance(10, 20)"
+        );
+
+        // the arguments don't fit the signature
+        assert!(decode_custom_error(&data[..SELECTOR_LEN + 32], &mut identifier).await.is_none());
+
+        // unknown selectors are not looked up offline
+        let unknown = [0xde, 0xad, 0xbe, 0xef];
+        assert!(decode_custom_error(&unknown, &mut identifier).await.is_none());
+        assert_eq!(
+            decode_revert_data(&unknown, Some(&mut identifier)).await.unwrap_err().to_string(),
+            "No error signature found for selector 0xdeadbeef"
+        );
+    }
+
+    #[tokio::test]
+    async fn decodes_builtin_errors_without_identifier() {
+        let mut panic = PANIC_SELECTOR.to_vec();
+        panic.extend(ethers::abi::encode(&[Token::Uint(0x11.into())]));
+        assert_eq!(
+            decode_revert_data(&panic, None).await.unwrap(),
+            "Panic(0x11): Arithmetic over/underflow"
+        );
+
+        let mut error = ERROR_SELECTOR.to_vec();
+        error.extend(ethers::abi::encode(&[Token::String("insufficient balance".to_string())]));
+        assert_eq!(decode_revert_data(&error, None).await.unwrap(), "insufficient balance");
+
+        assert!(decode_revert_data(&[0x4e, 0x48, 0x7b], None).await.is_err());
+    }
+}
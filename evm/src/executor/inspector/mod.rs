@@ -22,10 +22,14 @@ pub use cheatcodes::{Cheatcodes, CheatsConfig, DEFAULT_CREATE2_DEPLOYER};
 use ethers::types::U256;
 
 use revm::BlockEnv;
+use std::time::Instant;
 
 mod fuzzer;
 pub use fuzzer::Fuzzer;
 
+mod timeout;
+pub use timeout::Timeout;
+
 #[derive(Default, Clone, Debug)]
 pub struct InspectorStackConfig {
     /// The cheatcode inspector and its state, if cheatcodes are enabled.
@@ -49,6 +53,8 @@ pub struct InspectorStackConfig {
     pub fuzzer: Option<Fuzzer>,
     /// Whether or not coverage info should be collected
     pub coverage: bool,
+    /// The instant after which the execution is halted, if any
+    pub deadline: Option<Instant>,
 }
 
 impl InspectorStackConfig {
@@ -73,6 +79,7 @@ impl InspectorStackConfig {
         if self.coverage {
             stack.coverage = Some(CoverageCollector::default());
         }
+        stack.timeout = self.deadline.map(Timeout::new);
         stack
     }
 
@@ -1,4 +1,4 @@
-use super::{Cheatcodes, Debugger, Fuzzer, LogCollector, Tracer};
+use super::{Cheatcodes, Debugger, Fuzzer, LogCollector, Timeout, Tracer};
 use crate::{
     coverage::HitMaps,
     debug::DebugArena,
@@ -43,6 +43,7 @@ pub struct InspectorStack {
     pub debugger: Option<Debugger>,
     pub fuzzer: Option<Fuzzer>,
     pub coverage: Option<CoverageCollector>,
+    pub timeout: Option<Timeout>,
 }
 
 impl InspectorStack {
@@ -103,6 +104,7 @@ where
         call_inspectors!(
             inspector,
             [
+                &mut self.timeout,
                 &mut self.fuzzer,
                 &mut self.debugger,
                 &mut self.tracer,
@@ -0,0 +1,49 @@
+use revm::{Database, EVMData, Inspector, Interpreter, Return};
+use std::time::Instant;
+
+/// The number of steps between two checks of the deadline.
+///
+/// Reading the clock on every step noticeably slows down the execution.
+const STEPS_PER_CHECK: u32 = 1024;
+
+/// An inspector that halts the execution once a deadline passed.
+///
+/// Every frame halts at its next step with [Return::OutOfGas], so the whole call unwinds instead
+/// of running to completion.
+#[derive(Debug, Clone)]
+pub struct Timeout {
+    /// The instant after which the execution is halted
+    pub deadline: Instant,
+    /// The number of steps since the deadline was last checked
+    steps: u32,
+    /// Whether the deadline passed
+    expired: bool,
+}
+
+impl Timeout {
+    pub fn new(deadline: Instant) -> Self {
+        Self { deadline, steps: 0, expired: false }
+    }
+}
+
+impl<DB> Inspector<DB> for Timeout
+where
+    DB: Database,
+{
+    fn step(&mut self, _: &mut Interpreter, _: &mut EVMData<'_, DB>, _: bool) -> Return {
+        if !self.expired {
+            self.steps += 1;
+            if self.steps < STEPS_PER_CHECK {
+                return Return::Continue
+            }
+            self.steps = 0;
+            self.expired = Instant::now() >= self.deadline;
+        }
+
+        if self.expired {
+            Return::OutOfGas
+        } else {
+            Return::Continue
+        }
+    }
+}
@@ -407,6 +407,22 @@ impl Executor {
         convert_executed_call(inspector, executed_call)
     }
 
+    /// Executes the transaction of the given `env` on the current state of the VM.
+    ///
+    /// Any state modifications made by the transaction are not committed, but returned as
+    /// `state_changeset`.
+    pub fn call_raw_with_env(&self, env: Env) -> eyre::Result<RawCallResult> {
+        let mut inspector = self.inspector_config.stack();
+        let stipend = calc_stipend(&env.tx.data, env.cfg.spec_id);
+        let mut db = FuzzBackendWrapper::new(self.backend());
+        let (status, out, gas, state_changeset, logs) = db.inspect_ref(env, &mut inspector);
+
+        let logs = db.backend.merged_logs(logs);
+
+        let executed_call = ExecutedCall { status, out, gas, state_changeset, logs, stipend };
+        convert_executed_call(inspector, executed_call)
+    }
+
     /// Deploys a contract using the given `env` and commits the new state to the underlying
     /// database
     pub fn deploy_with_env(
@@ -0,0 +1,221 @@
+//! Persistence of the call sequences that increased the coverage of invariant runs
+use super::BasicTxDetails;
+use crate::coverage::HitMaps;
+use ethers::{
+    types::{Address, Bytes, H256},
+    utils::keccak256,
+};
+use eyre::WrapErr;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
+use tracing::{trace, warn};
+
+/// The version of the format of corpus entries, entries of other versions are discarded
+const CORPUS_VERSION: u32 = 1;
+
+/// A call sequence of the corpus, stored as `<keccak256 of the entry>.json`
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CorpusEntry {
+    version: u32,
+    /// The hash of the code of the contracts the sequence was recorded against
+    code_hash: H256,
+    sequence: Vec<CorpusCall>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CorpusCall {
+    sender: Address,
+    target: Address,
+    calldata: Bytes,
+}
+
+/// The corpus of an invariant test contract: the call sequences of previous runs that reached
+/// new code, which are replayed at the start of the next run to seed the exploration.
+///
+/// Entries are content addressed, and only entries recorded against the same code of the test
+/// contract and its targets are loaded. Stale entries are removed.
+#[derive(Debug)]
+pub struct InvariantCorpus {
+    dir: PathBuf,
+    code_hash: H256,
+    /// The hashes of the entries in the corpus
+    entries: HashSet<H256>,
+    sequences: Vec<Vec<BasicTxDetails>>,
+}
+
+impl InvariantCorpus {
+    /// Loads the corpus in `dir`, discarding all entries that were not recorded against
+    /// `code_hash` or with another version of the format.
+    pub fn load(dir: impl Into<PathBuf>, code_hash: H256) -> eyre::Result<Self> {
+        let mut corpus =
+            Self { dir: dir.into(), code_hash, entries: HashSet::new(), sequences: Vec::new() };
+        if !corpus.dir.exists() {
+            return Ok(corpus)
+        }
+
+        let mut paths = fs::read_dir(&corpus.dir)
+            .wrap_err_with(|| format!("Failed to read corpus {}", corpus.dir.display()))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().map_or(false, |ext| ext == "json"))
+            .collect::<Vec<_>>();
+        // load the sequences in a deterministic order
+        paths.sort();
+
+        for path in paths {
+            match read_entry(&path) {
+                Some(entry) if entry.version == CORPUS_VERSION && entry.code_hash == code_hash => {
+                    if let Some(hash) = entry_hash(&path) {
+                        corpus.entries.insert(hash);
+                    }
+                    corpus.sequences.push(
+                        entry
+                            .sequence
+                            .into_iter()
+                            .map(|call| (call.sender, (call.target, call.calldata)))
+                            .collect(),
+                    );
+                }
+                _ => {
+                    trace!(target: "forge::test::fuzz", ?path, "discarding stale corpus entry");
+                    let _ = fs::remove_file(&path);
+                }
+            }
+        }
+        trace!(target: "forge::test::fuzz", sequences = corpus.sequences.len(), "loaded corpus");
+        Ok(corpus)
+    }
+
+    /// Returns the sequences of the corpus
+    pub fn sequences(&self) -> &[Vec<BasicTxDetails>] {
+        &self.sequences
+    }
+
+    /// Adds `sequence` to the corpus.
+    ///
+    /// Returns `false` if the corpus already contained the sequence.
+    pub fn insert(&mut self, sequence: &[BasicTxDetails]) -> eyre::Result<bool> {
+        let entry = CorpusEntry {
+            version: CORPUS_VERSION,
+            code_hash: self.code_hash,
+            sequence: sequence
+                .iter()
+                .map(|(sender, (target, calldata))| CorpusCall {
+                    sender: *sender,
+                    target: *target,
+                    calldata: calldata.clone(),
+                })
+                .collect(),
+        };
+        let content = serde_json::to_string(&entry)?;
+        let hash = H256(keccak256(&content));
+        if !self.entries.insert(hash) {
+            return Ok(false)
+        }
+
+        fs::create_dir_all(&self.dir)?;
+        let path = self.dir.join(format!("{hash:x}.json"));
+        fs::write(&path, content)
+            .wrap_err_with(|| format!("Failed to write corpus entry {}", path.display()))?;
+        self.sequences.push(sequence.to_vec());
+        Ok(true)
+    }
+}
+
+/// Reads a corpus entry, returning `None` if it is malformed
+fn read_entry(path: &Path) -> Option<CorpusEntry> {
+    let content = fs::read_to_string(path).ok()?;
+    match serde_json::from_str(&content) {
+        Ok(entry) => Some(entry),
+        Err(err) => {
+            warn!(target: "forge::test::fuzz", ?path, %err, "malformed corpus entry");
+            None
+        }
+    }
+}
+
+/// Returns the hash a corpus entry is named after
+fn entry_hash(path: &Path) -> Option<H256> {
+    path.file_stem()?.to_str()?.parse().ok()
+}
+
+/// The program counters that were hit during the invariant runs of a test contract
+#[derive(Debug, Default)]
+pub struct CoverageTracker {
+    hits: HashSet<(Address, usize)>,
+}
+
+impl CoverageTracker {
+    /// Records the program counters hit by a call, returning `true` if any of them was not hit
+    /// before
+    pub fn record(&mut self, hit_maps: &HitMaps) -> bool {
+        let mut new_coverage = false;
+        for (address, hit_map) in hit_maps {
+            for pc in hit_map.pcs() {
+                new_coverage |= self.hits.insert((*address, pc));
+            }
+        }
+        new_coverage
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coverage::HitMap;
+
+    fn sequence(calls: u64) -> Vec<BasicTxDetails> {
+        (0..calls)
+            .map(|i| {
+                (
+                    Address::from_low_u64_be(i),
+                    (Address::from_low_u64_be(0xbeef), vec![i as u8; 4].into()),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn persists_sequences_by_code_hash() {
+        let dir = tempfile::tempdir().unwrap();
+        let code_hash = H256::from_low_u64_be(1);
+
+        let mut corpus = InvariantCorpus::load(dir.path(), code_hash).unwrap();
+        assert!(corpus.sequences().is_empty());
+        assert!(corpus.insert(&sequence(2)).unwrap());
+        assert!(!corpus.insert(&sequence(2)).unwrap());
+        assert!(corpus.insert(&sequence(3)).unwrap());
+        fs::write(dir.path().join("malformed.json"), "{").unwrap();
+
+        let corpus = InvariantCorpus::load(dir.path(), code_hash).unwrap();
+        assert_eq!(corpus.sequences().len(), 2);
+        assert!(corpus.sequences().contains(&sequence(3)));
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+
+        // the contracts changed, so the corpus is stale
+        let corpus = InvariantCorpus::load(dir.path(), H256::from_low_u64_be(2)).unwrap();
+        assert!(corpus.sequences().is_empty());
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn tracks_new_coverage() {
+        let mut hit_map = HitMap::default();
+        hit_map.hit(1);
+        hit_map.hit(2);
+        let mut hit_maps = HitMaps::new();
+        hit_maps.insert(Address::zero(), hit_map.clone());
+
+        let mut tracker = CoverageTracker::default();
+        assert!(tracker.record(&hit_maps));
+        assert!(!tracker.record(&hit_maps));
+
+        hit_map.hit(3);
+        hit_maps.insert(Address::zero(), hit_map);
+        assert!(tracker.record(&hit_maps));
+    }
+}
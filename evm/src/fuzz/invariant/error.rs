@@ -75,6 +75,8 @@ impl InvariantFuzzError {
     }
 
     /// Replays the error case and collects all necessary traces.
+    ///
+    /// The failing sequence is shrunk first, see [`Self::try_shrinking`].
     pub fn replay(
         &self,
         mut executor: Executor,
@@ -82,6 +84,7 @@ impl InvariantFuzzError {
         mut ided_contracts: ContractsByAddress,
         logs: &mut Vec<Log>,
         traces: &mut Vec<(TraceKind, CallTraceArena)>,
+        fail_on_revert: bool,
     ) -> Option<CounterExample> {
         let mut counterexample_sequence = vec![];
         let calls = match self.test_error {
@@ -90,7 +93,7 @@ impl InvariantFuzzError {
             TestError::Fail(_, ref calls) => calls,
         };
 
-        let calls = self.try_shrinking(calls, &executor);
+        let calls = self.try_shrinking(calls, &executor, fail_on_revert);
 
         // We want traces for a failed case.
         executor.set_tracing(true);
@@ -137,31 +140,29 @@ impl InvariantFuzzError {
             .then_some(CounterExample::Sequence(counterexample_sequence))
     }
 
-    /// Tests that the modified sequence of calls successfully reverts on the error function.
+    /// Tests that the given sequence of calls still leads to this failure.
+    ///
+    /// Returns the sequence up to and including the call after which the failure occurred, or
+    /// `None` if the sequence does not reproduce it.
+    ///
+    /// If `fail_on_revert` is set, a run ends with the first reverting call. A sequence that
+    /// reverts before breaking the invariant therefore has a different outcome and is rejected.
     fn fails_successfully<'a>(
         &self,
         mut executor: Executor,
-        calls: &'a [BasicTxDetails],
-        anchor: usize,
-        removed_calls: &[usize],
-    ) -> Result<Vec<&'a BasicTxDetails>, ()> {
-        let calls = calls.iter().enumerate().filter_map(|(index, element)| {
-            if anchor > index || removed_calls.contains(&index) {
-                return None
-            }
-            Some(element)
-        });
-
-        let mut new_sequence = vec![];
-        for details in calls {
-            new_sequence.push(details);
-
-            let (sender, (addr, bytes)) = details;
-
-            executor
+        calls: &[&'a BasicTxDetails],
+        fail_on_revert: bool,
+    ) -> Option<Vec<&'a BasicTxDetails>> {
+        for (index, (sender, (addr, bytes))) in calls.iter().enumerate() {
+            let call_result = executor
                 .call_raw_committing(*sender, *addr, bytes.0.clone(), 0.into())
                 .expect("bad call to evm");
 
+            if fail_on_revert && call_result.reverted {
+                // Only reproduces the failure if the failure was the revert itself.
+                return self.func.is_none().then(|| calls[..=index].to_vec())
+            }
+
             // Checks the invariant. If we exit before the last call, all the better.
             if let Some(func) = &self.func {
                 let error_call_result = executor
@@ -169,66 +170,100 @@ impl InvariantFuzzError {
                     .expect("bad call to evm");
 
                 if error_call_result.reverted {
-                    return Ok(new_sequence)
+                    return Some(calls[..=index].to_vec())
                 }
             }
         }
 
-        Err(())
+        None
     }
 
-    /// Tries to shrink the failure case to its smallest sequence of calls.
-    ///
-    /// Sets an anchor at the beginning (index=0) and tries to remove all other calls one by one,
-    /// until it reaches the last one. The elements which were removed and lead to a failure are
-    /// kept in the removal list. The removed ones that didn't lead to a failure are inserted
-    /// back into the sequence.
-    ///
-    /// Once it reaches the end, it increments the anchor, resets the removal list and starts the
-    /// same process again.
-    ///
-    /// Returns the smallest sequence found.
+    /// Tries to shrink the failure case to its smallest sequence of calls, see [shrink_sequence].
     fn try_shrinking<'a>(
         &self,
         calls: &'a [BasicTxDetails],
         executor: &Executor,
+        fail_on_revert: bool,
     ) -> Vec<&'a BasicTxDetails> {
-        let mut anchor = 0;
-        let mut removed_calls = vec![];
-        let mut shrinked = calls.iter().collect::<Vec<_>>();
-
-        while anchor != calls.len() {
-            // Get the latest removed element, so we know which one to remove next.
-            let removed =
-                match self.fails_successfully(executor.clone(), calls, anchor, &removed_calls) {
-                    Ok(new_sequence) => {
-                        if shrinked.len() > new_sequence.len() {
-                            shrinked = new_sequence;
-                        }
-                        removed_calls.last().cloned()
-                    }
-                    Err(_) => removed_calls.pop(),
-                };
+        shrink_sequence(calls.iter().collect(), |calls| {
+            self.fails_successfully(executor.clone(), calls, fail_on_revert)
+        })
+    }
+}
 
-            if let Some(last_removed) = removed {
-                // If we haven't reached the end of the sequence, then remove the next element.
-                // Otherwise, restart the process with an incremented anchor.
+/// Shrinks a failing sequence of calls to its smallest failing subsequence.
+///
+/// `fails` returns the sequence up to and including the call after which the failure occurred, or
+/// `None` if the sequence does not reproduce it.
+///
+/// Starts by trying to drop the first and the second half of the sequence, and bisects the size of
+/// the dropped chunk whenever none of the chunks of the current size can be dropped without losing
+/// the failure. Dropping stops once not even single calls can be removed.
+///
+/// The relative order of the remaining calls is preserved. Returns the smallest sequence found.
+fn shrink_sequence<T: Copy>(
+    calls: Vec<T>,
+    mut fails: impl FnMut(&[T]) -> Option<Vec<T>>,
+) -> Vec<T> {
+    let mut shrunk = fails(&calls).unwrap_or(calls);
 
-                let next_removed = last_removed + 1;
+    let mut chunk_size = (shrunk.len() / 2).max(1);
+    while chunk_size > 0 && shrunk.len() > 1 {
+        let mut dropped_any = false;
+        let mut start = 0;
 
-                if next_removed > calls.len() - 1 {
-                    anchor += 1;
-                    removed_calls = vec![];
-                    continue
-                }
+        while start < shrunk.len() {
+            let end = (start + chunk_size).min(shrunk.len());
+            let candidate =
+                shrunk[..start].iter().chain(&shrunk[end..]).copied().collect::<Vec<_>>();
 
-                removed_calls.push(next_removed);
-            } else {
-                // When the process is restarted, `removed_calls` will be empty.
-                removed_calls.push(anchor + 1);
+            match fails(&candidate) {
+                Some(new_sequence) => {
+                    shrunk = new_sequence;
+                    dropped_any = true;
+                }
+                None => start += chunk_size,
             }
         }
 
-        shrinked
+        if !dropped_any {
+            chunk_size /= 2;
+        }
+    }
+
+    shrunk
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fails once both `1` and `3` were called, in this order
+    fn fails_after_1_and_3(calls: &[u32]) -> Option<Vec<u32>> {
+        let first = calls.iter().position(|call| *call == 1)?;
+        let second = first + calls[first..].iter().position(|call| *call == 3)?;
+        Some(calls[..=second].to_vec())
+    }
+
+    #[test]
+    fn shrinks_to_minimal_failing_sequence() {
+        let calls = vec![0, 1, 2, 2, 3, 4, 5, 6];
+        assert_eq!(shrink_sequence(calls, fails_after_1_and_3), vec![1, 3]);
+
+        // the sequence is first truncated to the calls up to the failure
+        let mut runs = vec![];
+        let shrunk = shrink_sequence(vec![3, 1, 3, 7, 7], |calls| {
+            runs.push(calls.to_vec());
+            fails_after_1_and_3(calls)
+        });
+        assert_eq!(runs[0], vec![3, 1, 3, 7, 7]);
+        assert_eq!(shrunk, vec![1, 3]);
+    }
+
+    #[test]
+    fn keeps_sequence_that_cannot_be_shrunk() {
+        assert_eq!(shrink_sequence(vec![1, 3], fails_after_1_and_3), vec![1, 3]);
+        // a sequence that doesn't reproduce the failure is returned as is
+        assert_eq!(shrink_sequence(vec![3, 1], fails_after_1_and_3), vec![3, 1]);
     }
 }
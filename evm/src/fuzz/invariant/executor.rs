@@ -1,7 +1,9 @@
 use super::{
-    assert_invariants, filters::ArtifactFilters, BasicTxDetails, FuzzRunIdentifiedContracts,
-    InvariantContract, InvariantFuzzError, InvariantFuzzTestResult, InvariantTestOptions,
-    RandomCallGenerator, TargetedContracts,
+    assert_invariants,
+    corpus::{CoverageTracker, InvariantCorpus},
+    filters::ArtifactFilters,
+    BasicTxDetails, FuzzRunIdentifiedContracts, InvariantContract, InvariantFuzzError,
+    InvariantFuzzTestResult, InvariantTestOptions, RandomCallGenerator, TargetedContracts,
 };
 use crate::{
     executor::{
@@ -10,7 +12,8 @@ use crate::{
     fuzz::{
         strategies::{
             build_initial_state, collect_created_contracts, collect_state_from_call,
-            invariant_strat, override_call_strat, EvmFuzzState,
+            fuzzable_functions, invariant_strat, override_call_strat, selector_weight,
+            EvmFuzzState,
         },
         FuzzCase, FuzzedCases,
     },
@@ -19,7 +22,8 @@ use crate::{
 };
 use ethers::{
     abi::{Abi, Address, Detokenize, FixedBytes, Function, Tokenizable, TokenizableItem},
-    prelude::U256,
+    prelude::{H256, U256},
+    utils::keccak256,
 };
 use eyre::ContextCompat;
 use foundry_common::contracts::{ContractsByAddress, ContractsByArtifact};
@@ -28,7 +32,7 @@ use proptest::{
     strategy::{BoxedStrategy, Strategy, ValueTree},
     test_runner::{TestCaseError, TestRunner},
 };
-use revm::DatabaseCommit;
+use revm::{DatabaseCommit, DatabaseRef};
 use std::{cell::RefCell, collections::BTreeMap, sync::Arc};
 use tracing::warn;
 
@@ -79,7 +83,21 @@ impl<'a> InvariantExecutor<'a> {
         test_options: InvariantTestOptions,
     ) -> eyre::Result<Option<InvariantFuzzTestResult>> {
         let (fuzz_state, targeted_contracts, strat) =
-            self.prepare_fuzzing(&invariant_contract, test_options)?;
+            self.prepare_fuzzing(&invariant_contract, &test_options)?;
+
+        // The corpus is versioned against the code of the test contract and its targets, so that a
+        // stale corpus of changed contracts is discarded.
+        let mut corpus = match test_options.corpus_dir {
+            Some(ref dir) => {
+                let code_hash = self.code_hash(invariant_contract.address, &targeted_contracts);
+                // The runs that hit new code are added to the corpus.
+                self.executor.inspector_config_mut().coverage = true;
+                Some(InvariantCorpus::load(dir, code_hash)?)
+            }
+            None => None,
+        };
+        let coverage = RefCell::new(CoverageTracker::default());
+        let new_sequences: RefCell<Vec<Vec<BasicTxDetails>>> = RefCell::new(Vec::new());
 
         // Stores the consumed gas and calldata of every successful fuzz call.
         let fuzz_cases: RefCell<Vec<FuzzedCases>> = RefCell::new(Default::default());
@@ -102,6 +120,41 @@ impl<'a> InvariantExecutor<'a> {
             fuzz_cases.borrow_mut().push(FuzzedCases::new(vec![]));
         }
 
+        // Replay the corpus before fuzzing, which seeds the fuzz dictionary with the state its
+        // sequences reach and re-checks the invariants they broke in previous runs.
+        for sequence in corpus.iter().flat_map(|corpus| corpus.sequences()) {
+            if failures.borrow().broken_invariants_count ==
+                invariant_contract.invariant_functions.len()
+            {
+                break
+            }
+
+            let mut executor = blank_executor.borrow().clone();
+            for (i, (sender, (address, calldata))) in sequence.iter().enumerate() {
+                let mut call_result =
+                    executor.call_raw(*sender, *address, calldata.0.clone(), U256::zero())?;
+                if let Some(hit_maps) = call_result.coverage.take() {
+                    coverage.borrow_mut().record(&hit_maps);
+                }
+
+                let state_changeset =
+                    call_result.state_changeset.to_owned().expect("to have a state changeset.");
+                collect_state_from_call(&call_result.logs, &state_changeset, fuzz_state.clone());
+                executor.backend_mut().commit(state_changeset);
+
+                if !can_continue(
+                    &invariant_contract,
+                    call_result,
+                    &executor,
+                    &sequence[..=i],
+                    &mut failures.borrow_mut(),
+                    &test_options,
+                ) {
+                    break
+                }
+            }
+        }
+
         if failures.borrow().broken_invariants_count < invariant_contract.invariant_functions.len()
         {
             // The strategy only comes with the first `input`. We fill the rest of the `inputs`
@@ -132,6 +185,9 @@ impl<'a> InvariantExecutor<'a> {
                 // Created contracts during a run.
                 let mut created_contracts = vec![];
 
+                // Whether a call of this run hit code that no previous call hit.
+                let mut new_coverage = false;
+
                 'fuzz_run: for _ in 0..test_options.depth {
                     let (sender, (address, calldata)) =
                         inputs.last().expect("to have the next randomly generated input.");
@@ -141,6 +197,10 @@ impl<'a> InvariantExecutor<'a> {
                         .call_raw(*sender, *address, calldata.0.clone(), U256::zero())
                         .expect("could not make raw evm call");
 
+                    if let Some(ref hit_maps) = call_result.coverage {
+                        new_coverage |= coverage.borrow_mut().record(hit_maps);
+                    }
+
                     // Collect data for fuzzing from the state changeset.
                     let state_changeset =
                         call_result.state_changeset.to_owned().expect("to have a state changeset.");
@@ -177,7 +237,7 @@ impl<'a> InvariantExecutor<'a> {
                         &executor,
                         &inputs,
                         &mut failures.borrow_mut(),
-                        test_options,
+                        &test_options,
                     ) {
                         break 'fuzz_run
                     }
@@ -200,12 +260,22 @@ impl<'a> InvariantExecutor<'a> {
                     }
                 }
 
+                if new_coverage {
+                    new_sequences.borrow_mut().push(inputs[..fuzz_runs.len()].to_vec());
+                }
+
                 fuzz_cases.borrow_mut().push(FuzzedCases::new(fuzz_runs));
 
                 Ok(())
             });
         }
 
+        if let Some(ref mut corpus) = corpus {
+            for sequence in new_sequences.into_inner() {
+                corpus.insert(&sequence)?;
+            }
+        }
+
         let (reverts, invariants) = failures.into_inner().into_inner();
 
         Ok(Some(InvariantFuzzTestResult { invariants, cases: fuzz_cases.into_inner(), reverts }))
@@ -218,17 +288,33 @@ impl<'a> InvariantExecutor<'a> {
     fn prepare_fuzzing(
         &mut self,
         invariant_contract: &InvariantContract,
-        test_options: InvariantTestOptions,
+        test_options: &InvariantTestOptions,
     ) -> eyre::Result<InvariantPreparation> {
         // Finds out the chosen deployed contracts and/or senders.
         self.select_contract_artifacts(invariant_contract.address, invariant_contract.abi)?;
-        let (targeted_senders, targeted_contracts) =
+        let (mut targeted_senders, targeted_contracts) =
             self.select_contracts_and_senders(invariant_contract.address, invariant_contract.abi)?;
 
+        // Explicitly configured senders replace `targetSenders()` and are the only callers.
+        let only_targeted_senders = !test_options.senders.is_empty();
+        if only_targeted_senders {
+            targeted_senders = test_options.senders.clone();
+        }
+
         if targeted_contracts.is_empty() {
             eyre::bail!("No contracts to fuzz.");
         }
 
+        if !test_options.selector_weights.is_empty() &&
+            targeted_contracts.iter().all(|(_, (name, abi, functions))| {
+                fuzzable_functions(abi, functions)
+                    .iter()
+                    .all(|func| selector_weight(&test_options.selector_weights, name, func) == 0)
+            })
+        {
+            eyre::bail!("All targeted selectors have a weight of zero.");
+        }
+
         // Stores fuzz state for use with [fuzz_calldata_from_state].
         let fuzz_state: EvmFuzzState = build_initial_state(self.executor.backend().mem_db());
 
@@ -238,10 +324,15 @@ impl<'a> InvariantExecutor<'a> {
             Arc::new(Mutex::new(targeted_contracts));
 
         // Creates the invariant strategy.
-        let strat =
-            invariant_strat(fuzz_state.clone(), targeted_senders, targeted_contracts.clone())
-                .no_shrink()
-                .boxed();
+        let strat = invariant_strat(
+            fuzz_state.clone(),
+            targeted_senders,
+            only_targeted_senders,
+            targeted_contracts.clone(),
+            test_options.selector_weights.clone(),
+        )
+        .no_shrink()
+        .boxed();
 
         // Allows `override_call_strat` to use the address given by the Fuzzer inspector during
         // EVM execution.
@@ -271,6 +362,23 @@ impl<'a> InvariantExecutor<'a> {
         Ok((fuzz_state, targeted_contracts, strat))
     }
 
+    /// Returns the hash of the code of the invariant test contract and the targeted contracts.
+    fn code_hash(
+        &self,
+        invariant_address: Address,
+        targeted_contracts: &FuzzRunIdentifiedContracts,
+    ) -> H256 {
+        let mut addresses = targeted_contracts.lock().keys().copied().collect::<Vec<_>>();
+        addresses.push(invariant_address);
+
+        let mut preimage = Vec::with_capacity(addresses.len() * 52);
+        for address in addresses {
+            preimage.extend_from_slice(address.as_bytes());
+            preimage.extend_from_slice(self.executor.backend().basic(address).code_hash.as_bytes());
+        }
+        H256(keccak256(preimage))
+    }
+
     /// Fills the `InvariantExecutor` with the artifact identifier filters (in `path:name` string
     /// format). They will be used to filter contracts after the `setUp`, and more importantly,
     /// during the runs.
@@ -486,7 +594,7 @@ fn can_continue(
     executor: &Executor,
     calldata: &[BasicTxDetails],
     failures: &mut InvariantFailures,
-    test_options: InvariantTestOptions,
+    test_options: &InvariantTestOptions,
 ) -> bool {
     if !call_result.reverted {
         if assert_invariants(invariant_contract, executor, calldata, failures).is_err() {
@@ -5,6 +5,8 @@ use error::*;
 mod filters;
 pub use filters::ArtifactFilters;
 mod call_override;
+mod corpus;
+pub use corpus::{CoverageTracker, InvariantCorpus};
 pub use call_override::{set_up_inner_replay, RandomCallGenerator};
 mod executor;
 use crate::executor::Executor;
@@ -13,9 +15,11 @@ use ethers::{
     types::{Address, Bytes, U256},
 };
 pub use executor::{InvariantExecutor, InvariantFailures};
+mod replay;
 use parking_lot::Mutex;
 pub use proptest::test_runner::Config as FuzzConfig;
-use std::{collections::BTreeMap, sync::Arc};
+pub use replay::replay_test_source;
+use std::{collections::BTreeMap, path::PathBuf, sync::Arc};
 
 pub type TargetedContracts = BTreeMap<Address, (String, Abi, Vec<Function>)>;
 pub type FuzzRunIdentifiedContracts = Arc<Mutex<TargetedContracts>>;
@@ -35,7 +39,7 @@ pub struct InvariantContract<'a> {
 }
 
 /// Metadata on how to run invariant tests
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct InvariantTestOptions {
     /// The number of calls executed to attempt to break invariants in one run.
     pub depth: u32,
@@ -44,6 +48,15 @@ pub struct InvariantTestOptions {
     /// Allows overriding an unsafe external call when running invariant tests. eg. reetrancy
     /// checks
     pub call_override: bool,
+    /// Relative weights of the calls to the targeted selectors, see
+    /// [`selector_weight`](crate::fuzz::strategies::selector_weight)
+    pub selector_weights: BTreeMap<String, u32>,
+    /// The only addresses that call the targeted contracts. If not empty, this overrides
+    /// `targetSenders()` and no random senders are generated.
+    pub senders: Vec<Address>,
+    /// Optional directory of the corpus of the test contract: the call sequences of previous runs
+    /// that hit new code are replayed before fuzzing, and new ones are added to it.
+    pub corpus_dir: Option<PathBuf>,
 }
 
 /// Given the executor state, asserts that no invariant has been broken. Otherwise, it fills the
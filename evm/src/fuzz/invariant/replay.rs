@@ -0,0 +1,162 @@
+//! Generation of Solidity tests that replay failing invariant sequences
+use crate::{abi::CHEATCODE_ADDRESS, fuzz::BaseCounterExample};
+use ethers::{
+    abi::Token,
+    types::I256,
+    utils::{hex, to_checksum},
+};
+use std::fmt::Write;
+
+/// Returns the source of a Solidity test that replays `sequence` and then checks `invariant`.
+///
+/// `test_contract` is the identifier of the invariant test contract (`<path>:<name>`). The
+/// generated contract inherits from it, so `setUp` deploys the targets at the addresses of the
+/// fuzz run, and every call is pranked with the sender that made it during the run.
+///
+/// Calls whose arguments can be written as Solidity literals are encoded with
+/// `abi.encodeWithSignature`, all others are replayed from their raw calldata.
+///
+/// If `fail_on_revert` is set, the test fails as soon as one of the replayed calls reverts, like
+/// the invariant run did.
+pub fn replay_test_source(
+    test_contract: &str,
+    invariant: &str,
+    sequence: &[BaseCounterExample],
+    fail_on_revert: bool,
+) -> String {
+    let (path, name) = test_contract.rsplit_once(':').unwrap_or(("", test_contract));
+    let path = path.trim_start_matches("./");
+
+    let mut calls = String::new();
+    for call in sequence {
+        let target = call.addr.map(|addr| to_checksum(&addr, None)).unwrap_or_default();
+        writeln!(calls).unwrap();
+        writeln!(calls, "        // {}", call.to_string().replace('\n', "\\n")).unwrap();
+        if let Some(sender) = call.sender {
+            writeln!(calls, "        replayVm.prank({});", to_checksum(&sender, None)).unwrap();
+        }
+        writeln!(calls, "        (success, ) = address({target}).call({});", calldata_expr(call))
+            .unwrap();
+        if fail_on_revert {
+            writeln!(calls, "        require(success, \"replayed call reverted\");").unwrap();
+        }
+    }
+
+    format!(
+        r#"// SPDX-License-Identifier: UNLICENSED
+pragma solidity >=0.6.2;
+
+import "{path}";
+
+interface ReplayCheats {{
+    function prank(address) external;
+}}
+
+/// Replays the call sequence that broke `{invariant}` of `{name}`.
+contract {name}Replay_{invariant} is {name} {{
+    function test_replay_{invariant}() public {{
+        ReplayCheats replayVm = ReplayCheats({cheats});
+        bool success;
+{calls}
+        this.{invariant}();
+    }}
+}}
+"#,
+        cheats = to_checksum(&CHEATCODE_ADDRESS, None),
+    )
+}
+
+/// Returns the Solidity expression of the calldata of `call`
+fn calldata_expr(call: &BaseCounterExample) -> String {
+    if let Some(signature) = &call.signature {
+        let signature = signature.split(':').next().unwrap_or(signature);
+        if let Some(args) = call.args.iter().map(token_literal).collect::<Option<Vec<_>>>() {
+            let args = args.iter().map(|arg| format!(", {arg}")).collect::<String>();
+            return format!("abi.encodeWithSignature(\"{signature}\"{args})")
+        }
+    }
+    format!("hex\"{}\"", hex::encode(&call.calldata))
+}
+
+/// Returns the Solidity literal of `token`, if it is a value type, `bytes` or `string`, whose ABI
+/// encoding is the same as the one of the token.
+fn token_literal(token: &Token) -> Option<String> {
+    let literal = match token {
+        Token::Address(addr) => to_checksum(addr, None),
+        Token::Bool(b) => b.to_string(),
+        Token::Uint(n) => n.to_string(),
+        Token::Int(n) => I256::from_raw(*n).to_string(),
+        Token::FixedBytes(bytes) => format!("bytes{}(hex\"{}\")", bytes.len(), hex::encode(bytes)),
+        Token::Bytes(bytes) => format!("bytes(hex\"{}\")", hex::encode(bytes)),
+        Token::String(s) => {
+            let escaped: String = s
+                .bytes()
+                .map(|b| match b {
+                    b'"' => "\\\"".to_string(),
+                    b'\\' => "\\\\".to_string(),
+                    0x20..=0x7e => (b as char).to_string(),
+                    _ => format!("\\x{b:02x}"),
+                })
+                .collect();
+            format!("string(\"{escaped}\")")
+        }
+        Token::Array(_) | Token::FixedArray(_) | Token::Tuple(_) => return None,
+    };
+    Some(literal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::{Address, U256};
+
+    fn call(signature: Option<&str>, args: Vec<Token>) -> BaseCounterExample {
+        BaseCounterExample {
+            sender: Some(Address::from_low_u64_be(0x1337)),
+            addr: Some(Address::from_low_u64_be(0xbeef)),
+            calldata: vec![0x12, 0x34, 0x56, 0x78].into(),
+            signature: signature.map(str::to_string),
+            contract_name: Some("InvariantBreaker".to_string()),
+            args,
+        }
+    }
+
+    #[test]
+    fn generates_replay_test() {
+        let sequence = vec![
+            call(
+                Some("set0(int256):(bool)"),
+                vec![Token::Int(I256::from_dec_str("-100").unwrap().into_raw())],
+            ),
+            call(
+                Some("set1(uint256,string)"),
+                vec![Token::Uint(U256::from(10)), Token::String("a\"b".to_string())],
+            ),
+            call(Some("set2(uint256[])"), vec![Token::Array(vec![])]),
+        ];
+        let source = replay_test_source(
+            "fuzz/invariant/InvariantTest1.t.sol:InvariantTest",
+            "invariant_neverFalse",
+            &sequence,
+            true,
+        );
+
+        assert!(source.contains("import \"fuzz/invariant/InvariantTest1.t.sol\";"));
+        assert!(source.contains(
+            "contract InvariantTestReplay_invariant_neverFalse is InvariantTest {\n    function \
+             test_replay_invariant_neverFalse() public {"
+        ));
+        let sender = to_checksum(&Address::from_low_u64_be(0x1337), None);
+        let target = to_checksum(&Address::from_low_u64_be(0xbeef), None);
+        assert!(source.contains(&format!("replayVm.prank({sender});")));
+        assert!(source.contains(&format!(
+            "(success, ) = address({target}).call(abi.encodeWithSignature(\"set0(int256)\", -100));"
+        )));
+        assert!(source.contains(
+            "abi.encodeWithSignature(\"set1(uint256,string)\", 10, string(\"a\\\"b\")));"
+        ));
+        assert!(source.contains(".call(hex\"12345678\");"));
+        assert_eq!(source.matches("require(success, \"replayed call reverted\");").count(), 3);
+        assert!(source.contains("this.invariant_neverFalse();"));
+    }
+}
@@ -11,10 +11,16 @@ use foundry_common::{calc, contracts::ContractsByAddress};
 pub use proptest::test_runner::{Config as FuzzConfig, Reason};
 use proptest::test_runner::{TestCaseError, TestError, TestRunner};
 use serde::{Deserialize, Serialize};
-use std::{cell::RefCell, collections::BTreeMap, fmt};
+use std::{
+    borrow::Cow,
+    cell::{Cell, RefCell},
+    collections::BTreeMap,
+    fmt,
+    time::{Duration, Instant},
+};
 use strategies::{
-    build_initial_state, collect_state_from_call, fuzz_calldata, fuzz_calldata_from_state,
-    EvmFuzzState,
+    build_initial_state, collect_deployed_addresses, collect_state_from_call, fuzz_calldata,
+    fuzz_calldata_from_state, EvmFuzzState, FuzzParamConfig,
 };
 pub mod invariant;
 pub mod strategies;
@@ -22,6 +28,9 @@ pub mod strategies;
 /// Magic return code for the `assume` cheatcode
 pub const ASSUME_MAGIC_RETURN_CODE: &[u8] = b"FOUNDRY::ASSUME";
 
+/// How often, in percent, inputs are generated from the fuzz dictionary by default
+pub const DEFAULT_DICTIONARY_WEIGHT: u32 = 40;
+
 /// Wrapper around an [`Executor`] which provides fuzzing support using [`proptest`](https://docs.rs/proptest/1.0.0/proptest/).
 ///
 /// After instantiation, calling `fuzz` will proceed to hammer the deployed smart contract with
@@ -34,12 +43,49 @@ pub struct FuzzedExecutor<'a> {
     runner: TestRunner,
     /// The account that calls tests
     sender: Address,
+    /// The maximum time spent fuzzing a function
+    timeout: Option<Duration>,
+    /// How often, in percent, inputs are generated from the fuzz dictionary instead of at random
+    dictionary_weight: u32,
+    /// Overrides of how the values of some parameter types are generated
+    param_config: FuzzParamConfig,
 }
 
 impl<'a> FuzzedExecutor<'a> {
     /// Instantiates a fuzzed executor given a testrunner
     pub fn new(executor: &'a Executor, runner: TestRunner, sender: Address) -> Self {
-        Self { executor, runner, sender }
+        Self {
+            executor,
+            runner,
+            sender,
+            timeout: None,
+            dictionary_weight: DEFAULT_DICTIONARY_WEIGHT,
+            param_config: FuzzParamConfig::default(),
+        }
+    }
+
+    /// Overrides how the values of some parameter types are generated, see [`FuzzParamConfig`].
+    pub fn with_param_config(mut self, param_config: FuzzParamConfig) -> Self {
+        self.param_config = param_config;
+        self
+    }
+
+    /// Bounds the time spent fuzzing a function.
+    ///
+    /// Once the timeout elapsed, the in-flight call is halted and the remaining cases are skipped.
+    /// The fuzz test then fails, unless a counterexample was found before.
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets how often, in percent, inputs are generated from the fuzz dictionary, which is seeded
+    /// with the state and bytecode of the deployed contracts, instead of at random.
+    ///
+    /// A weight of 0 disables the dictionary, weights above 100 are treated as 100.
+    pub fn with_dictionary_weight(mut self, weight: u32) -> Self {
+        self.dictionary_weight = weight.min(100);
+        self
     }
 
     /// Fuzzes the provided function, assuming it is available at the contract at `address`
@@ -60,23 +106,55 @@ impl<'a> FuzzedExecutor<'a> {
         // Stores the result and calldata of the last failed call, if any.
         let counterexample: RefCell<(Bytes, RawCallResult)> = RefCell::new(Default::default());
 
+        // Halts calls that are still executing once the timeout elapsed.
+        let deadline = self.timeout.map(|timeout| Instant::now() + timeout);
+        let executor = match deadline {
+            Some(deadline) => {
+                let mut executor = self.executor.clone();
+                executor.inspector_config_mut().deadline = Some(deadline);
+                Cow::Owned(executor)
+            }
+            None => Cow::Borrowed(self.executor),
+        };
+        let timed_out = Cell::new(false);
+
+        // Counts the inputs discarded by the `assume` cheatcode
+        let rejects = Cell::new(0u32);
+
         // Stores fuzz state for use with [fuzz_calldata_from_state]
+        let mut param_config = self.param_config.clone();
         let state: EvmFuzzState = if let Some(fork_db) = self.executor.backend().active_fork_db() {
+            if param_config.deployed_addresses {
+                param_config.addresses.extend(collect_deployed_addresses(fork_db));
+            }
             build_initial_state(fork_db)
         } else {
-            build_initial_state(self.executor.backend().mem_db())
+            let mem_db = self.executor.backend().mem_db();
+            if param_config.deployed_addresses {
+                param_config.addresses.extend(collect_deployed_addresses(mem_db));
+            }
+            build_initial_state(mem_db)
         };
 
-        // TODO: We should have a `FuzzerOpts` struct where we can configure the fuzzer. When we
-        // have that, we should add a way to configure strategy weights
-        let strat = proptest::strategy::Union::new_weighted(vec![
-            (60, fuzz_calldata(func.clone())),
-            (40, fuzz_calldata_from_state(func.clone(), state.clone())),
-        ]);
+        let strat = proptest::strategy::Union::new_weighted(
+            [
+                (100 - self.dictionary_weight, fuzz_calldata(func.clone(), &param_config)),
+                (
+                    self.dictionary_weight,
+                    fuzz_calldata_from_state(func.clone(), state.clone(), &param_config),
+                ),
+            ]
+            .into_iter()
+            .filter(|(weight, _)| *weight > 0)
+            .collect(),
+        );
         tracing::debug!(func = ?func.name, should_fail, "fuzzing");
         let run_result = self.runner.clone().run(&strat, |calldata| {
-            let call = self
-                .executor
+            if timed_out.get() {
+                return Ok(())
+            }
+
+            let call = executor
                 .call_raw(self.sender, address, calldata.0.clone(), 0.into())
                 .expect("Could not call contract with fuzzed input.");
             let state_changeset =
@@ -87,15 +165,18 @@ impl<'a> FuzzedExecutor<'a> {
 
             // When assume cheat code is triggered return a special string "FOUNDRY::ASSUME"
             if call.result.as_ref() == ASSUME_MAGIC_RETURN_CODE {
+                rejects.set(rejects.get().saturating_add(1));
                 return Err(TestCaseError::reject("ASSUME: Too many rejects"))
             }
 
-            let success = self.executor.is_success(
-                address,
-                call.reverted,
-                state_changeset.clone(),
-                should_fail,
-            );
+            let success =
+                executor.is_success(address, call.reverted, state_changeset.clone(), should_fail);
+
+            // The outcome of a halted call is meaningless
+            if deadline.map_or(false, |deadline| Instant::now() >= deadline) {
+                timed_out.set(true);
+                return Ok(())
+            }
 
             if success {
                 cases.borrow_mut().push(FuzzCase {
@@ -125,10 +206,17 @@ impl<'a> FuzzedExecutor<'a> {
         });
 
         let (calldata, call) = counterexample.into_inner();
+        let cases = cases.into_inner();
+        let timed_out = run_result.is_ok() && timed_out.get();
+        let abort = timed_out.then(|| FuzzAbortError::TimedOut {
+            timeout: self.timeout.unwrap_or_default(),
+            cases: cases.len(),
+        });
         let mut result = FuzzTestResult {
-            cases: FuzzedCases::new(cases.into_inner()),
-            success: run_result.is_ok(),
-            reason: None,
+            reason: abort.as_ref().map(ToString::to_string),
+            abort,
+            cases: FuzzedCases::new(cases),
+            success: run_result.is_ok() && !timed_out,
             counterexample: None,
             logs: call.logs,
             traces: call.traces,
@@ -137,7 +225,18 @@ impl<'a> FuzzedExecutor<'a> {
 
         match run_result {
             Err(TestError::Abort(reason)) => {
-                result.reason = Some(reason.to_string());
+                // proptest gives up once the global reject limit is hit, which is only counted
+                // for inputs discarded by `assume`
+                if rejects.get() >= self.runner.config().max_global_rejects {
+                    let abort = FuzzAbortError::TooManyRejects {
+                        rejects: rejects.get(),
+                        accepted: result.cases.cases().len(),
+                    };
+                    result.reason = Some(abort.to_string());
+                    result.abort = Some(abort);
+                } else {
+                    result.reason = Some(reason.to_string());
+                }
             }
             Err(TestError::Fail(reason, _)) => {
                 let reason = reason.to_string();
@@ -257,6 +356,9 @@ pub struct FuzzTestResult {
     /// still be successful (i.e self.success == true) when it's expected to fail.
     pub reason: Option<String>,
 
+    /// Why the fuzzer gave up before running all cases, if it did
+    pub abort: Option<FuzzAbortError>,
+
     /// Minimal reproduction test case for failing fuzz tests
     pub counterexample: Option<CounterExample>,
 
@@ -271,6 +373,57 @@ pub struct FuzzTestResult {
     pub labeled_addresses: BTreeMap<Address, String>,
 }
 
+/// Why a fuzz test was abandoned before all of its cases ran
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FuzzAbortError {
+    /// The global reject limit was hit, because `vm.assume` discarded too many inputs
+    TooManyRejects {
+        /// The number of inputs discarded by `vm.assume`
+        rejects: u32,
+        /// The number of inputs that passed `vm.assume`
+        accepted: usize,
+    },
+    /// The fuzz timeout elapsed before all cases ran
+    TimedOut {
+        /// The configured timeout
+        timeout: Duration,
+        /// The number of cases that completed in time
+        cases: usize,
+    },
+}
+
+impl FuzzAbortError {
+    /// Returns the share of inputs, in percent, that were discarded by `vm.assume`
+    pub fn rejection_rate(&self) -> Option<f64> {
+        match self {
+            FuzzAbortError::TooManyRejects { rejects, accepted } => {
+                let total = *rejects as f64 + *accepted as f64;
+                Some(if total == 0.0 { 0.0 } else { *rejects as f64 * 100.0 / total })
+            }
+            FuzzAbortError::TimedOut { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for FuzzAbortError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FuzzAbortError::TooManyRejects { rejects, accepted } => write!(
+                f,
+                "Too many inputs rejected by `vm.assume`: {} rejected, {} accepted ({:.2}% rejection rate). The assume condition is likely too restrictive",
+                rejects,
+                accepted,
+                self.rejection_rate().unwrap_or_default()
+            ),
+            FuzzAbortError::TimedOut { timeout, cases } => {
+                write!(f, "Fuzz test timed out after {:?}, {} cases completed", timeout, cases)
+            }
+        }
+    }
+}
+
+impl std::error::Error for FuzzAbortError {}
+
 /// Container type for all successful test cases
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(transparent)]
@@ -1,18 +1,32 @@
-use super::fuzz_param;
+use super::{fuzz_param_with_addresses, FuzzParamConfig};
 use ethers::{abi::Function, types::Bytes};
 use proptest::prelude::{BoxedStrategy, Strategy};
 
 /// Given a function, it returns a strategy which generates valid calldata
 /// for that function's input types.
-pub fn fuzz_calldata(func: Function) -> BoxedStrategy<Bytes> {
+pub fn fuzz_calldata(func: Function, config: &FuzzParamConfig) -> BoxedStrategy<Bytes> {
     // We need to compose all the strategies generated for each parameter in all
     // possible combinations
-    let strats = func.inputs.iter().map(|input| fuzz_param(&input.kind)).collect::<Vec<_>>();
+    let strats = func
+        .inputs
+        .iter()
+        .map(|input| fuzz_param_with_addresses(&input.kind, &config.addresses))
+        .collect::<Vec<_>>();
 
+    let config = config.clone();
     strats
-        .prop_map(move |tokens| {
+        .prop_filter_map("zero values are excluded", move |tokens| {
+            let tokens = if config.is_default() {
+                tokens
+            } else {
+                func.inputs
+                    .iter()
+                    .zip(tokens)
+                    .map(|(input, token)| config.configure(&input.kind, token))
+                    .collect::<Option<Vec<_>>>()?
+            };
             tracing::trace!(input = ?tokens);
-            func.encode_input(&tokens).unwrap().into()
+            Some(func.encode_input(&tokens).unwrap().into())
         })
         .boxed()
 }
@@ -1,7 +1,7 @@
 use crate::fuzz::{
     fuzz_calldata, fuzz_calldata_from_state,
     invariant::{BasicTxDetails, FuzzRunIdentifiedContracts},
-    strategies::fuzz_param,
+    strategies::{fuzz_param, FuzzParamConfig},
     EvmFuzzState,
 };
 use ethers::{
@@ -11,7 +11,7 @@ use ethers::{
 use parking_lot::RwLock;
 use proptest::prelude::*;
 pub use proptest::test_runner::Config as FuzzConfig;
-use std::sync::Arc;
+use std::{collections::BTreeMap, sync::Arc};
 
 /// Given a target address, we generate random calldata.
 pub fn override_call_strat(
@@ -51,14 +51,22 @@ pub fn override_call_strat(
 /// contract:
 ///
 /// `targetContracts()`, `targetSenders()`, `excludeContracts()`, `targetSelectors()`
+///
+/// If `selector_weights` is not empty, the next target and selector are picked according to their
+/// [`selector_weight`] instead of uniformly.
+///
+/// If `only_senders` is set, every call is made by one of the `senders`, see
+/// [`select_random_sender`].
 pub fn invariant_strat(
     fuzz_state: EvmFuzzState,
     senders: Vec<Address>,
+    only_senders: bool,
     contracts: FuzzRunIdentifiedContracts,
+    selector_weights: BTreeMap<String, u32>,
 ) -> BoxedStrategy<Vec<BasicTxDetails>> {
     // We only want to seed the first value, since we want to generate the rest as we mutate the
     // state
-    vec![generate_call(fuzz_state, senders, contracts); 1].boxed()
+    vec![generate_call(fuzz_state, senders, only_senders, contracts, selector_weights); 1].boxed()
 }
 
 /// Strategy to generate a transaction where the `sender`, `target` and `calldata` are all generated
@@ -66,8 +74,19 @@ pub fn invariant_strat(
 fn generate_call(
     fuzz_state: EvmFuzzState,
     senders: Vec<Address>,
+    only_senders: bool,
     contracts: FuzzRunIdentifiedContracts,
+    selector_weights: BTreeMap<String, u32>,
 ) -> BoxedStrategy<BasicTxDetails> {
+    if !selector_weights.is_empty() {
+        return select_weighted_call(contracts, selector_weights)
+            .prop_flat_map(move |(contract, func)| {
+                let sender = select_random_sender(senders.clone(), only_senders);
+                (sender, fuzz_contract_with_calldata(fuzz_state.clone(), contract, func))
+            })
+            .boxed()
+    }
+
     let random_contract = select_random_contract(contracts);
     random_contract
         .prop_flat_map(move |(contract, abi, functions)| {
@@ -75,7 +94,7 @@ fn generate_call(
             let senders = senders.clone();
             let fuzz_state = fuzz_state.clone();
             func.prop_flat_map(move |func| {
-                let sender = select_random_sender(senders.clone());
+                let sender = select_random_sender(senders.clone(), only_senders);
                 (sender, fuzz_contract_with_calldata(fuzz_state.clone(), contract, func))
             })
         })
@@ -86,13 +105,21 @@ fn generate_call(
 /// * If `senders` is empty, then it's a completely random address.
 /// * If `senders` is not empty, then there's an 80% chance that one from the list is selected. The
 ///   remaining 20% will be random.
-fn select_random_sender(senders: Vec<Address>) -> impl Strategy<Value = Address> {
+/// * If `senders` is not empty and `only_senders` is set, then one from the list is always
+///   selected.
+fn select_random_sender(
+    senders: Vec<Address>,
+    only_senders: bool,
+) -> impl Strategy<Value = Address> {
     let fuzz_strategy =
         fuzz_param(&ParamType::Address).prop_map(move |addr| addr.into_address().unwrap()).boxed();
 
     if !senders.is_empty() {
         let selector =
             any::<prop::sample::Selector>().prop_map(move |selector| *selector.select(&*senders));
+        if only_senders {
+            return selector.boxed()
+        }
         proptest::strategy::Union::new_weighted(vec![(80, selector.boxed()), (20, fuzz_strategy)])
             .boxed()
     } else {
@@ -114,6 +141,81 @@ fn select_random_contract(
     })
 }
 
+/// Strategy to select a target and one of its functions according to their [`selector_weight`].
+///
+/// Functions with a weight of zero are never selected.
+fn select_weighted_call(
+    contracts: FuzzRunIdentifiedContracts,
+    selector_weights: BTreeMap<String, u32>,
+) -> impl Strategy<Value = (Address, Function)> {
+    any::<prop::sample::Index>().prop_map(move |index| {
+        let contracts = contracts.lock();
+        let calls = contracts
+            .iter()
+            .flat_map(|(addr, (name, abi, functions))| {
+                fuzzable_functions(abi, functions).into_iter().map(|func| {
+                    let weight = selector_weight(&selector_weights, name, &func) as usize;
+                    (*addr, func, weight)
+                })
+            })
+            .filter(|(_, _, weight)| *weight > 0)
+            .collect::<Vec<_>>();
+
+        let total_weight = calls.iter().map(|(_, _, weight)| weight).sum();
+        let mut pick = index.index(total_weight);
+        for (addr, func, weight) in calls {
+            if pick < weight {
+                return (addr, func)
+            }
+            pick -= weight;
+        }
+        unreachable!("the picked weight is below the total weight")
+    })
+}
+
+/// Returns the weight of calling `func` of the targeted contract `name`.
+///
+/// `selector_weights` is keyed by either `<contract name>:<function signature>`, e.g.
+/// `Handler:deposit(uint256)`, or `<contract name>` to weight all of the functions of a
+/// contract. The most specific entry is used, and functions without an entry have a weight of 1.
+pub fn selector_weight(
+    selector_weights: &BTreeMap<String, u32>,
+    name: &str,
+    func: &Function,
+) -> u32 {
+    let name = name.rsplit(':').next().unwrap_or(name);
+    let signature = func.signature();
+    let signature = signature.split(':').next().unwrap_or(&signature);
+    selector_weights
+        .get(&format!("{name}:{signature}"))
+        .or_else(|| selector_weights.get(name))
+        .copied()
+        .unwrap_or(1)
+}
+
+/// Returns the functions of a targeted contract that can be fuzzed.
+///
+/// These are the `targeted_functions` if there are any, or all the mutable functions of the abi.
+pub fn fuzzable_functions(abi: &Abi, targeted_functions: &[Function]) -> Vec<Function> {
+    if !targeted_functions.is_empty() {
+        return targeted_functions.to_vec()
+    }
+    mutable_functions(abi)
+}
+
+/// Returns all the functions of the abi that are neither `pure` nor `view`.
+fn mutable_functions(abi: &Abi) -> Vec<Function> {
+    abi.functions()
+        .filter(|func| {
+            !matches!(
+                func.state_mutability,
+                ethers::abi::StateMutability::Pure | ethers::abi::StateMutability::View
+            )
+        })
+        .cloned()
+        .collect()
+}
+
 /// Strategy to select a random mutable function from the abi.
 ///
 /// If `targeted_functions` is not empty, select one from it. Otherwise, take any
@@ -123,16 +225,7 @@ fn select_random_function(
     targeted_functions: Vec<Function>,
 ) -> impl Strategy<Value = Function> {
     let selectors = any::<prop::sample::Selector>();
-    let possible_funcs: Vec<ethers::abi::Function> = abi
-        .functions()
-        .filter(|func| {
-            !matches!(
-                func.state_mutability,
-                ethers::abi::StateMutability::Pure | ethers::abi::StateMutability::View
-            )
-        })
-        .cloned()
-        .collect();
+    let possible_funcs = mutable_functions(&abi);
 
     let total_random = selectors.prop_map(move |selector| {
         let func = selector.select(&possible_funcs);
@@ -159,8 +252,8 @@ pub fn fuzz_contract_with_calldata(
     // // We need to compose all the strategies generated for each parameter in all
     // // possible combinations
     let strats = proptest::strategy::Union::new_weighted(vec![
-        (60, fuzz_calldata(func.clone())),
-        (40, fuzz_calldata_from_state(func, fuzz_state)),
+        (60, fuzz_calldata(func.clone(), &FuzzParamConfig::default())),
+        (40, fuzz_calldata_from_state(func, fuzz_state, &FuzzParamConfig::default())),
     ]);
 
     strats.prop_map(move |calldata| {
@@ -168,3 +261,44 @@ pub fn fuzz_contract_with_calldata(
         (contract, calldata)
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::abi::{Param, StateMutability};
+
+    #[allow(deprecated)]
+    fn function(name: &str) -> Function {
+        Function {
+            name: name.to_string(),
+            inputs: vec![Param {
+                name: "val".to_string(),
+                kind: ParamType::Int(256),
+                internal_type: None,
+            }],
+            outputs: vec![Param {
+                name: "".to_string(),
+                kind: ParamType::Bool,
+                internal_type: None,
+            }],
+            constant: None,
+            state_mutability: StateMutability::NonPayable,
+        }
+    }
+
+    #[test]
+    fn weights_selectors() {
+        let weights = BTreeMap::from([
+            ("InvariantBreaker".to_string(), 5),
+            ("InvariantBreaker:set1(int256)".to_string(), 0),
+        ]);
+
+        assert_eq!(selector_weight(&weights, "InvariantBreaker", &function("set0")), 5);
+        assert_eq!(selector_weight(&weights, "InvariantBreaker", &function("set1")), 0);
+        assert_eq!(
+            selector_weight(&weights, "src/Breaker.sol:InvariantBreaker", &function("set1")),
+            0
+        );
+        assert_eq!(selector_weight(&weights, "Other", &function("set1")), 1);
+    }
+}
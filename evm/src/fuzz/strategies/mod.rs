@@ -4,15 +4,15 @@ pub use int::IntStrategy;
 pub use uint::UintStrategy;
 
 mod param;
-pub use param::{fuzz_param, fuzz_param_from_state};
+pub use param::{fuzz_param, fuzz_param_from_state, fuzz_param_with_addresses, FuzzParamConfig};
 
 mod calldata;
 pub use calldata::fuzz_calldata;
 
 mod state;
 pub use state::{
-    build_initial_state, collect_created_contracts, collect_state_from_call,
-    fuzz_calldata_from_state, EvmFuzzState,
+    build_initial_state, collect_created_contracts, collect_deployed_addresses,
+    collect_state_from_call, fuzz_calldata_from_state, EvmFuzzState,
 };
 
 mod invariants;
@@ -9,11 +9,98 @@ use super::state::EvmFuzzState;
 /// The max length of arrays we fuzz for is 256.
 pub const MAX_ARRAY_LEN: usize = 256;
 
+/// Overrides of how the values of some parameter types are generated.
+///
+/// The default configuration generates all values as usual.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FuzzParamConfig {
+    /// Addresses that half of the random `address` values are picked from
+    pub addresses: Vec<Address>,
+    /// Whether the contracts deployed before the test are added to the `addresses`
+    pub deployed_addresses: bool,
+    /// Inclusive lower and upper bound of all `uint256` values
+    pub uint256_bounds: Option<(U256, U256)>,
+    /// Whether zero is excluded from all `address`, `int` and `uint` values
+    pub exclude_zero: bool,
+}
+
+impl FuzzParamConfig {
+    /// Returns whether the values are generated as usual
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Bounds the `uint256` values of the `token`, which is a value of the `param` type.
+    ///
+    /// Returns `None` if the token contains a zero value that is excluded.
+    pub fn configure(&self, param: &ParamType, token: Token) -> Option<Token> {
+        match (param, token) {
+            (ParamType::Address, Token::Address(address)) => {
+                (!self.exclude_zero || !address.is_zero()).then(|| Token::Address(address))
+            }
+            (ParamType::Uint(size), Token::Uint(mut value)) => {
+                if let (256, Some((min, max))) = (*size, self.uint256_bounds) {
+                    value = bound(value, min, max);
+                }
+                (!self.exclude_zero || !value.is_zero()).then(|| Token::Uint(value))
+            }
+            (ParamType::Int(_), Token::Int(value)) => {
+                (!self.exclude_zero || !value.is_zero()).then(|| Token::Int(value))
+            }
+            (ParamType::Array(param), Token::Array(tokens)) => tokens
+                .into_iter()
+                .map(|token| self.configure(param, token))
+                .collect::<Option<_>>()
+                .map(Token::Array),
+            (ParamType::FixedArray(param, _), Token::FixedArray(tokens)) => tokens
+                .into_iter()
+                .map(|token| self.configure(param, token))
+                .collect::<Option<_>>()
+                .map(Token::FixedArray),
+            (ParamType::Tuple(params), Token::Tuple(tokens)) => params
+                .iter()
+                .zip(tokens)
+                .map(|(param, token)| self.configure(param, token))
+                .collect::<Option<_>>()
+                .map(Token::Tuple),
+            (_, token) => Some(token),
+        }
+    }
+}
+
+/// Maps the `value` into the inclusive range from `min` to `max`
+fn bound(value: U256, min: U256, max: U256) -> U256 {
+    let (min, max) = if min <= max { (min, max) } else { (max, min) };
+    if min.is_zero() && max == U256::MAX {
+        return value
+    }
+    min + value % (max - min + 1)
+}
+
 /// Given a parameter type, returns a strategy for generating values for that type.
 ///
 /// Works with ABI Encoder v2 tuples.
 pub fn fuzz_param(param: &ParamType) -> impl Strategy<Value = Token> {
+    fuzz_param_with_addresses(param, &[])
+}
+
+/// Given a parameter type, returns a strategy for generating values for that type, picking half of
+/// the `address` values from the given `addresses`, if any.
+///
+/// Works with ABI Encoder v2 tuples.
+pub fn fuzz_param_with_addresses(param: &ParamType, addresses: &[Address]) -> BoxedStrategy<Token> {
+    let fuzz_param = |param: &ParamType| fuzz_param_with_addresses(param, addresses);
     match param {
+        ParamType::Address if !addresses.is_empty() => {
+            let addresses = addresses.to_vec();
+            let selector = any::<prop::sample::Selector>()
+                .prop_map(move |selector| (*selector.select(&addresses)).into_token());
+            proptest::strategy::Union::new_weighted(vec![
+                (50, selector.boxed()),
+                (50, fuzz_param_with_addresses(param, &[])),
+            ])
+            .boxed()
+        }
         ParamType::Address => {
             // The key to making this work is the `boxed()` call which type erases everything
             // https://altsysrq.github.io/proptest-book/proptest/tutorial/transforming-strategies.html
@@ -128,8 +215,13 @@ pub fn fuzz_param_from_state(param: &ParamType, arc_state: EvmFuzzState) -> Boxe
 
 #[cfg(test)]
 mod tests {
-    use crate::fuzz::strategies::{build_initial_state, fuzz_calldata, fuzz_calldata_from_state};
-    use ethers::abi::HumanReadableParser;
+    use crate::fuzz::strategies::{
+        build_initial_state, fuzz_calldata, fuzz_calldata_from_state, FuzzParamConfig,
+    };
+    use ethers::{
+        abi::{HumanReadableParser, ParamType, Token},
+        types::{Address, U256},
+    };
     use revm::db::{CacheDB, EmptyDB};
 
     #[test]
@@ -141,8 +233,8 @@ mod tests {
         let state = build_initial_state(&db);
 
         let strat = proptest::strategy::Union::new_weighted(vec![
-            (60, fuzz_calldata(func.clone())),
-            (40, fuzz_calldata_from_state(func, state)),
+            (60, fuzz_calldata(func.clone(), &FuzzParamConfig::default())),
+            (40, fuzz_calldata_from_state(func, state, &FuzzParamConfig::default())),
         ]);
 
         let cfg = proptest::test_runner::Config { failure_persistence: None, ..Default::default() };
@@ -150,4 +242,41 @@ mod tests {
 
         let _ = runner.run(&strat, |_| Ok(()));
     }
+
+    #[test]
+    fn can_configure_params() {
+        let config = FuzzParamConfig {
+            uint256_bounds: Some((100.into(), 200.into())),
+            exclude_zero: true,
+            ..Default::default()
+        };
+        let param = ParamType::Tuple(vec![
+            ParamType::Uint(256),
+            ParamType::Uint(8),
+            ParamType::Array(Box::new(ParamType::Uint(256))),
+        ]);
+
+        let token = Token::Tuple(vec![
+            Token::Uint(U256::MAX),
+            Token::Uint(7.into()),
+            Token::Array(vec![Token::Uint(0.into()), Token::Uint(150.into())]),
+        ]);
+        assert_eq!(
+            config.configure(&param, token),
+            Some(Token::Tuple(vec![
+                Token::Uint(U256::MAX % 101 + 100),
+                Token::Uint(7.into()),
+                Token::Array(vec![Token::Uint(100.into()), Token::Uint(150.into())]),
+            ]))
+        );
+
+        let token = Token::Tuple(vec![
+            Token::Uint(150.into()),
+            Token::Uint(0.into()),
+            Token::Array(vec![]),
+        ]);
+        assert_eq!(config.configure(&param, token), None);
+        assert_eq!(config.configure(&ParamType::Address, Token::Address(Address::zero())), None);
+        assert!(FuzzParamConfig::default().is_default());
+    }
 }
@@ -1,4 +1,4 @@
-use super::fuzz_param_from_state;
+use super::{fuzz_param_from_state, FuzzParamConfig};
 use crate::{
     executor::StateChangeset,
     fuzz::invariant::{ArtifactFilters, FuzzRunIdentifiedContracts},
@@ -29,6 +29,12 @@ use std::{
 /// Wrapped in a shareable container.
 pub type EvmFuzzState = Arc<RwLock<FuzzDictionary>>;
 
+/// The maximum number of values in a [FuzzDictionary].
+///
+/// This is to limit the memory used by and the time spent on the dictionary of fuzz tests with
+/// large contracts or many state changes.
+pub const MAX_DICTIONARY_SIZE: usize = 1 << 16;
+
 #[derive(Debug, Default)]
 pub struct FuzzDictionary {
     inner: BTreeSet<[u8; 32]>,
@@ -36,6 +42,28 @@ pub struct FuzzDictionary {
     cache: HashSet<Address>,
 }
 
+impl FuzzDictionary {
+    /// Inserts a value, unless the dictionary is full.
+    ///
+    /// Returns whether the value was newly inserted.
+    pub fn insert(&mut self, value: [u8; 32]) -> bool {
+        if self.inner.len() >= MAX_DICTIONARY_SIZE {
+            return false
+        }
+        self.inner.insert(value)
+    }
+
+    /// Inserts the push bytes of the code deployed at `address`, unless they were already
+    /// collected.
+    fn insert_push_bytes(&mut self, address: Address, code: Bytes) {
+        if self.cache.insert(address) {
+            for push_byte in collect_push_bytes(code) {
+                self.insert(push_byte);
+            }
+        }
+    }
+}
+
 impl Deref for FuzzDictionary {
     type Target = BTreeSet<[u8; 32]>;
 
@@ -55,6 +83,7 @@ impl DerefMut for FuzzDictionary {
 pub fn fuzz_calldata_from_state(
     func: Function,
     state: EvmFuzzState,
+    config: &FuzzParamConfig,
 ) -> BoxedStrategy<ethers::types::Bytes> {
     let strats = func
         .inputs
@@ -62,23 +91,47 @@ pub fn fuzz_calldata_from_state(
         .map(|input| fuzz_param_from_state(&input.kind, state.clone()))
         .collect::<Vec<_>>();
 
+    let config = config.clone();
     strats
-        .prop_map(move |tokens| {
+        .prop_filter_map("zero values are excluded", move |tokens| {
+            let tokens = if config.is_default() {
+                tokens
+            } else {
+                func.inputs
+                    .iter()
+                    .zip(tokens)
+                    .map(|(input, token)| config.configure(&input.kind, token))
+                    .collect::<Option<Vec<_>>>()?
+            };
             tracing::trace!(input = ?tokens);
-            func.encode_input(&tokens)
-                .unwrap_or_else(|_| {
-                    panic!(
-                        r#"Fuzzer generated invalid tokens {:?} for function `{}` inputs {:?}
+            let calldata = func.encode_input(&tokens).unwrap_or_else(|_| {
+                panic!(
+                    r#"Fuzzer generated invalid tokens {:?} for function `{}` inputs {:?}
 This is a bug, please open an issue: https://github.com/foundry-rs/foundry/issues"#,
-                        tokens, func.name, func.inputs
-                    )
-                })
-                .into()
+                    tokens, func.name, func.inputs
+                )
+            });
+            Some(calldata.into())
         })
         .no_shrink()
         .boxed()
 }
 
+/// Returns the addresses of the contracts deployed in a database.
+pub fn collect_deployed_addresses<DB: DatabaseRef>(db: &CacheDB<DB>) -> Vec<Address> {
+    db.accounts
+        .keys()
+        .filter(|address| {
+            let info = db.basic(**address);
+            info.code
+                .as_ref()
+                .or_else(|| db.contracts.get(&info.code_hash))
+                .map_or(false, |code| !code.is_empty())
+        })
+        .copied()
+        .collect()
+}
+
 /// Builds the initial [EvmFuzzState] from a database.
 pub fn build_initial_state<DB: DatabaseRef>(db: &CacheDB<DB>) -> EvmFuzzState {
     let mut state = FuzzDictionary::default();
@@ -96,6 +149,13 @@ pub fn build_initial_state<DB: DatabaseRef>(db: &CacheDB<DB>) -> EvmFuzzState {
             state.insert(utils::u256_to_h256_le(*slot).into());
             state.insert(utils::u256_to_h256_le(*value).into());
         }
+
+        // Insert push bytes, which include magic numbers, addresses and event topics
+        if let Some(code) = info.code.as_ref().or_else(|| db.contracts.get(&info.code_hash)) {
+            if !code.is_empty() {
+                state.insert_push_bytes(*address, code.bytes().clone());
+            }
+        }
     }
 
     // need at least some state data if db is empty otherwise we can't select random data for state
@@ -130,13 +190,7 @@ pub fn collect_state_from_call(
 
         // Insert push bytes
         if let Some(code) = &account.info.code {
-            if !state.cache.contains(address) {
-                state.cache.insert(*address);
-
-                for push_byte in collect_push_bytes(code.bytes().clone()) {
-                    state.insert(push_byte);
-                }
-            }
+            state.insert_push_bytes(*address, code.bytes().clone());
         }
 
         // Insert log topics and data
@@ -229,3 +283,43 @@ pub fn collect_created_contracts(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revm::{AccountInfo, Bytecode, InMemoryDB};
+
+    #[test]
+    fn seeds_dictionary_with_push_bytes() {
+        let mut db = InMemoryDB::default();
+        // PUSH4 0xdeadbeef PUSH1 0x2a
+        let code = vec![0x63, 0xde, 0xad, 0xbe, 0xef, 0x60, 0x2a];
+        db.insert_account_info(
+            Address::from_low_u64_be(1),
+            AccountInfo {
+                code: Some(Bytecode::new_raw(code.into()).to_checked()),
+                ..Default::default()
+            },
+        );
+
+        let state = build_initial_state(&db);
+        let state = state.read();
+
+        let mut magic = [0u8; 32];
+        magic[..4].copy_from_slice(&[0xde, 0xad, 0xbe, 0xef]);
+        assert!(state.contains(&magic));
+
+        let mut answer = [0u8; 32];
+        answer[0] = 0x2a;
+        assert!(state.contains(&answer));
+    }
+
+    #[test]
+    fn caps_dictionary_size() {
+        let mut dictionary = FuzzDictionary::default();
+        for i in 0..MAX_DICTIONARY_SIZE + 10 {
+            dictionary.insert(H256::from_low_u64_be(i as u64).into());
+        }
+        assert_eq!(dictionary.len(), MAX_DICTIONARY_SIZE);
+    }
+}
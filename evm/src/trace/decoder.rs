@@ -290,11 +290,35 @@ impl CallTraceDecoder {
                 }
             }
 
+            // Decode custom errors that are not in the known errors
+            self.decode_custom_error(node).await;
+
             // Decode events
             self.decode_events(node).await;
         }
     }
 
+    /// Decodes the revert data of a failed call as custom error identified by the signature
+    /// identifier, if it was not decoded yet.
+    async fn decode_custom_error(&self, node: &mut CallTraceNode) {
+        if node.trace.success {
+            return
+        }
+        let identifier = match &self.signature_identifier {
+            Some(identifier) => identifier,
+            None => return,
+        };
+        if let RawOrDecodedReturnData::Raw(bytes) = &node.trace.output {
+            let mut identifier = identifier.write().await;
+            if let Some((error, args)) = decode::decode_custom_error(bytes, &mut identifier).await {
+                let args =
+                    args.iter().map(|arg| self.apply_label(arg)).collect::<Vec<_>>().join(", ");
+                node.trace.output =
+                    RawOrDecodedReturnData::Decoded(format!("{}({args})", error.name));
+            }
+        }
+    }
+
     async fn decode_events(&self, node: &mut CallTraceNode) {
         for log in node.logs.iter_mut() {
             self.decode_event(log).await;
@@ -367,3 +391,52 @@ where
 fn indexed_inputs(event: &Event) -> usize {
     event.inputs.iter().filter(|param| param.indexed).count()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trace::CallTrace;
+
+    #[tokio::test]
+    async fn decodes_custom_errors_offline() {
+        let mut output = ethers::utils::id("InsufficientBalance(uint256,uint256)").to_vec();
+        output.extend(ethers::abi::encode(&[Token::Uint(1.into()), Token::Uint(2.into())]));
+
+        // a cached signature, so the error is decoded without network access
+        let tmp = tempfile::tempdir().unwrap();
+        let selector = format!("0x{}", hex::encode(&output[..4]));
+        let cache = serde_json::json!({
+            "events": {},
+            "functions": {},
+            "candidates": {
+                "events": {},
+                "functions": {},
+                "errors": { (selector): ["InsufficientBalance(uint256,uint256)"] },
+            },
+        });
+        std::fs::write(tmp.path().join("signatures"), cache.to_string()).unwrap();
+
+        let mut decoder = CallTraceDecoderBuilder::new().build();
+        let identifier = SignaturesIdentifier::new(Some(tmp.path().into()), true).unwrap();
+        decoder.add_signature_identifier(identifier);
+
+        let mut traces = CallTraceArena::default();
+        traces.push_trace(
+            0,
+            CallTrace {
+                depth: 0,
+                success: false,
+                data: RawOrDecodedCall::Raw(vec![0xde, 0xad, 0xbe, 0xef]),
+                output: RawOrDecodedReturnData::Raw(output),
+                ..Default::default()
+            },
+        );
+        decoder.decode(&mut traces).await;
+
+        assert_eq!(
+            traces.arena[0].trace.output,
+            RawOrDecodedReturnData::Decoded("InsufficientBalance(1, 2)".to_string())
+        );
+        assert!(traces.to_string().contains("InsufficientBalance(1, 2)"));
+    }
+}
@@ -6,14 +6,18 @@ use tracing::warn;
 
 /// An identifier that tries to identify functions and events using signatures found at
 /// `sig.eth.samczsun.com`.
+///
+/// Signatures are looked up in the local cache first and every signature found online is added to
+/// the cache. In offline mode only the cache is used.
 #[derive(Debug, Default)]
 pub struct SignaturesIdentifier {
     cached: CachedSignatures,
     cached_path: Option<PathBuf>,
+    offline: bool,
 }
 
 impl SignaturesIdentifier {
-    pub fn new(cache_path: Option<PathBuf>) -> eyre::Result<Self> {
+    pub fn new(cache_path: Option<PathBuf>, offline: bool) -> eyre::Result<Self> {
         if let Some(cache_path) = cache_path {
             let path = cache_path.join("signatures");
             let cached = if path.is_file() {
@@ -24,9 +28,9 @@ impl SignaturesIdentifier {
                 }
                 CachedSignatures::default()
             };
-            return Ok(Self { cached, cached_path: Some(path) })
+            return Ok(Self { cached, cached_path: Some(path), offline })
         }
-        Ok(Self::default())
+        Ok(Self { offline, ..Default::default() })
     }
 
     pub fn save(&self) {
@@ -52,11 +56,12 @@ impl SignaturesIdentifier {
         let map = match selector_type {
             SelectorType::Function => &mut self.cached.functions,
             SelectorType::Event => &mut self.cached.events,
+            SelectorType::Error => &mut self.cached.errors,
         };
 
         let identifier = format!("0x{}", hex::encode(identifier));
 
-        if !map.contains_key(&identifier) {
+        if !self.offline && !map.contains_key(&identifier) {
             if let Ok(signatures) = decode_selector(&identifier, selector_type).await {
                 if let Some(signature) = signatures.into_iter().next() {
                     map.insert(identifier.to_string(), signature);
@@ -79,6 +84,52 @@ impl SignaturesIdentifier {
     pub async fn identify_event(&mut self, identifier: &[u8]) -> Option<Event> {
         self.identify(SelectorType::Event, identifier, get_event).await
     }
+
+    /// Identifies a custom error from its cache or `sig.eth.samczsun.com`.
+    ///
+    /// Errors are encoded like function calls, so the error is returned as `Function` whose inputs
+    /// are the parameters of the error.
+    pub async fn identify_error(&mut self, identifier: &[u8]) -> Option<Function> {
+        self.identify(SelectorType::Error, identifier, get_func).await
+    }
+
+    /// Returns the possible signatures of the `0x` prefixed function or error selector or event
+    /// topic.
+    ///
+    /// All signatures found online are added to the cache, and cached signatures are returned
+    /// without querying `sig.eth.samczsun.com`. In offline mode, a signature cached by the trace
+    /// identification is returned if no candidates are cached.
+    pub async fn decode_selector(
+        &mut self,
+        selector: &str,
+        selector_type: SelectorType,
+    ) -> eyre::Result<Vec<String>> {
+        let (map, candidates) = match selector_type {
+            SelectorType::Function => {
+                (&mut self.cached.functions, &mut self.cached.candidates.functions)
+            }
+            SelectorType::Event => (&mut self.cached.events, &mut self.cached.candidates.events),
+            SelectorType::Error => (&mut self.cached.errors, &mut self.cached.candidates.errors),
+        };
+        let selector = selector.to_lowercase();
+
+        if let Some(signatures) = candidates.get(&selector) {
+            return Ok(signatures.clone())
+        }
+        if self.offline {
+            if let Some(signature) = map.get(&selector) {
+                return Ok(vec![signature.clone()])
+            }
+            eyre::bail!("No signature found for {selector} in the local signature database")
+        }
+
+        let signatures = decode_selector(&selector, selector_type).await?;
+        if let Some(signature) = signatures.first() {
+            map.entry(selector.clone()).or_insert_with(|| signature.clone());
+            candidates.insert(selector, signatures.clone());
+        }
+        Ok(signatures)
+    }
 }
 
 impl Drop for SignaturesIdentifier {
@@ -91,6 +142,19 @@ impl Drop for SignaturesIdentifier {
 pub struct CachedSignatures {
     pub events: BTreeMap<String, String>,
     pub functions: BTreeMap<String, String>,
+    #[serde(default)]
+    pub errors: BTreeMap<String, String>,
+    /// All signatures found for a selector
+    #[serde(default)]
+    pub candidates: CachedCandidates,
+}
+
+/// All possible signatures of the selectors that were looked up, by selector
+#[derive(Debug, Deserialize, Serialize, Default)]
+pub struct CachedCandidates {
+    pub events: BTreeMap<String, Vec<String>>,
+    pub functions: BTreeMap<String, Vec<String>>,
+    pub errors: BTreeMap<String, Vec<String>>,
 }
 
 #[cfg(test)]
@@ -101,7 +165,7 @@ mod tests {
     async fn can_query_signatures() {
         let tmp = tempfile::tempdir().unwrap();
         {
-            let mut sigs = SignaturesIdentifier::new(Some(tmp.path().into())).unwrap();
+            let mut sigs = SignaturesIdentifier::new(Some(tmp.path().into()), false).unwrap();
 
             assert!(sigs.cached.events.is_empty());
             assert!(sigs.cached.functions.is_empty());
@@ -121,8 +185,43 @@ mod tests {
             // dropping saves the cache
         }
 
-        let sigs = SignaturesIdentifier::new(Some(tmp.path().into())).unwrap();
+        let sigs = SignaturesIdentifier::new(Some(tmp.path().into()), false).unwrap();
         assert!(sigs.cached.events.len() == 1);
         assert!(sigs.cached.functions.len() == 1);
     }
+
+    #[tokio::test]
+    async fn can_decode_selectors_offline() {
+        let tmp = tempfile::tempdir().unwrap();
+        {
+            let mut sigs = SignaturesIdentifier::new(Some(tmp.path().into()), true).unwrap();
+            assert!(sigs.decode_selector("0xa9059cbb", SelectorType::Function).await.is_err());
+
+            sigs.cached
+                .functions
+                .insert("0xa9059cbb".to_string(), "transfer(address,uint256)".to_string());
+            // dropping saves the cache
+        }
+
+        let mut sigs = SignaturesIdentifier::new(Some(tmp.path().into()), true).unwrap();
+        let decoded = sigs.decode_selector("0xA9059CBB", SelectorType::Function).await.unwrap();
+        assert_eq!(decoded, vec!["transfer(address,uint256)".to_string()]);
+        assert!(sigs.identify_function(&[35, 184, 114, 221]).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn caches_all_candidates() {
+        let tmp = tempfile::tempdir().unwrap();
+        let candidates =
+            vec!["collate_propagate_storage(bytes16)".to_string(), "burn(uint256)".to_string()];
+        {
+            let mut sigs = SignaturesIdentifier::new(Some(tmp.path().into()), true).unwrap();
+            sigs.cached.candidates.functions.insert("0x42966c68".to_string(), candidates.clone());
+            // dropping saves the cache
+        }
+
+        let mut sigs = SignaturesIdentifier::new(Some(tmp.path().into()), true).unwrap();
+        let decoded = sigs.decode_selector("0x42966c68", SelectorType::Function).await.unwrap();
+        assert_eq!(decoded, candidates);
+    }
 }
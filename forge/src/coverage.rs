@@ -1,6 +1,6 @@
 use comfy_table::{Attribute, Cell, Color, Row, Table};
 pub use foundry_evm::coverage::*;
-use std::io::Write;
+use std::{collections::BTreeMap, io::Write};
 
 /// A coverage reporter.
 pub trait CoverageReporter {
@@ -18,7 +18,14 @@ pub struct SummaryReporter {
 impl Default for SummaryReporter {
     fn default() -> Self {
         let mut table = Table::new();
-        table.set_header(&["File", "% Lines", "% Statements", "% Branches", "% Funcs"]);
+        table.set_header(&[
+            "File",
+            "% Lines",
+            "% Statements",
+            "% Branches",
+            "Partial Branches",
+            "% Funcs",
+        ]);
 
         Self { table, total: CoverageSummary::default() }
     }
@@ -31,6 +38,7 @@ impl SummaryReporter {
             .add_cell(format_cell(summary.line_hits, summary.line_count))
             .add_cell(format_cell(summary.statement_hits, summary.statement_count))
             .add_cell(format_cell(summary.branch_hits, summary.branch_count))
+            .add_cell(format_partial_cell(summary.partial_branch_count))
             .add_cell(format_cell(summary.function_hits, summary.function_count));
         self.table.add_row(row);
     }
@@ -66,6 +74,19 @@ fn format_cell(hits: usize, total: usize) -> Cell {
     cell
 }
 
+/// Formats the number of partially covered branches, which are highlighted since they are easy to
+/// miss when only looking at the branch percentage.
+fn format_partial_cell(partial: usize) -> Cell {
+    let cell = Cell::new(partial);
+    if partial == 0 {
+        cell.fg(Color::Grey).add_attribute(Attribute::Dim)
+    } else {
+        cell.fg(Color::Yellow)
+    }
+}
+
+/// A reporter that writes the coverage results in the LCOV format, which is understood by most
+/// coverage services.
 pub struct LcovReporter<'a> {
     /// Destination buffer
     destination: &'a mut (dyn Write + 'a),
@@ -79,50 +100,70 @@ impl<'a> LcovReporter<'a> {
 
 impl<'a> CoverageReporter for LcovReporter<'a> {
     fn report(self, report: CoverageReport) -> eyre::Result<()> {
-        for (file, items) in report.items_by_source() {
-            let summary = items.iter().fold(CoverageSummary::default(), |mut summary, item| {
-                summary += item;
-                summary
-            });
-
-            writeln!(self.destination, "TN:")?;
-            writeln!(self.destination, "SF:{}", file)?;
-
-            for item in items {
+        for (file, items) in report.items_by_source_path() {
+            // Multiple items of the same kind can end up on the same line, but LCOV expects a
+            // single record per line
+            let mut functions = BTreeMap::new();
+            let mut lines = BTreeMap::new();
+            let mut branches = BTreeMap::new();
+            for item in &items {
                 let line = item.loc.line;
-                let hits = item.hits;
-                match item.kind {
+                match &item.kind {
                     CoverageItemKind::Function { name } => {
                         let name = format!("{}.{}", item.loc.contract_name, name);
-                        writeln!(self.destination, "FN:{line},{name}")?;
-                        writeln!(self.destination, "FNDA:{hits},{name}")?;
+                        let (_, hits) = functions.entry(name).or_insert((line, 0));
+                        *hits += item.hits;
                     }
                     CoverageItemKind::Line => {
-                        writeln!(self.destination, "DA:{line},{hits}")?;
+                        let hits = lines.entry(line).or_insert(0);
+                        *hits = item.hits.max(*hits);
                     }
                     CoverageItemKind::Branch { branch_id, path_id } => {
-                        writeln!(
-                            self.destination,
-                            "BRDA:{line},{branch_id},{path_id},{}",
-                            if hits == 0 { "-".to_string() } else { hits.to_string() }
-                        )?;
+                        *branches.entry((line, *branch_id, *path_id)).or_insert(0) += item.hits;
                     }
                     // Statements are not in the LCOV format
                     CoverageItemKind::Statement => (),
                 }
             }
 
-            // Function summary
-            writeln!(self.destination, "FNF:{}", summary.function_count)?;
-            writeln!(self.destination, "FNH:{}", summary.function_hits)?;
-
-            // Line summary
-            writeln!(self.destination, "LF:{}", summary.line_count)?;
-            writeln!(self.destination, "LH:{}", summary.line_hits)?;
+            writeln!(self.destination, "TN:")?;
+            writeln!(self.destination, "SF:{}", file)?;
 
-            // Branch summary
-            writeln!(self.destination, "BRF:{}", summary.branch_count)?;
-            writeln!(self.destination, "BRH:{}", summary.branch_hits)?;
+            // Functions
+            for (name, (line, _)) in &functions {
+                writeln!(self.destination, "FN:{line},{name}")?;
+            }
+            for (name, (_, hits)) in &functions {
+                writeln!(self.destination, "FNDA:{hits},{name}")?;
+            }
+            writeln!(self.destination, "FNF:{}", functions.len())?;
+            writeln!(
+                self.destination,
+                "FNH:{}",
+                functions.values().filter(|(_, hits)| *hits > 0).count()
+            )?;
+
+            // Lines
+            for (line, hits) in &lines {
+                writeln!(self.destination, "DA:{line},{hits}")?;
+            }
+            writeln!(self.destination, "LF:{}", lines.len())?;
+            writeln!(self.destination, "LH:{}", lines.values().filter(|hits| **hits > 0).count())?;
+
+            // Branches
+            for ((line, branch_id, path_id), hits) in &branches {
+                writeln!(
+                    self.destination,
+                    "BRDA:{line},{branch_id},{path_id},{}",
+                    if *hits == 0 { "-".to_string() } else { hits.to_string() }
+                )?;
+            }
+            writeln!(self.destination, "BRF:{}", branches.len())?;
+            writeln!(
+                self.destination,
+                "BRH:{}",
+                branches.values().filter(|hits| **hits > 0).count()
+            )?;
 
             writeln!(self.destination, "end_of_record")?;
         }
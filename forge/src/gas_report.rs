@@ -5,6 +5,7 @@ use crate::{
 use comfy_table::{modifiers::UTF8_ROUND_CORNERS, presets::UTF8_FULL, *};
 use ethers::types::U256;
 use foundry_common::{calc, TestFunctionExt};
+use glob::{Pattern, PatternError};
 use serde::{Deserialize, Serialize};
 use std::{collections::BTreeMap, fmt::Display};
 
@@ -12,16 +13,33 @@ use std::{collections::BTreeMap, fmt::Display};
 pub struct GasReport {
     pub report_for: Vec<String>,
     pub ignore: Vec<String>,
+    /// Glob patterns of contract paths to report gas of, all paths are included if empty
+    #[serde(skip)]
+    pub include_paths: Vec<Pattern>,
+    /// Glob patterns of contract paths to ignore
+    #[serde(skip)]
+    pub exclude_paths: Vec<Pattern>,
     pub contracts: BTreeMap<String, ContractInfo>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Default)]
 pub struct ContractInfo {
+    /// The highest deployment cost of the contract
     pub gas: U256,
     pub size: U256,
+    /// The cost of every deployment of the contract, sorted once the report is finalized
+    #[serde(default)]
+    pub deployments: Vec<U256>,
     pub functions: BTreeMap<String, BTreeMap<String, GasInfo>>,
 }
 
+impl ContractInfo {
+    /// Returns the lowest deployment cost of the finalized report
+    pub fn min_deployment_cost(&self) -> U256 {
+        self.deployments.first().copied().unwrap_or(self.gas)
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Default)]
 pub struct GasInfo {
     pub calls: Vec<U256>,
@@ -31,11 +49,66 @@ pub struct GasInfo {
     pub max: U256,
 }
 
+/// A gas report in a stable, machine readable format, see [`GasReport::to_json_report`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JsonGasReport {
+    /// The gas usage of every contract, keyed by contract identifier
+    pub contracts: BTreeMap<String, JsonContractGas>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JsonContractGas {
+    /// The highest deployment cost
+    pub deployment_cost: u64,
+    /// The lowest deployment cost, which only differs from `deployment_cost` if the cost varied
+    /// between deployments, e.g. because of different constructor arguments
+    #[serde(default)]
+    pub deployment_cost_min: u64,
+    pub deployment_size: u64,
+    /// The gas usage of every function, keyed by function signature
+    pub functions: BTreeMap<String, JsonFunctionGas>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JsonFunctionGas {
+    pub calls: u64,
+    pub min: u64,
+    pub avg: u64,
+    pub median: u64,
+    pub max: u64,
+}
+
 impl GasReport {
     pub fn new(report_for: Vec<String>, ignore: Vec<String>) -> Self {
         Self { report_for, ignore, ..Default::default() }
     }
 
+    /// Only reports contracts whose source path matches one of the `include` glob patterns, if
+    /// any, and none of the `exclude` glob patterns.
+    pub fn with_path_filter(
+        mut self,
+        include: &[String],
+        exclude: &[String],
+    ) -> Result<Self, PatternError> {
+        self.include_paths = include.iter().map(|p| Pattern::new(p)).collect::<Result<_, _>>()?;
+        self.exclude_paths = exclude.iter().map(|p| Pattern::new(p)).collect::<Result<_, _>>()?;
+        Ok(self)
+    }
+
+    /// Returns whether the path of the contract, identified as `<path>:<name>`, passes the path
+    /// filter.
+    ///
+    /// Contracts without a path, like the ones identified using Etherscan, only pass if there are
+    /// no include patterns.
+    fn matches_path(&self, contract: &str) -> bool {
+        let path = contract.rsplit_once(':').map(|(path, _)| path.trim_start_matches("./"));
+        let matches = |patterns: &[Pattern]| {
+            path.map_or(false, |path| patterns.iter().any(|pattern| pattern.matches(path)))
+        };
+        (self.include_paths.is_empty() || matches(&self.include_paths)) &&
+            !matches(&self.exclude_paths)
+    }
+
     pub fn analyze(&mut self, traces: &[(TraceKind, CallTraceArena)]) {
         traces.iter().for_each(|(_, trace)| {
             self.analyze_node(0, trace);
@@ -68,13 +141,13 @@ impl GasReport {
                 self.report_for.contains(&"*".to_string())) ||
                 (!self.ignore.contains(&contract_name) && self.report_for.is_empty()) ||
                 (self.report_for.contains(&contract_name));
-            if report_contract {
+            if report_contract && self.matches_path(name) {
                 let mut contract_report =
                     self.contracts.entry(name.to_string()).or_insert_with(Default::default);
 
                 match &trace.data {
                     RawOrDecodedCall::Raw(bytes) if trace.created() => {
-                        contract_report.gas = trace.gas_cost.into();
+                        contract_report.deployments.push(trace.gas_cost.into());
                         contract_report.size = bytes.len().into();
                     }
                     // TODO: More robust test contract filtering
@@ -99,9 +172,52 @@ impl GasReport {
         });
     }
 
+    /// Converts the finalized report into [`JsonGasReport`].
+    ///
+    /// Unlike the report itself, gas values are plain numbers and functions are keyed by their
+    /// signature, which keeps the output stable for tools that compare reports across runs.
+    pub fn to_json_report(&self) -> JsonGasReport {
+        let contracts = self
+            .contracts
+            .iter()
+            .map(|(name, contract)| {
+                let functions = contract
+                    .functions
+                    .iter()
+                    .flat_map(|(name, sigs)| sigs.iter().map(move |sig| (name, sig)))
+                    .map(|(name, (sig, function))| {
+                        // signatures are `name(inputs):(outputs)`, and empty for the fallback
+                        let sig = match sig.split(':').next() {
+                            Some(sig) if !sig.is_empty() => sig.to_string(),
+                            _ => name.clone(),
+                        };
+                        let gas = JsonFunctionGas {
+                            calls: function.calls.len() as u64,
+                            min: function.min.low_u64(),
+                            avg: function.mean.low_u64(),
+                            median: function.median.low_u64(),
+                            max: function.max.low_u64(),
+                        };
+                        (sig, gas)
+                    })
+                    .collect();
+                let contract = JsonContractGas {
+                    deployment_cost: contract.gas.low_u64(),
+                    deployment_cost_min: contract.min_deployment_cost().low_u64(),
+                    deployment_size: contract.size.low_u64(),
+                    functions,
+                };
+                (name.clone(), contract)
+            })
+            .collect();
+        JsonGasReport { contracts }
+    }
+
     #[must_use]
     pub fn finalize(mut self) -> Self {
         self.contracts.iter_mut().for_each(|(_, contract)| {
+            contract.deployments.sort_unstable();
+            contract.gas = contract.deployments.last().copied().unwrap_or_default();
             contract.functions.iter_mut().for_each(|(_, sigs)| {
                 sigs.iter_mut().for_each(|(_, func)| {
                     func.calls.sort_unstable();
@@ -132,7 +248,16 @@ impl Display for GasReport {
                 Cell::new("Deployment Cost").add_attribute(Attribute::Bold).fg(Color::Cyan),
                 Cell::new("Deployment Size").add_attribute(Attribute::Bold).fg(Color::Cyan),
             ]);
-            table.add_row(vec![contract.gas.to_string(), contract.size.to_string()]);
+            let min_cost = contract.min_deployment_cost();
+            if min_cost == contract.gas {
+                table.add_row(vec![contract.gas.to_string(), contract.size.to_string()]);
+            } else {
+                // flag contracts whose deployment cost differs between deployments
+                table.add_row(vec![
+                    Cell::new(format!("{} - {}", min_cost, contract.gas)).fg(Color::Yellow),
+                    Cell::new(contract.size.to_string()),
+                ]);
+            }
 
             table.add_row(vec![
                 Cell::new("Function Name").add_attribute(Attribute::Bold).fg(Color::Magenta),
@@ -163,3 +288,32 @@ impl Display for GasReport {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filters_contracts_by_path() {
+        let report = GasReport::default();
+        assert!(report.matches_path("src/Counter.sol:Counter"));
+        assert!(report.matches_path("Counter"));
+
+        let report = GasReport::default()
+            .with_path_filter(&["src/**".to_string()], &["src/mocks/**".to_string()])
+            .unwrap();
+        assert!(report.matches_path("src/Counter.sol:Counter"));
+        assert!(report.matches_path("./src/tokens/Token.sol:Token"));
+        assert!(!report.matches_path("src/mocks/MockToken.sol:MockToken"));
+        assert!(!report.matches_path("lib/forge-std/src/Test.sol:Test"));
+        // contracts without a path don't match the include patterns
+        assert!(!report.matches_path("Counter"));
+
+        let report = GasReport::default().with_path_filter(&[], &["test/**".to_string()]).unwrap();
+        assert!(report.matches_path("src/Counter.sol:Counter"));
+        assert!(!report.matches_path("test/Counter.t.sol:CounterTest"));
+        assert!(report.matches_path("Counter"));
+
+        assert!(GasReport::default().with_path_filter(&["src/[".to_string()], &[]).is_err());
+    }
+}
@@ -1,5 +1,19 @@
-use proptest::test_runner::{RngAlgorithm, TestRng, TestRunner};
-use tracing::trace;
+use foundry_evm::fuzz::{strategies::FuzzParamConfig, DEFAULT_DICTIONARY_WEIGHT};
+use proptest::{
+    prelude::RngCore,
+    test_runner::{FailurePersistence, PersistedSeed, RngAlgorithm, TestRng, TestRunner},
+};
+use std::{
+    any::Any,
+    collections::BTreeMap,
+    fmt,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::PathBuf,
+    str::FromStr,
+    time::Duration,
+};
+use tracing::{debug, trace, warn};
 
 /// Gas reports
 pub mod gas_report;
@@ -14,7 +28,7 @@ pub use runner::ContractRunner;
 
 /// Forge test runners for multiple contracts
 mod multi_runner;
-pub use multi_runner::{MultiContractRunner, MultiContractRunnerBuilder};
+pub use multi_runner::{MultiContractRunner, MultiContractRunnerBuilder, Shard};
 
 /// reexport
 pub use foundry_common::traits::TestFilter;
@@ -24,8 +38,11 @@ pub mod result;
 /// The Forge EVM backend
 pub use foundry_evm::*;
 
+/// The environment variable with the fuzz seed that is used if [TestOptions::fuzz_seed] is not set
+pub const FUZZ_SEED_ENV: &str = "FOUNDRY_FUZZ_SEED";
+
 /// Metadata on how to run fuzz/invariant tests
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone)]
 pub struct TestOptions {
     /// The number of test cases that must execute for each fuzz test
     pub fuzz_runs: u32,
@@ -37,8 +54,32 @@ pub struct TestOptions {
     /// by proptest, to be encountered during usage of `vm.assume`
     /// cheatcode.
     pub fuzz_max_global_rejects: u32,
+    /// Whether to generate fuzz inputs from a dictionary of values seeded with the state and the
+    /// bytecode of the deployed contracts
+    pub fuzz_dictionary: bool,
+    /// How often, in percent, fuzz inputs are generated from the dictionary instead of at random
+    pub fuzz_dictionary_weight: u32,
     /// Optional seed for the fuzzing RNG algorithm
     pub fuzz_seed: Option<U256>,
+    /// Optional RNG algorithm used by the fuzzer.
+    ///
+    /// If not set, `ChaCha` is used for seeded runs and proptest's default otherwise.
+    pub fuzz_rng_algorithm: Option<RngAlgorithm>,
+    /// Optional directory where failing fuzz cases are persisted, so they can be replayed on the
+    /// next run
+    pub fuzz_failure_persist_dir: Option<PathBuf>,
+    /// Optional maximum time spent fuzzing a single function, after which the test fails
+    pub fuzz_timeout: Option<Duration>,
+    /// Addresses that half of the random `address` fuzz inputs are picked from
+    pub fuzz_addresses: Vec<Address>,
+    /// Whether the contracts deployed before a fuzz test are added to the `fuzz_addresses`
+    pub fuzz_addresses_deployed: bool,
+    /// Optional inclusive lower bound of `uint256` fuzz inputs
+    pub fuzz_uint256_min: Option<U256>,
+    /// Optional inclusive upper bound of `uint256` fuzz inputs
+    pub fuzz_uint256_max: Option<U256>,
+    /// Whether zero is excluded from `address`, `int` and `uint` fuzz inputs
+    pub fuzz_exclude_zero: bool,
     /// The number of runs that must execute for each invariant test group.
     pub invariant_runs: u32,
     /// The number of calls executed to attempt to break invariants in one run.
@@ -48,24 +89,140 @@ pub struct TestOptions {
     /// Allows overriding an unsafe external call when running invariant tests. eg. reetrancy
     /// checks
     pub invariant_call_override: bool,
+    /// Optional directory where tests replaying the call sequences that broke invariants are
+    /// written to
+    pub invariant_replay_dir: Option<PathBuf>,
+    /// Optional directory where the call sequences of invariant runs that hit new code are
+    /// persisted, per test contract, and replayed at the start of the next run
+    pub invariant_corpus_dir: Option<PathBuf>,
+    /// Relative weights of the calls to targeted contracts and selectors during invariant runs,
+    /// keyed by `<contract name>` or `<contract name>:<function signature>`
+    pub invariant_selector_weights: BTreeMap<String, u32>,
+    /// The only addresses that call the targeted contracts during invariant runs, overriding
+    /// `targetSenders()` if not empty
+    pub invariant_senders: Vec<Address>,
+}
+
+impl Default for TestOptions {
+    /// Options without any runs, with the fuzz dictionary enabled like the default config
+    fn default() -> Self {
+        Self {
+            fuzz_runs: 0,
+            fuzz_max_local_rejects: 0,
+            fuzz_max_global_rejects: 0,
+            fuzz_dictionary: true,
+            fuzz_dictionary_weight: DEFAULT_DICTIONARY_WEIGHT,
+            fuzz_seed: None,
+            fuzz_rng_algorithm: None,
+            fuzz_failure_persist_dir: None,
+            fuzz_timeout: None,
+            fuzz_addresses: vec![],
+            fuzz_addresses_deployed: false,
+            fuzz_uint256_min: None,
+            fuzz_uint256_max: None,
+            fuzz_exclude_zero: false,
+            invariant_runs: 0,
+            invariant_depth: 0,
+            invariant_fail_on_revert: false,
+            invariant_call_override: false,
+            invariant_replay_dir: None,
+            invariant_corpus_dir: None,
+            invariant_selector_weights: Default::default(),
+            invariant_senders: vec![],
+        }
+    }
 }
 
 impl TestOptions {
+    /// Returns the configured fuzz seed: `fuzz_seed` if set, otherwise the seed in the
+    /// [FUZZ_SEED_ENV] environment variable, if any.
+    pub fn configured_fuzz_seed(&self) -> Option<U256> {
+        self.fuzz_seed.or_else(fuzz_seed_from_env)
+    }
+
+    /// Returns a copy of these options with a `fuzz_seed` set, generating a random one if none was
+    /// configured.
+    ///
+    /// All fuzzers built from the returned options are deterministic, so a failing test can be
+    /// reproduced by running it again with the same seed.
+    pub fn with_pinned_fuzz_seed(&self) -> Self {
+        let fuzz_seed = match self.configured_fuzz_seed() {
+            Some(fuzz_seed) => {
+                debug!(target: "forge::test", "using fuzz seed {:#x}", fuzz_seed);
+                fuzz_seed
+            }
+            None => {
+                let fuzz_seed =
+                    generate_fuzz_seed(self.fuzz_rng_algorithm.unwrap_or(RngAlgorithm::ChaCha));
+                debug!(target: "forge::test", "using random fuzz seed {:#x}", fuzz_seed);
+                fuzz_seed
+            }
+        };
+        Self { fuzz_seed: Some(fuzz_seed), ..self.clone() }
+    }
+
+    /// Returns the overrides of how the fuzzer generates the inputs of fuzz tests
+    pub fn fuzz_param_config(&self) -> FuzzParamConfig {
+        let uint256_bounds = if self.fuzz_uint256_min.is_some() || self.fuzz_uint256_max.is_some() {
+            Some((
+                self.fuzz_uint256_min.unwrap_or_else(U256::zero),
+                self.fuzz_uint256_max.unwrap_or(U256::MAX),
+            ))
+        } else {
+            None
+        };
+        FuzzParamConfig {
+            addresses: self.fuzz_addresses.clone(),
+            deployed_addresses: self.fuzz_addresses_deployed,
+            uint256_bounds,
+            exclude_zero: self.fuzz_exclude_zero,
+        }
+    }
+
+    /// Returns a fuzzer that does not persist failures
     pub fn fuzzer(&self) -> TestRunner {
-        // TODO: Add Options to modify the persistence
+        self.fuzzer_with_persistence(None)
+    }
+
+    /// Returns a fuzzer for the given test of the given contract.
+    ///
+    /// If `fuzz_failure_persist_dir` is set, failing cases are persisted to
+    /// `<dir>/<contract>/<test>` and replayed before any new cases are generated.
+    pub fn test_fuzzer(&self, contract_name: &str, test_name: &str) -> TestRunner {
+        let persist_file = self.fuzz_failure_persist_dir.as_ref().map(|dir| {
+            dir.join(sanitize_persist_key(contract_name)).join(sanitize_persist_key(test_name))
+        });
+        self.fuzzer_with_persistence(persist_file)
+    }
+
+    fn fuzzer_with_persistence(&self, persist_file: Option<PathBuf>) -> TestRunner {
+        let failure_persistence = persist_file.map(|file| {
+            trace!(target: "forge::test", "persisting fuzz failures to {}", file.display());
+            Box::new(FuzzFailurePersistence { file }) as Box<dyn FailurePersistence>
+        });
+
         let cfg = proptest::test_runner::Config {
-            failure_persistence: None,
+            failure_persistence,
             cases: self.fuzz_runs,
             max_local_rejects: self.fuzz_max_local_rejects,
             max_global_rejects: self.fuzz_max_global_rejects,
             ..Default::default()
         };
 
-        if let Some(ref fuzz_seed) = self.fuzz_seed {
-            trace!(target: "forge::test", "building deterministic fuzzer with seed {}", fuzz_seed);
+        // an explicitly selected algorithm needs a seed, so we generate one that can be pinned
+        let fuzz_seed =
+            self.configured_fuzz_seed().or_else(|| self.fuzz_rng_algorithm.map(generate_fuzz_seed));
+
+        if let Some(fuzz_seed) = fuzz_seed {
+            let algorithm = self.fuzz_rng_algorithm.unwrap_or(RngAlgorithm::ChaCha);
+            trace!(target: "forge::test", "building deterministic fuzzer with seed {} and {:?}", fuzz_seed, algorithm);
             let mut bytes: [u8; 32] = [0; 32];
             fuzz_seed.to_big_endian(&mut bytes);
-            let rng = TestRng::from_seed(RngAlgorithm::ChaCha, &bytes);
+            let rng = match algorithm {
+                // XorShift takes a 16 byte seed, so we use the lower 128 bits
+                RngAlgorithm::XorShift => TestRng::from_seed(algorithm, &bytes[16..]),
+                _ => TestRng::from_seed(algorithm, &bytes),
+            };
             proptest::test_runner::TestRunner::new_with_rng(cfg, rng)
         } else {
             trace!(target: "forge::test", "building stochastic fuzzer");
@@ -73,3 +230,105 @@ impl TestOptions {
         }
     }
 }
+
+/// Generates a random fuzz seed
+fn generate_fuzz_seed(algorithm: RngAlgorithm) -> U256 {
+    let mut bytes: [u8; 32] = [0; 32];
+    TestRng::default_rng(algorithm).fill_bytes(&mut bytes);
+    let fuzz_seed = U256::from_big_endian(&bytes);
+    trace!(target: "forge::test", "generated fuzz seed {}", fuzz_seed);
+    fuzz_seed
+}
+
+/// Reads the fuzz seed from the [FUZZ_SEED_ENV] environment variable, as decimal or `0x` prefixed
+/// hex.
+///
+/// An invalid seed is ignored with a warning.
+fn fuzz_seed_from_env() -> Option<U256> {
+    let seed = std::env::var(FUZZ_SEED_ENV).ok()?;
+    let seed = seed.trim();
+    if seed.is_empty() {
+        return None
+    }
+    let parsed = match seed.strip_prefix("0x") {
+        Some(hex) => U256::from_str(hex).map_err(|err| err.to_string()),
+        None => U256::from_dec_str(seed).map_err(|err| err.to_string()),
+    };
+    match parsed {
+        Ok(seed) => Some(seed),
+        Err(err) => {
+            warn!(target: "forge::test", "ignoring invalid {} {:?}: {}", FUZZ_SEED_ENV, seed, err);
+            None
+        }
+    }
+}
+
+/// Replaces all characters of a contract identifier or test name that are not safe to use in a
+/// file name, e.g. `src/test/Foo.t.sol:FooTest` -> `src_test_Foo.t.sol_FooTest`
+fn sanitize_persist_key(key: &str) -> String {
+    key.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') { c } else { '_' })
+        .collect()
+}
+
+/// Persists the seeds of failing fuzz cases to a file, in the format of proptest's
+/// `FileFailurePersistence`, which only accepts a `&'static str` as path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FuzzFailurePersistence {
+    file: PathBuf,
+}
+
+impl FailurePersistence for FuzzFailurePersistence {
+    fn load_persisted_failures2(&self, _: Option<&'static str>) -> Vec<PersistedSeed> {
+        let content = match fs::read_to_string(&self.file) {
+            Ok(content) => content,
+            Err(_) => return vec![],
+        };
+        content
+            .lines()
+            // everything after `#` is a comment
+            .filter_map(|line| line.split('#').next())
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .filter_map(|line| {
+                let seed = PersistedSeed::from_str(line);
+                if seed.is_none() {
+                    warn!(target: "forge::test", "ignoring invalid persisted fuzz seed {:?} in {}", line, self.file.display());
+                }
+                seed
+            })
+            .collect()
+    }
+
+    fn save_persisted_failure2(
+        &mut self,
+        _: Option<&'static str>,
+        seed: PersistedSeed,
+        shrunken_value: &dyn fmt::Debug,
+    ) {
+        if self.load_persisted_failures2(None).contains(&seed) {
+            return
+        }
+        let res = self
+            .file
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| OpenOptions::new().create(true).append(true).open(&self.file))
+            .and_then(|mut file| writeln!(file, "{} # shrinks to {:?}", seed, shrunken_value));
+        if let Err(err) = res {
+            warn!(target: "forge::test", "failed to persist fuzz failure to {}: {}", self.file.display(), err);
+        }
+    }
+
+    fn box_clone(&self) -> Box<dyn FailurePersistence> {
+        Box::new(self.clone())
+    }
+
+    fn eq(&self, other: &dyn FailurePersistence) -> bool {
+        other.as_any().downcast_ref::<Self>().map_or(false, |other| self == other)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
@@ -1,4 +1,7 @@
-use crate::{result::SuiteResult, ContractRunner, TestFilter, TestOptions};
+use crate::{
+    result::{FinishedTest, SuiteResult},
+    ContractRunner, TestFilter, TestOptions,
+};
 use ethers::{
     abi::Abi,
     prelude::{artifacts::CompactContractBytecode, ArtifactId, ArtifactOutput},
@@ -16,7 +19,13 @@ use foundry_evm::{
 };
 use foundry_utils::PostLinkInput;
 use rayon::prelude::*;
-use std::{collections::BTreeMap, path::Path, sync::mpsc::Sender};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    path::Path,
+    str::FromStr,
+    sync::{mpsc::Sender, Mutex},
+};
 
 pub type DeployableContracts = BTreeMap<ArtifactId, (Abi, Bytes, Vec<Bytes>)>;
 
@@ -48,9 +57,30 @@ pub struct MultiContractRunner {
     pub coverage: bool,
     /// Settings related to fuzz and/or invariant tests
     pub test_options: TestOptions,
+    /// Whether to run and report the test contracts sorted by source path and name
+    pub sorted: bool,
+    /// Identifiers (`path:name`) or names of the test contracts to run and report first, in this
+    /// order
+    pub test_order: Vec<String>,
+    /// The shard of the test functions to run, if the tests are split across multiple runs
+    pub shard: Option<Shard>,
+    /// The only tests to run by contract identifier (`path:name`), e.g. the tests that failed in
+    /// the last run
+    pub only_tests: Option<BTreeMap<String, BTreeSet<String>>>,
+    /// Receives the result of every test as soon as it completed
+    test_stream: Option<Mutex<Sender<FinishedTest>>>,
 }
 
 impl MultiContractRunner {
+    /// Sends the result of every test to `sender` as soon as the test completed, in no particular
+    /// order
+    ///
+    /// Every [`FinishedTest`] carries the identifier of its contract and the test it belongs to.
+    pub fn with_test_stream(mut self, sender: Sender<FinishedTest>) -> Self {
+        self.test_stream = Some(Mutex::new(sender));
+        self
+    }
+
     /// Returns the number of matching tests
     pub fn count_filtered_tests(&self, filter: &impl TestFilter) -> usize {
         self.contracts
@@ -65,6 +95,27 @@ impl MultiContractRunner {
             .count()
     }
 
+    /// Returns the number of test and invariant functions that will be run, taking the configured
+    /// shard into account
+    pub fn count_tests_to_run(&self, filter: &impl TestFilter) -> usize {
+        if let Some(selected_tests) = self.selected_tests(filter) {
+            return selected_tests.values().map(BTreeSet::len).sum()
+        }
+        self.contracts
+            .iter()
+            .filter(|(id, _)| {
+                filter.matches_path(id.source.to_string_lossy()) &&
+                    filter.matches_contract(&id.name)
+            })
+            .flat_map(|(_, (abi, _, _))| {
+                abi.functions().filter(|func| {
+                    (func.is_test() || func.is_invariant_test()) &&
+                        filter.matches_test(func.signature())
+                })
+            })
+            .count()
+    }
+
     // Get all tests of matching path and contract
     pub fn get_tests(&self, filter: &impl TestFilter) -> Vec<String> {
         self.contracts
@@ -83,6 +134,7 @@ impl MultiContractRunner {
         &self,
         filter: &impl TestFilter,
     ) -> BTreeMap<String, BTreeMap<String, Vec<String>>> {
+        let selected_tests = self.selected_tests(filter);
         self.contracts
             .iter()
             .filter(|(id, _)| {
@@ -90,9 +142,12 @@ impl MultiContractRunner {
                     filter.matches_contract(&id.name)
             })
             .filter(|(_, (abi, _, _))| abi.functions().any(|func| filter.matches_test(&func.name)))
+            .filter(|(id, _)| selected_tests.as_ref().map_or(true, |tests| tests.contains_key(id)))
             .map(|(id, (abi, _, _))| {
                 let source = id.source.as_path().display().to_string();
                 let name = id.name.clone();
+                let filter =
+                    ShardFilter::new(filter, selected_tests.as_ref().map(|tests| &tests[id]));
                 let tests = abi
                     .functions()
                     .filter(|func| func.name.is_test())
@@ -108,6 +163,100 @@ impl MultiContractRunner {
             })
     }
 
+    /// Returns whether the test contracts are run and reported in a deterministic order
+    fn is_ordered(&self) -> bool {
+        self.sorted || !self.test_order.is_empty()
+    }
+
+    /// Returns the test contracts that match the given `filter`, in the order they are scheduled.
+    ///
+    /// If the runner is ordered, the contracts listed in `test_order` come first, in that order,
+    /// followed by all other contracts sorted by source path and name.
+    fn scheduled_contracts(
+        &self,
+        filter: &impl TestFilter,
+    ) -> Vec<(&ArtifactId, &(Abi, Bytes, Vec<Bytes>))> {
+        let mut contracts = self
+            .contracts
+            .iter()
+            .filter(|(id, _)| {
+                filter.matches_path(id.source.to_string_lossy()) &&
+                    filter.matches_contract(&id.name)
+            })
+            .filter(|(_, (abi, _, _))| abi.functions().any(|func| filter.matches_test(&func.name)))
+            .collect::<Vec<_>>();
+
+        if self.is_ordered() {
+            contracts.sort_by_cached_key(|(id, _)| {
+                let identifier = id.identifier();
+                let position = self
+                    .test_order
+                    .iter()
+                    .position(|contract| *contract == identifier || *contract == id.name)
+                    .unwrap_or(self.test_order.len());
+                (position, id.source.clone(), id.name.clone())
+            });
+        }
+        contracts
+    }
+
+    /// Returns the signatures of the test functions in the configured shard and selection of
+    /// tests, by contract, or `None` if the tests are neither sharded nor selected.
+    ///
+    /// All test and invariant functions that match the given `filter` are sorted by source path,
+    /// contract name and signature, and dealt out to the shards in turn. So every matching test
+    /// is in exactly one shard, and the same tests always end up in the same shard.
+    fn selected_tests(
+        &self,
+        filter: &impl TestFilter,
+    ) -> Option<BTreeMap<&ArtifactId, BTreeSet<String>>> {
+        if self.shard.is_none() && self.only_tests.is_none() {
+            return None
+        }
+
+        let mut tests = self
+            .contracts
+            .iter()
+            .filter(|(id, _)| {
+                filter.matches_path(id.source.to_string_lossy()) &&
+                    filter.matches_contract(&id.name)
+            })
+            .flat_map(|(id, (abi, _, _))| {
+                let only_tests = self.only_tests.as_ref().map(|only_tests| {
+                    only_tests.get(&id.identifier()).cloned().unwrap_or_default()
+                });
+                abi.functions()
+                    .filter(|func| func.is_test() || func.is_invariant_test())
+                    .filter(move |func| {
+                        only_tests.as_ref().map_or(true, |tests| {
+                            // a failing `setUp()` fails every test of the contract, and invariant
+                            // tests are reported by name
+                            tests.contains("setUp()") ||
+                                tests.contains(&func.signature()) ||
+                                tests.contains(&func.name)
+                        })
+                    })
+                    .map(|func| func.signature())
+                    .filter(|signature| filter.matches_test(signature))
+                    .map(move |signature| (id, signature))
+            })
+            .collect::<Vec<_>>();
+        tests.sort_by(|(a, a_sig), (b, b_sig)| {
+            (&a.source, &a.name, a_sig).cmp(&(&b.source, &b.name, b_sig))
+        });
+
+        Some(
+            tests
+                .into_iter()
+                .enumerate()
+                .filter(|(position, _)| self.shard.map_or(true, |shard| shard.contains(*position)))
+                .fold(BTreeMap::new(), |mut acc, (_, (id, signature))| {
+                    acc.entry(id).or_insert_with(BTreeSet::new).insert(signature);
+                    acc
+                }),
+        )
+    }
+
     /// Executes _all_ tests that match the given `filter`
     ///
     /// This will create the runtime based on the configured `evm` ops and create the `Backend`
@@ -122,20 +271,21 @@ impl MultiContractRunner {
     ) -> Result<BTreeMap<String, SuiteResult>> {
         let db = Backend::spawn(self.fork.take());
 
+        let selected_tests = self.selected_tests(filter);
+        let mut contracts = self.scheduled_contracts(filter);
+        if let Some(selected_tests) = selected_tests.as_ref() {
+            contracts.retain(|(id, _)| selected_tests.contains_key(id));
+        }
+        let stream_result =
+            stream_result.map(|sender| Mutex::new(ResultStream::new(sender, self.is_ordered())));
+
         let results =
             // the db backend that serves all the data, each contract gets its own instance
 
-             self
-                .contracts
-                .par_iter()
-                .filter(|(id, _)| {
-                    filter.matches_path(id.source.to_string_lossy()) &&
-                        filter.matches_contract(&id.name)
-                })
-                .filter(|(_, (abi, _, _))| {
-                    abi.functions().any(|func| filter.matches_test(&func.name))
-                })
-                .map(|(id, (abi, deploy_code, libs))| {
+             contracts
+                .into_par_iter()
+                .enumerate()
+                .map(|(index, (id, (abi, deploy_code, libs)))| {
                     let executor = ExecutorBuilder::default()
                         .with_cheatcodes(self.cheats_config.clone())
                         .with_config(self.env.clone())
@@ -146,50 +296,52 @@ impl MultiContractRunner {
                         .build(db.clone());
                     let identifier = id.identifier();
                     tracing::trace!(contract= ?identifier, "start executing all tests in contract");
+                    let filter =
+                        ShardFilter::new(filter, selected_tests.as_ref().map(|tests| &tests[id]));
 
-                    let result = self.run_tests(
-                        &identifier,
-                        abi,
-                        executor,
-                        deploy_code.clone(),
-                        libs,
-                        (filter, test_options),
-                    )?;
+                    let result = self
+                        .run_tests(
+                            &identifier,
+                            abi,
+                            executor,
+                            deploy_code.clone(),
+                            libs,
+                            (&filter, &test_options),
+                        )
+                        .ok()
+                        .filter(|result| !result.is_empty())
+                        .map(|result| (identifier.clone(), result));
 
                     tracing::trace!(contract= ?identifier, "executed all tests in contract");
-                    Ok((identifier, result))
-                })
-                .filter_map(Result::<_>::ok)
-                .filter(|(_, results)| !results.is_empty())
-                .map_with(stream_result, |stream_result, (name, result)| {
                     if let Some(stream_result) = stream_result.as_ref() {
-                        stream_result.send((name.clone(), result.clone())).unwrap();
+                        stream_result.lock().unwrap().send(index, result.clone());
                     }
-                    (name, result)
+                    result
                 })
+                .flatten()
                 .collect::<BTreeMap<_, _>>()
         ;
 
         Ok(results)
     }
 
-    // The _name field is unused because we only want it for tracing
     #[tracing::instrument(
         name = "contract",
         skip_all,
         err,
-        fields(name = %_name)
+        fields(name = %name)
     )]
     fn run_tests(
         &self,
-        _name: &str,
+        name: &str,
         contract: &Abi,
         executor: Executor,
         deploy_code: Bytes,
         libs: &[Bytes],
-        (filter, test_options): (&impl TestFilter, TestOptions),
+        (filter, test_options): (&impl TestFilter, &TestOptions),
     ) -> Result<SuiteResult> {
         let runner = ContractRunner::new(
+            name,
             executor,
             contract,
             deploy_code,
@@ -197,11 +349,128 @@ impl MultiContractRunner {
             self.sender,
             self.errors.as_ref(),
             libs,
-        );
+        )
+        .with_test_stream(self.test_stream.as_ref());
         runner.run_tests(filter, test_options, Some(&self.known_contracts))
     }
 }
 
+/// A shard of the test functions, for splitting a test suite across multiple runs, e.g. parallel
+/// CI jobs.
+///
+/// Parsed from `<index>/<count>`, where `index` is 1-based.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shard {
+    /// The 1-based index of this shard
+    pub index: usize,
+    /// The total number of shards
+    pub count: usize,
+}
+
+impl Shard {
+    /// Returns whether the test at `position` in the sorted list of all tests belongs to this
+    /// shard
+    pub fn contains(&self, position: usize) -> bool {
+        position % self.count == self.index - 1
+    }
+}
+
+impl FromStr for Shard {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (index, count) = s
+            .split_once('/')
+            .ok_or_else(|| format!("Invalid shard `{s}`, expected `<index>/<count>`"))?;
+        let index =
+            index.trim().parse::<usize>().map_err(|err| format!("Invalid shard index: {err}"))?;
+        let count =
+            count.trim().parse::<usize>().map_err(|err| format!("Invalid shard count: {err}"))?;
+        if count == 0 {
+            return Err("The shard count must be at least 1".to_string())
+        }
+        if index == 0 || index > count {
+            return Err(format!("The shard index must be between 1 and {count}, got {index}"))
+        }
+        Ok(Self { index, count })
+    }
+}
+
+impl fmt::Display for Shard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.index, self.count)
+    }
+}
+
+/// Wraps a [TestFilter] to only match the test functions of a shard.
+///
+/// If no shard tests are set, it matches the same tests as the wrapped filter.
+struct ShardFilter<'a, F> {
+    filter: &'a F,
+    /// The signatures of the selected test functions of the contract
+    tests: Option<&'a BTreeSet<String>>,
+}
+
+impl<'a, F> ShardFilter<'a, F> {
+    fn new(filter: &'a F, tests: Option<&'a BTreeSet<String>>) -> Self {
+        Self { filter, tests }
+    }
+}
+
+impl<'a, F: TestFilter> TestFilter for ShardFilter<'a, F> {
+    fn matches_test(&self, test_name: impl AsRef<str>) -> bool {
+        let test_name = test_name.as_ref();
+        self.filter.matches_test(test_name) &&
+            self.tests.map_or(true, |tests| tests.contains(test_name))
+    }
+
+    fn matches_contract(&self, contract_name: impl AsRef<str>) -> bool {
+        self.filter.matches_contract(contract_name)
+    }
+
+    fn matches_path(&self, path: impl AsRef<str>) -> bool {
+        self.filter.matches_path(path)
+    }
+}
+
+/// Streams the results of the test contracts as they complete.
+///
+/// If ordered, results are sent in the order their contracts were scheduled in, holding back the
+/// ones that complete before a contract scheduled earlier.
+struct ResultStream {
+    sender: Sender<(String, SuiteResult)>,
+    ordered: bool,
+    /// The index of the next result to send, if ordered
+    next: usize,
+    /// Results that completed before the next result to send, by index
+    pending: BTreeMap<usize, Option<(String, SuiteResult)>>,
+}
+
+impl ResultStream {
+    fn new(sender: Sender<(String, SuiteResult)>, ordered: bool) -> Self {
+        Self { sender, ordered, next: 0, pending: Default::default() }
+    }
+
+    /// Sends the `result` of the contract scheduled at `index`, `None` if the contract has no
+    /// results.
+    fn send(&mut self, index: usize, result: Option<(String, SuiteResult)>) {
+        if !self.ordered {
+            if let Some(result) = result {
+                self.sender.send(result).unwrap();
+            }
+            return
+        }
+
+        self.pending.insert(index, result);
+        while let Some(result) = self.pending.remove(&self.next) {
+            self.next += 1;
+            if let Some(result) = result {
+                self.sender.send(result).unwrap();
+            }
+        }
+    }
+}
+
 /// Builder used for instantiating the multi-contract runner
 #[derive(Debug, Default)]
 pub struct MultiContractRunnerBuilder {
@@ -220,6 +489,15 @@ pub struct MultiContractRunnerBuilder {
     pub coverage: bool,
     /// Settings related to fuzz and/or invariant tests
     pub test_options: Option<TestOptions>,
+    /// Whether to run and report the test contracts sorted by source path and name
+    pub sorted: bool,
+    /// Identifiers (`path:name`) or names of the test contracts to run and report first, in this
+    /// order
+    pub test_order: Vec<String>,
+    /// The shard of the test functions to run
+    pub shard: Option<Shard>,
+    /// The only tests to run by contract identifier
+    pub only_tests: Option<BTreeMap<String, BTreeSet<String>>>,
 }
 
 impl MultiContractRunnerBuilder {
@@ -319,6 +597,11 @@ impl MultiContractRunnerBuilder {
             cheats_config: self.cheats_config.unwrap_or_default(),
             coverage: self.coverage,
             test_options: self.test_options.unwrap_or_default(),
+            sorted: self.sorted,
+            test_order: self.test_order,
+            shard: self.shard,
+            only_tests: self.only_tests,
+            test_stream: None,
         })
     }
 
@@ -363,4 +646,38 @@ impl MultiContractRunnerBuilder {
         self.coverage = enable;
         self
     }
+    /// Runs and reports the test contracts sorted by source path and name, so that identical runs
+    /// produce identical output.
+    ///
+    /// This does not affect how many contracts are run in parallel.
+    #[must_use]
+    pub fn sorted(mut self, sorted: bool) -> Self {
+        self.sorted = sorted;
+        self
+    }
+
+    /// Runs and reports the given test contracts, identified by `path:name` or name, first and in
+    /// this order. All other contracts follow sorted by source path and name.
+    #[must_use]
+    pub fn with_test_order(mut self, test_order: Vec<String>) -> Self {
+        self.test_order = test_order;
+        self
+    }
+
+    /// Only runs the test functions in the given shard, see [Shard].
+    #[must_use]
+    pub fn with_shard(mut self, shard: Option<Shard>) -> Self {
+        self.shard = shard;
+        self
+    }
+
+    /// Only runs the given test functions, by contract identifier.
+    #[must_use]
+    pub fn with_only_tests(
+        mut self,
+        only_tests: Option<BTreeMap<String, BTreeSet<String>>>,
+    ) -> Self {
+        self.only_tests = only_tests;
+        self
+    }
 }
@@ -1,14 +1,18 @@
 //! test outcomes
 
 use crate::Address;
-use ethers::prelude::Log;
+use ethers::{prelude::Log, types::U256};
 use foundry_evm::{
     coverage::HitMaps,
-    fuzz::{CounterExample, FuzzedCases},
+    fuzz::{CounterExample, FuzzAbortError, FuzzedCases},
     trace::{CallTraceArena, TraceKind},
 };
 use serde::{Deserialize, Serialize};
-use std::{collections::BTreeMap, fmt, time::Duration};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Write},
+    time::Duration,
+};
 
 /// Results and duration for a set of tests included in the same test contract
 #[derive(Debug, Clone, Serialize)]
@@ -68,9 +72,16 @@ pub struct TestResult {
     /// still be successful (i.e self.success == true) when it's expected to fail.
     pub reason: Option<String>,
 
+    /// Why the fuzzer gave up on a fuzz test before running all of its cases, if it did
+    pub fuzz_abort: Option<FuzzAbortError>,
+
     /// Minimal reproduction test case for failing test
     pub counterexample: Option<CounterExample>,
 
+    /// The fuzz seed of the run that produced a failing fuzz or invariant test, which can be used
+    /// to reproduce the failure
+    pub counterexample_seed: Option<U256>,
+
     /// Any captured & parsed as strings logs along the test's execution which should
     /// be printed to the user.
     pub logs: Vec<Log>,
@@ -78,6 +89,9 @@ pub struct TestResult {
     /// What kind of test this was
     pub kind: TestKind,
 
+    /// How long it took to run the test
+    pub duration: Duration,
+
     /// Traces
     pub traces: Vec<(TraceKind, CallTraceArena)>,
 
@@ -89,6 +103,16 @@ pub struct TestResult {
     pub labeled_addresses: BTreeMap<Address, String>,
 }
 
+/// The result of a single test, streamed as soon as the test completed
+#[derive(Clone, Debug)]
+pub struct FinishedTest {
+    /// The identifier of the test contract
+    pub contract: String,
+    /// The test, as keyed in [`SuiteResult::test_results`]
+    pub test: String,
+    pub result: TestResult,
+}
+
 impl TestResult {
     /// Returns `true` if this is the result of a fuzz test
     pub fn is_fuzz(&self) -> bool {
@@ -96,6 +120,104 @@ impl TestResult {
     }
 }
 
+/// Returns the results of all test contracts as a JUnit XML document.
+///
+/// Every test contract is a `<testsuite>` and every test a `<testcase>`, timed by the duration
+/// the runner measured. Failing tests contain a `<failure>` with the revert reason, and the
+/// counterexample and fuzz seed of failing fuzz and invariant tests.
+pub fn junit_xml(results: &BTreeMap<String, SuiteResult>) -> String {
+    let tests: usize = results.values().map(|suite| suite.len()).sum();
+    let failures: usize = results.values().map(|suite| suite.failures().count()).sum();
+    let time: Duration = results.values().map(|suite| suite.duration).sum();
+
+    let mut xml = String::new();
+    writeln!(xml, r#"<?xml version="1.0" encoding="UTF-8"?>"#).unwrap();
+    writeln!(
+        xml,
+        r#"<testsuites name="forge" tests="{tests}" failures="{failures}" time="{}">"#,
+        junit_time(time)
+    )
+    .unwrap();
+    for (contract, suite) in results {
+        let contract = xml_escape(contract);
+        writeln!(
+            xml,
+            r#"  <testsuite name="{contract}" tests="{}" failures="{}" time="{}">"#,
+            suite.len(),
+            suite.failures().count(),
+            junit_time(suite.duration)
+        )
+        .unwrap();
+        for (name, result) in suite.tests() {
+            write!(
+                xml,
+                r#"    <testcase name="{}" classname="{contract}" time="{}""#,
+                xml_escape(name),
+                junit_time(result.duration)
+            )
+            .unwrap();
+            if result.success {
+                writeln!(xml, "/>").unwrap();
+                continue
+            }
+
+            let message = result.reason.as_deref().unwrap_or("Assertion failed.");
+            let mut body = format!("Reason: {message}");
+            match &result.counterexample {
+                Some(CounterExample::Single(counterexample)) => {
+                    write!(body, "\nCounterexample: {counterexample}").unwrap();
+                }
+                Some(CounterExample::Sequence(sequence)) => {
+                    write!(body, "\nSequence:").unwrap();
+                    for call in sequence {
+                        write!(body, "\n  {call}").unwrap();
+                    }
+                }
+                None => {}
+            }
+            if let Some(seed) = result.counterexample_seed {
+                write!(body, "\nFuzz seed: {seed:#x}").unwrap();
+            }
+            writeln!(xml, ">").unwrap();
+            writeln!(
+                xml,
+                r#"      <failure message="{}">{}</failure>"#,
+                xml_escape(message),
+                xml_escape(&body)
+            )
+            .unwrap();
+            writeln!(xml, "    </testcase>").unwrap();
+        }
+        writeln!(xml, "  </testsuite>").unwrap();
+    }
+    writeln!(xml, "</testsuites>").unwrap();
+    xml
+}
+
+/// Formats a duration in seconds, as JUnit expects it
+fn junit_time(duration: Duration) -> String {
+    format!("{:.3}", duration.as_secs_f64())
+}
+
+/// Escapes the characters of `s` that are not allowed in XML text and attribute values
+fn xml_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\n' | '\t' | '\r' => escaped.push(c),
+            // control characters are not allowed in XML 1.0
+            c if c.is_control() => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
 /// Data report by a test.
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum TestKindReport {
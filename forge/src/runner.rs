@@ -1,6 +1,6 @@
 use crate::{
-    result::{SuiteResult, TestKind, TestResult, TestSetup},
-    TestFilter, TestOptions,
+    result::{FinishedTest, SuiteResult, TestKind, TestResult, TestSetup},
+    sanitize_persist_key, TestFilter, TestOptions,
 };
 use ethers::{
     abi::{Abi, Function},
@@ -9,27 +9,35 @@ use ethers::{
 use eyre::Result;
 use foundry_common::{
     contracts::{ContractsByAddress, ContractsByArtifact},
-    TestFunctionExt,
+    fs, TestFunctionExt,
 };
 use foundry_evm::{
     executor::{CallResult, DeployResult, EvmError, Executor},
     fuzz::{
         invariant::{
-            InvariantContract, InvariantExecutor, InvariantFuzzTestResult, InvariantTestOptions,
+            replay_test_source, InvariantContract, InvariantExecutor, InvariantFuzzTestResult,
+            InvariantTestOptions,
         },
-        FuzzedExecutor,
+        BaseCounterExample, CounterExample, FuzzedExecutor,
     },
     trace::{load_contracts, TraceKind},
     CALLER,
 };
 use proptest::test_runner::{TestError, TestRunner};
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
-use std::{collections::BTreeMap, time::Instant};
+use std::{
+    collections::BTreeMap,
+    path::Path,
+    sync::{mpsc::Sender, Mutex},
+    time::Instant,
+};
 use tracing::{error, trace};
 
 /// A type that executes all tests of a contract
 #[derive(Debug, Clone)]
 pub struct ContractRunner<'a> {
+    /// The name of the test contract
+    pub name: &'a str,
     /// The executor used by the runner.
     pub executor: Executor,
 
@@ -46,11 +54,14 @@ pub struct ContractRunner<'a> {
     pub initial_balance: U256,
     /// The address which will be used as the `from` field in all EVM calls
     pub sender: Address,
+    /// Receives the result of every test as soon as it completed, if set
+    pub test_stream: Option<&'a Mutex<Sender<FinishedTest>>>,
 }
 
 impl<'a> ContractRunner<'a> {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
+        name: &'a str,
         executor: Executor,
         contract: &'a Abi,
         code: Bytes,
@@ -60,6 +71,7 @@ impl<'a> ContractRunner<'a> {
         predeploy_libs: &'a [Bytes],
     ) -> Self {
         Self {
+            name,
             executor,
             contract,
             code,
@@ -67,8 +79,15 @@ impl<'a> ContractRunner<'a> {
             sender: sender.unwrap_or_default(),
             errors,
             predeploy_libs,
+            test_stream: None,
         }
     }
+
+    /// Sends the result of every test to `stream` as soon as the test completed
+    pub fn with_test_stream(mut self, stream: Option<&'a Mutex<Sender<FinishedTest>>>) -> Self {
+        self.test_stream = stream;
+        self
+    }
 }
 
 impl<'a> ContractRunner<'a> {
@@ -183,7 +202,7 @@ impl<'a> ContractRunner<'a> {
     pub fn run_tests(
         mut self,
         filter: &impl TestFilter,
-        test_options: TestOptions,
+        test_options: &TestOptions,
         known_contracts: Option<&ContractsByArtifact>,
     ) -> Result<SuiteResult> {
         tracing::info!("starting tests");
@@ -209,18 +228,22 @@ impl<'a> ContractRunner<'a> {
         if setup_fns.len() > 1 {
             return Ok(SuiteResult::new(
                 start.elapsed(),
-                [(
+                [self.finish_test(
                     "setUp()".to_string(),
                     TestResult {
                         success: false,
                         reason: Some("Multiple setUp functions".to_string()),
+                        fuzz_abort: None,
                         counterexample: None,
+                        counterexample_seed: None,
                         logs: vec![],
                         kind: TestKind::Standard(0),
+                        duration: start.elapsed(),
                         traces: vec![],
                         coverage: None,
                         labeled_addresses: BTreeMap::new(),
                     },
+                    test_options,
                 )]
                 .into(),
                 warnings,
@@ -241,24 +264,31 @@ impl<'a> ContractRunner<'a> {
             // The setup failed, so we return a single test result for `setUp`
             return Ok(SuiteResult::new(
                 start.elapsed(),
-                [(
+                [self.finish_test(
                     "setUp()".to_string(),
                     TestResult {
                         success: false,
                         reason: setup.reason,
+                        fuzz_abort: None,
                         counterexample: None,
+                        counterexample_seed: None,
                         logs: setup.logs,
                         kind: TestKind::Standard(0),
+                        duration: start.elapsed(),
                         traces: setup.traces,
                         coverage: None,
                         labeled_addresses: setup.labeled_addresses,
                     },
+                    test_options,
                 )]
                 .into(),
                 warnings,
             ))
         }
 
+        // Pin the fuzz seed for this run, so failing fuzz and invariant tests can be reproduced
+        let test_options = &test_options.with_pinned_fuzz_seed();
+
         // Collect valid test functions
         let tests: Vec<_> = self
             .contract
@@ -278,13 +308,14 @@ impl<'a> ContractRunner<'a> {
                             self.run_fuzz_test(
                                 func,
                                 *should_fail,
-                                test_options.fuzzer(),
+                                test_options.test_fuzzer(self.name, &func.name),
+                                test_options,
                                 setup.clone(),
                             )
                         } else {
                             self.clone().run_test(func, *should_fail, setup.clone())
                         }
-                        .map(|result| Ok((func.signature(), result)))
+                        .map(|result| Ok(self.finish_test(func.signature(), result, test_options)))
                     })
                     .collect::<Result<BTreeMap<_, _>>>()?,
             );
@@ -313,7 +344,9 @@ impl<'a> ContractRunner<'a> {
             results.into_iter().zip(functions.iter()).for_each(|(result, function)| {
                 match result.kind {
                     TestKind::Invariant(ref _cases, _) => {
-                        test_results.insert(function.name.clone(), result);
+                        let (name, result) =
+                            self.finish_test(function.name.clone(), result, test_options);
+                        test_results.insert(name, result);
                     }
                     _ => unreachable!(),
                 }
@@ -334,6 +367,31 @@ impl<'a> ContractRunner<'a> {
         Ok(SuiteResult::new(duration, test_results, warnings))
     }
 
+    /// Completes the `result` of the `test` and sends it to the test stream, if any
+    fn finish_test(
+        &self,
+        test: String,
+        mut result: TestResult,
+        test_options: &TestOptions,
+    ) -> (String, TestResult) {
+        // Record the seed that produced the failing fuzz and invariant tests
+        if !result.success && !matches!(result.kind, TestKind::Standard(_)) {
+            result.counterexample_seed = test_options.fuzz_seed;
+        }
+
+        if let Some(stream) = self.test_stream {
+            let finished = FinishedTest {
+                contract: self.name.to_string(),
+                test: test.clone(),
+                result: result.clone(),
+            };
+            // the receiving end may have hung up, which must not abort the run
+            let _ = stream.lock().unwrap().send(finished);
+        }
+
+        (test, result)
+    }
+
     /// Runs a single test
     ///
     /// Calls the given functions and returns the `TestResult`.
@@ -405,8 +463,9 @@ impl<'a> ContractRunner<'a> {
         );
 
         // Record test execution time
+        let duration = start.elapsed();
         tracing::debug!(
-            duration = ?start.elapsed(),
+            ?duration,
             %success,
             %gas
         );
@@ -414,9 +473,12 @@ impl<'a> ContractRunner<'a> {
         Ok(TestResult {
             success,
             reason,
+            fuzz_abort: None,
             counterexample: None,
+            counterexample_seed: None,
             logs,
             kind: TestKind::Standard(gas.overflowing_sub(stipend).0),
+            duration,
             traces,
             coverage,
             labeled_addresses,
@@ -428,7 +490,7 @@ impl<'a> ContractRunner<'a> {
         &mut self,
         runner: TestRunner,
         setup: TestSetup,
-        test_options: TestOptions,
+        test_options: &TestOptions,
         functions: Vec<&Function>,
         known_contracts: Option<&ContractsByArtifact>,
         identified_contracts: ContractsByAddress,
@@ -438,6 +500,8 @@ impl<'a> ContractRunner<'a> {
         let project_contracts = known_contracts.unwrap_or(&empty);
         let TestSetup { address, logs, traces, labeled_addresses, .. } = setup;
 
+        // All invariants are checked in the same runs, so they share the duration of the runs
+        let start = Instant::now();
         let mut evm = InvariantExecutor::new(
             &mut self.executor,
             runner,
@@ -454,11 +518,18 @@ impl<'a> ContractRunner<'a> {
                 depth: test_options.invariant_depth,
                 fail_on_revert: test_options.invariant_fail_on_revert,
                 call_override: test_options.invariant_call_override,
+                selector_weights: test_options.invariant_selector_weights.clone(),
+                senders: test_options.invariant_senders.clone(),
+                corpus_dir: test_options
+                    .invariant_corpus_dir
+                    .as_ref()
+                    .map(|dir| dir.join(sanitize_persist_key(self.name))),
             },
         )? {
+            let duration = start.elapsed();
             let results = invariants
                 .iter()
-                .map(|(_, test_error)| {
+                .map(|(invariant, test_error)| {
                     let mut counterexample = None;
                     let mut logs = logs.clone();
                     let mut traces = traces.clone();
@@ -471,24 +542,39 @@ impl<'a> ContractRunner<'a> {
                                 identified_contracts.clone(),
                                 &mut logs,
                                 &mut traces,
+                                test_options.invariant_fail_on_revert,
                             );
                         }
                     }
 
-                    TestResult {
+                    if let (Some(dir), Some(CounterExample::Sequence(sequence))) =
+                        (&test_options.invariant_replay_dir, &counterexample)
+                    {
+                        self.write_invariant_replay(
+                            dir,
+                            invariant,
+                            sequence,
+                            test_options.invariant_fail_on_revert,
+                        )?;
+                    }
+
+                    Ok(TestResult {
                         success: test_error.is_none(),
                         reason: test_error.as_ref().and_then(|err| {
                             (!err.revert_reason.is_empty()).then(|| err.revert_reason.clone())
                         }),
+                        fuzz_abort: None,
                         counterexample,
+                        counterexample_seed: None,
                         logs,
                         kind: TestKind::Invariant(cases.clone(), reverts),
+                        duration,
                         coverage: None, // todo?
                         traces,
                         labeled_addresses: labeled_addresses.clone(),
-                    }
+                    })
                 })
-                .collect();
+                .collect::<Result<_>>()?;
 
             Ok(results)
         } else {
@@ -496,24 +582,45 @@ impl<'a> ContractRunner<'a> {
         }
     }
 
+    /// Writes a Solidity test replaying the `sequence` that broke `invariant` to `dir`.
+    fn write_invariant_replay(
+        &self,
+        dir: &Path,
+        invariant: &str,
+        sequence: &[BaseCounterExample],
+        fail_on_revert: bool,
+    ) -> Result<()> {
+        let source = replay_test_source(self.name, invariant, sequence, fail_on_revert);
+        let contract_name = self.name.rsplit(':').next().unwrap_or(self.name);
+        let path = dir.join(format!("{contract_name}.{invariant}.t.sol"));
+        fs::create_dir_all(dir)?;
+        fs::write(&path, source)?;
+        trace!(target: "forge::test::fuzz", ?path, "wrote invariant replay test");
+        Ok(())
+    }
+
     #[tracing::instrument(name = "fuzz-test", skip_all, fields(name = %func.signature(), %should_fail))]
     pub fn run_fuzz_test(
         &self,
         func: &Function,
         should_fail: bool,
         runner: TestRunner,
+        test_options: &TestOptions,
         setup: TestSetup,
     ) -> Result<TestResult> {
         let TestSetup { address, mut logs, mut traces, mut labeled_addresses, .. } = setup;
 
         // Run fuzz test
         let start = Instant::now();
-        let mut result = FuzzedExecutor::new(&self.executor, runner, self.sender).fuzz(
-            func,
-            address,
-            should_fail,
-            self.errors,
-        );
+        let mut result = FuzzedExecutor::new(&self.executor, runner, self.sender)
+            .with_timeout(test_options.fuzz_timeout)
+            .with_param_config(test_options.fuzz_param_config())
+            .with_dictionary_weight(if test_options.fuzz_dictionary {
+                test_options.fuzz_dictionary_weight
+            } else {
+                0
+            })
+            .fuzz(func, address, should_fail, self.errors);
 
         // Record logs, labels and traces
         logs.append(&mut result.logs);
@@ -521,17 +628,21 @@ impl<'a> ContractRunner<'a> {
         traces.extend(result.traces.map(|traces| (TraceKind::Execution, traces)).into_iter());
 
         // Record test execution time
+        let duration = start.elapsed();
         tracing::debug!(
-            duration = ?start.elapsed(),
+            ?duration,
             success = %result.success
         );
 
         Ok(TestResult {
             success: result.success,
             reason: result.reason,
+            fuzz_abort: result.abort,
             counterexample: result.counterexample,
+            counterexample_seed: None,
             logs,
             kind: TestKind::Fuzz(result.cases),
+            duration,
             traces,
             // TODO: Maybe support coverage for fuzz tests
             coverage: None,
@@ -15,7 +15,7 @@ fn test_cheats_local() {
         .test(
             &Filter::new(".*", ".*", &format!(".*cheats{}[^Fork]", RE_PATH_SEPARATOR)),
             None,
-            TEST_OPTS,
+            TEST_OPTS.clone(),
         )
         .unwrap();
     assert!(!suite_result.is_empty());
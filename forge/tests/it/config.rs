@@ -1,21 +1,36 @@
 //! Test setup
 
 use crate::test_helpers::{COMPILED, COMPILED_WITH_LIBS, EVM_OPTS, LIBS_PROJECT, PROJECT};
+use ethers::prelude::Lazy;
 use forge::{result::SuiteResult, MultiContractRunner, MultiContractRunnerBuilder, TestOptions};
 use foundry_config::{Config, RpcEndpoint, RpcEndpoints};
 use foundry_evm::{decode::decode_console_logs, executor::inspector::CheatsConfig};
 use std::collections::BTreeMap;
 
-pub static TEST_OPTS: TestOptions = TestOptions {
+pub static TEST_OPTS: Lazy<TestOptions> = Lazy::new(|| TestOptions {
     fuzz_runs: 256,
     fuzz_max_local_rejects: 1024,
     fuzz_max_global_rejects: 65536,
+    fuzz_dictionary: true,
+    fuzz_dictionary_weight: 40,
     fuzz_seed: None,
+    fuzz_rng_algorithm: None,
+    fuzz_failure_persist_dir: None,
+    fuzz_timeout: None,
+    fuzz_addresses: vec![],
+    fuzz_addresses_deployed: false,
+    fuzz_uint256_min: None,
+    fuzz_uint256_max: None,
+    fuzz_exclude_zero: false,
     invariant_runs: 256,
     invariant_depth: 15,
     invariant_fail_on_revert: false,
     invariant_call_override: false,
-};
+    invariant_replay_dir: None,
+    invariant_corpus_dir: None,
+    invariant_selector_weights: Default::default(),
+    invariant_senders: vec![],
+});
 
 /// Builds a base runner
 pub fn base_runner() -> MultiContractRunnerBuilder {
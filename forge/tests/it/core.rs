@@ -1,14 +1,14 @@
 //! forge tests for core functionality
 
 use crate::{config::*, test_helpers::filter::Filter};
-use forge::result::SuiteResult;
+use forge::{result::SuiteResult, Shard};
 use foundry_evm::trace::TraceKind;
 use std::{collections::BTreeMap, env};
 
 #[test]
 fn test_core() {
     let mut runner = runner();
-    let results = runner.test(&Filter::new(".*", ".*", ".*core"), None, TEST_OPTS).unwrap();
+    let results = runner.test(&Filter::new(".*", ".*", ".*core"), None, TEST_OPTS.clone()).unwrap();
 
     assert_multiple(
         &results,
@@ -74,7 +74,7 @@ fn test_core() {
 #[test]
 fn test_logs() {
     let mut runner = runner();
-    let results = runner.test(&Filter::new(".*", ".*", ".*logs"), None, TEST_OPTS).unwrap();
+    let results = runner.test(&Filter::new(".*", ".*", ".*logs"), None, TEST_OPTS.clone()).unwrap();
 
     assert_multiple(
         &results,
@@ -637,7 +637,7 @@ fn test_env_vars() {
 
     // test `setEnv` first, and confirm that it can correctly set environment variables,
     // so that we can use it in subsequent `env*` tests
-    runner.test(&Filter::new("testSetEnv", ".*", ".*"), None, TEST_OPTS).unwrap();
+    runner.test(&Filter::new("testSetEnv", ".*", ".*"), None, TEST_OPTS.clone()).unwrap();
     let env_var_key = "_foundryCheatcodeSetEnvTestKey";
     let env_var_val = "_foundryCheatcodeSetEnvTestVal";
     let res = env::var(env_var_key);
@@ -654,7 +654,11 @@ Reason: `setEnv` failed to set an environment variable `{}={}`",
 fn test_doesnt_run_abstract_contract() {
     let mut runner = runner();
     let results = runner
-        .test(&Filter::new(".*", ".*", ".*Abstract.t.sol".to_string().as_str()), None, TEST_OPTS)
+        .test(
+            &Filter::new(".*", ".*", ".*Abstract.t.sol".to_string().as_str()),
+            None,
+            TEST_OPTS.clone(),
+        )
         .unwrap();
     assert!(results.get("core/Abstract.t.sol:AbstractTestBase").is_none());
     assert!(results.get("core/Abstract.t.sol:AbstractTest").is_some());
@@ -663,7 +667,8 @@ fn test_doesnt_run_abstract_contract() {
 #[test]
 fn test_trace() {
     let mut runner = tracing_runner();
-    let suite_result = runner.test(&Filter::new(".*", ".*", ".*trace"), None, TEST_OPTS).unwrap();
+    let suite_result =
+        runner.test(&Filter::new(".*", ".*", ".*trace"), None, TEST_OPTS.clone()).unwrap();
 
     // TODO: This trace test is very basic - it is probably a good candidate for snapshot
     // testing.
@@ -691,3 +696,91 @@ fn test_trace() {
         }
     }
 }
+
+#[test]
+fn test_ordered_result_stream() {
+    let mut runner = runner();
+    runner.sorted = true;
+    runner.test_order =
+        vec!["SetupConsistencyCheck".to_string(), "core/DSStyle.t.sol:DSStyleTest".to_string()];
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    runner.test(&Filter::new(".*", ".*", ".*core"), Some(tx), TEST_OPTS.clone()).unwrap();
+    let order = rx.into_iter().map(|(name, _)| name).collect::<Vec<_>>();
+
+    assert_eq!(
+        order[..2],
+        [
+            "core/SetupConsistency.t.sol:SetupConsistencyCheck".to_string(),
+            "core/DSStyle.t.sol:DSStyleTest".to_string()
+        ]
+    );
+    let mut rest = order[2..].to_vec();
+    rest.sort();
+    assert_eq!(order[2..], rest);
+}
+
+#[test]
+fn test_shards_partition_tests() {
+    let filter = Filter::new(".*", ".*", ".*core");
+    let tests = |results: BTreeMap<String, SuiteResult>| {
+        results
+            .into_iter()
+            .flat_map(|(contract, suite)| {
+                suite
+                    .test_results
+                    .into_keys()
+                    .filter(|test| test != "setUp()")
+                    .map(move |test| format!("{contract}:{test}"))
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let mut all = tests(runner().test(&filter, None, TEST_OPTS.clone()).unwrap());
+
+    let mut sharded = Vec::new();
+    for index in 1..=3 {
+        let mut runner = runner();
+        runner.shard = Some(format!("{index}/3").parse().unwrap());
+        let shard = tests(runner.test(&filter, None, TEST_OPTS.clone()).unwrap());
+        assert!(!shard.is_empty(), "shard {index} runs no tests");
+        sharded.extend(shard);
+    }
+    sharded.sort();
+    all.sort();
+    assert_eq!(sharded, all);
+
+    assert!("0/3".parse::<Shard>().is_err());
+    assert!("4/3".parse::<Shard>().is_err());
+    assert!("1/0".parse::<Shard>().is_err());
+    assert!("1".parse::<Shard>().is_err());
+}
+
+#[test]
+fn test_streams_every_test() {
+    let (tx, rx) = std::sync::mpsc::channel();
+    let mut runner = runner().with_test_stream(tx);
+    let results = runner.test(&Filter::new(".*", ".*", ".*core"), None, TEST_OPTS.clone()).unwrap();
+    // dropping the runner closes the stream
+    drop(runner);
+
+    let mut streamed = rx
+        .into_iter()
+        .map(|finished| {
+            (format!("{}:{}", finished.contract, finished.test), finished.result.success)
+        })
+        .collect::<Vec<_>>();
+    let mut expected = results
+        .into_iter()
+        .flat_map(|(contract, suite)| {
+            suite
+                .test_results
+                .into_iter()
+                .map(move |(test, result)| (format!("{contract}:{test}"), result.success))
+        })
+        .collect::<Vec<_>>();
+    streamed.sort();
+    expected.sort();
+    assert!(!streamed.is_empty());
+    assert_eq!(streamed, expected);
+}
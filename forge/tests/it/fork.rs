@@ -19,7 +19,7 @@ fn test_cheats_fork_revert() {
                 &format!(".*cheats{}Fork", RE_PATH_SEPARATOR),
             ),
             None,
-            TEST_OPTS,
+            TEST_OPTS.clone(),
         )
         .unwrap();
     assert_eq!(suite_result.len(), 1);
@@ -43,7 +43,7 @@ fn test_cheats_fork() {
             &Filter::new(".*", ".*", &format!(".*cheats{}Fork", RE_PATH_SEPARATOR))
                 .exclude_tests(".*Revert"),
             None,
-            TEST_OPTS,
+            TEST_OPTS.clone(),
         )
         .unwrap();
     assert!(!suite_result.is_empty());
@@ -66,7 +66,8 @@ fn test_cheats_fork() {
 fn test_fork() {
     let rpc_url = foundry_utils::rpc::next_http_archive_rpc_endpoint();
     let mut runner = forked_runner(&rpc_url);
-    let suite_result = runner.test(&Filter::new(".*", ".*", ".*fork"), None, TEST_OPTS).unwrap();
+    let suite_result =
+        runner.test(&Filter::new(".*", ".*", ".*fork"), None, TEST_OPTS.clone()).unwrap();
 
     for (_, SuiteResult { test_results, .. }) in suite_result {
         for (test_name, result) in test_results {
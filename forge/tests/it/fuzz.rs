@@ -3,14 +3,17 @@
 use crate::{config::*, test_helpers::filter::Filter};
 use forge::result::SuiteResult;
 
-use foundry_evm::decode::decode_console_logs;
+use foundry_evm::{decode::decode_console_logs, fuzz::FuzzAbortError};
+use std::time::Duration;
 
 #[test]
 fn test_fuzz() {
     let mut runner = runner();
 
-    let suite_result =
-        runner.test(&Filter::new(".*", ".*", ".*fuzz/[^invariant]"), None, TEST_OPTS).unwrap();
+    // the fixtures in the subdirectories need their own options and are tested separately
+    let filter = Filter::new(".*", ".*", ".*fuzz/")
+        .exclude_paths("fuzz/(invariant|ranges|rejects|timeout)/");
+    let suite_result = runner.test(&filter, None, TEST_OPTS.clone()).unwrap();
 
     assert!(!suite_result.is_empty());
 
@@ -40,3 +43,129 @@ fn test_fuzz() {
         }
     }
 }
+
+#[test]
+fn test_fuzz_failure_persistence() {
+    let tmp = tempfile::tempdir().unwrap();
+    let persist_dir = tmp.path().join("failures");
+
+    let mut runner = runner();
+
+    let mut opts = TEST_OPTS.clone();
+    opts.fuzz_failure_persist_dir = Some(persist_dir.clone());
+    runner.test_options = opts.clone();
+
+    let suite_result = runner
+        .test(&Filter::new("testFailFuzz", "FuzzTest", ".*fuzz/Fuzz.t.sol"), None, opts)
+        .unwrap();
+
+    for (_, SuiteResult { test_results, .. }) in suite_result {
+        for (_, result) in test_results {
+            assert!(!result.success);
+        }
+    }
+
+    // the failing seed is persisted together with its RNG algorithm (`cc` for ChaCha)
+    let persisted =
+        std::fs::read_to_string(persist_dir.join("fuzz_Fuzz.t.sol_FuzzTest").join("testFailFuzz"))
+            .unwrap();
+    assert!(persisted.lines().any(|line| line.starts_with("cc ")));
+}
+
+#[test]
+fn test_fuzz_counterexample_seed_replays() {
+    let filter = Filter::new("testFailFuzz", "FuzzTest", ".*fuzz/Fuzz.t.sol");
+
+    let (_, suite_result) =
+        runner().test(&filter, None, TEST_OPTS.clone()).unwrap().into_iter().next().unwrap();
+    let (_, result) = suite_result.test_results.into_iter().next().unwrap();
+    assert!(!result.success);
+    let seed = result.counterexample_seed.expect("failing fuzz test should record its seed");
+
+    // re-running with the recorded seed reproduces the same counterexample
+    let mut opts = TEST_OPTS.clone();
+    opts.fuzz_seed = Some(seed);
+    let (_, suite_result) = runner().test(&filter, None, opts).unwrap().into_iter().next().unwrap();
+    let (_, replayed) = suite_result.test_results.into_iter().next().unwrap();
+    assert!(!replayed.success);
+    assert_eq!(replayed.counterexample_seed, Some(seed));
+    assert_eq!(
+        serde_json::to_string(&replayed.counterexample).unwrap(),
+        serde_json::to_string(&result.counterexample).unwrap()
+    );
+}
+
+#[test]
+fn test_fuzz_timeout() {
+    let mut runner = runner();
+
+    let mut opts = TEST_OPTS.clone();
+    opts.fuzz_timeout = Some(Duration::from_secs(1));
+    runner.test_options = opts.clone();
+
+    let results = runner
+        .test(&Filter::new(".*", ".*", ".*fuzz/timeout/FuzzTimeout.t.sol"), None, opts)
+        .unwrap();
+
+    let (_, suite_result) = results.into_iter().next().unwrap();
+    let (_, result) = suite_result.test_results.into_iter().next().unwrap();
+    assert!(!result.success);
+    assert!(result.reason.unwrap().starts_with("Fuzz test timed out after 1s"));
+    assert!(matches!(result.fuzz_abort, Some(FuzzAbortError::TimedOut { .. })));
+    assert!(result.counterexample.is_none());
+}
+
+#[test]
+fn test_fuzz_too_many_rejects() {
+    let mut runner = runner();
+
+    let mut opts = TEST_OPTS.clone();
+    opts.fuzz_max_global_rejects = 100;
+    runner.test_options = opts.clone();
+
+    let results = runner
+        .test(&Filter::new(".*", ".*", ".*fuzz/rejects/FuzzRejects.t.sol"), None, opts)
+        .unwrap();
+
+    let (_, suite_result) = results.into_iter().next().unwrap();
+    let (_, result) = suite_result.test_results.into_iter().next().unwrap();
+    assert!(!result.success);
+    assert!(result.counterexample.is_none());
+    match result.fuzz_abort {
+        Some(FuzzAbortError::TooManyRejects { rejects, .. }) => assert!(rejects >= 100),
+        abort => panic!("expected the fuzz test to be abandoned for rejects, got {:?}", abort),
+    }
+    assert!(result.reason.unwrap().contains("rejection rate"));
+}
+
+#[test]
+fn test_fuzz_param_config() {
+    let mut runner = runner();
+
+    let mut opts = TEST_OPTS.clone();
+    opts.fuzz_uint256_min = Some(100.into());
+    opts.fuzz_uint256_max = Some(200.into());
+    opts.fuzz_exclude_zero = true;
+    opts.fuzz_addresses_deployed = true;
+    runner.test_options = opts.clone();
+
+    let results = runner
+        .test(&Filter::new(".*", ".*", ".*fuzz/ranges/FuzzParams.t.sol"), None, opts)
+        .unwrap();
+
+    let (_, suite_result) = results.into_iter().next().unwrap();
+    for (test_name, result) in suite_result.test_results {
+        match test_name.as_str() {
+            "testBoundedUint(uint256)" | "testNonZero(uint256,int256,address)" => assert!(
+                result.success,
+                "Test {} did not pass as expected.\nReason: {:?}",
+                test_name, result.reason
+            ),
+            // half of the addresses are picked from the deployed contracts
+            "testMissesTarget(address)" => {
+                assert!(!result.success, "Test {} did not fail as expected", test_name)
+            }
+            _ => panic!("unexpected test {}", test_name),
+        }
+    }
+}
@@ -1,7 +1,7 @@
 //! Tests for invariants
 
 use crate::{config::*, test_helpers::filter::Filter};
-use ethers::types::U256;
+use ethers::types::{Address, U256};
 use forge::fuzz::CounterExample;
 use std::collections::BTreeMap;
 
@@ -9,8 +9,9 @@ use std::collections::BTreeMap;
 fn test_invariant() {
     let mut runner = runner();
 
-    let results =
-        runner.test(&Filter::new(".*", ".*", ".*fuzz/invariant/"), None, TEST_OPTS).unwrap();
+    let results = runner
+        .test(&Filter::new(".*", ".*", ".*fuzz/invariant/"), None, TEST_OPTS.clone())
+        .unwrap();
 
     assert_multiple(
         &results,
@@ -70,9 +71,9 @@ fn test_invariant() {
 fn test_invariant_override() {
     let mut runner = runner();
 
-    let mut opts = TEST_OPTS;
+    let mut opts = TEST_OPTS.clone();
     opts.invariant_call_override = true;
-    runner.test_options = opts;
+    runner.test_options = opts.clone();
 
     let results = runner
         .test(&Filter::new(".*", ".*", ".*fuzz/invariant/InvariantReentrancy.t.sol"), None, opts)
@@ -91,9 +92,9 @@ fn test_invariant_override() {
 fn test_invariant_shrink() {
     let mut runner = runner();
 
-    let mut opts = TEST_OPTS;
+    let mut opts = TEST_OPTS.clone();
     opts.fuzz_seed = Some(U256::from(100u32));
-    runner.test_options = opts;
+    runner.test_options = opts.clone();
 
     let results = runner
         .test(&Filter::new(".*", ".*", ".*fuzz/invariant/InvariantInnerContract.t.sol"), None, opts)
@@ -116,3 +117,106 @@ fn test_invariant_shrink() {
         CounterExample::Sequence(sequence) => assert!(sequence.len() == 2),
     };
 }
+
+#[test]
+fn test_invariant_replay_dir() {
+    let tmp = tempfile::tempdir().unwrap();
+    let replay_dir = tmp.path().join("replay");
+
+    let mut runner = runner();
+
+    let mut opts = TEST_OPTS.clone();
+    opts.invariant_replay_dir = Some(replay_dir.clone());
+    runner.test_options = opts.clone();
+
+    runner
+        .test(&Filter::new(".*", ".*", ".*fuzz/invariant/InvariantTest1.t.sol"), None, opts)
+        .unwrap();
+
+    let source =
+        std::fs::read_to_string(replay_dir.join("InvariantTest.invariant_neverFalse.t.sol"))
+            .expect("a replay test should have been written for the broken invariant.");
+    assert!(source.contains("import \"fuzz/invariant/InvariantTest1.t.sol\";"));
+    assert!(source.contains("contract InvariantTestReplay_invariant_neverFalse is InvariantTest"));
+    assert!(source.contains("abi.encodeWithSignature(\"set0(int256)\""));
+    assert!(source.contains("this.invariant_neverFalse();"));
+}
+
+#[test]
+fn test_invariant_corpus_dir() {
+    let tmp = tempfile::tempdir().unwrap();
+    let corpus_dir = tmp.path().join("corpus");
+
+    let mut runner = runner();
+
+    let mut opts = TEST_OPTS.clone();
+    opts.invariant_corpus_dir = Some(corpus_dir.clone());
+    runner.test_options = opts.clone();
+
+    let filter = Filter::new(".*", ".*", ".*fuzz/invariant/InvariantTest1.t.sol");
+    runner.test(&filter, None, opts.clone()).unwrap();
+
+    let contract_dirs = std::fs::read_dir(&corpus_dir)
+        .expect("the corpus should have been written.")
+        .map(|entry| entry.unwrap().path())
+        .collect::<Vec<_>>();
+    assert_eq!(contract_dirs.len(), 1);
+    let entries = std::fs::read_dir(&contract_dirs[0]).unwrap().count();
+    assert!(entries > 0);
+
+    // the invariant is broken again when the corpus is replayed
+    let results = runner.test(&filter, None, opts).unwrap();
+    assert_multiple(
+        &results,
+        BTreeMap::from([(
+            "fuzz/invariant/InvariantTest1.t.sol:InvariantTest",
+            vec![("invariant_neverFalse", false, Some("false.".into()), None, None)],
+        )]),
+    );
+    assert!(std::fs::read_dir(&contract_dirs[0]).unwrap().count() >= entries);
+}
+
+#[test]
+fn test_invariant_selector_weights() {
+    let mut runner = runner();
+
+    // `set1` is the only function that can break the invariant.
+    let mut opts = TEST_OPTS.clone();
+    opts.invariant_selector_weights =
+        BTreeMap::from([("InvariantBreaker:set1(int256)".to_string(), 0)]);
+    runner.test_options = opts.clone();
+
+    let results = runner
+        .test(&Filter::new(".*", ".*", ".*fuzz/invariant/InvariantTest1.t.sol"), None, opts)
+        .unwrap();
+
+    assert_multiple(
+        &results,
+        BTreeMap::from([(
+            "fuzz/invariant/InvariantTest1.t.sol:InvariantTest",
+            vec![("invariant_neverFalse", true, None, None, None)],
+        )]),
+    );
+}
+
+#[test]
+fn test_invariant_senders_override() {
+    let mut runner = runner();
+
+    // Only `0xdeadbeef`, returned by `targetSenders()`, can break the invariant.
+    let mut opts = TEST_OPTS.clone();
+    opts.invariant_senders = vec![Address::from_low_u64_be(0x1234)];
+    runner.test_options = opts.clone();
+
+    let results = runner
+        .test(&Filter::new(".*", ".*", ".*fuzz/invariant/target/TargetSenders.t.sol"), None, opts)
+        .unwrap();
+
+    assert_multiple(
+        &results,
+        BTreeMap::from([(
+            "fuzz/invariant/target/TargetSenders.t.sol:TargetSenders",
+            vec![("invariantTrueWorld", true, None, None, None)],
+        )]),
+    );
+}
@@ -8,8 +8,9 @@ use foundry_evm::decode::decode_console_logs;
 #[test]
 fn test_issue_2623() {
     let mut runner = runner();
-    let suite_result =
-        runner.test(&Filter::new(".*", ".*", ".*repros/Issue2623"), None, TEST_OPTS).unwrap();
+    let suite_result = runner
+        .test(&Filter::new(".*", ".*", ".*repros/Issue2623"), None, TEST_OPTS.clone())
+        .unwrap();
     assert!(!suite_result.is_empty());
 
     for (_, SuiteResult { test_results, .. }) in suite_result {
@@ -30,8 +31,9 @@ fn test_issue_2623() {
 #[test]
 fn test_issue_2629() {
     let mut runner = runner();
-    let suite_result =
-        runner.test(&Filter::new(".*", ".*", ".*repros/Issue2629"), None, TEST_OPTS).unwrap();
+    let suite_result = runner
+        .test(&Filter::new(".*", ".*", ".*repros/Issue2629"), None, TEST_OPTS.clone())
+        .unwrap();
     assert!(!suite_result.is_empty());
 
     for (_, SuiteResult { test_results, .. }) in suite_result {
@@ -52,8 +54,9 @@ fn test_issue_2629() {
 #[test]
 fn test_issue_2723() {
     let mut runner = runner();
-    let suite_result =
-        runner.test(&Filter::new(".*", ".*", ".*repros/Issue2723"), None, TEST_OPTS).unwrap();
+    let suite_result = runner
+        .test(&Filter::new(".*", ".*", ".*repros/Issue2723"), None, TEST_OPTS.clone())
+        .unwrap();
     assert!(!suite_result.is_empty());
 
     for (_, SuiteResult { test_results, .. }) in suite_result {
@@ -0,0 +1,26 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+
+contract Target {}
+
+contract FuzzParamsTest is DSTest {
+    Target target;
+
+    function setUp() public {
+        target = new Target();
+    }
+
+    function testBoundedUint(uint256 x) public {
+        assertTrue(x >= 100 && x <= 200);
+    }
+
+    function testNonZero(uint256 x, int256 y, address a) public {
+        assertTrue(x != 0 && y != 0 && a != address(0));
+    }
+
+    function testMissesTarget(address a) public {
+        assertTrue(a != address(target));
+    }
+}
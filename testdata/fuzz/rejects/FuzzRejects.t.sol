@@ -0,0 +1,15 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+import "../../cheats/Cheats.sol";
+
+contract FuzzRejectsTest is DSTest {
+    Cheats constant cheats = Cheats(HEVM_ADDRESS);
+
+    function testAssumeTooRestrictive(uint256 x) public {
+        // Discards nearly every input, so the fuzzer gives up on the test
+        cheats.assume(x == 42);
+        assertEq(x, 42);
+    }
+}
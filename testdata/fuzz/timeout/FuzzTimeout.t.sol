@@ -0,0 +1,16 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+
+contract FuzzTimeoutTest is DSTest {
+    function testFuzzSlow(uint256 x) public {
+        // Each run takes long enough to exceed a short fuzz timeout, but terminates
+        for (uint256 i = 0; i < 1_000_000; i++) {
+            unchecked {
+                x = x * 31 + i;
+            }
+        }
+        assertTrue(x == x);
+    }
+}
@@ -1080,4 +1080,16 @@ mod tests {
         assert!(parsed.params[2].name == "param2");
         assert!(parsed.params[2].value == Token::Address(param2.into()));
     }
+
+    #[test]
+    fn test_format_custom_error() {
+        // InsufficientBalance(uint256 available, uint256 required)
+        let data = "0xcf47918100000000000000000000000000000000000000000000000000000000000000\
+                    0a0000000000000000000000000000000000000000000000000000000000000014";
+        assert_eq!(
+            selectors::format_custom_error("InsufficientBalance(uint256,uint256)", data).unwrap(),
+            "InsufficientBalance(10, 20)"
+        );
+        assert!(selectors::format_custom_error("InsufficientBalance(string)", data).is_err());
+    }
 }
@@ -1,4 +1,4 @@
-use crate::abi_decode;
+use crate::{abi_decode, format_token};
 use ethers_solc::artifacts::LosslessAbi;
 use eyre::Result;
 use serde::{Deserialize, Serialize};
@@ -48,9 +48,12 @@ impl fmt::Display for PossibleSigs {
 pub enum SelectorType {
     Function,
     Event,
+    /// A custom error, which is identified by a 4 byte selector like a function and looked up in
+    /// the function signatures
+    Error,
 }
 
-/// Decodes the given function or event selector using sig.eth.samczsun.com
+/// Decodes the given function, event or error selector using sig.eth.samczsun.com
 pub async fn decode_selector(selector: &str, selector_type: SelectorType) -> Result<Vec<String>> {
     #[derive(Deserialize)]
     struct Decoded {
@@ -73,7 +76,9 @@ pub async fn decode_selector(selector: &str, selector_type: SelectorType) -> Res
     // using samczsun signature database over 4byte
     // see https://github.com/foundry-rs/foundry/issues/1672
     let url = match selector_type {
-        SelectorType::Function => format!("{SELECTOR_DATABASE_URL}?function={selector}"),
+        SelectorType::Function | SelectorType::Error => {
+            format!("{SELECTOR_DATABASE_URL}?function={selector}")
+        }
         SelectorType::Event => format!("{SELECTOR_DATABASE_URL}?event={selector}"),
     };
 
@@ -90,7 +95,7 @@ pub async fn decode_selector(selector: &str, selector_type: SelectorType) -> Res
     }
 
     let decoded = match selector_type {
-        SelectorType::Function => api_response.result.function,
+        SelectorType::Function | SelectorType::Error => api_response.result.function,
         SelectorType::Event => api_response.result.event,
     };
 
@@ -102,14 +107,27 @@ pub async fn decode_selector(selector: &str, selector_type: SelectorType) -> Res
         .collect::<Vec<String>>())
 }
 
-/// Fetches a function signature given the selector using sig.eth.samczsun.com
-pub async fn decode_function_selector(selector: &str) -> Result<Vec<String>> {
+/// Returns the `0x` prefixed function selector, failing if it's not 4 bytes long
+pub fn prefixed_function_selector(selector: &str) -> Result<String> {
     let prefixed_selector = format!("0x{}", selector.strip_prefix("0x").unwrap_or(selector));
     if prefixed_selector.len() != 10 {
         eyre::bail!("Invalid selector: expected 8 characters (excluding 0x prefix), got {} characters (including 0x prefix).", prefixed_selector.len())
     }
+    Ok(prefixed_selector)
+}
+
+/// Returns the `0x` prefixed event topic, failing if it's not 32 bytes long
+pub fn prefixed_event_topic(topic: &str) -> Result<String> {
+    let prefixed_topic = format!("0x{}", topic.strip_prefix("0x").unwrap_or(topic));
+    if prefixed_topic.len() != 66 {
+        eyre::bail!("Invalid topic: expected 64 characters (excluding 0x prefix), got {} characters (including 0x prefix).", prefixed_topic.len())
+    }
+    Ok(prefixed_topic)
+}
 
-    decode_selector(&prefixed_selector[..10], SelectorType::Function).await
+/// Fetches a function signature given the selector using sig.eth.samczsun.com
+pub async fn decode_function_selector(selector: &str) -> Result<Vec<String>> {
+    decode_selector(&prefixed_function_selector(selector)?, SelectorType::Function).await
 }
 
 /// Fetches all possible signatures and attempts to abi decode the calldata
@@ -132,13 +150,17 @@ pub async fn decode_calldata(calldata: &str) -> Result<Vec<String>> {
         .collect::<Vec<String>>())
 }
 
+/// Decodes the revert data as the custom error with the given signature and formats it like
+/// `InsufficientBalance(10, 20)`
+pub fn format_custom_error(sig: &str, revert_data: &str) -> Result<String> {
+    let args = abi_decode(sig, revert_data, true)?;
+    let name = sig.split('(').next().unwrap_or(sig).trim_start_matches("error ").trim();
+    Ok(format!("{name}({})", args.iter().map(format_token).collect::<Vec<_>>().join(", ")))
+}
+
 /// Fetches a event signature given the 32 byte topic using sig.eth.samczsun.com
 pub async fn decode_event_topic(topic: &str) -> Result<Vec<String>> {
-    let prefixed_topic = format!("0x{}", topic.strip_prefix("0x").unwrap_or(topic));
-    if prefixed_topic.len() != 66 {
-        eyre::bail!("Invalid topic: expected 64 characters (excluding 0x prefix), got {} characters (including 0x prefix).", prefixed_topic.len())
-    }
-    decode_selector(&prefixed_topic[..66], SelectorType::Event).await
+    decode_selector(&prefixed_event_topic(topic)?, SelectorType::Event).await
 }
 
 /// Pretty print calldata and if available, fetch possible function signatures